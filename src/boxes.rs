@@ -0,0 +1,114 @@
+//! Box definitions, one module per box type.
+//!
+//! Every module is named after the fourcc of the box it parses (`moov`, `trak`,
+//! `stsd`, ...) and exposes the box struct along with its entry types. The
+//! public fields follow the ISO/IEC 14496-12 definitions; the most commonly used
+//! types are also re-exported from the crate root.
+//!
+//! This API is not stable yet: while the crate is pre-1.0, a minor release may
+//! rename or retype public fields and add boxes, fields and enum variants. Build
+//! box structs with `..Default::default()` and match enums with a wildcard arm.
+
+pub mod av01;
+pub mod avc1;
+pub mod c608;
+pub mod chpl;
+pub mod co64;
+pub mod ctts;
+pub mod data;
+pub mod dinf;
+pub mod edts;
+pub mod elst;
+pub mod emsg;
+pub mod ftyp;
+pub mod hdlr;
+pub mod hevc;
+pub mod ilst;
+pub mod keys;
+pub mod mdhd;
+pub mod mdia;
+pub mod mehd;
+pub mod meta;
+pub mod mfhd;
+pub mod mfra;
+pub mod minf;
+pub mod moof;
+pub mod moov;
+pub mod mp4a;
+pub mod mvex;
+pub mod mvhd;
+pub mod pssh;
+pub mod saio;
+pub mod saiz;
+pub mod senc;
+pub mod sidx;
+pub mod sinf;
+pub mod smhd;
+pub mod ssix;
+pub mod stbl;
+pub mod stco;
+pub mod stpp;
+pub mod stsc;
+pub mod stsd;
+pub mod stss;
+pub mod stsz;
+pub mod stts;
+pub mod styp;
+pub mod tfdt;
+pub mod tfhd;
+pub mod tkhd;
+pub mod traf;
+pub mod trak;
+pub mod tref;
+pub mod trex;
+pub mod trun;
+pub mod tx3g;
+pub mod udta;
+pub mod vmhd;
+pub mod vp08;
+pub mod vp09;
+pub mod vpcc;
+pub mod wvtt;
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Cursor;
+
+    use crate::{BoxHeader, Mp4Box, ReadBox, Result, UnknownBox, WriteBox};
+
+    pub(crate) fn unknown(fourcc: &[u8; 4], payload: &[u8]) -> UnknownBox {
+        UnknownBox {
+            fourcc: (*fourcc).into(),
+            payload: payload.to_vec(),
+        }
+    }
+
+    // Reads a single box, header included
+    pub(crate) fn read<T: for<'a> ReadBox<&'a mut Cursor<Vec<u8>>>>(data: &[u8]) -> Result<T> {
+        let mut reader = Cursor::new(data.to_vec());
+        let header = BoxHeader::read(&mut reader)?;
+        T::read_box(&mut reader, header.size)
+    }
+
+    pub(crate) fn write<T: for<'a> WriteBox<&'a mut Vec<u8>>>(bx: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        let size = bx.write_box(&mut data).unwrap();
+        assert_eq!(size, data.len() as u64);
+        data
+    }
+
+    // Writes `bx`, checks its size and type, and reads it back. Writing what was read
+    // must give the same bytes.
+    pub(crate) fn round_trip<T>(bx: &T) -> T
+    where
+        T: Mp4Box + for<'a> WriteBox<&'a mut Vec<u8>> + for<'a> ReadBox<&'a mut Cursor<Vec<u8>>>,
+    {
+        let data = write(bx);
+        assert_eq!(data.len() as u64, bx.box_size());
+        assert_eq!(&data[4..8], &u32::from(bx.box_type()).to_be_bytes());
+
+        let read = read::<T>(&data).unwrap();
+        assert_eq!(write(&read), data);
+        read
+    }
+}
@@ -0,0 +1,316 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, skip_box, skip_bytes_to, write_children, BigEndian, BoxHeader, BoxType, Error,
+    FixedPointU16, Mp4Box, RawBox, ReadBox, ReadChild, ReadPayload, Result, UnknownBox, WriteBox,
+    HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Av1CBox<B = Vec<u8>> {
+    pub profile: u8,
+    pub level: u8,
+    pub tier: u8,
+    pub bit_depth: u8,
+    pub monochrome: bool,
+    pub chroma_subsampling_x: u8,
+    pub chroma_subsampling_y: u8,
+    pub chroma_sample_position: u8,
+    pub initial_presentation_delay_present: bool,
+    pub initial_presentation_delay_minus_one: u8,
+    pub config_obus: B, // Holds the variable-length config0BUs
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for Av1CBox<B> {
+    fn box_type(&self) -> BoxType {
+        BoxType::Av1CBox
+    }
+
+    fn box_size(&self) -> u64 {
+        HEADER_SIZE + 4 + self.config_obus.as_ref().len() as u64
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for Av1CBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let marker_byte = BigEndian::read_u8(reader)?;
+
+        if marker_byte & 0x80 != 0x80 {
+            return Err(Error::invalid_value("missing av1c marker bit"));
+        }
+
+        if marker_byte & 0x7F != 0x01 {
+            return Err(Error::invalid_value("missing av1c marker bit"));
+        }
+
+        let profile_byte = BigEndian::read_u8(reader)?;
+        let profile = (profile_byte & 0xE0) >> 5;
+        let level = profile_byte & 0x1F;
+        let flags_byte = BigEndian::read_u8(reader)?;
+        let tier = (flags_byte & 0x80) >> 7;
+        let bit_depth: u8 = match flags_byte & 0x60 {
+            0x60 => 0b1100,
+            0x40 => 0x0A,
+            _ => 8,
+        };
+        let monochrome = flags_byte & 0x10 == 0x10;
+        let chroma_subsampling_x = (flags_byte & 0x08) >> 3;
+        let chroma_subsampling_y = (flags_byte & 0x04) >> 2;
+        let chroma_sample_position = flags_byte & 0x03;
+        let delay_byte = BigEndian::read_u8(reader)?;
+        let initial_presentation_delay_present = (delay_byte & 0x10) == 0x10;
+        let initial_presentation_delay_minus_one = if initial_presentation_delay_present {
+            delay_byte & 0x0F
+        } else {
+            0
+        };
+        let config_obus_size = size
+            .checked_sub(HEADER_SIZE + 4)
+            .ok_or(Error::invalid_value("invalid box size"))?;
+        let config_obus = reader.read_payload(config_obus_size)?;
+
+        Ok(Self {
+            profile,
+            level,
+            tier,
+            bit_depth,
+            monochrome,
+            chroma_subsampling_x,
+            chroma_subsampling_y,
+            chroma_sample_position,
+            initial_presentation_delay_present,
+            initial_presentation_delay_minus_one,
+            config_obus,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for Av1CBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u8(writer, 0x81)?; // marker + version
+        BigEndian::write_u8(writer, (self.profile << 5) | (self.level & 0x1F))?;
+
+        let bit_depth_flags = match self.bit_depth {
+            12 => 0x60,
+            10 => 0x40,
+            _ => 0,
+        };
+        BigEndian::write_u8(
+            writer,
+            (self.tier << 7)
+                | bit_depth_flags
+                | ((self.monochrome as u8) << 4)
+                | (self.chroma_subsampling_x << 3)
+                | (self.chroma_subsampling_y << 2)
+                | self.chroma_sample_position,
+        )?;
+
+        let delay_byte = if self.initial_presentation_delay_present {
+            0x10 | (self.initial_presentation_delay_minus_one & 0x0F)
+        } else {
+            0
+        };
+        BigEndian::write_u8(writer, delay_byte)?;
+
+        writer.write_all(self.config_obus.as_ref())?;
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Av01Box<B = Vec<u8>> {
+    pub data_reference_index: u16,
+    pub width: u16,
+    pub height: u16,
+    pub horizresolution: FixedPointU16,
+    pub vertresolution: FixedPointU16,
+    pub frame_count: u16,
+    pub compressorname: [u8; 32],
+    pub depth: u16,
+    pub av1c: RawBox<Av1CBox<B>, B>,
+    /// Children this crate doesn't model (`pasp`, `colr`, `btrt`, ...), written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+    /// How many of the `unknown` children come before `av1c`
+    pub unknown_before_av1c: usize,
+}
+
+impl<B: AsRef<[u8]>> Av01Box<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::Av01Box
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + 8
+            + 70
+            + self.av1c.box_size()
+            + self
+                .unknown
+                .iter()
+                .map(|unknown| unknown.box_size())
+                .sum::<u64>()
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for Av01Box<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for Av01Box<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let _ = BigEndian::read_u16(reader)?; // reserved
+        let data_reference_index = BigEndian::read_u16(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // pre-defined, reserved
+        let _ = BigEndian::read_u64(reader)?; // pre-defined
+        let _ = BigEndian::read_u32(reader)?; // pre-defined
+
+        let width = BigEndian::read_u16(reader)?;
+        let height = BigEndian::read_u16(reader)?;
+        let horizresolution = FixedPointU16::new_raw(BigEndian::read_u32(reader)?);
+        let vertresolution = FixedPointU16::new_raw(BigEndian::read_u32(reader)?);
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let frame_count = BigEndian::read_u16(reader)?;
+        let mut compressorname = [0u8; 32];
+        reader.read_exact(&mut compressorname)?;
+        let depth = BigEndian::read_u16(reader)?;
+        let _ = BigEndian::read_i16(reader)?; // pre-defined
+
+        let end = start + size;
+        let mut av1c = None;
+        let mut unknown = Vec::new();
+        let mut unknown_before_av1c = 0;
+
+        let mut current = reader.stream_position()?;
+        // entries may end with a 4 byte terminator
+        while current + HEADER_SIZE <= end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+            if header.size == 0 {
+                break;
+            }
+
+            match header.name {
+                BoxType::Av1CBox => {
+                    av1c.replace(RawBox::read_child(reader, &header)?);
+                    unknown_before_av1c = unknown.len();
+                }
+                // the protection of encrypted entries is kept by `StsdBoxContent::Encrypted`
+                BoxType::SinfBox => {
+                    skip_box(reader, header.size)?;
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(av1c) = av1c else {
+            return Err(Error::missing_box(BoxType::Av1CBox, start));
+        };
+        skip_bytes_to(reader, end)?;
+
+        Ok(Self {
+            data_reference_index,
+            width,
+            height,
+            horizresolution,
+            vertresolution,
+            frame_count,
+            compressorname,
+            depth,
+            av1c,
+            unknown,
+            unknown_before_av1c,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for Av01Box<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+
+        BigEndian::write_u32(writer, 0)?; // pre-defined, reserved
+        BigEndian::write_u64(writer, 0)?; // pre-defined
+        BigEndian::write_u32(writer, 0)?; // pre-defined
+
+        BigEndian::write_u16(writer, self.width)?;
+        BigEndian::write_u16(writer, self.height)?;
+        BigEndian::write_u32(writer, self.horizresolution.raw_value())?;
+        BigEndian::write_u32(writer, self.vertresolution.raw_value())?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.frame_count)?;
+        writer.write_all(&self.compressorname)?;
+        BigEndian::write_u16(writer, self.depth)?;
+        BigEndian::write_i16(writer, -1)?; // pre-defined
+
+        write_children(writer, &self.av1c, &self.unknown, self.unknown_before_av1c)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{round_trip, unknown};
+
+    #[test]
+    fn av01_round_trip() {
+        let av01 = Av01Box {
+            data_reference_index: 1,
+            width: 1280,
+            height: 720,
+            horizresolution: FixedPointU16::new(0x48),
+            vertresolution: FixedPointU16::new(0x48),
+            frame_count: 1,
+            compressorname: [0; 32],
+            depth: 24,
+            av1c: RawBox {
+                contents: Av1CBox {
+                    profile: 0,
+                    level: 8,
+                    bit_depth: 10,
+                    chroma_subsampling_x: 1,
+                    chroma_subsampling_y: 1,
+                    config_obus: vec![0x0A, 0x0B, 0, 0, 0],
+                    ..Default::default()
+                },
+                raw: Vec::new(),
+            },
+            unknown: vec![
+                unknown(b"colr", b"nclx\0\x09\0\x10\0\x09\0"),
+                unknown(b"pasp", &[0, 0, 0, 1, 0, 0, 0, 1]),
+            ],
+            unknown_before_av1c: 2,
+        };
+        let read = round_trip(&av01);
+        assert_eq!(read.av1c.contents, av01.av1c.contents);
+        assert_eq!(read.unknown, av01.unknown);
+        assert_eq!(read.unknown_before_av1c, 2);
+    }
+}
@@ -0,0 +1,365 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, skip_box, skip_bytes_to, write_children, BigEndian, BoxHeader, BoxType, Error,
+    FixedPointU16, Mp4Box, RawBox, ReadBox, ReadChild, ReadPayload, Result, UnknownBox, WriteBox,
+    HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Avc1Box<B = Vec<u8>> {
+    pub data_reference_index: u16,
+    pub width: u16,
+    pub height: u16,
+    pub horizresolution: FixedPointU16,
+    pub vertresolution: FixedPointU16,
+    pub frame_count: u16,
+    pub compressorname: [u8; 32],
+    pub depth: u16, // This is usually 24, even for HDR with bit_depth=10
+    pub avcc: RawBox<AvcCBox<B>, B>,
+    /// Children this crate doesn't model (`pasp`, `colr`, `btrt`, ...), written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+    /// How many of the `unknown` children come before `avcc`
+    pub unknown_before_avcc: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AvcCBox<B = Vec<u8>> {
+    pub configuration_version: u8,
+    pub avc_profile_indication: u8,
+    pub profile_compatibility: u8,
+    pub avc_level_indication: u8,
+    pub length_size_minus_one: u8,
+    pub sequence_parameter_sets: Vec<NalUnit<B>>,
+    pub picture_parameter_sets: Vec<NalUnit<B>>,
+    pub ext: B,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NalUnit<B = Vec<u8>> {
+    pub bytes: B,
+}
+
+impl<B: Default> Default for Avc1Box<B> {
+    fn default() -> Self {
+        Self {
+            data_reference_index: 0,
+            width: 0,
+            height: 0,
+            horizresolution: FixedPointU16::new(0x48),
+            vertresolution: FixedPointU16::new(0x48),
+            frame_count: 1,
+            compressorname: [0; 32],
+            depth: 0x0018,
+            avcc: RawBox::default(),
+            unknown: Vec::new(),
+            unknown_before_avcc: 0,
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>> Avc1Box<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::Avc1Box
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + 8
+            + 70
+            + self.avcc.box_size()
+            + self
+                .unknown
+                .iter()
+                .map(|unknown| unknown.box_size())
+                .sum::<u64>()
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for Avc1Box<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for Avc1Box<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let _ = BigEndian::read_u16(reader)?; // reserved
+        let data_reference_index = BigEndian::read_u16(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // pre-defined, reserved
+        let _ = BigEndian::read_u64(reader)?; // pre-defined
+        let _ = BigEndian::read_u32(reader)?; // pre-defined
+
+        let width = BigEndian::read_u16(reader)?;
+        let height = BigEndian::read_u16(reader)?;
+        let horizresolution = FixedPointU16::new_raw(BigEndian::read_u32(reader)?);
+        let vertresolution = FixedPointU16::new_raw(BigEndian::read_u32(reader)?);
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let frame_count = BigEndian::read_u16(reader)?;
+        let mut compressorname = [0u8; 32];
+        reader.read_exact(&mut compressorname)?;
+        let depth = BigEndian::read_u16(reader)?;
+        let _ = BigEndian::read_i16(reader)?; // pre-defined
+
+        let end = start + size;
+        let mut avcc = None;
+        let mut unknown = Vec::new();
+        let mut unknown_before_avcc = 0;
+
+        let mut current = reader.stream_position()?;
+        // entries may end with a 4 byte terminator
+        while current + HEADER_SIZE <= end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+            if header.size == 0 {
+                break;
+            }
+
+            match header.name {
+                BoxType::AvcCBox => {
+                    avcc.replace(RawBox::read_child(reader, &header)?);
+                    unknown_before_avcc = unknown.len();
+                }
+                // the protection of encrypted entries is kept by `StsdBoxContent::Encrypted`
+                BoxType::SinfBox => {
+                    skip_box(reader, header.size)?;
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(avcc) = avcc else {
+            return Err(Error::missing_box(BoxType::AvcCBox, start));
+        };
+        skip_bytes_to(reader, end)?;
+
+        Ok(Self {
+            data_reference_index,
+            width,
+            height,
+            horizresolution,
+            vertresolution,
+            frame_count,
+            compressorname,
+            depth,
+            avcc,
+            unknown,
+            unknown_before_avcc,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for Avc1Box<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+
+        BigEndian::write_u32(writer, 0)?; // pre-defined, reserved
+        BigEndian::write_u64(writer, 0)?; // pre-defined
+        BigEndian::write_u32(writer, 0)?; // pre-defined
+
+        BigEndian::write_u16(writer, self.width)?;
+        BigEndian::write_u16(writer, self.height)?;
+        BigEndian::write_u32(writer, self.horizresolution.raw_value())?;
+        BigEndian::write_u32(writer, self.vertresolution.raw_value())?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.frame_count)?;
+        writer.write_all(&self.compressorname)?;
+        BigEndian::write_u16(writer, self.depth)?;
+        BigEndian::write_i16(writer, -1)?; // pre-defined
+
+        write_children(writer, &self.avcc, &self.unknown, self.unknown_before_avcc)?;
+
+        Ok(size)
+    }
+}
+
+impl AvcCBox {
+    pub fn new(sps: &[u8], pps: &[u8]) -> Self {
+        Self {
+            configuration_version: 1,
+            avc_profile_indication: sps[1],
+            profile_compatibility: sps[2],
+            avc_level_indication: sps[3],
+            length_size_minus_one: 0xFF, // length_size = 4
+            sequence_parameter_sets: vec![NalUnit::from(sps)],
+            picture_parameter_sets: vec![NalUnit::from(pps)],
+            ext: Vec::new(),
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for AvcCBox<B> {
+    fn box_type(&self) -> BoxType {
+        BoxType::AvcCBox
+    }
+
+    fn box_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + 7;
+        for sps in &self.sequence_parameter_sets {
+            size += sps.size() as u64;
+        }
+        for pps in &self.picture_parameter_sets {
+            size += pps.size() as u64;
+        }
+        size += self.ext.as_ref().len() as u64;
+        size
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for AvcCBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let content_start = reader.stream_position()?;
+
+        let configuration_version = BigEndian::read_u8(reader)?;
+        let avc_profile_indication = BigEndian::read_u8(reader)?;
+        let profile_compatibility = BigEndian::read_u8(reader)?;
+        let avc_level_indication = BigEndian::read_u8(reader)?;
+        let length_size_minus_one = BigEndian::read_u8(reader)? & 0x3;
+
+        let num_of_spss = BigEndian::read_u8(reader)? & 0x1F;
+        let mut sequence_parameter_sets = Vec::with_capacity(num_of_spss as _);
+
+        for _ in 0..num_of_spss {
+            let nal_unit = NalUnit::read(reader)?;
+            sequence_parameter_sets.push(nal_unit)
+        }
+
+        let num_of_ppss = BigEndian::read_u8(reader)?;
+        let mut picture_parameter_sets = Vec::with_capacity(num_of_ppss as _);
+
+        for _ in 0..num_of_ppss {
+            let nal_unit = NalUnit::read(reader)?;
+            picture_parameter_sets.push(nal_unit)
+        }
+
+        let content_end = reader.stream_position()?;
+        let remainder = size - HEADER_SIZE - (content_end - content_start);
+        let ext = reader.read_payload(remainder)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            configuration_version,
+            avc_profile_indication,
+            profile_compatibility,
+            avc_level_indication,
+            length_size_minus_one,
+            sequence_parameter_sets,
+            picture_parameter_sets,
+            ext,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for AvcCBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u8(writer, self.configuration_version)?;
+        BigEndian::write_u8(writer, self.avc_profile_indication)?;
+        BigEndian::write_u8(writer, self.profile_compatibility)?;
+        BigEndian::write_u8(writer, self.avc_level_indication)?;
+        BigEndian::write_u8(writer, 0xFC | (self.length_size_minus_one & 0x3))?;
+
+        BigEndian::write_u8(writer, 0xE0 | self.sequence_parameter_sets.len() as u8)?;
+        for sps in &self.sequence_parameter_sets {
+            sps.write(writer)?;
+        }
+
+        BigEndian::write_u8(writer, self.picture_parameter_sets.len() as u8)?;
+        for pps in &self.picture_parameter_sets {
+            pps.write(writer)?;
+        }
+
+        writer.write_all(self.ext.as_ref())?;
+
+        Ok(size)
+    }
+}
+
+impl From<&[u8]> for NalUnit {
+    fn from(value: &[u8]) -> Self {
+        Self {
+            bytes: value.to_vec(),
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>> NalUnit<B> {
+    fn size(&self) -> usize {
+        2 + self.bytes.as_ref().len()
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        BigEndian::write_u16(writer, self.bytes.as_ref().len() as u16)?;
+        writer.write_all(self.bytes.as_ref())?;
+
+        Ok(self.size() as u64)
+    }
+}
+
+impl<B> NalUnit<B> {
+    fn read<R: Read + Seek + ReadPayload<B>>(reader: &mut R) -> Result<Self> {
+        let length = BigEndian::read_u16(reader)? as u64;
+        let bytes = reader.read_payload(length)?;
+
+        Ok(Self { bytes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{round_trip, unknown};
+
+    #[test]
+    fn avc1_round_trip() {
+        let avc1 = Avc1Box {
+            data_reference_index: 1,
+            width: 1920,
+            height: 1080,
+            compressorname: [7; 32],
+            avcc: RawBox {
+                contents: AvcCBox {
+                    length_size_minus_one: 3,
+                    ..AvcCBox::new(&[0x67, 0x64, 0, 0x28, 0xAC], &[0x68, 0xEE, 0x3C, 0x80])
+                },
+                raw: Vec::new(),
+            },
+            unknown: vec![
+                unknown(b"colr", b"nclx\0\x01\0\x01\0\x01\0"),
+                unknown(b"pasp", &[0, 0, 0, 1, 0, 0, 0, 1]),
+            ],
+            unknown_before_avcc: 1,
+            ..Default::default()
+        };
+        let read = round_trip(&avc1);
+        assert_eq!(read.avcc.contents, avc1.avcc.contents);
+        assert_eq!(read.unknown, avc1.unknown);
+        assert_eq!(read.unknown_before_avcc, 1);
+        assert_eq!(read.compressorname, [7; 32]);
+    }
+}
@@ -0,0 +1,70 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, skip_bytes_to, BigEndian, BoxHeader, BoxType, Mp4Box, ReadBox, Result, WriteBox,
+    HEADER_SIZE,
+};
+
+// QuickTime CEA-608 closed caption sample entry. Samples are `cdat` (field 1) and
+// `cdt2` (field 2) boxes of byte pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct C608Box {
+    pub data_reference_index: u16,
+}
+
+impl Default for C608Box {
+    fn default() -> Self {
+        Self {
+            data_reference_index: 1,
+        }
+    }
+}
+
+impl C608Box {
+    fn get_type(&self) -> BoxType {
+        BoxType::C608Box
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + 8
+    }
+}
+
+impl Mp4Box for C608Box {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for C608Box {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let _ = BigEndian::read_u16(reader)?; // reserved
+        let data_reference_index = BigEndian::read_u16(reader)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            data_reference_index,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for C608Box {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,123 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChplEntry {
+    /// In units of 100 nanoseconds
+    pub start: u64,
+    pub title: String,
+}
+
+// Nero chapter list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChplBox {
+    pub version: u8,
+    pub flags: u32,
+    pub entries: Vec<ChplEntry>,
+}
+
+impl Default for ChplBox {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            flags: 0,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl ChplBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::ChplBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE;
+        if self.version > 0 {
+            size += 4; // reserved
+        }
+        size += 1; // entry_count
+        for entry in self.entries.iter().take(u8::MAX as usize) {
+            size += 8 + 1 + title_len(&entry.title) as u64;
+        }
+        size
+    }
+}
+
+// Titles are prefixed with a one byte length, cut at a character boundary
+fn title_len(title: &str) -> usize {
+    let mut len = title.len().min(u8::MAX as usize);
+    while !title.is_char_boundary(len) {
+        len -= 1;
+    }
+    len
+}
+
+impl Mp4Box for ChplBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for ChplBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        if version > 0 {
+            let _reserved = BigEndian::read_u32(reader)?;
+        }
+
+        let entry_count = BigEndian::read_u8(reader)?;
+        let mut entries = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            let start = BigEndian::read_u64(reader)?;
+            let len = BigEndian::read_u8(reader)?;
+            let mut title = vec![0u8; len as usize];
+            reader.read_exact(&mut title)?;
+
+            entries.push(ChplEntry {
+                start,
+                title: String::from_utf8_lossy(&title).into_owned(),
+            });
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            entries,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for ChplBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if self.version > 0 {
+            BigEndian::write_u32(writer, 0)?; // reserved
+        }
+
+        BigEndian::write_u8(writer, self.entries.len().min(u8::MAX as usize) as u8)?;
+        for entry in self.entries.iter().take(u8::MAX as usize) {
+            let len = title_len(&entry.title);
+            BigEndian::write_u64(writer, entry.start)?;
+            BigEndian::write_u8(writer, len as u8)?;
+            writer.write_all(&entry.title.as_bytes()[..len])?;
+        }
+
+        Ok(size)
+    }
+}
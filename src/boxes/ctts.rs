@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -39,7 +39,7 @@ impl Mp4Box for CttsBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for CttsBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -56,8 +56,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for CttsBox {
                 .saturating_sub(other_size as u64)
                 / entry_size as u64
         {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
+            return Err(Error::invalid_value(
                 "ctts entry_count indicates more entries than could fit in the box",
             ));
         }
@@ -81,3 +80,47 @@ impl<R: Read + Seek> ReadBox<&mut R> for CttsBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for CttsBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.entries.len() as u32)?;
+
+        for entry in &self.entries {
+            BigEndian::write_u32(writer, entry.sample_count)?;
+            BigEndian::write_i32(writer, entry.sample_offset)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::round_trip;
+
+    #[test]
+    fn ctts_round_trip() {
+        for version in [0, 1] {
+            let ctts = CttsBox {
+                version,
+                flags: 0,
+                entries: vec![
+                    CttsEntry {
+                        sample_count: 1,
+                        sample_offset: 2000,
+                    },
+                    CttsEntry {
+                        sample_count: 2,
+                        sample_offset: if version == 1 { -1000 } else { 0 },
+                    },
+                ],
+            };
+            assert_eq!(round_trip(&ctts), ctts);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, BigEndian, BoxHeader, BoxType, DataType, Mp4Box, ReadBox, ReadPayload, Result,
+    WriteBox, HEADER_SIZE,
+};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DataBox<B = Vec<u8>> {
+    pub data: B,
+    pub data_type: DataType,
+}
+
+impl<B: AsRef<[u8]>> DataBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::DataBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE;
+        size += 4; // data_type
+        size += 4; // reserved
+        size += self.data.as_ref().len() as u64;
+        size
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for DataBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for DataBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let num = BigEndian::read_u32(reader)?;
+        let data_type = DataType::try_from(num)?;
+
+        let _reserved = BigEndian::read_u32(reader)?;
+
+        let current = reader.stream_position()?;
+        let data = reader.read_payload(start + size - current)?;
+
+        Ok(Self { data, data_type })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for DataBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, self.data_type.clone() as u32)?;
+        BigEndian::write_u32(writer, 0)?; // reserved
+
+        writer.write_all(self.data.as_ref())?;
+
+        Ok(size)
+    }
+}
@@ -1,8 +1,9 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_box, skip_bytes_to, BigEndian, BoxHeader, BoxType, Mp4Box,
-    ReadBox, HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_box, skip_bytes_to, write_box_header_ext, BigEndian,
+    BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, Result, WriteBox, HEADER_EXT_SIZE,
+    HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,7 +31,7 @@ impl UrlBox {
     fn get_size(&self) -> u64 {
         let mut size = HEADER_SIZE + HEADER_EXT_SIZE;
         if !self.location.is_empty() {
-            size += self.location.bytes().len() as u64 + 1;
+            size += self.location.len() as u64 + 1;
         }
         size
     }
@@ -47,20 +48,17 @@ impl Mp4Box for UrlBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for UrlBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
         let buf_size = size
             .checked_sub(HEADER_SIZE + HEADER_EXT_SIZE)
-            .ok_or(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "url size too small",
-            ))?;
+            .ok_or(Error::invalid_value("url size too small"))?;
 
         let mut buf = vec![0u8; buf_size as _];
         reader.read_exact(&mut buf)?;
 
-        if let Some(end) = buf.iter().position(|b| *b == b'0') {
+        if let Some(end) = buf.iter().position(|b| *b == b'\0') {
             buf.truncate(end);
         }
         let location = String::from_utf8(buf).unwrap_or_default();
@@ -75,6 +73,21 @@ impl<R: Read + Seek> ReadBox<&mut R> for UrlBox {
     }
 }
 
+impl<W: Write> WriteBox<&mut W> for UrlBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if !self.location.is_empty() {
+            writer.write_all(self.location.as_bytes())?;
+            BigEndian::write_u8(writer, 0)?;
+        }
+
+        Ok(size)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DrefBox {
     pub version: u8,
@@ -117,7 +130,7 @@ impl Mp4Box for DrefBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for DrefBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let mut current = reader.stream_position()?;
         let (version, flags) = read_box_header_ext(reader)?;
@@ -134,15 +147,12 @@ impl<R: Read + Seek> ReadBox<&mut R> for DrefBox {
             let header = BoxHeader::read(reader)?;
 
             if header.size > size {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "dinf box contains a box wiht larger size than itself",
-                ));
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
             }
 
             match header.name {
                 BoxType::UrlBox => {
-                    url.replace(UrlBox::read_box(reader, header.size)?);
+                    url.replace(UrlBox::read_child(reader, &header)?);
                 }
                 _ => {
                     skip_box(reader, header.size)?;
@@ -162,9 +172,25 @@ impl<R: Read + Seek> ReadBox<&mut R> for DrefBox {
     }
 }
 
+impl<W: Write> WriteBox<&mut W> for DrefBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.url.is_some() as u32)?;
+
+        if let Some(ref url) = self.url {
+            url.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct DinfBox {
-    dref: DrefBox,
+    pub dref: DrefBox,
 }
 
 impl DinfBox {
@@ -188,7 +214,7 @@ impl Mp4Box for DinfBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for DinfBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let mut dref: Option<DrefBox> = None;
         let mut current = reader.stream_position()?;
@@ -198,15 +224,12 @@ impl<R: Read + Seek> ReadBox<&mut R> for DinfBox {
             let header = BoxHeader::read(reader)?;
 
             if header.size > size {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "dinf box contains a box with a larger size than itself",
-                ));
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
             }
 
             match header.name {
                 BoxType::DrefBox => {
-                    dref.replace(DrefBox::read_box(reader, header.size)?);
+                    dref.replace(DrefBox::read_child(reader, &header)?);
                 }
                 _ => {
                     skip_box(reader, header.size)?;
@@ -217,10 +240,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for DinfBox {
         }
 
         let Some(drefbox) = dref else {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                "DrefBox is not found",
-            ));
+            return Err(Error::missing_box(BoxType::DrefBox, start));
         };
 
         skip_bytes_to(reader, start + size)?;
@@ -228,3 +248,14 @@ impl<R: Read + Seek> ReadBox<&mut R> for DinfBox {
         Ok(Self { dref: drefbox })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for DinfBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        self.dref.write_box(writer)?;
+
+        Ok(size)
+    }
+}
@@ -1,7 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, elst::ElstBox, skip_bytes_to, BoxHeader, BoxType, Mp4Box, ReadBox, HEADER_SIZE,
+    box_start, boxes::elst::ElstBox, skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox,
+    ReadChild, Result, WriteBox, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -34,20 +35,17 @@ impl Mp4Box for EdtsBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for EdtsBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let mut edts = Self::default();
         let header = BoxHeader::read(reader)?;
 
         if header.size > size {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "edts box contains a box with a larger size than itself",
-            ));
+            return Err(Error::size_overflow(header.name, box_start(reader)?));
         }
 
         if header.name == BoxType::ElstBox {
-            let elst = ElstBox::read_box(reader, header.size)?;
+            let elst = ElstBox::read_child(reader, &header)?;
             edts.elst.replace(elst);
         }
 
@@ -56,3 +54,16 @@ impl<R: Read + Seek> ReadBox<&mut R> for EdtsBox {
         Ok(edts)
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for EdtsBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        if let Some(ref elst) = self.elst {
+            elst.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
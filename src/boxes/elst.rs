@@ -0,0 +1,180 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ElstEntry {
+    pub segment_duration: u64,
+    pub media_time: i64,
+    pub media_rate: i16,
+    pub media_rate_fraction: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ElstBox {
+    pub version: u8,
+    pub flags: u32,
+    pub entries: Vec<ElstEntry>,
+}
+
+impl ElstBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::ElstBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 4;
+        if self.version == 1 {
+            size += self.entries.len() as u64 * 20;
+        } else if self.version == 0 {
+            size += self.entries.len() as u64 * 12;
+        }
+        size
+    }
+}
+
+impl Mp4Box for ElstBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for ElstBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let header_size = HEADER_SIZE + HEADER_EXT_SIZE;
+        let entry_count = BigEndian::read_u32(reader)?;
+        let other_size = size_of::<i32>(); // entry_count
+
+        let entry_size = {
+            let mut entry_size = 0;
+            entry_size += if version == 1 {
+                size_of::<u64>() + size_of::<i64>() // segment_duration + media_time
+            } else {
+                size_of::<u32>() + size_of::<i32>() // segment_duration + media_time
+            };
+            entry_size += size_of::<i16>() + size_of::<i16>(); // media_rate_integer + media_rate_fraction
+            entry_size
+        };
+
+        if u64::from(entry_count)
+            > size
+                .saturating_sub(header_size)
+                .saturating_sub(other_size as u64)
+                / entry_size as u64
+        {
+            return Err(Error::invalid_value(
+                "elst entry_count indicates more entries than could fit in the box",
+            ));
+        }
+
+        let mut entries: Vec<ElstEntry> = Vec::with_capacity(entry_count as _);
+
+        for _ in 0..entry_count {
+            let (segment_duration, media_time) = if version == 1 {
+                (
+                    BigEndian::read_u64(reader)?,
+                    BigEndian::read_u64(reader)? as i64,
+                )
+            } else {
+                (
+                    BigEndian::read_u32(reader)? as u64,
+                    BigEndian::read_i32(reader)? as i64,
+                )
+            };
+
+            let entry = ElstEntry {
+                segment_duration,
+                media_time,
+                media_rate: BigEndian::read_i16(reader)?,
+                media_rate_fraction: BigEndian::read_u16(reader)?,
+            };
+            entries.push(entry);
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            entries,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for ElstBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.entries.len() as u32)?;
+
+        for entry in &self.entries {
+            if self.version == 1 {
+                BigEndian::write_u64(writer, entry.segment_duration)?;
+                BigEndian::write_u64(writer, entry.media_time as u64)?;
+            } else {
+                BigEndian::write_u32(writer, entry.segment_duration as u32)?;
+                BigEndian::write_i32(writer, entry.media_time as i32)?;
+            }
+            BigEndian::write_i16(writer, entry.media_rate)?;
+            BigEndian::write_u16(writer, entry.media_rate_fraction)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::round_trip;
+
+    #[test]
+    fn elst_v0_round_trip() {
+        let elst = ElstBox {
+            version: 0,
+            flags: 0,
+            entries: vec![
+                ElstEntry {
+                    segment_duration: 500,
+                    media_time: -1,
+                    media_rate: 1,
+                    media_rate_fraction: 0,
+                },
+                ElstEntry {
+                    segment_duration: 10_000,
+                    media_time: 1024,
+                    media_rate: 1,
+                    media_rate_fraction: 0,
+                },
+            ],
+        };
+        assert_eq!(round_trip(&elst), elst);
+    }
+
+    #[test]
+    fn elst_v1_round_trip() {
+        let elst = ElstBox {
+            version: 1,
+            flags: 0,
+            entries: vec![ElstEntry {
+                segment_duration: u32::MAX as u64 * 3,
+                media_time: i32::MAX as i64 + 1,
+                media_rate: 0,
+                media_rate_fraction: 0,
+            }],
+        };
+        assert_eq!(round_trip(&elst), elst);
+    }
+}
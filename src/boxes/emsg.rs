@@ -0,0 +1,228 @@
+use std::io::{self, Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, ReadPayload, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EmsgBox<B = Vec<u8>> {
+    pub version: u8,
+    pub flags: u32,
+    pub timescale: u32,
+    pub presentation_time: Option<u64>,
+    pub presentation_time_delta: Option<u32>,
+    pub event_duration: u32,
+    pub id: u32,
+    pub scheme_id_uri: String,
+    pub value: String,
+    pub message_data: B,
+}
+
+impl<B> EmsgBox<B> {
+    fn size_without_message(version: u8, scheme_id_uri: &str, value: &str) -> u64 {
+        HEADER_SIZE
+            + HEADER_EXT_SIZE
+            + 4
+            + Self::time_size(version)
+            + (scheme_id_uri.len() + 1) as u64
+            + (value.len() as u64 + 1)
+    }
+
+    fn time_size(version: u8) -> u64 {
+        match version {
+            0 => 12,
+            1 => 16,
+            // other versions are refused when the box is read or written
+            _ => 0,
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for EmsgBox<B> {
+    fn box_type(&self) -> BoxType {
+        BoxType::EmsgBox
+    }
+
+    fn box_size(&self) -> u64 {
+        Self::size_without_message(self.version, &self.scheme_id_uri, &self.value)
+            + self.message_data.as_ref().len() as u64
+    }
+}
+
+fn read_null_terminated_utf8_string<R: Read + Seek>(reader: &mut R) -> Result<String> {
+    let mut bytes = Vec::new();
+
+    loop {
+        let byte = BigEndian::read_u8(reader)?;
+        bytes.push(byte);
+        if byte == 0 {
+            break;
+        }
+    }
+
+    if let Ok(s) = unsafe { std::ffi::CStr::from_bytes_with_nul_unchecked(&bytes) }.to_str() {
+        Ok(s.to_owned())
+    } else {
+        Err(Error::invalid_value("invalid utf8"))
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for EmsgBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let (
+            timescale,
+            presentation_time,
+            presentation_time_delta,
+            event_duration,
+            id,
+            scheme_id_uri,
+            value,
+        ) = match version {
+            0 => {
+                let scheme_id_uri = read_null_terminated_utf8_string(reader)?;
+                let value = read_null_terminated_utf8_string(reader)?;
+
+                (
+                    BigEndian::read_u32(reader)?,
+                    None,
+                    Some(BigEndian::read_u32(reader)?),
+                    BigEndian::read_u32(reader)?,
+                    BigEndian::read_u32(reader)?,
+                    scheme_id_uri,
+                    value,
+                )
+            }
+            1 => (
+                BigEndian::read_u32(reader)?,
+                Some(BigEndian::read_u64(reader)?),
+                None,
+                BigEndian::read_u32(reader)?,
+                BigEndian::read_u32(reader)?,
+                read_null_terminated_utf8_string(reader)?,
+                read_null_terminated_utf8_string(reader)?,
+            ),
+            _ => return Err(Error::unsupported_version(BoxType::EmsgBox, version, start)),
+        };
+
+        let fields_size = Self::size_without_message(version, &scheme_id_uri, &value);
+        let Some(message_size) = size.checked_sub(fields_size) else {
+            return Err(Error::Truncated {
+                box_type: BoxType::EmsgBox,
+                offset: start,
+                path: Default::default(),
+            });
+        };
+        let message_data = reader.read_payload(message_size)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            timescale,
+            presentation_time,
+            presentation_time_delta,
+            event_duration,
+            id,
+            scheme_id_uri,
+            value,
+            message_data,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for EmsgBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        if self.version > 1 {
+            return Err(Error::unsupported_version(self.box_type(), self.version, 0));
+        }
+
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        match self.version {
+            0 => {
+                write_null_terminated_utf8_string(writer, &self.scheme_id_uri)?;
+                write_null_terminated_utf8_string(writer, &self.value)?;
+                BigEndian::write_u32(writer, self.timescale)?;
+                BigEndian::write_u32(writer, self.presentation_time_delta.unwrap_or(0))?;
+                BigEndian::write_u32(writer, self.event_duration)?;
+                BigEndian::write_u32(writer, self.id)?;
+            }
+            _ => {
+                BigEndian::write_u32(writer, self.timescale)?;
+                BigEndian::write_u64(writer, self.presentation_time.unwrap_or(0))?;
+                BigEndian::write_u32(writer, self.event_duration)?;
+                BigEndian::write_u32(writer, self.id)?;
+                write_null_terminated_utf8_string(writer, &self.scheme_id_uri)?;
+                write_null_terminated_utf8_string(writer, &self.value)?;
+            }
+        }
+
+        writer.write_all(self.message_data.as_ref())?;
+
+        Ok(size)
+    }
+}
+
+fn write_null_terminated_utf8_string<W: Write>(writer: &mut W, string: &str) -> io::Result<()> {
+    writer.write_all(string.as_bytes())?;
+    BigEndian::write_u8(writer, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::round_trip;
+
+    #[test]
+    fn emsg_v0_round_trip() {
+        let emsg = EmsgBox {
+            version: 0,
+            timescale: 90000,
+            presentation_time_delta: Some(180000),
+            event_duration: 0xFFFF_FFFF,
+            id: 7,
+            scheme_id_uri: "urn:scte:scte35:2013:bin".into(),
+            value: "1".into(),
+            message_data: vec![0xFC, 0x30, 0x11],
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&emsg), emsg);
+    }
+
+    #[test]
+    fn emsg_v1_round_trip() {
+        let emsg = EmsgBox {
+            version: 1,
+            timescale: 1000,
+            presentation_time: Some(u32::MAX as u64 + 5),
+            event_duration: 2000,
+            id: 1,
+            scheme_id_uri: "https://aomedia.org/emsg/ID3".into(),
+            value: String::new(),
+            message_data: b"ID3".to_vec(),
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&emsg), emsg);
+    }
+
+    #[test]
+    fn emsg_unsupported_version() {
+        let emsg = EmsgBox::<Vec<u8>> {
+            version: 2,
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        assert!(matches!(
+            emsg.write_box(&mut buf),
+            Err(Error::UnsupportedVersion { .. })
+        ));
+        assert!(buf.is_empty());
+    }
+}
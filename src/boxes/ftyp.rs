@@ -1,12 +1,15 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
-use crate::{box_start, skip_bytes_to, BigEndian, BoxType, FourCC, Mp4Box, ReadBox, HEADER_SIZE};
+use crate::{
+    box_start, skip_bytes_to, BigEndian, BoxHeader, BoxType, Error, FourCC, Mp4Box, ReadBox,
+    Result, WriteBox, HEADER_SIZE,
+};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct FtypBox {
-    major_brand: FourCC,
-    minor_version: u32,
-    compatible_brands: Vec<FourCC>,
+    pub major_brand: FourCC,
+    pub minor_version: u32,
+    pub compatible_brands: Vec<FourCC>,
 }
 
 impl FtypBox {
@@ -30,14 +33,11 @@ impl Mp4Box for FtypBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for FtypBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
 
         if size < 16 || size % 4 != 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "ftyp size too small or not aligned",
-            ));
+            return Err(Error::invalid_value("ftyp size too small or not aligned"));
         }
 
         let brand_count = (size - 16) / 4;
@@ -59,3 +59,19 @@ impl<R: Read + Seek> ReadBox<&mut R> for FtypBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for FtypBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, self.major_brand.into())?;
+        BigEndian::write_u32(writer, self.minor_version)?;
+
+        for brand in &self.compatible_brands {
+            BigEndian::write_u32(writer, brand.into())?;
+        }
+
+        Ok(size)
+    }
+}
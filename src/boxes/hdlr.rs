@@ -1,8 +1,9 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes, skip_bytes_to, BigEndian, BoxType, FourCC, Mp4Box,
-    ReadBox, HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes, skip_bytes_to, write_box_header_ext, BigEndian,
+    BoxHeader, BoxType, Error, FourCC, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE,
+    HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -34,7 +35,7 @@ impl Mp4Box for HdlrBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for HdlrBox {
-    fn read_box(reader: &mut R, size: u64) -> std::io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -43,12 +44,9 @@ impl<R: Read + Seek> ReadBox<&mut R> for HdlrBox {
 
         skip_bytes(reader, 12)?; // reserved
 
-        let buf_size =
-            size.checked_sub(HEADER_SIZE + HEADER_EXT_SIZE + 20)
-                .ok_or(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "hdlr size too small",
-                ))?;
+        let buf_size = size
+            .checked_sub(HEADER_SIZE + HEADER_EXT_SIZE + 20)
+            .ok_or(Error::invalid_value("hdlr size too small"))?;
 
         let mut buf = vec![0u8; buf_size as _];
         reader.read_exact(&mut buf)?;
@@ -67,3 +65,21 @@ impl<R: Read + Seek> ReadBox<&mut R> for HdlrBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for HdlrBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, 0)?; // pre-defined
+        BigEndian::write_u32(writer, self.handler_type.into())?;
+
+        writer.write_all(&[0u8; 12])?; // reserved
+
+        writer.write_all(self.name.as_bytes())?;
+        BigEndian::write_u8(writer, 0)?;
+
+        Ok(size)
+    }
+}
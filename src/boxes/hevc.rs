@@ -0,0 +1,406 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, skip_box, skip_bytes_to, write_children, BigEndian, BoxHeader, BoxType, Error,
+    FixedPointU16, Mp4Box, RawBox, ReadBox, ReadChild, ReadPayload, Result, UnknownBox, WriteBox,
+    HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HevcBox<B = Vec<u8>> {
+    pub data_reference_index: u16,
+    pub width: u16,
+    pub height: u16,
+    pub horizresolution: FixedPointU16,
+    pub vertresolution: FixedPointU16,
+    pub frame_count: u16,
+    pub compressorname: [u8; 32],
+    pub depth: u16, // This is usually 24, even for HDR with bit depth=10
+    pub hvcc: RawBox<HevcDecoderConfigurationRecord<B>, B>,
+    /// Children this crate doesn't model (`pasp`, `colr`, `btrt`, ...), written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+    /// How many of the `unknown` children come before `hvcc`
+    pub unknown_before_hvcc: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HevcDecoderConfigurationRecord<B = Vec<u8>> {
+    pub configuration_version: u8,
+    pub general_profile_space: u8,
+    pub general_tier_flag: bool,
+    pub general_profile_idc: u8,
+    pub general_profile_compatibility_flags: u32,
+    pub general_constraint_indicator_flag: u64,
+    pub general_level_idc: u8,
+    pub min_spatial_segmentation_idc: u16,
+    pub parallelism_type: u8,
+    pub chroma_format_idc: u8,
+    pub bit_depth_luma_minus8: u8,
+    pub bit_depth_chroma_minus8: u8,
+    pub avg_frame_rate: u16,
+    pub constant_frame_rate: u8,
+    pub num_temporal_layers: u8,
+    pub temporal_id_nested: bool,
+    pub length_size_minus_one: u8,
+    pub arrays: Vec<HvcCArray<B>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HvcCArray<B = Vec<u8>> {
+    pub completeness: bool,
+    pub nal_unit_type: u8,
+    pub nalus: Vec<HvcCArrayNalu<B>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HvcCArrayNalu<B = Vec<u8>> {
+    pub size: u16,
+    pub data: B,
+}
+
+impl<B: Default> Default for HevcBox<B> {
+    fn default() -> Self {
+        Self {
+            data_reference_index: 0,
+            width: 0,
+            height: 0,
+            horizresolution: FixedPointU16::new(0x48),
+            vertresolution: FixedPointU16::new(0x48),
+            frame_count: 1,
+            compressorname: [0; 32],
+            depth: 0x0018,
+            hvcc: RawBox::default(),
+            unknown: Vec::new(),
+            unknown_before_hvcc: 0,
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>> HevcBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::Hvc1Box
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + 8
+            + 70
+            + self.hvcc.box_size()
+            + self
+                .unknown
+                .iter()
+                .map(|unknown| unknown.box_size())
+                .sum::<u64>()
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for HevcBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for HevcBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let _ = BigEndian::read_u16(reader)?; // reserved
+        let data_reference_index = BigEndian::read_u16(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // pre-defined, reserved
+        let _ = BigEndian::read_u64(reader)?; // pre-defined
+        let _ = BigEndian::read_u32(reader)?; // pre-defined
+
+        let width = BigEndian::read_u16(reader)?;
+        let height = BigEndian::read_u16(reader)?;
+        let horizresolution = FixedPointU16::new_raw(BigEndian::read_u32(reader)?);
+        let vertresolution = FixedPointU16::new_raw(BigEndian::read_u32(reader)?);
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let frame_count = BigEndian::read_u16(reader)?;
+        let mut compressorname = [0u8; 32];
+        reader.read_exact(&mut compressorname)?;
+        let depth = BigEndian::read_u16(reader)?;
+        let _ = BigEndian::read_i16(reader)?; // pre-defined
+
+        let end = start + size;
+        let mut hvcc = None;
+        let mut unknown = Vec::new();
+        let mut unknown_before_hvcc = 0;
+
+        let mut current = reader.stream_position()?;
+        // entries may end with a 4 byte terminator
+        while current + HEADER_SIZE <= end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+            if header.size == 0 {
+                break;
+            }
+
+            match header.name {
+                BoxType::HvcCBox => {
+                    hvcc.replace(RawBox::read_child(reader, &header)?);
+                    unknown_before_hvcc = unknown.len();
+                }
+                // the protection of encrypted entries is kept by `StsdBoxContent::Encrypted`
+                BoxType::SinfBox => {
+                    skip_box(reader, header.size)?;
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(hvcc) = hvcc else {
+            return Err(Error::missing_box(BoxType::HvcCBox, start));
+        };
+        skip_bytes_to(reader, end)?;
+
+        Ok(Self {
+            data_reference_index,
+            width,
+            height,
+            horizresolution,
+            vertresolution,
+            frame_count,
+            compressorname,
+            depth,
+            hvcc,
+            unknown,
+            unknown_before_hvcc,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>> HevcBox<B> {
+    pub(crate) fn write_entry<W: Write>(&self, writer: &mut W, name: BoxType) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(name, size).write(writer)?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+
+        BigEndian::write_u32(writer, 0)?; // pre-defined, reserved
+        BigEndian::write_u64(writer, 0)?; // pre-defined
+        BigEndian::write_u32(writer, 0)?; // pre-defined
+
+        BigEndian::write_u16(writer, self.width)?;
+        BigEndian::write_u16(writer, self.height)?;
+        BigEndian::write_u32(writer, self.horizresolution.raw_value())?;
+        BigEndian::write_u32(writer, self.vertresolution.raw_value())?;
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.frame_count)?;
+        writer.write_all(&self.compressorname)?;
+        BigEndian::write_u16(writer, self.depth)?;
+        BigEndian::write_i16(writer, -1)?; // pre-defined
+
+        write_children(writer, &self.hvcc, &self.unknown, self.unknown_before_hvcc)?;
+
+        Ok(size)
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for HevcBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        self.write_entry(writer, self.box_type())
+    }
+}
+
+impl HevcDecoderConfigurationRecord {
+    pub fn new() -> Self {
+        Self {
+            configuration_version: 1,
+            ..Default::default()
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for HevcDecoderConfigurationRecord<B> {
+    fn box_type(&self) -> BoxType {
+        BoxType::HvcCBox
+    }
+
+    fn box_size(&self) -> u64 {
+        HEADER_SIZE
+            + 23
+            + self
+                .arrays
+                .iter()
+                .map(|a| {
+                    3 + a
+                        .nalus
+                        .iter()
+                        .map(|x| 2 + x.data.as_ref().len() as u64)
+                        .sum::<u64>()
+                })
+                .sum::<u64>()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for HevcDecoderConfigurationRecord<B> {
+    fn read_box(reader: &mut R, _: u64) -> Result<Self> {
+        let configuration_version = BigEndian::read_u8(reader)?;
+        let params = BigEndian::read_u8(reader)?;
+        let general_profile_space = params >> 6;
+        let general_tier_flag = ((params & 0b00100000) >> 5) > 0;
+        let general_profile_idc = params & 0b00011111;
+
+        let general_profile_compatibility_flags = BigEndian::read_u32(reader)?;
+        let general_constraint_indicator_flag = BigEndian::read_u48(reader)?;
+        let general_level_idc = BigEndian::read_u8(reader)?;
+        let min_spatial_segmentation_idc = BigEndian::read_u16(reader)? & 0x0FFF;
+        let parallelism_type = BigEndian::read_u8(reader)? & 0b11;
+        let chroma_format_idc = BigEndian::read_u8(reader)? & 0b11;
+        let bit_depth_luma_minus8 = BigEndian::read_u8(reader)? & 0b111;
+        let bit_depth_chroma_minus8 = BigEndian::read_u8(reader)? & 0b111;
+        let avg_frame_rate = BigEndian::read_u16(reader)?;
+
+        let params = BigEndian::read_u8(reader)?;
+        let constant_frame_rate = (params & 0b11000000) >> 6;
+        let num_temporal_layers = (params & 0b00111000) >> 3;
+        let temporal_id_nested = ((params & 0b00000100) >> 2) > 0;
+        let length_size_minus_one = params & 0b000011;
+
+        let num_of_arrays = BigEndian::read_u8(reader)?;
+        let mut arrays = Vec::with_capacity(num_of_arrays as _);
+
+        for _ in 0..num_of_arrays {
+            let params = BigEndian::read_u8(reader)?;
+            let num_nalus = BigEndian::read_u16(reader)?;
+            let mut nalus = Vec::with_capacity(num_nalus as _);
+
+            for _ in 0..num_nalus {
+                let size = BigEndian::read_u16(reader)?;
+                let data = reader.read_payload(size as u64)?;
+
+                nalus.push(HvcCArrayNalu { size, data })
+            }
+
+            arrays.push(HvcCArray {
+                completeness: (params & 0b10000000) > 0,
+                nal_unit_type: params & 0b111111,
+                nalus,
+            });
+        }
+
+        Ok(Self {
+            configuration_version,
+            general_profile_space,
+            general_tier_flag,
+            general_profile_idc,
+            general_profile_compatibility_flags,
+            general_constraint_indicator_flag,
+            general_level_idc,
+            min_spatial_segmentation_idc,
+            parallelism_type,
+            chroma_format_idc,
+            bit_depth_luma_minus8,
+            bit_depth_chroma_minus8,
+            avg_frame_rate,
+            constant_frame_rate,
+            num_temporal_layers,
+            temporal_id_nested,
+            length_size_minus_one,
+            arrays,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for HevcDecoderConfigurationRecord<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u8(writer, self.configuration_version)?;
+        BigEndian::write_u8(
+            writer,
+            (self.general_profile_space << 6)
+                | ((self.general_tier_flag as u8) << 5)
+                | self.general_profile_idc,
+        )?;
+        BigEndian::write_u32(writer, self.general_profile_compatibility_flags)?;
+        BigEndian::write_u48(writer, self.general_constraint_indicator_flag)?;
+        BigEndian::write_u8(writer, self.general_level_idc)?;
+        BigEndian::write_u16(writer, 0xF000 | self.min_spatial_segmentation_idc)?;
+        BigEndian::write_u8(writer, 0xFC | self.parallelism_type)?;
+        BigEndian::write_u8(writer, 0xFC | self.chroma_format_idc)?;
+        BigEndian::write_u8(writer, 0xF8 | self.bit_depth_luma_minus8)?;
+        BigEndian::write_u8(writer, 0xF8 | self.bit_depth_chroma_minus8)?;
+        BigEndian::write_u16(writer, self.avg_frame_rate)?;
+        BigEndian::write_u8(
+            writer,
+            (self.constant_frame_rate << 6)
+                | (self.num_temporal_layers << 3)
+                | ((self.temporal_id_nested as u8) << 2)
+                | self.length_size_minus_one,
+        )?;
+
+        BigEndian::write_u8(writer, self.arrays.len() as u8)?;
+
+        for array in &self.arrays {
+            BigEndian::write_u8(
+                writer,
+                ((array.completeness as u8) << 7) | (array.nal_unit_type & 0b111111),
+            )?;
+            BigEndian::write_u16(writer, array.nalus.len() as u16)?;
+
+            for nalu in &array.nalus {
+                BigEndian::write_u16(writer, nalu.data.as_ref().len() as u16)?;
+                writer.write_all(nalu.data.as_ref())?;
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{round_trip, unknown};
+
+    #[test]
+    fn hvc1_round_trip() {
+        let hvc1 = HevcBox {
+            data_reference_index: 1,
+            width: 3840,
+            height: 2160,
+            hvcc: RawBox {
+                contents: HevcDecoderConfigurationRecord {
+                    general_profile_idc: 2,
+                    general_level_idc: 153,
+                    chroma_format_idc: 1,
+                    bit_depth_luma_minus8: 2,
+                    bit_depth_chroma_minus8: 2,
+                    length_size_minus_one: 3,
+                    arrays: vec![HvcCArray {
+                        completeness: true,
+                        nal_unit_type: 33,
+                        nalus: vec![HvcCArrayNalu {
+                            size: 3,
+                            data: vec![0x42, 0x01, 0x01],
+                        }],
+                    }],
+                    ..HevcDecoderConfigurationRecord::new()
+                },
+                raw: Vec::new(),
+            },
+            unknown: vec![unknown(b"btrt", &[0; 12])],
+            ..Default::default()
+        };
+        let read = round_trip(&hvc1);
+        assert_eq!(read.hvcc.contents, hvc1.hvcc.contents);
+        assert_eq!(read.unknown, hvc1.unknown);
+        assert_eq!(read.unknown_before_hvcc, 0);
+    }
+}
@@ -0,0 +1,565 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::{Read, Seek, Write},
+};
+
+use crate::{
+    box_start, boxes::data::DataBox, read_box_header_ext, skip_box, skip_bytes_to,
+    write_box_header_ext, BoxHeader, BoxType, DataType, Error, FourCC, Metadata, MetadataKey,
+    MetadataValue, Mp4Box, ReadBox, ReadChild, ReadPayload, Result, WriteBox, HEADER_EXT_SIZE,
+    HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IlstItemBox<B = Vec<u8>> {
+    pub data: DataBox<B>,
+    /// The `data` children after the first, e.g. the other images of `covr`
+    pub more_data: Vec<DataBox<B>>,
+}
+
+impl<B: AsRef<[u8]>> IlstItemBox<B> {
+    fn get_size(&self, key: &MetadataKey) -> u64 {
+        let mut size = HEADER_SIZE
+            + self.data.box_size()
+            + self.more_data.iter().map(DataBox::box_size).sum::<u64>();
+        if let MetadataKey::Freeform { mean, name } = key {
+            size += 2 * (HEADER_SIZE + HEADER_EXT_SIZE) + mean.len() as u64 + name.len() as u64;
+        }
+        size
+    }
+
+    fn write_item<W: Write>(&self, writer: &mut W, key: &MetadataKey) -> Result<u64> {
+        let size = self.get_size(key);
+        BoxHeader::new(BoxType::from(u32::from(key.fourcc())), size).write(writer)?;
+
+        if let MetadataKey::Freeform { mean, name } = key {
+            write_string_box(writer, BoxType::MeanBox, mean)?;
+            write_string_box(writer, BoxType::FreeformNameBox, name)?;
+        }
+
+        self.data.write_box(writer)?;
+        for data in &self.more_data {
+            data.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for IlstItemBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let item = RawItem::read_box(reader, size)?;
+
+        item.into_item()
+            .ok_or(Error::missing_box(BoxType::DataBox, start))
+    }
+}
+
+// An item as found in the file, `----` items carry their key in the `mean` and `name`
+// children. Items without `data` are dropped instead of failing the whole `ilst`.
+struct RawItem<B> {
+    mean: Option<String>,
+    name: Option<String>,
+    data: Vec<DataBox<B>>,
+}
+
+impl<B> RawItem<B> {
+    fn into_item(mut self) -> Option<IlstItemBox<B>> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let data = self.data.remove(0);
+        Some(IlstItemBox {
+            data,
+            more_data: self.data,
+        })
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for RawItem<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let mut mean = None;
+        let mut name = None;
+        let mut data = Vec::new();
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::DataBox => {
+                    data.push(DataBox::read_child(reader, &header)?);
+                }
+                BoxType::MeanBox => {
+                    mean.replace(read_string_box(reader, &header)?);
+                }
+                BoxType::FreeformNameBox => {
+                    name.replace(read_string_box(reader, &header)?);
+                }
+                _ => {
+                    skip_box(reader, header.size)?;
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self { mean, name, data })
+    }
+}
+
+fn read_string_box<R: Read + Seek>(reader: &mut R, header: &BoxHeader) -> Result<String> {
+    let start = box_start(reader)?;
+    let read = |reader: &mut R| -> Result<String> {
+        read_box_header_ext(reader)?;
+        let len = header.size.saturating_sub(HEADER_SIZE + HEADER_EXT_SIZE);
+        let mut buf = vec![0u8; len as usize];
+        reader.read_exact(&mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    };
+    read(reader).map_err(|err| err.within(header.name, start))
+}
+
+fn write_string_box<W: Write>(writer: &mut W, box_type: BoxType, value: &str) -> Result<u64> {
+    let size = HEADER_SIZE + HEADER_EXT_SIZE + value.len() as u64;
+    BoxHeader::new(box_type, size).write(writer)?;
+    write_box_header_ext(writer, 0, 0)?;
+    writer.write_all(value.as_bytes())?;
+    Ok(size)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IlstBox<B = Vec<u8>> {
+    pub items: BTreeMap<MetadataKey, IlstItemBox<B>>,
+}
+
+impl<B: AsRef<[u8]>> IlstBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::IlstBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + self
+                .items
+                .iter()
+                .map(|(key, item)| item.get_size(key))
+                .sum::<u64>()
+    }
+}
+
+impl IlstBox {
+    // Stores the value the way iTunes does for the key, e.g. `trkn` as a binary
+    // number/total pair and `cpil` as a one byte integer
+    pub fn set(&mut self, key: MetadataKey, value: MetadataValue<'_>) {
+        let (data_type, data) = match value {
+            MetadataValue::Text(text) => (DataType::Text, text.into_owned().into_bytes()),
+            MetadataValue::Integer(value) => {
+                let width = key.integer_width().unwrap_or(match value {
+                    _ if i8::try_from(value).is_ok() => 1,
+                    _ if i16::try_from(value).is_ok() => 2,
+                    _ if i32::try_from(value).is_ok() => 4,
+                    _ => 8,
+                });
+                let data_type = if key == MetadataKey::GenreId {
+                    DataType::Binary
+                } else {
+                    DataType::TempoCpil
+                };
+                (data_type, value.to_be_bytes()[8 - width..].to_vec())
+            }
+            MetadataValue::Pair { number, total } => {
+                let mut data = vec![0, 0];
+                data.extend(number.to_be_bytes());
+                data.extend(total.to_be_bytes());
+                if key != MetadataKey::DiskNumber {
+                    data.extend([0, 0]);
+                }
+                (DataType::Binary, data)
+            }
+            MetadataValue::Float(value) => (DataType::Float64, value.to_be_bytes().to_vec()),
+            MetadataValue::Image { data_type, data } => (data_type, data.to_vec()),
+            MetadataValue::Binary(data) => (DataType::Binary, data.to_vec()),
+        };
+
+        self.items.insert(
+            key,
+            IlstItemBox {
+                data: DataBox { data, data_type },
+                more_data: Vec::new(),
+            },
+        );
+    }
+
+    pub fn remove(&mut self, key: &MetadataKey) -> Option<IlstItemBox> {
+        self.items.remove(key)
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for IlstBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for IlstBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let mut items = BTreeMap::new();
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            let mut item = RawItem::read_child(reader, &header)?;
+            let key = match (item.mean.take(), item.name.take()) {
+                (Some(mean), Some(name)) if header.name == BoxType::FreeformBox => {
+                    MetadataKey::Freeform { mean, name }
+                }
+                _ => MetadataKey::from(FourCC::from(header.name)),
+            };
+            if let Some(item) = item.into_item() {
+                items.insert(key, item);
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self { items })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for IlstBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        for (key, item) in &self.items {
+            item.write_item(writer, key)?;
+        }
+
+        Ok(size)
+    }
+}
+
+fn read_integer(bytes: &[u8], signed: bool) -> Option<i64> {
+    if !matches!(bytes.len(), 1..=4 | 8) {
+        return None;
+    }
+
+    let negative = signed && bytes[0] & 0x80 != 0;
+    let init = if negative { -1 } else { 0 };
+    Some(
+        bytes
+            .iter()
+            .fold(init, |value, byte| value << 8 | *byte as i64),
+    )
+}
+
+fn decode<'a>(key: &MetadataKey, data: &'a DataBox<impl AsRef<[u8]>>) -> MetadataValue<'a> {
+    let bytes = data.data.as_ref();
+    let integer = match data.data_type {
+        DataType::Text => return MetadataValue::Text(String::from_utf8_lossy(bytes)),
+        DataType::Utf16 => {
+            let units = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]));
+            return MetadataValue::Text(Cow::Owned(String::from_utf16_lossy(
+                &units.collect::<Vec<_>>(),
+            )));
+        }
+        DataType::Image | DataType::Png | DataType::Bmp => {
+            return MetadataValue::Image {
+                data_type: data.data_type.clone(),
+                data: bytes,
+            }
+        }
+        DataType::Float32 => match <[u8; 4]>::try_from(bytes) {
+            Ok(float) => return MetadataValue::Float(f32::from_be_bytes(float).into()),
+            Err(_) => None,
+        },
+        DataType::Float64 => match <[u8; 8]>::try_from(bytes) {
+            Ok(float) => return MetadataValue::Float(f64::from_be_bytes(float)),
+            Err(_) => None,
+        },
+        DataType::TempoCpil => read_integer(bytes, true),
+        DataType::UnsignedInteger => read_integer(bytes, false),
+        // Older taggers write integer items untyped
+        DataType::Binary => match key {
+            MetadataKey::TrackNumber | MetadataKey::DiskNumber if bytes.len() >= 6 => {
+                return MetadataValue::Pair {
+                    number: u16::from_be_bytes([bytes[2], bytes[3]]),
+                    total: u16::from_be_bytes([bytes[4], bytes[5]]),
+                }
+            }
+            _ if key.integer_width().is_some() => read_integer(bytes, false),
+            _ => None,
+        },
+    };
+
+    match integer {
+        Some(value) => MetadataValue::Integer(value),
+        None => MetadataValue::Binary(bytes),
+    }
+}
+
+impl<B: AsRef<[u8]>> IlstBox<B> {
+    // Every value of the item, `value` only decodes the first
+    pub fn values<'a>(&'a self, key: &'a MetadataKey) -> impl Iterator<Item = MetadataValue<'a>> {
+        self.items
+            .get(key)
+            .into_iter()
+            .flat_map(|item| std::iter::once(&item.data).chain(&item.more_data))
+            .map(move |data| decode(key, data))
+    }
+}
+
+impl<'a, B: AsRef<[u8]>> Metadata<'a> for IlstBox<B> {
+    fn title(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Title)
+    }
+
+    fn year(&self) -> Option<u32> {
+        match self.value(&MetadataKey::Year)? {
+            // Usually a full date like `2021-06-04T07:00:00Z`
+            MetadataValue::Text(text) => text.get(..4)?.parse().ok(),
+            MetadataValue::Integer(year) => u32::try_from(year).ok(),
+            MetadataValue::Binary(bytes) => Some(u32::from_be_bytes(bytes.try_into().ok()?)),
+            _ => None,
+        }
+    }
+
+    fn poster(&self) -> Option<&[u8]> {
+        self.items
+            .get(&MetadataKey::Poster)
+            .map(|t| t.data.data.as_ref())
+    }
+
+    fn summary(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Summary)
+    }
+
+    fn value(&self, key: &MetadataKey) -> Option<MetadataValue<'_>> {
+        self.items.get(key).map(|item| decode(key, &item.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{read, round_trip, write};
+
+    fn item_data(ilst: &IlstBox, key: &MetadataKey) -> (DataType, Vec<u8>) {
+        let data = &ilst.items[key].data;
+        (data.data_type.clone(), data.data.clone())
+    }
+
+    // An item box holding `data` children with the given type and payload
+    fn item_bytes(fourcc: &[u8; 4], values: &[(u32, &[u8])]) -> Vec<u8> {
+        let mut children = Vec::new();
+        for (data_type, payload) in values {
+            children.extend((HEADER_SIZE as u32 + 8 + payload.len() as u32).to_be_bytes());
+            children.extend(b"data");
+            children.extend(data_type.to_be_bytes());
+            children.extend([0; 4]);
+            children.extend(*payload);
+        }
+        let mut item = (HEADER_SIZE as u32 + children.len() as u32)
+            .to_be_bytes()
+            .to_vec();
+        item.extend(fourcc);
+        item.extend(children);
+        item
+    }
+
+    fn ilst_bytes(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        let mut ilst = (HEADER_SIZE as u32 + payload.len() as u32)
+            .to_be_bytes()
+            .to_vec();
+        ilst.extend(b"ilst");
+        ilst.extend(payload);
+        ilst
+    }
+
+    #[test]
+    fn track_and_disk_numbers() {
+        let mut ilst = IlstBox::default();
+        let pair = MetadataValue::Pair {
+            number: 3,
+            total: 12,
+        };
+        ilst.set(MetadataKey::TrackNumber, pair.clone());
+        ilst.set(
+            MetadataKey::DiskNumber,
+            MetadataValue::Pair {
+                number: 1,
+                total: 2,
+            },
+        );
+
+        // trkn has two trailing padding bytes, disk doesn't
+        assert_eq!(
+            item_data(&ilst, &MetadataKey::TrackNumber),
+            (DataType::Binary, vec![0, 0, 0, 3, 0, 12, 0, 0])
+        );
+        assert_eq!(
+            item_data(&ilst, &MetadataKey::DiskNumber),
+            (DataType::Binary, vec![0, 0, 0, 1, 0, 2])
+        );
+
+        let written = round_trip(&ilst);
+        assert_eq!(written.track_number(), Some((3, 12)));
+        assert_eq!(written.disk_number(), Some((1, 2)));
+        assert_eq!(written.value(&MetadataKey::TrackNumber), Some(pair));
+    }
+
+    #[test]
+    fn integer_widths() {
+        let mut ilst = IlstBox::default();
+        ilst.set(MetadataKey::Compilation, MetadataValue::Integer(1));
+        ilst.set(MetadataKey::Tempo, MetadataValue::Integer(120));
+        ilst.set(MetadataKey::TvSeason, MetadataValue::Integer(2));
+        let other = MetadataKey::Other(FourCC::from(*b"xint"));
+        ilst.set(other.clone(), MetadataValue::Integer(-300));
+
+        let widths: Vec<_> = [
+            MetadataKey::Compilation,
+            MetadataKey::Tempo,
+            MetadataKey::TvSeason,
+            other.clone(),
+        ]
+        .iter()
+        .map(|key| item_data(&ilst, key))
+        .collect();
+        assert_eq!(
+            widths,
+            [
+                (DataType::TempoCpil, vec![1]),
+                (DataType::TempoCpil, vec![0, 120]),
+                (DataType::TempoCpil, vec![0, 0, 0, 2]),
+                (DataType::TempoCpil, vec![0xFE, 0xD4]),
+            ]
+        );
+
+        let written = round_trip(&ilst);
+        assert_eq!(written.integer(&MetadataKey::Compilation), Some(1));
+        assert_eq!(written.tempo(), Some(120));
+        assert_eq!(written.integer(&MetadataKey::TvSeason), Some(2));
+        assert_eq!(written.integer(&other), Some(-300));
+
+        // older taggers write them untyped, and unsigned integers don't sign extend
+        let parsed = read::<IlstBox>(&ilst_bytes(&[
+            item_bytes(b"tmpo", &[(0, &[0, 90])]),
+            item_bytes(b"cnID", &[(0x16, &[0xFF, 0xFF, 0xFF, 0xFF])]),
+            item_bytes(b"xbin", &[(0, &[1, 2])]),
+        ]))
+        .unwrap();
+        assert_eq!(parsed.tempo(), Some(90));
+        assert_eq!(
+            parsed.integer(&MetadataKey::ContentId),
+            Some(u32::MAX as i64)
+        );
+        assert_eq!(
+            parsed.value(&MetadataKey::Other(FourCC::from(*b"xbin"))),
+            Some(MetadataValue::Binary(&[1, 2]))
+        );
+    }
+
+    #[test]
+    fn freeform_items() {
+        let key = MetadataKey::Freeform {
+            mean: String::from("com.apple.iTunes"),
+            name: String::from("iTunSMPB"),
+        };
+        let mut ilst = IlstBox::default();
+        ilst.set(key.clone(), MetadataValue::Text(Cow::Borrowed(" 00000000")));
+
+        let data = write(&ilst);
+        assert_eq!(&data[12..16], b"----");
+        assert_eq!(&data[20..24], b"mean");
+        assert_eq!(&data[28..44], b"com.apple.iTunes");
+        assert_eq!(&data[48..52], b"name");
+        assert_eq!(&data[56..64], b"iTunSMPB");
+
+        let written = round_trip(&ilst);
+        assert_eq!(written.text(&key).as_deref(), Some(" 00000000"));
+
+        // without `mean` and `name` the item is kept under `----`
+        let parsed = read::<IlstBox>(&ilst_bytes(&[item_bytes(b"----", &[(1, b"text")])])).unwrap();
+        let other = MetadataKey::Other(FourCC::from(*b"----"));
+        assert_eq!(parsed.text(&other).as_deref(), Some("text"));
+    }
+
+    #[test]
+    fn genre_ids() {
+        let mut ilst = IlstBox::default();
+        ilst.set(MetadataKey::GenreId, MetadataValue::Integer(18));
+        ilst.set(MetadataKey::StoreGenreId, MetadataValue::Integer(1_002));
+        assert_eq!(
+            item_data(&ilst, &MetadataKey::GenreId),
+            (DataType::Binary, vec![0, 18])
+        );
+        assert_eq!(
+            item_data(&ilst, &MetadataKey::StoreGenreId),
+            (DataType::TempoCpil, vec![0, 0, 0x03, 0xEA])
+        );
+
+        let mut written = round_trip(&ilst);
+        assert_eq!(written.genre_id(), Some(18));
+        assert_eq!(written.genre().as_deref(), Some("Rock"));
+        assert_eq!(written.integer(&MetadataKey::StoreGenreId), Some(1_002));
+
+        // `©gen` wins over the standard genre
+        written.set(
+            MetadataKey::Genre,
+            MetadataValue::Text(Cow::Borrowed("Shoegaze")),
+        );
+        assert_eq!(written.genre().as_deref(), Some("Shoegaze"));
+        written.remove(&MetadataKey::Genre);
+        written.set(MetadataKey::GenreId, MetadataValue::Integer(0));
+        assert_eq!(written.genre(), None);
+    }
+
+    #[test]
+    fn every_data_child() {
+        let data = ilst_bytes(&[item_bytes(b"covr", &[(13, b"jpeg"), (14, b"png")])]);
+        let parsed = read::<IlstBox>(&data).unwrap();
+        assert_eq!(parsed.poster(), Some(&b"jpeg"[..]));
+        let images: Vec<_> = parsed.values(&MetadataKey::Poster).collect();
+        assert_eq!(
+            images,
+            [
+                MetadataValue::Image {
+                    data_type: DataType::Image,
+                    data: b"jpeg",
+                },
+                MetadataValue::Image {
+                    data_type: DataType::Png,
+                    data: b"png",
+                },
+            ]
+        );
+        assert_eq!(write(&parsed), data);
+    }
+}
@@ -0,0 +1,118 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, FourCC, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeyEntry {
+    /// Almost always `mdta`
+    pub namespace: FourCC,
+    /// Reverse DNS name, e.g. `com.apple.quicktime.make`
+    pub name: String,
+}
+
+impl KeyEntry {
+    fn get_size(&self) -> u64 {
+        8 + self.name.len() as u64
+    }
+}
+
+// The key table of an `mdta` meta box. Items of the `ilst` next to it are named by
+// their 1-based index into `entries` instead of a fourcc.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeysBox {
+    pub version: u8,
+    pub flags: u32,
+    pub entries: Vec<KeyEntry>,
+}
+
+impl KeysBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::KeysBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + 4 + self.entries.iter().map(KeyEntry::get_size).sum::<u64>()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<u32> {
+        let position = self.entries.iter().position(|entry| entry.name == name)?;
+        Some(position as u32 + 1)
+    }
+
+    pub fn get(&self, index: u32) -> Option<&KeyEntry> {
+        self.entries.get(index.checked_sub(1)? as usize)
+    }
+}
+
+impl Mp4Box for KeysBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for KeysBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let entry_count = BigEndian::read_u32(reader)?;
+        // Every entry takes at least its size and namespace
+        if u64::from(entry_count) > size.saturating_sub(HEADER_SIZE + HEADER_EXT_SIZE + 4) / 8 {
+            return Err(Error::invalid_value(
+                "keys entry_count indicates more entries than could fit in the box",
+            ));
+        }
+
+        let mut entries = Vec::with_capacity(entry_count as _);
+        for _ in 0..entry_count {
+            let key_size = BigEndian::read_u32(reader)?;
+            if key_size < 8 {
+                return Err(Error::invalid_value(
+                    "keys entry is smaller than its header",
+                ));
+            }
+            let namespace = FourCC::from(BigEndian::read_u32(reader)?);
+
+            let mut name = vec![0u8; key_size as usize - 8];
+            reader.read_exact(&mut name)?;
+
+            entries.push(KeyEntry {
+                namespace,
+                name: String::from_utf8_lossy(&name).into_owned(),
+            });
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            entries,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for KeysBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.entries.len() as u32)?;
+
+        for entry in &self.entries {
+            BigEndian::write_u32(writer, entry.get_size() as u32)?;
+            BigEndian::write_u32(writer, entry.namespace.into())?;
+            writer.write_all(entry.name.as_bytes())?;
+        }
+
+        Ok(size)
+    }
+}
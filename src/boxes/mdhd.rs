@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -58,7 +58,7 @@ impl Mp4Box for MdhdBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for MdhdBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
         let (creation_time, modification_time, timescale, duration) = if version == 1 {
@@ -76,10 +76,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MdhdBox {
                 BigEndian::read_u32(reader)? as u64,
             )
         } else {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "version must be 0 or 1",
-            ));
+            return Err(Error::unsupported_version(BoxType::MdhdBox, version, start));
         };
         let language_code = BigEndian::read_u16(reader)?;
         let language = language_string(language_code);
@@ -108,3 +105,42 @@ fn language_string(language_code: u16) -> String {
         .map(|r| r.unwrap_or(std::char::REPLACEMENT_CHARACTER))
         .collect::<String>()
 }
+
+impl<W: Write> WriteBox<&mut W> for MdhdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if self.version == 1 {
+            BigEndian::write_u64(writer, self.creation_time)?;
+            BigEndian::write_u64(writer, self.modification_time)?;
+            BigEndian::write_u32(writer, self.timescale)?;
+            BigEndian::write_u64(writer, self.duration)?;
+        } else if self.version == 0 {
+            BigEndian::write_u32(writer, self.creation_time as u32)?;
+            BigEndian::write_u32(writer, self.modification_time as u32)?;
+            BigEndian::write_u32(writer, self.timescale)?;
+            BigEndian::write_u32(writer, self.duration as u32)?;
+        } else {
+            return Err(Error::unsupported_version(self.box_type(), self.version, 0));
+        }
+
+        BigEndian::write_u16(writer, language_code(&self.language))?;
+        BigEndian::write_u16(writer, 0)?; // pre-defined
+
+        Ok(size)
+    }
+}
+
+fn language_code(language: &str) -> u16 {
+    let mut code = 0u16;
+    let mut chars = language.encode_utf16();
+
+    for shift in [10, 5, 0] {
+        let c = chars.next().unwrap_or(0x60);
+        code |= (c.wrapping_sub(0x60) & 0x1F) << shift;
+    }
+
+    code
+}
@@ -0,0 +1,118 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start,
+    boxes::{hdlr::HdlrBox, mdhd::MdhdBox, minf::MinfBox},
+    skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, ReadPayload, Result,
+    UnknownBox, WriteBox, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MdiaBox<B = Vec<u8>> {
+    pub mdhd: MdhdBox,
+    pub hdlr: HdlrBox,
+    pub minf: MinfBox<B>,
+    /// Children this crate doesn't model, written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+}
+
+impl<B: AsRef<[u8]>> MdiaBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::MdiaBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size =
+            HEADER_SIZE + self.mdhd.box_size() + self.hdlr.box_size() + self.minf.box_size();
+        for unknown in &self.unknown {
+            size += unknown.box_size();
+        }
+
+        size
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for MdiaBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for MdiaBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut mdhd = None;
+        let mut hdlr = None;
+        let mut minf = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::MdhdBox => {
+                    mdhd.replace(MdhdBox::read_child(reader, &header)?);
+                }
+                BoxType::HdlrBox => {
+                    hdlr.replace(HdlrBox::read_child(reader, &header)?);
+                }
+                BoxType::MinfBox => {
+                    minf.replace(MinfBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(mdhd) = mdhd else {
+            return Err(Error::missing_box(BoxType::MdhdBox, start));
+        };
+        let Some(hdlr) = hdlr else {
+            return Err(Error::missing_box(BoxType::HdlrBox, start));
+        };
+        let Some(minf) = minf else {
+            return Err(Error::missing_box(BoxType::MinfBox, start));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            mdhd,
+            hdlr,
+            minf,
+            unknown,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for MdiaBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        self.mdhd.write_box(writer)?;
+        self.hdlr.write_box(writer)?;
+        self.minf.write_box(writer)?;
+
+        for unknown in &self.unknown {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -41,7 +41,7 @@ impl Mp4Box for MehdBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for MehdBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
         let fragment_duration = if version == 1 {
@@ -49,10 +49,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MehdBox {
         } else if version == 0 {
             BigEndian::read_u32(reader)? as u64
         } else {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "version must be 0 or 1",
-            ));
+            return Err(Error::unsupported_version(BoxType::MehdBox, version, start));
         };
 
         skip_bytes_to(reader, start + size)?;
@@ -64,3 +61,21 @@ impl<R: Read + Seek> ReadBox<&mut R> for MehdBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for MehdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if self.version == 1 {
+            BigEndian::write_u64(writer, self.fragment_duration)?;
+        } else if self.version == 0 {
+            BigEndian::write_u32(writer, self.fragment_duration as u32)?;
+        } else {
+            return Err(Error::unsupported_version(self.box_type(), self.version, 0));
+        }
+
+        Ok(size)
+    }
+}
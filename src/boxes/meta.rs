@@ -0,0 +1,431 @@
+use std::io::{self, Read, Seek, Write};
+
+use crate::{
+    box_start,
+    boxes::{hdlr::HdlrBox, ilst::IlstBox, keys::KeysBox},
+    skip_box, write_box_header_ext, BigEndian, BoxHeader, BoxType, Error, FourCC, Metadata,
+    MetadataKey, MetadataValue, Mp4Box, ReadBox, ReadChild, ReadPayload, Result, WriteBox,
+    HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+const MDIR: FourCC = FourCC { value: *b"mdir" };
+const MDTA: FourCC = FourCC { value: *b"mdta" };
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetaBox<B = Vec<u8>> {
+    Mdir {
+        ilst: Option<IlstBox<B>>,
+    },
+    /// QuickTime metadata, the `ilst` items are keyed by their index into `keys`
+    Mdta {
+        keys: KeysBox,
+        ilst: Option<IlstBox<B>>,
+    },
+    Unknown {
+        hdlr: HdlrBox,
+        data: Vec<(BoxType, B)>,
+    },
+}
+
+impl<B: AsRef<[u8]>> MetaBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::MetaBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE;
+        match self {
+            Self::Mdir { ilst } => {
+                size += HdlrBox::default().box_size();
+                if let Some(ilstbox) = ilst {
+                    size += ilstbox.box_size();
+                }
+            }
+            Self::Mdta { keys, ilst } => {
+                size += HdlrBox::default().box_size() + keys.box_size();
+                if let Some(ilstbox) = ilst {
+                    size += ilstbox.box_size();
+                }
+            }
+            Self::Unknown { hdlr, data } => {
+                size += hdlr.box_size()
+                    + data
+                        .iter()
+                        .map(|(_, d)| d.as_ref().len() as u64 + HEADER_SIZE)
+                        .sum::<u64>();
+            }
+        }
+        size
+    }
+
+    // Value of a QuickTime key such as `com.apple.quicktime.make`
+    pub fn mdta_value(&self, name: &str) -> Option<MetadataValue<'_>> {
+        let Self::Mdta {
+            keys,
+            ilst: Some(ilst),
+        } = self
+        else {
+            return None;
+        };
+
+        let index = keys.index_of(name)?;
+        ilst.value(&MetadataKey::Other(FourCC::from(index)))
+    }
+
+    // All QuickTime keys that have a value, in the order of the `keys` box
+    pub fn mdta_values(&self) -> Vec<(&str, MetadataValue<'_>)> {
+        let Self::Mdta {
+            keys,
+            ilst: Some(ilst),
+        } = self
+        else {
+            return Vec::new();
+        };
+
+        (1..=keys.entries.len() as u32)
+            .filter_map(|index| {
+                let value = ilst.value(&MetadataKey::Other(FourCC::from(index)))?;
+                Some((keys.get(index)?.name.as_str(), value))
+            })
+            .collect()
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for MetaBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B> Default for MetaBox<B> {
+    fn default() -> Self {
+        Self::Unknown {
+            hdlr: Default::default(),
+            data: Default::default(),
+        }
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for MetaBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let extended_header = BigEndian::read_u32(reader)?;
+
+        if extended_header != 0 {
+            let num = BigEndian::read_u32(reader)?;
+            let possible_hdlr = BoxType::from(num);
+
+            if possible_hdlr == BoxType::HdlrBox {
+                reader.seek(io::SeekFrom::Current(-8))?;
+            } else {
+                let version = (extended_header >> 24) as u8;
+                return Err(Error::unsupported_version(BoxType::MetaBox, version, start));
+            }
+        }
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+        let content_start = current;
+
+        let mut hdlr: Option<HdlrBox> = None;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+            match header.name {
+                BoxType::HdlrBox => {
+                    hdlr.replace(HdlrBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    skip_box(reader, header.size)?;
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(hdlr) = hdlr else {
+            return Err(Error::missing_box(BoxType::HdlrBox, start));
+        };
+
+        reader.seek(io::SeekFrom::Start(content_start))?;
+        current = reader.stream_position()?;
+
+        let mut ilst = None;
+
+        if hdlr.handler_type == MDIR {
+            while current < end {
+                let header = BoxHeader::read(reader)?;
+                match header.name {
+                    BoxType::IlstBox => {
+                        ilst.replace(IlstBox::read_child(reader, &header)?);
+                    }
+                    _ => {
+                        skip_box(reader, header.size)?;
+                    }
+                }
+
+                current = reader.stream_position()?;
+            }
+
+            Ok(Self::Mdir { ilst })
+        } else if hdlr.handler_type == MDTA {
+            let mut keys = None;
+            let mut ilst = None;
+
+            while current < end {
+                let header = BoxHeader::read(reader)?;
+                match header.name {
+                    BoxType::KeysBox => {
+                        keys.replace(KeysBox::read_child(reader, &header)?);
+                    }
+                    BoxType::IlstBox => {
+                        ilst.replace(IlstBox::read_child(reader, &header)?);
+                    }
+                    _ => {
+                        skip_box(reader, header.size)?;
+                    }
+                }
+
+                current = reader.stream_position()?;
+            }
+
+            let Some(keys) = keys else {
+                return Err(Error::missing_box(BoxType::KeysBox, start));
+            };
+
+            Ok(Self::Mdta { keys, ilst })
+        } else {
+            let mut data = Vec::new();
+
+            while current < end {
+                let header = BoxHeader::read(reader)?;
+                if header.name == BoxType::HdlrBox {
+                    skip_box(reader, header.size)?;
+                } else {
+                    let box_data = reader.read_payload(header.size - HEADER_SIZE)?;
+                    data.push((header.name, box_data));
+                }
+
+                current = reader.stream_position()?;
+            }
+
+            Ok(Self::Unknown { hdlr, data })
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for MetaBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, 0, 0)?;
+
+        match self {
+            Self::Mdir { ilst } => {
+                let hdlr = HdlrBox {
+                    handler_type: MDIR,
+                    ..Default::default()
+                };
+                hdlr.write_box(writer)?;
+
+                if let Some(ilst) = ilst {
+                    ilst.write_box(writer)?;
+                }
+            }
+            Self::Mdta { keys, ilst } => {
+                let hdlr = HdlrBox {
+                    handler_type: MDTA,
+                    ..Default::default()
+                };
+                hdlr.write_box(writer)?;
+                keys.write_box(writer)?;
+
+                if let Some(ilst) = ilst {
+                    ilst.write_box(writer)?;
+                }
+            }
+            Self::Unknown { hdlr, data } => {
+                hdlr.write_box(writer)?;
+
+                for (box_type, box_data) in data {
+                    let box_data = box_data.as_ref();
+                    BoxHeader::new(*box_type, box_data.len() as u64 + HEADER_SIZE).write(writer)?;
+                    writer.write_all(box_data)?;
+                }
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::{
+        boxes::{
+            data::DataBox,
+            ilst::IlstItemBox,
+            keys::KeyEntry,
+            moov::MoovBox,
+            tests::{read, round_trip, write},
+        },
+        DataType, Location,
+    };
+
+    #[test]
+    fn mdir_round_trip() {
+        let mut ilst = IlstBox::default();
+        ilst.set(MetadataKey::Title, MetadataValue::Text(Cow::from("title")));
+        ilst.set(
+            MetadataKey::TrackNumber,
+            MetadataValue::Pair {
+                number: 3,
+                total: 12,
+            },
+        );
+        ilst.set(
+            MetadataKey::Freeform {
+                mean: "com.apple.iTunes".into(),
+                name: "iTunNORM".into(),
+            },
+            MetadataValue::Text(Cow::from(" 0000021C")),
+        );
+        let meta = MetaBox::Mdir { ilst: Some(ilst) };
+        assert_eq!(round_trip(&meta), meta);
+        assert_eq!(round_trip(&MetaBox::default()), MetaBox::default());
+    }
+
+    #[test]
+    fn mdta_round_trip() {
+        let mut ilst = IlstBox::default();
+        ilst.set(
+            MetadataKey::Other(FourCC::from(2)),
+            MetadataValue::Text(Cow::from("+48.8577+002.2950/")),
+        );
+        let meta = MetaBox::Mdta {
+            keys: KeysBox {
+                entries: [
+                    "com.apple.quicktime.make",
+                    "com.apple.quicktime.location.ISO6709",
+                ]
+                .into_iter()
+                .map(|name| KeyEntry {
+                    namespace: MDTA,
+                    name: name.into(),
+                })
+                .collect(),
+                ..Default::default()
+            },
+            ilst: Some(ilst),
+        };
+        let read = round_trip(&meta);
+        assert_eq!(read, meta);
+        assert_eq!(
+            read.mdta_value("com.apple.quicktime.location.ISO6709"),
+            Some(MetadataValue::Text(Cow::from("+48.8577+002.2950/")))
+        );
+    }
+
+    #[test]
+    fn mdta_values() {
+        let names = [
+            "com.apple.quicktime.make",
+            "com.apple.quicktime.model",
+            "com.apple.quicktime.location.ISO6709",
+            "com.apple.quicktime.software",
+            "com.apple.quicktime.content.identifier",
+            "com.android.capture.fps",
+            "com.apple.quicktime.full-frame-rate-playback-intent",
+        ];
+        let keys = KeysBox {
+            entries: names
+                .into_iter()
+                .map(|name| KeyEntry {
+                    namespace: MDTA,
+                    name: name.into(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        // the way phones type them, the model has no value
+        let values: [(u32, DataType, &[u8]); 6] = [
+            (1, DataType::Text, b"Apple"),
+            (3, DataType::Text, b"+37.3349-122.0090+012.345/"),
+            (4, DataType::Utf16, &[0, b'1', 0, b'7', 0, b'.', 0, b'1']),
+            (5, DataType::Text, b"8A1C4E2F"),
+            (6, DataType::Float32, &30f32.to_be_bytes()),
+            (7, DataType::TempoCpil, &[1]),
+        ];
+        let mut ilst = IlstBox::default();
+        for (index, data_type, data) in values {
+            ilst.items.insert(
+                MetadataKey::Other(FourCC::from(index)),
+                IlstItemBox {
+                    data: DataBox {
+                        data: data.to_vec(),
+                        data_type,
+                    },
+                    more_data: Vec::new(),
+                },
+            );
+        }
+
+        // QuickTime writes `meta` without the version and flags
+        let mut data = write(&MetaBox::Mdta {
+            keys,
+            ilst: Some(ilst),
+        });
+        data.drain(8..12);
+        let size = data.len() as u32;
+        data[..4].copy_from_slice(&size.to_be_bytes());
+        let meta = read::<MetaBox>(&data).unwrap();
+
+        assert_eq!(
+            meta.mdta_value("com.apple.quicktime.make"),
+            Some(MetadataValue::Text(Cow::from("Apple")))
+        );
+        assert_eq!(meta.mdta_value("com.apple.quicktime.model"), None);
+        assert_eq!(meta.mdta_value("com.apple.quicktime.author"), None);
+        assert_eq!(
+            meta.mdta_value("com.android.capture.fps"),
+            Some(MetadataValue::Float(30.0))
+        );
+        let listed: Vec<_> = meta
+            .mdta_values()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            listed,
+            [names[0], names[2], names[3], names[4], names[5], names[6]]
+        );
+
+        let moov = MoovBox {
+            meta: Some(meta),
+            ..Default::default()
+        };
+        assert_eq!(moov.make().as_deref(), Some("Apple"));
+        assert_eq!(moov.model(), None);
+        assert_eq!(moov.software().as_deref(), Some("17.1"));
+        assert_eq!(moov.content_identifier().as_deref(), Some("8A1C4E2F"));
+        assert_eq!(
+            moov.location(),
+            Some(Location {
+                latitude: 37.3349,
+                longitude: -122.009,
+                altitude: Some(12.345),
+            })
+        );
+        assert_eq!(
+            moov.quicktime_value("com.apple.quicktime.full-frame-rate-playback-intent"),
+            Some(MetadataValue::Integer(1))
+        );
+    }
+}
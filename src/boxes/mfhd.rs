@@ -1,8 +1,8 @@
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,7 +43,7 @@ impl Mp4Box for MfhdBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for MfhdBox {
-    fn read_box(reader: &mut R, size: u64) -> std::io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
         let sequence_number = BigEndian::read_u32(reader)?;
@@ -57,3 +57,15 @@ impl<R: Read + Seek> ReadBox<&mut R> for MfhdBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for MfhdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.sequence_number)?;
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,312 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_box, skip_bytes_to, write_box_header_ext, BigEndian,
+    BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, Result, WriteBox, HEADER_EXT_SIZE,
+    HEADER_SIZE,
+};
+
+// Random access index of a fragmented file, usually its last box
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MfraBox {
+    pub tfras: Vec<TfraBox>,
+    pub mfro: MfroBox,
+}
+
+impl MfraBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::MfraBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let tfras_size: u64 = self.tfras.iter().map(|tfra| tfra.box_size()).sum();
+        HEADER_SIZE + tfras_size + self.mfro.box_size()
+    }
+
+    pub fn tfra(&self, track_id: u32) -> Option<&TfraBox> {
+        self.tfras.iter().find(|tfra| tfra.track_id == track_id)
+    }
+}
+
+impl Mp4Box for MfraBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for MfraBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut tfras = Vec::new();
+        let mut mfro = None;
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::TfraBox => {
+                    tfras.push(TfraBox::read_child(reader, &header)?);
+                }
+                BoxType::MfroBox => {
+                    mfro.replace(MfroBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    skip_box(reader, header.size)?;
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(mfro) = mfro else {
+            return Err(Error::missing_box(BoxType::MfroBox, start));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self { tfras, mfro })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for MfraBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        for tfra in &self.tfras {
+            tfra.write_box(writer)?;
+        }
+        // the size is recomputed, as changing the tfras changes it
+        MfroBox {
+            parent_size: size as u32,
+            ..self.mfro
+        }
+        .write_box(writer)?;
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TfraEntry {
+    /// Presentation time of the sync sample, in the track's timescale
+    pub time: u64,
+    /// File offset of the `moof` holding the sample
+    pub moof_offset: u64,
+    /// 1-based numbers of the traf, trun and sample within the moof
+    pub traf_number: u32,
+    pub trun_number: u32,
+    pub sample_number: u32,
+}
+
+// Sync samples of a track and the fragments they are in
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TfraBox {
+    pub version: u8,
+    pub flags: u32,
+    pub track_id: u32,
+    /// Byte sizes of the traf, trun and sample numbers, minus one
+    pub length_size_of_traf_num: u8,
+    pub length_size_of_trun_num: u8,
+    pub length_size_of_sample_num: u8,
+    pub entries: Vec<TfraEntry>,
+}
+
+impl TfraBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::TfraBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + 12 + self.entry_size() * self.entries.len() as u64
+    }
+
+    fn entry_size(&self) -> u64 {
+        let times_size = if self.version == 1 { 16 } else { 8 };
+        times_size
+            + self.length_size_of_traf_num as u64
+            + self.length_size_of_trun_num as u64
+            + self.length_size_of_sample_num as u64
+            + 3
+    }
+}
+
+impl Mp4Box for TfraBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+fn read_number<R: Read>(reader: &mut R, length_size: u8) -> Result<u32> {
+    Ok(match length_size {
+        0 => BigEndian::read_u8(reader)? as u32,
+        1 => BigEndian::read_u16(reader)? as u32,
+        2 => BigEndian::read_u24(reader)?,
+        _ => BigEndian::read_u32(reader)?,
+    })
+}
+
+fn write_number<W: Write>(writer: &mut W, length_size: u8, n: u32) -> Result<()> {
+    match length_size {
+        0 => BigEndian::write_u8(writer, n as u8)?,
+        1 => BigEndian::write_u16(writer, n as u16)?,
+        2 => BigEndian::write_u24(writer, n)?,
+        _ => BigEndian::write_u32(writer, n)?,
+    }
+    Ok(())
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for TfraBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let track_id = BigEndian::read_u32(reader)?;
+        let length_sizes = BigEndian::read_u32(reader)?;
+        let mut tfra = Self {
+            version,
+            flags,
+            track_id,
+            length_size_of_traf_num: ((length_sizes >> 4) & 0x3) as u8,
+            length_size_of_trun_num: ((length_sizes >> 2) & 0x3) as u8,
+            length_size_of_sample_num: (length_sizes & 0x3) as u8,
+            entries: Vec::new(),
+        };
+
+        let entry_count = BigEndian::read_u32(reader)?;
+        if u64::from(entry_count) > (start + size - reader.stream_position()?) / tfra.entry_size() {
+            return Err(Error::invalid_value(
+                "tfra entry_count indicates more entries than could fit in the box",
+            ));
+        }
+
+        tfra.entries.reserve(entry_count as usize);
+        for _ in 0..entry_count {
+            let (time, moof_offset) = match version {
+                1 => (BigEndian::read_u64(reader)?, BigEndian::read_u64(reader)?),
+                _ => (
+                    BigEndian::read_u32(reader)? as u64,
+                    BigEndian::read_u32(reader)? as u64,
+                ),
+            };
+
+            tfra.entries.push(TfraEntry {
+                time,
+                moof_offset,
+                traf_number: read_number(reader, tfra.length_size_of_traf_num)?,
+                trun_number: read_number(reader, tfra.length_size_of_trun_num)?,
+                sample_number: read_number(reader, tfra.length_size_of_sample_num)?,
+            });
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(tfra)
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for TfraBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.track_id)?;
+        BigEndian::write_u32(
+            writer,
+            (self.length_size_of_traf_num as u32 & 0x3) << 4
+                | (self.length_size_of_trun_num as u32 & 0x3) << 2
+                | self.length_size_of_sample_num as u32 & 0x3,
+        )?;
+
+        BigEndian::write_u32(writer, self.entries.len() as u32)?;
+        for entry in &self.entries {
+            if self.version == 1 {
+                BigEndian::write_u64(writer, entry.time)?;
+                BigEndian::write_u64(writer, entry.moof_offset)?;
+            } else {
+                BigEndian::write_u32(writer, entry.time as u32)?;
+                BigEndian::write_u32(writer, entry.moof_offset as u32)?;
+            }
+            write_number(writer, self.length_size_of_traf_num, entry.traf_number)?;
+            write_number(writer, self.length_size_of_trun_num, entry.trun_number)?;
+            write_number(writer, self.length_size_of_sample_num, entry.sample_number)?;
+        }
+
+        Ok(size)
+    }
+}
+
+// Closes `mfra`, so readers can find it from the end of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MfroBox {
+    pub version: u8,
+    pub flags: u32,
+    /// Size of the enclosing `mfra`
+    pub parent_size: u32,
+}
+
+impl MfroBox {
+    pub const SIZE: u64 = HEADER_SIZE + HEADER_EXT_SIZE + 4;
+
+    fn get_type(&self) -> BoxType {
+        BoxType::MfroBox
+    }
+
+    fn get_size(&self) -> u64 {
+        Self::SIZE
+    }
+}
+
+impl Mp4Box for MfroBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for MfroBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+        let parent_size = BigEndian::read_u32(reader)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            parent_size,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for MfroBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.parent_size)?;
+
+        Ok(size)
+    }
+}
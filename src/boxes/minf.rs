@@ -0,0 +1,134 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start,
+    boxes::{dinf::DinfBox, smhd::SmhdBox, stbl::StblBox, vmhd::VmhdBox},
+    skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, ReadPayload, Result,
+    UnknownBox, WriteBox, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MinfBox<B = Vec<u8>> {
+    pub vmhd: Option<VmhdBox>,
+    pub smhd: Option<SmhdBox>,
+    pub dinf: DinfBox,
+    pub stbl: StblBox<B>,
+    /// Children this crate doesn't model, written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+}
+
+impl<B: AsRef<[u8]>> MinfBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::MinfBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE;
+        if let Some(ref vmhd) = self.vmhd {
+            size += vmhd.box_size()
+        }
+        if let Some(ref smhd) = self.smhd {
+            size += smhd.box_size()
+        }
+        size += self.dinf.box_size();
+        size += self.stbl.box_size();
+        for unknown in &self.unknown {
+            size += unknown.box_size();
+        }
+
+        size
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for MinfBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for MinfBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut vmhd = None;
+        let mut smhd = None;
+        let mut dinf = None;
+        let mut stbl = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::VmhdBox => {
+                    vmhd.replace(VmhdBox::read_child(reader, &header)?);
+                }
+                BoxType::SmhdBox => {
+                    smhd.replace(SmhdBox::read_child(reader, &header)?);
+                }
+                BoxType::DinfBox => {
+                    dinf.replace(DinfBox::read_child(reader, &header)?);
+                }
+                BoxType::StblBox => {
+                    stbl.replace(StblBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(dinf) = dinf else {
+            return Err(Error::missing_box(BoxType::DinfBox, start));
+        };
+        let Some(stbl) = stbl else {
+            return Err(Error::missing_box(BoxType::StblBox, start));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            vmhd,
+            smhd,
+            dinf,
+            stbl,
+            unknown,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for MinfBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        if let Some(ref vmhd) = self.vmhd {
+            vmhd.write_box(writer)?;
+        }
+        if let Some(ref smhd) = self.smhd {
+            smhd.write_box(writer)?;
+        }
+
+        self.dinf.write_box(writer)?;
+        self.stbl.write_box(writer)?;
+
+        for unknown in &self.unknown {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
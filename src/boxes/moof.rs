@@ -1,8 +1,10 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, mfhd::MfhdBox, skip_box, skip_bytes_to, traf::TrafBox, BoxHeader, BoxType, Mp4Box,
-    ReadBox, HEADER_SIZE,
+    box_start,
+    boxes::{mfhd::MfhdBox, pssh::PsshBox, traf::TrafBox},
+    skip_box, skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, Result,
+    WriteBox, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -10,6 +12,7 @@ pub struct MoofBox {
     pub start: u64,
     pub mfhd: MfhdBox,
     pub trafs: Vec<TrafBox>,
+    pub psshs: Vec<PsshBox>,
 }
 
 impl MoofBox {
@@ -22,6 +25,9 @@ impl MoofBox {
         for traf in &self.trafs {
             size += traf.box_size()
         }
+        for pssh in &self.psshs {
+            size += pssh.box_size()
+        }
 
         size
     }
@@ -38,11 +44,12 @@ impl Mp4Box for MoofBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for MoofBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
 
         let mut mfhd = None;
         let mut trafs = Vec::new();
+        let mut psshs = Vec::new();
 
         let mut current = reader.stream_position()?;
         let end = start + size;
@@ -51,20 +58,20 @@ impl<R: Read + Seek> ReadBox<&mut R> for MoofBox {
             let header = BoxHeader::read(reader)?;
 
             if header.size > size {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "moof box contains a box with a larger size than itself",
-                ));
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
             }
 
             match header.name {
                 BoxType::MfhdBox => {
-                    mfhd.replace(MfhdBox::read_box(reader, header.size)?);
+                    mfhd.replace(MfhdBox::read_child(reader, &header)?);
                 }
                 BoxType::TrafBox => {
-                    let traf = TrafBox::read_box(reader, header.size)?;
+                    let traf = TrafBox::read_child(reader, &header)?;
                     trafs.push(traf);
                 }
+                BoxType::PsshBox => {
+                    psshs.push(PsshBox::read_child(reader, &header)?);
+                }
                 _ => {
                     skip_box(reader, header.size)?;
                 }
@@ -73,14 +80,35 @@ impl<R: Read + Seek> ReadBox<&mut R> for MoofBox {
         }
 
         let Some(mfhd) = mfhd else {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                "mfhd box not found",
-            ));
+            return Err(Error::missing_box(BoxType::MfhdBox, start));
         };
 
         skip_bytes_to(reader, start + size)?;
 
-        Ok(Self { start, mfhd, trafs })
+        Ok(Self {
+            start,
+            mfhd,
+            trafs,
+            psshs,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for MoofBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        self.mfhd.write_box(writer)?;
+
+        for pssh in &self.psshs {
+            pssh.write_box(writer)?;
+        }
+
+        for traf in &self.trafs {
+            traf.write_box(writer)?;
+        }
+
+        Ok(size)
     }
 }
@@ -0,0 +1,338 @@
+use std::{
+    borrow::Cow,
+    io::{Read, Seek, Write},
+};
+
+use crate::{
+    box_start,
+    boxes::{
+        ilst::IlstBox, meta::MetaBox, mvex::MvexBox, mvhd::MvhdBox, pssh::PsshBox, trak::TrakBox,
+        udta::UdtaBox,
+    },
+    skip_bytes_to, BoxHeader, BoxType, Error, Location, Metadata, MetadataKey, MetadataValue,
+    Mp4Box, ReadBox, ReadChild, ReadPayload, Result, UnknownBox, WriteBox, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MoovBox<B = Vec<u8>> {
+    pub mvhd: MvhdBox,
+    pub meta: Option<MetaBox<B>>,
+    pub mvex: Option<MvexBox>,
+    pub traks: Vec<TrakBox<B>>,
+    pub udta: Option<UdtaBox<B>>,
+    pub psshs: Vec<PsshBox>,
+    /// Children this crate doesn't model, written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+}
+
+impl<B: AsRef<[u8]>> MoovBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::MoovBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + self.mvhd.box_size();
+
+        for trak in &self.traks {
+            size += trak.box_size();
+        }
+
+        if let Some(mvex) = &self.mvex {
+            size += mvex.box_size();
+        }
+        if let Some(meta) = &self.meta {
+            size += meta.box_size();
+        }
+        if let Some(udta) = &self.udta {
+            size += udta.box_size();
+        }
+        for pssh in &self.psshs {
+            size += pssh.box_size();
+        }
+        for unknown in &self.unknown {
+            size += unknown.box_size();
+        }
+
+        size
+    }
+
+    // QuickTime `mdta` metadata is usually in `moov/meta`, check `udta/meta` as well
+    pub fn quicktime_value(&self, name: &str) -> Option<MetadataValue<'_>> {
+        let udta_meta = self.udta.as_ref().and_then(|udta| udta.meta.as_ref());
+        [self.meta.as_ref(), udta_meta]
+            .into_iter()
+            .flatten()
+            .find_map(|meta| meta.mdta_value(name))
+    }
+
+    fn quicktime_text(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.quicktime_value(name)?.into_text()
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        Location::from_iso6709(&self.quicktime_text("com.apple.quicktime.location.ISO6709")?)
+    }
+
+    pub fn make(&self) -> Option<Cow<'_, str>> {
+        self.quicktime_text("com.apple.quicktime.make")
+    }
+
+    pub fn model(&self) -> Option<Cow<'_, str>> {
+        self.quicktime_text("com.apple.quicktime.model")
+    }
+
+    pub fn software(&self) -> Option<Cow<'_, str>> {
+        self.quicktime_text("com.apple.quicktime.software")
+    }
+
+    pub fn creation_date(&self) -> Option<Cow<'_, str>> {
+        self.quicktime_text("com.apple.quicktime.creationdate")
+    }
+
+    pub fn content_identifier(&self) -> Option<Cow<'_, str>> {
+        self.quicktime_text("com.apple.quicktime.content.identifier")
+    }
+}
+
+impl<B> MoovBox<B> {
+    // iTunes style metadata lives in `udta/meta`, some muxers put it in `moov/meta`
+    pub fn ilst(&self) -> Option<&IlstBox<B>> {
+        let udta_meta = self.udta.as_ref().and_then(|udta| udta.meta.as_ref());
+        [udta_meta, self.meta.as_ref()]
+            .into_iter()
+            .find_map(|meta| match meta {
+                Some(MetaBox::Mdir { ilst: Some(ilst) }) => Some(ilst),
+                _ => None,
+            })
+    }
+}
+
+impl MoovBox {
+    // Creates `udta/meta/ilst` if the file has no metadata yet. A `udta/meta` with a
+    // handler other than `mdir` is replaced.
+    pub fn ilst_mut(&mut self) -> &mut IlstBox {
+        let in_udta = matches!(
+            self.udta.as_ref().and_then(|udta| udta.meta.as_ref()),
+            Some(MetaBox::Mdir { ilst: Some(_) })
+        );
+        if !in_udta {
+            if let Some(MetaBox::Mdir { ilst }) = &mut self.meta {
+                return ilst.get_or_insert_with(Default::default);
+            }
+        }
+
+        let meta = self
+            .udta
+            .get_or_insert_with(Default::default)
+            .meta
+            .get_or_insert(MetaBox::Mdir { ilst: None });
+        if !matches!(meta, MetaBox::Mdir { .. }) {
+            *meta = MetaBox::Mdir { ilst: None };
+        }
+        let MetaBox::Mdir { ilst } = meta else {
+            unreachable!("meta was just replaced with an mdir meta");
+        };
+        ilst.get_or_insert_with(Default::default)
+    }
+}
+
+impl<'a, B: AsRef<[u8]>> Metadata<'a> for MoovBox<B> {
+    fn title(&self) -> Option<Cow<'_, str>> {
+        self.ilst()?.title()
+    }
+
+    fn year(&self) -> Option<u32> {
+        self.ilst()?.year()
+    }
+
+    fn poster(&self) -> Option<&[u8]> {
+        self.ilst()?.poster()
+    }
+
+    fn summary(&self) -> Option<Cow<'_, str>> {
+        self.ilst()?.summary()
+    }
+
+    fn value(&self, key: &MetadataKey) -> Option<MetadataValue<'_>> {
+        self.ilst()?.value(key)
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for MoovBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for MoovBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut mvhd = None;
+        let mut meta = None;
+        let mut udta = None;
+        let mut mvex = None;
+        let mut traks = Vec::new();
+        let mut psshs = Vec::new();
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::MvhdBox => {
+                    mvhd.replace(MvhdBox::read_child(reader, &header)?);
+                }
+                BoxType::MetaBox => {
+                    meta.replace(MetaBox::read_child(reader, &header)?);
+                }
+                BoxType::MvexBox => {
+                    mvex.replace(MvexBox::read_child(reader, &header)?);
+                }
+                BoxType::TrakBox => {
+                    let trak = TrakBox::read_child(reader, &header)?;
+                    traks.push(trak);
+                }
+                BoxType::UdtaBox => {
+                    udta.replace(UdtaBox::read_child(reader, &header)?);
+                }
+                BoxType::PsshBox => {
+                    psshs.push(PsshBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(mvhd) = mvhd else {
+            return Err(Error::missing_box(BoxType::MvhdBox, start));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            mvhd,
+            meta,
+            mvex,
+            traks,
+            udta,
+            psshs,
+            unknown,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for MoovBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        self.mvhd.write_box(writer)?;
+
+        for pssh in &self.psshs {
+            pssh.write_box(writer)?;
+        }
+        for trak in &self.traks {
+            trak.write_box(writer)?;
+        }
+        if let Some(mvex) = &self.mvex {
+            mvex.write_box(writer)?;
+        }
+        if let Some(udta) = &self.udta {
+            udta.write_box(writer)?;
+        }
+        if let Some(meta) = &self.meta {
+            meta.write_box(writer)?;
+        }
+
+        for unknown in &self.unknown {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, time::Duration};
+
+    use super::*;
+    use crate::{
+        boxes::{mp4a::Mp4aBox, stsd::StsdBoxContent, tests::round_trip},
+        Chapter, Edit, Mp4, Mp4Writer, Sample, TrackConfig,
+    };
+
+    #[test]
+    fn moov_round_trip() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let track_id = writer
+            .add_track(TrackConfig::new(
+                48000,
+                StsdBoxContent::Mp4a(Mp4aBox::default()),
+            ))
+            .unwrap();
+        writer
+            .set_edits(
+                track_id,
+                vec![Edit {
+                    presentation_time: 0,
+                    duration: 3 * 1024,
+                    media_time: Some(1024),
+                    media_rate: 1,
+                }],
+            )
+            .unwrap();
+        for id in 0..4u32 {
+            let sample = Sample {
+                id,
+                is_sync: true,
+                timescale: 48000,
+                decode_timestamp: id as i64 * 1024,
+                composition_timestamp: id as i64 * 1024,
+                duration: 1024,
+                sample_description_index: 1,
+                ..Default::default()
+            };
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 8])
+                .unwrap();
+        }
+        let mut moov = MoovBox::default();
+        moov.ilst_mut()
+            .set(MetadataKey::Title, MetadataValue::Text(Cow::from("title")));
+        writer.set_metadata(None, moov.udta);
+        writer.set_chapters(vec![Chapter {
+            start: Duration::ZERO,
+            title: String::from("chapter"),
+        }]);
+        let file = writer.finish().unwrap().into_inner();
+
+        let mp4 = Mp4::read(&mut Cursor::new(&file[..]), file.len() as u64).unwrap();
+        let read = round_trip(&mp4.moov);
+        assert_eq!(read.mvhd, mp4.moov.mvhd);
+        assert_eq!(read.traks.len(), 2);
+        assert_eq!(read.title(), Some(Cow::from("title")));
+        for trak in &mp4.moov.traks {
+            let read = round_trip(trak);
+            assert_eq!(read.tkhd, trak.tkhd);
+            assert_eq!(read.edts, trak.edts);
+            assert_eq!(read.tref, trak.tref);
+            assert_eq!(read.mdia.minf.stbl.stsz, trak.mdia.minf.stbl.stsz);
+        }
+    }
+}
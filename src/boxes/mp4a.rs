@@ -0,0 +1,986 @@
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_box, skip_bytes_to, write_box_header_ext, AacConfig,
+    BigEndian, BoxHeader, BoxType, Error, FixedPointU16, Mp4Box, ReadBox, ReadChild, Result,
+    UnknownBox, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mp4aBox {
+    pub data_reference_index: u16,
+    /// QuickTime sound description version, 0 in ISO files
+    pub version: u16,
+    pub revision_level: u16,
+    pub vendor: u32,
+    pub channelcount: u16,
+    pub samplesize: u16,
+    pub compression_id: u16,
+    pub packet_size: u16,
+    pub samplerate: FixedPointU16,
+    /// The fields QuickTime sound descriptions version 1 (16 bytes) and 2 (36 bytes) add
+    /// after `samplerate`, kept as read
+    pub qt_fields: Vec<u8>,
+    pub esds: Option<EsdsBox>,
+    /// `esds` was read from the QuickTime `wave` child, which is written back as read
+    /// with the other `unknown` children instead of a separate `esds`
+    pub esds_in_wave: bool,
+    /// Children this crate doesn't model (`btrt`, `chnl`, `wave`, ...), written back as read
+    pub unknown: Vec<UnknownBox>,
+    /// How many of the `unknown` children come before `esds`
+    pub unknown_before_esds: usize,
+}
+
+impl Default for Mp4aBox {
+    fn default() -> Self {
+        Self {
+            data_reference_index: 0,
+            version: 0,
+            revision_level: 0,
+            vendor: 0,
+            channelcount: 2,
+            samplesize: 16,
+            compression_id: 0,
+            packet_size: 0,
+            samplerate: FixedPointU16::new(48000),
+            qt_fields: Vec::new(),
+            esds: Some(EsdsBox::default()),
+            esds_in_wave: false,
+            unknown: Vec::new(),
+            unknown_before_esds: 0,
+        }
+    }
+}
+
+impl Mp4aBox {
+    pub fn new(config: &AacConfig) -> Self {
+        Self {
+            data_reference_index: 1,
+            channelcount: config.chan_conf as u16,
+            samplerate: FixedPointU16::new(config.freq_index.freq() as _),
+            esds: Some(EsdsBox::new(config)),
+            ..Default::default()
+        }
+    }
+
+    fn get_type(&self) -> BoxType {
+        BoxType::Mp4aBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + 8 + 20 + self.qt_fields.len() as u64;
+        if let Some(ref esds) = self.esds {
+            if !self.esds_in_wave {
+                size += esds.box_size();
+            }
+        }
+        size + self
+            .unknown
+            .iter()
+            .map(|unknown| unknown.box_size())
+            .sum::<u64>()
+    }
+}
+
+impl Mp4Box for Mp4aBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+// The `esds` inside the payload of a QuickTime `wave` box, which usually also holds
+// `frma`, a copy of `mp4a` and a terminator box
+fn wave_esds(payload: &[u8], offset: u64) -> Result<Option<EsdsBox>> {
+    let mut reader = Cursor::new(payload);
+    while reader.position() + HEADER_SIZE <= payload.len() as u64 {
+        let header = BoxHeader::read(&mut reader).map_err(|err| err.shifted(offset))?;
+        if header.size < HEADER_SIZE
+            || reader.position() - HEADER_SIZE + header.size > payload.len() as u64
+        {
+            break;
+        }
+        if header.name == BoxType::EsdsBox {
+            let esds =
+                EsdsBox::read_child(&mut reader, &header).map_err(|err| err.shifted(offset))?;
+            return Ok(Some(esds));
+        }
+        skip_box(&mut reader, header.size)?;
+    }
+
+    Ok(None)
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for Mp4aBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let _ = BigEndian::read_u16(reader)?; // reserved
+
+        let data_reference_index = BigEndian::read_u16(reader)?;
+        let version = BigEndian::read_u16(reader)?;
+        let revision_level = BigEndian::read_u16(reader)?;
+        let vendor = BigEndian::read_u32(reader)?;
+
+        let channelcount = BigEndian::read_u16(reader)?;
+        let samplesize = BigEndian::read_u16(reader)?;
+
+        let compression_id = BigEndian::read_u16(reader)?;
+        let packet_size = BigEndian::read_u16(reader)?;
+        let samplerate = FixedPointU16::new_raw(BigEndian::read_u32(reader)?);
+
+        let mut qt_fields = vec![
+            0u8;
+            match version {
+                1 => 16,
+                2 => 36,
+                _ => 0,
+            }
+        ];
+        reader.read_exact(&mut qt_fields)?;
+
+        let mut esds = None;
+        let mut esds_in_wave = false;
+        let mut unknown = Vec::new();
+        let mut unknown_before_esds = 0;
+        let end = start + size;
+
+        while reader.stream_position()? + HEADER_SIZE <= end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::EsdsBox if esds.is_none() => {
+                    esds = Some(EsdsBox::read_child(reader, &header)?);
+                    unknown_before_esds = unknown.len();
+                }
+                // the protection of encrypted entries is kept by `StsdBoxContent::Encrypted`
+                BoxType::SinfBox => {
+                    skip_box(reader, header.size)?;
+                }
+                _ => {
+                    let payload_start = reader.stream_position()?;
+                    let child = UnknownBox::read_child(reader, &header)?;
+                    if header.name == BoxType::WaveBox && esds.is_none() {
+                        esds = wave_esds(&child.payload, payload_start)?;
+                        esds_in_wave = esds.is_some();
+                    }
+                    unknown.push(child);
+                }
+            }
+        }
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(Self {
+            data_reference_index,
+            version,
+            revision_level,
+            vendor,
+            channelcount,
+            samplesize,
+            compression_id,
+            packet_size,
+            samplerate,
+            qt_fields,
+            esds,
+            esds_in_wave,
+            unknown,
+            unknown_before_esds,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for Mp4aBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, 0)?; // reserved
+
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+        BigEndian::write_u16(writer, self.version)?;
+        BigEndian::write_u16(writer, self.revision_level)?;
+        BigEndian::write_u32(writer, self.vendor)?;
+
+        BigEndian::write_u16(writer, self.channelcount)?;
+        BigEndian::write_u16(writer, self.samplesize)?;
+
+        BigEndian::write_u16(writer, self.compression_id)?;
+        BigEndian::write_u16(writer, self.packet_size)?;
+        BigEndian::write_u32(writer, self.samplerate.raw_value())?;
+        writer.write_all(&self.qt_fields)?;
+
+        let before = self.unknown_before_esds.min(self.unknown.len());
+        for unknown in &self.unknown[..before] {
+            unknown.write_box(writer)?;
+        }
+        if let Some(ref esds) = self.esds {
+            if !self.esds_in_wave {
+                esds.write_box(writer)?;
+            }
+        }
+        for unknown in &self.unknown[before..] {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EsdsBox {
+    pub version: u8,
+    pub flags: u32,
+    pub es_desc: ESDescriptor,
+}
+
+impl EsdsBox {
+    fn new(config: &AacConfig) -> Self {
+        Self {
+            version: 0,
+            flags: 0,
+            es_desc: ESDescriptor::new(config),
+        }
+    }
+}
+
+impl Mp4Box for EsdsBox {
+    fn box_type(&self) -> BoxType {
+        BoxType::EsdsBox
+    }
+
+    fn box_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + self.es_desc.total_size() as u64
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for EsdsBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let mut es_desc: Option<ESDescriptor> = None;
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let (desc_tag, desc_size, length_size) = read_desc(reader)?;
+            match desc_tag {
+                0x03 => {
+                    let mut desc = ESDescriptor::read_desc(reader, desc_size)?;
+                    desc.length_size = length_size;
+                    es_desc.replace(desc);
+                }
+                _ => break,
+            }
+            current = reader.stream_position()?;
+        }
+
+        let Some(es_desc) = es_desc else {
+            return Err(Error::invalid_value("ESDescriptor not found"));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            es_desc,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for EsdsBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        self.es_desc.write_desc(writer)?;
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ESDescriptor {
+    pub es_id: u16,
+    /// streamDependenceFlag, URL_Flag, OCRstreamFlag and streamPriority
+    pub flags: u8,
+    /// dependsOn_ES_ID, URL and OCR_ES_Id as selected by `flags`, kept as read
+    pub optional_fields: Vec<u8>,
+    pub dec_config: DecoderConfigDescriptor,
+    pub sl_config: SLConfifDescriptor,
+    /// Descriptors this crate doesn't model, tag and length included, written back as read
+    pub unknown: Vec<u8>,
+    /// Bytes the length was read with, padded lengths like `80 80 80 22` take 4. With 0
+    /// the fewest that fit are written.
+    pub length_size: u8,
+}
+
+impl ESDescriptor {
+    fn new(config: &AacConfig) -> Self {
+        Self {
+            es_id: 1,
+            dec_config: DecoderConfigDescriptor::new(config),
+            sl_config: SLConfifDescriptor::new(),
+            ..Default::default()
+        }
+    }
+}
+
+impl Descriptor for ESDescriptor {
+    fn desc_tag() -> u8 {
+        0x03
+    }
+
+    fn desc_size(&self) -> u32 {
+        3 + self.optional_fields.len() as u32
+            + self.dec_config.total_size()
+            + self.sl_config.total_size()
+            + self.unknown.len() as u32
+    }
+
+    fn length_size(&self) -> u8 {
+        self.length_size
+    }
+}
+
+impl<R: Read + Seek> ReadDesc<&mut R> for ESDescriptor {
+    fn read_desc(reader: &mut R, size: u32) -> Result<Self> {
+        let start = reader.stream_position()?;
+
+        let es_id = BigEndian::read_u16(reader)?;
+        let flags = BigEndian::read_u8(reader)?;
+
+        let mut optional_fields = Vec::new();
+        if flags & 0x80 != 0 {
+            optional_fields.extend(BigEndian::read_u16(reader)?.to_be_bytes()); // dependsOn_ES_ID
+        }
+        if flags & 0x40 != 0 {
+            let url_length = BigEndian::read_u8(reader)?;
+            let mut url = vec![0u8; url_length as usize];
+            reader.read_exact(&mut url)?;
+            optional_fields.push(url_length);
+            optional_fields.extend(url);
+        }
+        if flags & 0x20 != 0 {
+            optional_fields.extend(BigEndian::read_u16(reader)?.to_be_bytes()); // OCR_ES_Id
+        }
+
+        let mut dec_config: Option<DecoderConfigDescriptor> = None;
+        let mut sl_config: Option<SLConfifDescriptor> = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size as u64;
+
+        while current < end {
+            let (desc_tag, desc_size, length_size) = read_desc(reader)?;
+            match desc_tag {
+                0x04 => {
+                    let mut desc = DecoderConfigDescriptor::read_desc(reader, desc_size)?;
+                    desc.length_size = length_size;
+                    dec_config.replace(desc);
+                }
+                0x06 => {
+                    let mut desc = SLConfifDescriptor::read_desc(reader, desc_size)?;
+                    desc.length_size = length_size;
+                    sl_config.replace(desc);
+                }
+                _ => read_unknown_desc(reader, current, desc_size, &mut unknown)?,
+            }
+            current = reader.stream_position()?;
+        }
+
+        Ok(Self {
+            es_id,
+            flags,
+            optional_fields,
+            dec_config: dec_config.unwrap_or_default(),
+            sl_config: sl_config.unwrap_or_default(),
+            unknown,
+            length_size: 0,
+        })
+    }
+}
+
+impl<W: Write> WriteDesc<&mut W> for ESDescriptor {
+    fn write_desc(&self, writer: &mut W) -> Result<u32> {
+        let size = self.desc_size();
+        let header_size = write_desc(writer, Self::desc_tag(), size, self.length_size)?;
+
+        BigEndian::write_u16(writer, self.es_id)?;
+        BigEndian::write_u8(writer, self.flags)?;
+        writer.write_all(&self.optional_fields)?;
+
+        self.dec_config.write_desc(writer)?;
+        self.sl_config.write_desc(writer)?;
+        writer.write_all(&self.unknown)?;
+
+        Ok(header_size + size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DecoderConfigDescriptor {
+    pub object_type_indication: u8,
+    pub stream_type: u8,
+    pub up_stream: u8,
+    pub buffer_size_db: u32,
+    pub max_bitrate: u32,
+    pub avg_bitrate: u32,
+    /// Absent for some object types, e.g. MP3 (0x6B)
+    pub dec_specific: Option<DecoderSpecificDescriptor>,
+    /// Descriptors this crate doesn't model, tag and length included, written back as read
+    pub unknown: Vec<u8>,
+    /// Bytes the length was read with, 0 for the fewest that fit
+    pub length_size: u8,
+}
+
+impl DecoderConfigDescriptor {
+    fn new(config: &AacConfig) -> Self {
+        Self {
+            object_type_indication: 0x40, // XXX AAC
+            stream_type: 0x05,            // XXX Audio
+            up_stream: 0,
+            buffer_size_db: 0,
+            max_bitrate: config.bitrate, // XXX
+            avg_bitrate: config.bitrate,
+            dec_specific: Some(DecoderSpecificDescriptor::new(config)),
+            unknown: Vec::new(),
+            length_size: 0,
+        }
+    }
+}
+
+impl Descriptor for DecoderConfigDescriptor {
+    fn desc_tag() -> u8 {
+        0x04
+    }
+
+    fn desc_size(&self) -> u32 {
+        13 + self
+            .dec_specific
+            .as_ref()
+            .map_or(0, |dec_specific| dec_specific.total_size())
+            + self.unknown.len() as u32
+    }
+
+    fn length_size(&self) -> u8 {
+        self.length_size
+    }
+}
+
+impl<R: Read + Seek> ReadDesc<&mut R> for DecoderConfigDescriptor {
+    fn read_desc(reader: &mut R, size: u32) -> Result<Self> {
+        let start = reader.stream_position()?;
+
+        let object_type_indication = BigEndian::read_u8(reader)?;
+        let byte_a = BigEndian::read_u8(reader)?;
+        let stream_type = (byte_a & 0xFC) >> 2;
+        let up_stream = byte_a & 0x02;
+        let buffer_size_db = BigEndian::read_u24(reader)?;
+        let max_bitrate = BigEndian::read_u32(reader)?;
+        let avg_bitrate = BigEndian::read_u32(reader)?;
+
+        let mut dec_specific: Option<DecoderSpecificDescriptor> = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size as u64;
+
+        while current < end {
+            let (desc_tag, desc_size, length_size) = read_desc(reader)?;
+            match desc_tag {
+                0x05 => {
+                    let mut desc = DecoderSpecificDescriptor::read_desc(reader, desc_size)?;
+                    desc.length_size = length_size;
+                    dec_specific.replace(desc);
+                }
+                _ => read_unknown_desc(reader, current, desc_size, &mut unknown)?,
+            }
+            current = reader.stream_position()?;
+        }
+
+        Ok(Self {
+            object_type_indication,
+            stream_type,
+            up_stream,
+            buffer_size_db,
+            max_bitrate,
+            avg_bitrate,
+            dec_specific,
+            unknown,
+            length_size: 0,
+        })
+    }
+}
+
+impl<W: Write> WriteDesc<&mut W> for DecoderConfigDescriptor {
+    fn write_desc(&self, writer: &mut W) -> Result<u32> {
+        let size = self.desc_size();
+        let header_size = write_desc(writer, Self::desc_tag(), size, self.length_size)?;
+
+        BigEndian::write_u8(writer, self.object_type_indication)?;
+        BigEndian::write_u8(
+            writer,
+            (self.stream_type << 2) | (self.up_stream & 0x02) | 1, // 1 reserved
+        )?;
+        BigEndian::write_u24(writer, self.buffer_size_db)?;
+        BigEndian::write_u32(writer, self.max_bitrate)?;
+        BigEndian::write_u32(writer, self.avg_bitrate)?;
+
+        if let Some(dec_specific) = &self.dec_specific {
+            dec_specific.write_desc(writer)?;
+        }
+        writer.write_all(&self.unknown)?;
+
+        Ok(header_size + size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SLConfifDescriptor {
+    /// `predefined` (2 for MP4 files) and the fields following it when it's 0
+    pub data: Vec<u8>,
+    /// Bytes the length was read with, 0 for the fewest that fit
+    pub length_size: u8,
+}
+
+impl Default for SLConfifDescriptor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The AudioSpecificConfig of AAC streams. `profile`, `freq_index` and `chan_conf` are
+// decoded from `data` when reading, and only written when `data` is empty.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DecoderSpecificDescriptor {
+    pub profile: u8,
+    pub freq_index: u8,
+    pub chan_conf: u8,
+    /// The descriptor payload as read, e.g. with the explicit SBR signalling of HE-AAC
+    pub data: Vec<u8>,
+    /// Bytes the length was read with, 0 for the fewest that fit
+    pub length_size: u8,
+}
+
+trait Descriptor: Sized {
+    fn desc_tag() -> u8;
+    fn desc_size(&self) -> u32;
+    fn length_size(&self) -> u8;
+
+    // With the tag and length
+    fn total_size(&self) -> u32 {
+        let size = self.desc_size();
+        1 + size_of_length(size).max(self.length_size() as u32) + size
+    }
+}
+
+trait ReadDesc<T>: Sized {
+    fn read_desc(_: T, size: u32) -> Result<Self>;
+}
+
+trait WriteDesc<T>: Sized {
+    fn write_desc(&self, _: T) -> Result<u32>;
+}
+
+// The tag, payload size and, when it was padded, how many bytes the size took
+fn read_desc<R: Read>(reader: &mut R) -> Result<(u8, u32, u8)> {
+    let tag = BigEndian::read_u8(reader)?;
+    let mut size: u32 = 0;
+    let mut length_size = 0;
+
+    for _ in 0..4 {
+        let b = BigEndian::read_u8(reader)?;
+        size = (size << 7) | (b & 0x7F) as u32;
+        length_size += 1;
+
+        if b & 0x80 == 0 {
+            break;
+        }
+    }
+
+    // only padded lengths are worth remembering
+    if length_size as u32 == size_of_length(size) {
+        length_size = 0;
+    }
+
+    Ok((tag, size, length_size))
+}
+
+// Appends a descriptor whose tag and length were just read, `start` being where its
+// tag is
+fn read_unknown_desc<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    size: u32,
+    unknown: &mut Vec<u8>,
+) -> Result<()> {
+    let header_size = reader.stream_position()? - start;
+    reader.seek(SeekFrom::Start(start))?;
+    let len = unknown.len();
+    unknown.resize(len + (header_size + size as u64) as usize, 0);
+    reader.read_exact(&mut unknown[len..])?;
+    Ok(())
+}
+
+fn write_desc<W: Write>(writer: &mut W, tag: u8, size: u32, length_size: u8) -> Result<u32> {
+    BigEndian::write_u8(writer, tag)?;
+
+    let nbytes = size_of_length(size).max(length_size as u32);
+    for i in (0..nbytes).rev() {
+        let mut b = ((size >> (i * 7)) & 0x7F) as u8;
+        if i > 0 {
+            b |= 0x80;
+        }
+        BigEndian::write_u8(writer, b)?;
+    }
+
+    Ok(1 + nbytes)
+}
+
+fn size_of_length(size: u32) -> u32 {
+    match size {
+        0x0..=0x7F => 1,
+        0x80..=0x3FFF => 2,
+        0x4000..=0x1FFFFF => 3,
+        _ => 4,
+    }
+}
+
+// audioObjectType, samplingFrequencyIndex and channelConfiguration at the start of an
+// AudioSpecificConfig, bits past the end read as 0
+fn audio_specific_config(data: &[u8]) -> (u8, u8, u8) {
+    let mut bits = data
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1));
+    let mut read = |n: u32| {
+        (0..n).fold(0u32, |value, _| {
+            value << 1 | bits.next().unwrap_or(0) as u32
+        })
+    };
+
+    let mut profile = read(5);
+    if profile == 31 {
+        profile = 32 + read(6);
+    }
+    let freq_index = read(4);
+    if freq_index == 15 {
+        read(24); // samplingFrequency
+    }
+    let chan_conf = read(4);
+
+    (profile as u8, freq_index as u8, chan_conf as u8)
+}
+
+impl SLConfifDescriptor {
+    fn new() -> Self {
+        Self {
+            data: vec![2],
+            length_size: 0,
+        }
+    }
+}
+
+impl Descriptor for SLConfifDescriptor {
+    fn desc_tag() -> u8 {
+        0x06
+    }
+
+    fn desc_size(&self) -> u32 {
+        self.data.len() as u32
+    }
+
+    fn length_size(&self) -> u8 {
+        self.length_size
+    }
+}
+
+impl<R: Read + Seek> ReadDesc<&mut R> for SLConfifDescriptor {
+    fn read_desc(reader: &mut R, size: u32) -> Result<Self> {
+        let mut data = vec![0u8; size as usize];
+        reader.read_exact(&mut data)?;
+
+        Ok(Self {
+            data,
+            length_size: 0,
+        })
+    }
+}
+
+impl<W: Write> WriteDesc<&mut W> for SLConfifDescriptor {
+    fn write_desc(&self, writer: &mut W) -> Result<u32> {
+        let size = self.desc_size();
+        let header_size = write_desc(writer, Self::desc_tag(), size, self.length_size)?;
+
+        writer.write_all(&self.data)?;
+
+        Ok(header_size + size)
+    }
+}
+
+impl DecoderSpecificDescriptor {
+    pub fn new(config: &AacConfig) -> Self {
+        Self {
+            profile: config.profile as _,
+            freq_index: config.freq_index as _,
+            chan_conf: config.chan_conf as _,
+            ..Default::default()
+        }
+    }
+}
+
+impl Descriptor for DecoderSpecificDescriptor {
+    fn desc_tag() -> u8 {
+        0x05
+    }
+
+    fn desc_size(&self) -> u32 {
+        match self.data.len() {
+            0 => 2,
+            len => len as u32,
+        }
+    }
+
+    fn length_size(&self) -> u8 {
+        self.length_size
+    }
+}
+
+impl<R: Read + Seek> ReadDesc<&mut R> for DecoderSpecificDescriptor {
+    fn read_desc(reader: &mut R, size: u32) -> Result<Self> {
+        let mut data = vec![0u8; size as usize];
+        reader.read_exact(&mut data)?;
+        let (profile, freq_index, chan_conf) = audio_specific_config(&data);
+
+        Ok(Self {
+            profile,
+            freq_index,
+            chan_conf,
+            data,
+            length_size: 0,
+        })
+    }
+}
+
+impl<W: Write> WriteDesc<&mut W> for DecoderSpecificDescriptor {
+    fn write_desc(&self, writer: &mut W) -> Result<u32> {
+        let size = self.desc_size();
+        let header_size = write_desc(writer, Self::desc_tag(), size, self.length_size)?;
+
+        if self.data.is_empty() {
+            BigEndian::write_u8(writer, (self.profile << 3) | (self.freq_index >> 1))?;
+            BigEndian::write_u8(writer, (self.freq_index << 7) | (self.chan_conf << 3))?;
+        } else {
+            writer.write_all(&self.data)?;
+        }
+
+        Ok(header_size + size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{read, round_trip, write};
+
+    fn bx(name: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut data = (8 + payload.len() as u32).to_be_bytes().to_vec();
+        data.extend(name);
+        data.extend(payload);
+        data
+    }
+
+    // With a 4 byte length like ffmpeg writes, or a single byte one
+    fn desc(tag: u8, payload: &[u8], padded: bool) -> Vec<u8> {
+        let mut data = vec![tag];
+        if padded {
+            data.extend([0x80, 0x80, 0x80]);
+        }
+        data.push(payload.len() as u8);
+        data.extend(payload);
+        data
+    }
+
+    fn esds(es_flags: &[u8], dec_config: &[u8], padded: bool) -> Vec<u8> {
+        let mut es = vec![0, 1];
+        es.extend(es_flags);
+        es.extend(desc(0x04, dec_config, padded));
+        es.extend(desc(0x06, &[2], padded));
+        let mut payload = vec![0; 4];
+        payload.extend(desc(0x03, &es, padded));
+        bx(b"esds", &payload)
+    }
+
+    fn sample_entry(version: u16, qt_fields: &[u8], children: &[Vec<u8>]) -> Vec<u8> {
+        let mut payload = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        payload.extend(version.to_be_bytes());
+        payload.extend([0; 6]); // revision level, vendor
+        payload.extend([0, 2, 0, 16]);
+        payload.extend(if version == 0 { [0, 0] } else { [0xFF, 0xFE] }); // compression id
+        payload.extend([0, 0]);
+        payload.extend((44100u32 << 16).to_be_bytes());
+        payload.extend(qt_fields);
+        for child in children {
+            payload.extend(child);
+        }
+        bx(b"mp4a", &payload)
+    }
+
+    fn aac_config(dsi: &[u8]) -> Vec<u8> {
+        let mut dec_config = vec![0x40, 0x15, 0, 0x18, 0, 0, 1, 0xF4, 0, 0, 1, 0xF4, 0];
+        dec_config.extend(desc(0x05, dsi, true));
+        dec_config
+    }
+
+    #[test]
+    fn he_aac_round_trip() {
+        let btrt = bx(b"btrt", &[0; 12]);
+        let data = sample_entry(
+            0,
+            &[],
+            &[
+                esds(&[0], &aac_config(&[0x2b, 0x11, 0x88, 0, 0]), true),
+                btrt,
+            ],
+        );
+
+        let mp4a = read::<Mp4aBox>(&data).unwrap();
+        let dec_specific = mp4a
+            .esds
+            .as_ref()
+            .unwrap()
+            .es_desc
+            .dec_config
+            .dec_specific
+            .as_ref();
+        let dec_specific = dec_specific.unwrap();
+        assert_eq!(dec_specific.profile, 5);
+        assert_eq!(dec_specific.freq_index, 6);
+        assert_eq!(dec_specific.chan_conf, 2);
+        assert_eq!(dec_specific.data, [0x2b, 0x11, 0x88, 0, 0]);
+        assert_eq!(mp4a.unknown.len(), 1);
+        assert_eq!(mp4a.unknown_before_esds, 0);
+        assert_eq!(write(&mp4a), data);
+        assert_eq!(mp4a.box_size(), data.len() as u64);
+    }
+
+    #[test]
+    fn escaped_object_type_and_explicit_rate() {
+        // AOT 36 (ALS) escaped past 31, frequency index 15 with a 24-bit rate, 1 channel
+        let dsi = [0xF8, 0x9E, 0x01, 0x77, 0x00, 0x20];
+        let data = sample_entry(
+            0,
+            &[],
+            &[bx(b"chnl", &[0; 8]), esds(&[0], &aac_config(&dsi), false)],
+        );
+
+        let mp4a = read::<Mp4aBox>(&data).unwrap();
+        let esds = mp4a.esds.as_ref().unwrap();
+        let dec_specific = esds.es_desc.dec_config.dec_specific.as_ref().unwrap();
+        assert_eq!(
+            (
+                dec_specific.profile,
+                dec_specific.freq_index,
+                dec_specific.chan_conf
+            ),
+            (36, 15, 1)
+        );
+        assert_eq!(mp4a.unknown_before_esds, 1);
+        assert_eq!(write(&mp4a), data);
+    }
+
+    #[test]
+    fn mp3_without_decoder_specific_info() {
+        // dependsOn_ES_ID and a URL before the descriptors, and an unknown descriptor
+        let mut dec_config = vec![0x6B, 0x15, 0, 0, 0, 0, 2, 0x71, 0, 0, 2, 0x71, 0];
+        dec_config.extend(desc(0x14, &[0xFE], false));
+        let es_flags = [0xC0, 0, 3, 4, b'u', b'r', b'l', b'!'];
+        let data = sample_entry(0, &[], &[esds(&es_flags, &dec_config, false)]);
+
+        let mp4a = read::<Mp4aBox>(&data).unwrap();
+        let es_desc = &mp4a.esds.as_ref().unwrap().es_desc;
+        assert_eq!(es_desc.dec_config.object_type_indication, 0x6B);
+        assert_eq!(es_desc.dec_config.dec_specific, None);
+        assert_eq!(es_desc.dec_config.unknown, [0x14, 1, 0xFE]);
+        assert_eq!(es_desc.optional_fields, [0, 3, 4, b'u', b'r', b'l', b'!']);
+        assert_eq!(write(&mp4a), data);
+    }
+
+    #[test]
+    fn quicktime_sound_descriptions() {
+        let mut wave = bx(b"frma", b"mp4a");
+        wave.extend(esds(&[0], &aac_config(&[0x12, 0x10]), false));
+        wave.extend([0, 0, 0, 8, 0, 0, 0, 0]);
+        let data = sample_entry(1, &[1; 16], &[bx(b"wave", &wave)]);
+
+        let mp4a = read::<Mp4aBox>(&data).unwrap();
+        assert_eq!(mp4a.version, 1);
+        assert_eq!(mp4a.compression_id, 0xFFFE);
+        assert!(mp4a.esds_in_wave);
+        let dec_specific = mp4a
+            .esds
+            .as_ref()
+            .unwrap()
+            .es_desc
+            .dec_config
+            .dec_specific
+            .as_ref();
+        assert_eq!(dec_specific.unwrap().profile, 2);
+        assert_eq!(write(&mp4a), data);
+
+        let data = sample_entry(
+            2,
+            &[2; 36],
+            &[esds(&[0], &aac_config(&[0x12, 0x10]), false)],
+        );
+        let mp4a = read::<Mp4aBox>(&data).unwrap();
+        assert_eq!(mp4a.qt_fields, [2; 36]);
+        assert_eq!(write(&mp4a), data);
+    }
+
+    #[test]
+    fn new_round_trip() {
+        let mp4a = Mp4aBox::new(&AacConfig::default());
+        let read = round_trip(&mp4a);
+        let dec_specific = read
+            .esds
+            .as_ref()
+            .unwrap()
+            .es_desc
+            .dec_config
+            .dec_specific
+            .as_ref();
+        assert_eq!(dec_specific.unwrap().profile, 2);
+        assert_eq!(dec_specific.unwrap().data.len(), 2);
+    }
+
+    #[test]
+    fn sinf_is_not_a_child() {
+        let frma = bx(b"frma", b"mp4a");
+        let data = sample_entry(
+            0,
+            &[],
+            &[
+                esds(&[0], &aac_config(&[0x12, 0x10]), false),
+                bx(b"sinf", &frma),
+            ],
+        );
+        let mp4a: Mp4aBox = read(&data).unwrap();
+        assert!(mp4a.unknown.is_empty());
+        assert!(mp4a.esds.is_some());
+    }
+}
@@ -1,8 +1,10 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, mehd::MehdBox, skip_box, skip_bytes_to, trex::TrexBox, BoxHeader, BoxType, Mp4Box,
-    ReadBox, HEADER_SIZE,
+    box_start,
+    boxes::{mehd::MehdBox, trex::TrexBox},
+    skip_box, skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, Result,
+    WriteBox, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -34,7 +36,7 @@ impl Mp4Box for MvexBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for MvexBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
 
         let mut mehd: Option<MehdBox> = None;
@@ -46,18 +48,15 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvexBox {
         while current < end {
             let header = BoxHeader::read(reader)?;
             if header.size > size {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "mvex box contains a box with a larger size that itself",
-                ));
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
             }
 
             match header.name {
                 BoxType::MehdBox => {
-                    mehd.replace(MehdBox::read_box(reader, header.size)?);
+                    mehd.replace(MehdBox::read_child(reader, &header)?);
                 }
                 BoxType::TrexBox => {
-                    trexs.push(TrexBox::read_box(reader, header.size)?);
+                    trexs.push(TrexBox::read_child(reader, &header)?);
                 }
                 _ => {
                     skip_box(reader, header.size)?;
@@ -68,7 +67,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvexBox {
         }
 
         if trexs.is_empty() {
-            return Err(io::Error::new(io::ErrorKind::NotFound, "TrexBox not found"));
+            return Err(Error::missing_box(BoxType::TrexBox, start));
         }
 
         skip_bytes_to(reader, start + size)?;
@@ -76,3 +75,20 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvexBox {
         Ok(Self { mehd, trexs })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for MvexBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        if let Some(ref mehd) = self.mehd {
+            mehd.write_box(writer)?;
+        }
+
+        for trex in &self.trexs {
+            trex.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
@@ -1,8 +1,9 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes, skip_bytes_to, BigEndian, BoxType, FixedPointU16,
-    FixedPointU8, Matrix, Mp4Box, ReadBox, HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes, skip_bytes_to, write_box_header_ext, BigEndian,
+    BoxHeader, BoxType, Error, FixedPointU16, FixedPointU8, Matrix, Mp4Box, ReadBox, Result,
+    WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -66,7 +67,7 @@ impl Mp4Box for MvhdBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for MvhdBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
         let (creation_time, modification_time, timescale, duration) = if version == 1 {
@@ -84,10 +85,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvhdBox {
 
             (num0, num1, num2, num3)
         } else {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "version must be 0 or 1",
-            ));
+            return Err(Error::unsupported_version(BoxType::MvhdBox, version, start));
         };
 
         let num0 = BigEndian::read_u32(reader)?;
@@ -97,7 +95,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvhdBox {
         let volume = FixedPointU8::new_raw(num1);
 
         let _ = BigEndian::read_u16(reader)?; // reserved = 0
-        let _ = BigEndian::read_u16(reader)?; // reserved = 0
+        let _ = BigEndian::read_u64(reader)?; // reserved = 0
 
         let matrix = Matrix::read_i32(reader)?;
 
@@ -121,3 +119,39 @@ impl<R: Read + Seek> ReadBox<&mut R> for MvhdBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for MvhdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if self.version == 1 {
+            BigEndian::write_u64(writer, self.creation_time)?;
+            BigEndian::write_u64(writer, self.modification_time)?;
+            BigEndian::write_u32(writer, self.timescale)?;
+            BigEndian::write_u64(writer, self.duration)?;
+        } else if self.version == 0 {
+            BigEndian::write_u32(writer, self.creation_time as u32)?;
+            BigEndian::write_u32(writer, self.modification_time as u32)?;
+            BigEndian::write_u32(writer, self.timescale)?;
+            BigEndian::write_u32(writer, self.duration as u32)?;
+        } else {
+            return Err(Error::unsupported_version(self.box_type(), self.version, 0));
+        }
+
+        BigEndian::write_u32(writer, self.rate.raw_value())?;
+        BigEndian::write_u16(writer, self.volume.raw_value())?;
+
+        BigEndian::write_u16(writer, 0)?; // reserved = 0
+        BigEndian::write_u64(writer, 0)?; // reserved = 0
+
+        self.matrix.write_i32(writer)?;
+
+        writer.write_all(&[0u8; 24])?; // pre-defined = 0
+
+        BigEndian::write_u32(writer, self.next_track_id)?;
+
+        Ok(size)
+    }
+}
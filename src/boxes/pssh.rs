@@ -0,0 +1,187 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+// DRM system specific header, e.g. a Widevine or PlayReady license request payload
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PsshBox {
+    pub version: u8,
+    pub flags: u32,
+    pub system_id: [u8; 16],
+    /// Version 1 only
+    pub kids: Vec<[u8; 16]>,
+    pub data: Vec<u8>,
+}
+
+impl PsshBox {
+    pub const WIDEVINE: [u8; 16] = [
+        0xed, 0xef, 0x8b, 0xa9, 0x79, 0xd6, 0x4a, 0xce, 0xa3, 0xc8, 0x27, 0xdc, 0xd5, 0x1d, 0x21,
+        0xed,
+    ];
+    pub const PLAYREADY: [u8; 16] = [
+        0x9a, 0x04, 0xf0, 0x79, 0x98, 0x40, 0x42, 0x86, 0xab, 0x92, 0xe6, 0x5b, 0xe0, 0x88, 0x5f,
+        0x95,
+    ];
+    pub const FAIRPLAY: [u8; 16] = [
+        0x94, 0xce, 0x86, 0xfb, 0x07, 0xff, 0x4f, 0x43, 0xad, 0xb8, 0x93, 0xd2, 0xfa, 0x96, 0x8c,
+        0xa2,
+    ];
+    pub const CLEARKEY: [u8; 16] = [
+        0x10, 0x77, 0xef, 0xec, 0xc0, 0xb2, 0x4d, 0x02, 0xac, 0xe3, 0x3c, 0x1e, 0x52, 0xe2, 0xfb,
+        0x4b,
+    ];
+
+    fn get_type(&self) -> BoxType {
+        BoxType::PsshBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 16 + 4 + self.data.len() as u64;
+        if self.version > 0 {
+            size += 4 + 16 * self.kids.len() as u64;
+        }
+        size
+    }
+}
+
+impl Mp4Box for PsshBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for PsshBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let mut system_id = [0u8; 16];
+        reader.read_exact(&mut system_id)?;
+
+        let mut kids = Vec::new();
+        if version > 0 {
+            let kid_count = BigEndian::read_u32(reader)?;
+            if u64::from(kid_count) > size.saturating_sub(HEADER_SIZE + HEADER_EXT_SIZE + 24) / 16 {
+                return Err(Error::invalid_value(
+                    "pssh kid_count indicates more entries than could fit in the box",
+                ));
+            }
+            for _ in 0..kid_count {
+                let mut kid = [0u8; 16];
+                reader.read_exact(&mut kid)?;
+                kids.push(kid);
+            }
+        }
+
+        let data_size = BigEndian::read_u32(reader)?;
+        let Some(remaining) = (start + size).checked_sub(reader.stream_position()?) else {
+            return Err(Error::truncated(BoxType::PsshBox, start));
+        };
+        if u64::from(data_size) > remaining {
+            return Err(Error::invalid_value("pssh data_size exceeds the box"));
+        }
+        let mut data = vec![0u8; data_size as usize];
+        reader.read_exact(&mut data)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            system_id,
+            kids,
+            data,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for PsshBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        writer.write_all(&self.system_id)?;
+        if self.version > 0 {
+            BigEndian::write_u32(writer, self.kids.len() as u32)?;
+            for kid in &self.kids {
+                writer.write_all(kid)?;
+            }
+        }
+        BigEndian::write_u32(writer, self.data.len() as u32)?;
+        writer.write_all(&self.data)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{read, round_trip, write};
+
+    #[test]
+    fn pssh_round_trip() {
+        let pssh = PsshBox {
+            version: 0,
+            flags: 0,
+            system_id: PsshBox::WIDEVINE,
+            kids: Vec::new(),
+            data: vec![8, 1, 18, 16],
+        };
+        assert_eq!(round_trip(&pssh), pssh);
+
+        let pssh = PsshBox {
+            version: 1,
+            flags: 0,
+            system_id: PsshBox::CLEARKEY,
+            kids: vec![[1; 16], [2; 16]],
+            data: Vec::new(),
+        };
+        assert_eq!(round_trip(&pssh), pssh);
+    }
+
+    #[test]
+    fn pssh_malformed_size() {
+        let pssh = PsshBox {
+            system_id: PsshBox::PLAYREADY,
+            data: vec![0; 10],
+            ..Default::default()
+        };
+        let mut data = write(&pssh);
+
+        data[3] = 30;
+        assert!(matches!(
+            read::<PsshBox>(&data),
+            Err(Error::Truncated {
+                box_type: BoxType::PsshBox,
+                ..
+            })
+        ));
+
+        data[3] = 40;
+        assert!(matches!(
+            read::<PsshBox>(&data),
+            Err(Error::InvalidValue { .. })
+        ));
+
+        let kids = PsshBox {
+            version: 1,
+            kids: vec![[1; 16]],
+            ..Default::default()
+        };
+        let mut data = write(&kids);
+        data[31] = 2;
+        assert!(matches!(
+            read::<PsshBox>(&data),
+            Err(Error::InvalidValue { .. })
+        ));
+    }
+}
@@ -0,0 +1,163 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, FourCC, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+// Where the sample auxiliary information is stored. In `stbl` offsets are absolute
+// with one per chunk (or a single one for all samples), in `traf` they're relative
+// to the fragment's base data offset.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SaioBox {
+    pub version: u8,
+    pub flags: u32,
+    /// Present when `flags & 1`
+    pub aux_info_type: Option<FourCC>,
+    pub aux_info_type_parameter: u32,
+    pub offsets: Vec<u64>,
+}
+
+impl SaioBox {
+    pub const FLAG_AUX_INFO_TYPE: u32 = 0x000001;
+
+    fn get_type(&self) -> BoxType {
+        BoxType::SaioBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 4;
+        if self.aux_info_type.is_some() {
+            size += 8;
+        }
+        let offset_size = if self.version == 0 { 4 } else { 8 };
+        size + offset_size * self.offsets.len() as u64
+    }
+}
+
+impl Mp4Box for SaioBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SaioBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let (aux_info_type, aux_info_type_parameter) = if flags & Self::FLAG_AUX_INFO_TYPE != 0 {
+            (
+                Some(BigEndian::read_u32(reader)?.into()),
+                BigEndian::read_u32(reader)?,
+            )
+        } else {
+            (None, 0)
+        };
+
+        let entry_count = BigEndian::read_u32(reader)?;
+        let offset_size = if version == 0 { 4 } else { 8 };
+        let Some(remaining) = (start + size).checked_sub(reader.stream_position()?) else {
+            return Err(Error::truncated(BoxType::SaioBox, start));
+        };
+        if u64::from(entry_count) > remaining / offset_size {
+            return Err(Error::invalid_value(
+                "saio entry_count indicates more entries than could fit in the box",
+            ));
+        }
+
+        let mut offsets = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            offsets.push(match version {
+                0 => BigEndian::read_u32(reader)? as u64,
+                _ => BigEndian::read_u64(reader)?,
+            });
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            aux_info_type,
+            aux_info_type_parameter,
+            offsets,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SaioBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if let Some(aux_info_type) = self.aux_info_type {
+            BigEndian::write_u32(writer, aux_info_type.into())?;
+            BigEndian::write_u32(writer, self.aux_info_type_parameter)?;
+        }
+
+        BigEndian::write_u32(writer, self.offsets.len() as u32)?;
+        for offset in &self.offsets {
+            match self.version {
+                0 => BigEndian::write_u32(writer, *offset as u32)?,
+                _ => BigEndian::write_u64(writer, *offset)?,
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{read, round_trip, write};
+
+    #[test]
+    fn saio_round_trip() {
+        let saio = SaioBox {
+            version: 0,
+            flags: SaioBox::FLAG_AUX_INFO_TYPE,
+            aux_info_type: Some((*b"cbcs").into()),
+            aux_info_type_parameter: 0,
+            offsets: vec![1234],
+        };
+        assert_eq!(round_trip(&saio), saio);
+
+        let saio = SaioBox {
+            version: 1,
+            offsets: vec![u32::MAX as u64 + 1, 5],
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&saio), saio);
+    }
+
+    #[test]
+    fn saio_malformed_size() {
+        let saio = SaioBox {
+            offsets: vec![1, 2],
+            ..Default::default()
+        };
+        let mut data = write(&saio);
+
+        data[3] = 12;
+        assert!(matches!(
+            read::<SaioBox>(&data),
+            Err(Error::Truncated {
+                box_type: BoxType::SaioBox,
+                ..
+            })
+        ));
+
+        data[3] = 20;
+        assert!(matches!(
+            read::<SaioBox>(&data),
+            Err(Error::InvalidValue { .. })
+        ));
+    }
+}
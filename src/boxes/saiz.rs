@@ -0,0 +1,182 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, FourCC, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+// Sizes of the sample auxiliary information, e.g. CENC IVs and subsample maps
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SaizBox {
+    pub version: u8,
+    pub flags: u32,
+    /// Present when `flags & 1`, the protection scheme for CENC
+    pub aux_info_type: Option<FourCC>,
+    pub aux_info_type_parameter: u32,
+    /// Size shared by all samples, 0 if they have their own in `sample_info_sizes`
+    pub default_sample_info_size: u8,
+    pub sample_count: u32,
+    pub sample_info_sizes: Vec<u8>,
+}
+
+impl SaizBox {
+    pub const FLAG_AUX_INFO_TYPE: u32 = 0x000001;
+
+    fn get_type(&self) -> BoxType {
+        BoxType::SaizBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 5;
+        if self.aux_info_type.is_some() {
+            size += 8;
+        }
+        if self.default_sample_info_size == 0 {
+            size += self.sample_info_sizes.len() as u64;
+        }
+        size
+    }
+
+    pub fn sample_info_size(&self, index: usize) -> u8 {
+        match self.default_sample_info_size {
+            0 => self
+                .sample_info_sizes
+                .get(index)
+                .copied()
+                .unwrap_or_default(),
+            size => size,
+        }
+    }
+}
+
+impl Mp4Box for SaizBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SaizBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let (aux_info_type, aux_info_type_parameter) = if flags & Self::FLAG_AUX_INFO_TYPE != 0 {
+            (
+                Some(BigEndian::read_u32(reader)?.into()),
+                BigEndian::read_u32(reader)?,
+            )
+        } else {
+            (None, 0)
+        };
+
+        let default_sample_info_size = BigEndian::read_u8(reader)?;
+        let sample_count = BigEndian::read_u32(reader)?;
+
+        let mut sample_info_sizes = Vec::new();
+        if default_sample_info_size == 0 {
+            let Some(remaining) = (start + size).checked_sub(reader.stream_position()?) else {
+                return Err(Error::truncated(BoxType::SaizBox, start));
+            };
+            if u64::from(sample_count) > remaining {
+                return Err(Error::invalid_value(
+                    "saiz sample_count indicates more entries than could fit in the box",
+                ));
+            }
+            sample_info_sizes = vec![0u8; sample_count as usize];
+            reader.read_exact(&mut sample_info_sizes)?;
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            aux_info_type,
+            aux_info_type_parameter,
+            default_sample_info_size,
+            sample_count,
+            sample_info_sizes,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SaizBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if let Some(aux_info_type) = self.aux_info_type {
+            BigEndian::write_u32(writer, aux_info_type.into())?;
+            BigEndian::write_u32(writer, self.aux_info_type_parameter)?;
+        }
+
+        BigEndian::write_u8(writer, self.default_sample_info_size)?;
+        BigEndian::write_u32(writer, self.sample_count)?;
+        if self.default_sample_info_size == 0 {
+            writer.write_all(&self.sample_info_sizes)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{read, round_trip};
+
+    #[test]
+    fn saiz_round_trip() {
+        let saiz = SaizBox {
+            version: 0,
+            flags: SaizBox::FLAG_AUX_INFO_TYPE,
+            aux_info_type: Some((*b"cenc").into()),
+            aux_info_type_parameter: 0,
+            default_sample_info_size: 0,
+            sample_count: 3,
+            sample_info_sizes: vec![16, 22, 28],
+        };
+        assert_eq!(round_trip(&saiz), saiz);
+        assert_eq!(saiz.sample_info_size(1), 22);
+
+        let saiz = SaizBox {
+            default_sample_info_size: 8,
+            sample_count: 100,
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&saiz), saiz);
+        assert_eq!(saiz.sample_info_size(99), 8);
+    }
+
+    #[test]
+    fn saiz_malformed_size() {
+        let saiz = SaizBox {
+            sample_count: 3,
+            sample_info_sizes: vec![16, 22, 28],
+            ..Default::default()
+        };
+        let mut data = crate::boxes::tests::write(&saiz);
+
+        // declared smaller than its fixed fields
+        data[3] = 12;
+        assert!(matches!(
+            read::<SaizBox>(&data),
+            Err(Error::Truncated {
+                box_type: BoxType::SaizBox,
+                ..
+            })
+        ));
+
+        // sample_count past the end of the box
+        data[3] = 18;
+        assert!(matches!(
+            read::<SaizBox>(&data),
+            Err(Error::InvalidValue { .. })
+        ));
+    }
+}
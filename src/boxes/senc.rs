@@ -0,0 +1,216 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Subsample {
+    pub clear_bytes: u16,
+    pub encrypted_bytes: u32,
+}
+
+// Per-sample encryption parameters, from `senc` or the auxiliary information
+// `saiz`/`saio` point at. Without subsamples the whole sample is encrypted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SampleEncryption {
+    /// Per-sample IV, or the track's constant IV when it has no per-sample ones
+    pub iv: Vec<u8>,
+    pub subsamples: Vec<Subsample>,
+}
+
+impl SampleEncryption {
+    // Parses an entry at the start of `data`, returning it with its size
+    pub(crate) fn parse(data: &[u8], iv_size: u8, subsamples: bool) -> Option<(Self, usize)> {
+        let iv_size = iv_size as usize;
+        let iv = data.get(..iv_size)?.to_vec();
+        if !subsamples {
+            return Some((
+                Self {
+                    iv,
+                    subsamples: Vec::new(),
+                },
+                iv_size,
+            ));
+        }
+
+        let count = u16::from_be_bytes(data.get(iv_size..iv_size + 2)?.try_into().ok()?) as usize;
+        let entries = data.get(iv_size + 2..iv_size + 2 + count * 6)?;
+        let subsamples = entries
+            .chunks_exact(6)
+            .map(|entry| Subsample {
+                clear_bytes: u16::from_be_bytes([entry[0], entry[1]]),
+                encrypted_bytes: u32::from_be_bytes([entry[2], entry[3], entry[4], entry[5]]),
+            })
+            .collect();
+
+        Some((Self { iv, subsamples }, iv_size + 2 + count * 6))
+    }
+
+    fn write(&self, data: &mut Vec<u8>, subsamples: bool) {
+        data.extend(&self.iv);
+        if subsamples {
+            data.extend((self.subsamples.len() as u16).to_be_bytes());
+            for subsample in &self.subsamples {
+                data.extend(subsample.clear_bytes.to_be_bytes());
+                data.extend(subsample.encrypted_bytes.to_be_bytes());
+            }
+        }
+    }
+}
+
+// Sample encryption box of a track fragment. The IV size comes from the track's
+// `tenc`, so the entries are kept as raw bytes until `entries` is given it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SencBox {
+    pub version: u8,
+    pub flags: u32,
+    pub sample_count: u32,
+    pub data: Vec<u8>,
+}
+
+impl SencBox {
+    pub const FLAG_SUBSAMPLES: u32 = 0x000002;
+
+    pub fn new(entries: &[SampleEncryption]) -> Self {
+        let subsamples = entries.iter().any(|entry| !entry.subsamples.is_empty());
+        let mut data = Vec::new();
+        for entry in entries {
+            entry.write(&mut data, subsamples);
+        }
+
+        Self {
+            version: 0,
+            flags: if subsamples { Self::FLAG_SUBSAMPLES } else { 0 },
+            sample_count: entries.len() as u32,
+            data,
+        }
+    }
+
+    fn get_type(&self) -> BoxType {
+        BoxType::SencBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + HEADER_EXT_SIZE + 4 + self.data.len() as u64
+    }
+
+    pub fn entries(&self, iv_size: u8) -> Result<Vec<SampleEncryption>> {
+        let subsamples = self.flags & Self::FLAG_SUBSAMPLES != 0;
+        let mut entries = Vec::with_capacity(self.sample_count as usize);
+        let mut data = self.data.as_slice();
+
+        for _ in 0..self.sample_count {
+            let Some((entry, len)) = SampleEncryption::parse(data, iv_size, subsamples) else {
+                return Err(Error::invalid_value(
+                    "senc sample_count indicates more entries than could fit in the box",
+                ));
+            };
+            entries.push(entry);
+            data = &data[len..];
+        }
+
+        Ok(entries)
+    }
+}
+
+impl Mp4Box for SencBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SencBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let sample_count = BigEndian::read_u32(reader)?;
+        let mut data = vec![0u8; size.saturating_sub(HEADER_SIZE + HEADER_EXT_SIZE + 4) as usize];
+        reader.read_exact(&mut data)?;
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            sample_count,
+            data,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SencBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.sample_count)?;
+        writer.write_all(&self.data)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::round_trip;
+
+    #[test]
+    fn senc_round_trip() {
+        let entries = vec![
+            SampleEncryption {
+                iv: vec![1; 8],
+                subsamples: vec![Subsample {
+                    clear_bytes: 5,
+                    encrypted_bytes: 32,
+                }],
+            },
+            SampleEncryption {
+                iv: vec![2; 8],
+                subsamples: vec![
+                    Subsample {
+                        clear_bytes: 0,
+                        encrypted_bytes: 16,
+                    },
+                    Subsample {
+                        clear_bytes: 100,
+                        encrypted_bytes: 0,
+                    },
+                ],
+            },
+        ];
+        let senc = round_trip(&SencBox::new(&entries));
+        assert_eq!(senc.flags, SencBox::FLAG_SUBSAMPLES);
+        assert_eq!(senc.entries(8).unwrap(), entries);
+
+        let whole = vec![SampleEncryption {
+            iv: vec![9; 16],
+            subsamples: Vec::new(),
+        }];
+        assert_eq!(
+            round_trip(&SencBox::new(&whole)).entries(16).unwrap(),
+            whole
+        );
+    }
+
+    #[test]
+    fn senc_sample_count_past_the_box() {
+        let mut senc = SencBox::new(&[SampleEncryption {
+            iv: vec![1; 8],
+            subsamples: Vec::new(),
+        }]);
+        senc.sample_count = 2;
+        assert!(matches!(
+            round_trip(&senc).entries(8),
+            Err(Error::InvalidValue { .. })
+        ));
+    }
+}
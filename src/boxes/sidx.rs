@@ -0,0 +1,273 @@
+use std::{
+    io::{Read, Seek, Write},
+    ops::Range,
+};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SidxReferenceType {
+    /// The reference points at a subsegment (moofs and their mdats)
+    #[default]
+    Media,
+    /// The reference points at another `sidx`, which indexes the referenced range
+    Index,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SidxReference {
+    pub reference_type: SidxReferenceType,
+    pub referenced_size: u32,
+    pub subsegment_duration: u32,
+    pub starts_with_sap: bool,
+    pub sap_type: u8,
+    pub sap_delta_time: u32,
+}
+
+// A media subsegment resolved through a (possibly hierarchical) segment index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subsegment {
+    pub reference_id: u32,
+    pub timescale: u32,
+    pub earliest_presentation_time: u64,
+    pub duration: u32,
+    /// Byte range of the subsegment in the file
+    pub range: Range<u64>,
+    pub starts_with_sap: bool,
+    pub sap_type: u8,
+    pub sap_delta_time: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SidxBox {
+    /// Offset of the box in the file, references are relative to its end
+    pub start: u64,
+    pub version: u8,
+    pub flags: u32,
+    pub reference_id: u32,
+    pub timescale: u32,
+    pub earliest_presentation_time: u64,
+    pub first_offset: u64,
+    pub references: Vec<SidxReference>,
+}
+
+impl SidxBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::SidxBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let times_size = if self.version == 0 { 8 } else { 16 };
+        HEADER_SIZE + HEADER_EXT_SIZE + 8 + times_size + 4 + 12 * self.references.len() as u64
+    }
+
+    // The first byte after the box, which `first_offset` counts from
+    pub fn anchor(&self) -> u64 {
+        self.start + self.get_size()
+    }
+
+    // Byte range and earliest presentation time of every reference
+    pub fn reference_ranges(&self) -> Vec<(Range<u64>, u64)> {
+        let mut offset = self.anchor() + self.first_offset;
+        let mut time = self.earliest_presentation_time;
+
+        self.references
+            .iter()
+            .map(|reference| {
+                let range = offset..offset + reference.referenced_size as u64;
+                let start_time = time;
+                offset = range.end;
+                time += reference.subsegment_duration as u64;
+                (range, start_time)
+            })
+            .collect()
+    }
+}
+
+impl Mp4Box for SidxBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SidxBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let reference_id = BigEndian::read_u32(reader)?;
+        let timescale = BigEndian::read_u32(reader)?;
+        let (earliest_presentation_time, first_offset) = match version {
+            0 => (
+                BigEndian::read_u32(reader)? as u64,
+                BigEndian::read_u32(reader)? as u64,
+            ),
+            1 => (BigEndian::read_u64(reader)?, BigEndian::read_u64(reader)?),
+            _ => return Err(Error::unsupported_version(BoxType::SidxBox, version, start)),
+        };
+        BigEndian::read_u16(reader)?; // reserved
+
+        let reference_count = BigEndian::read_u16(reader)?;
+        if u64::from(reference_count) > (start + size - reader.stream_position()?) / 12 {
+            return Err(Error::invalid_value(
+                "sidx reference_count indicates more entries than could fit in the box",
+            ));
+        }
+
+        let mut references = Vec::with_capacity(reference_count as usize);
+        for _ in 0..reference_count {
+            let reference = BigEndian::read_u32(reader)?;
+            let subsegment_duration = BigEndian::read_u32(reader)?;
+            let sap = BigEndian::read_u32(reader)?;
+
+            references.push(SidxReference {
+                reference_type: match reference >> 31 {
+                    0 => SidxReferenceType::Media,
+                    _ => SidxReferenceType::Index,
+                },
+                referenced_size: reference & 0x7FFF_FFFF,
+                subsegment_duration,
+                starts_with_sap: sap >> 31 != 0,
+                sap_type: ((sap >> 28) & 0x7) as u8,
+                sap_delta_time: sap & 0x0FFF_FFFF,
+            });
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            start,
+            version,
+            flags,
+            reference_id,
+            timescale,
+            earliest_presentation_time,
+            first_offset,
+            references,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SidxBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.reference_id)?;
+        BigEndian::write_u32(writer, self.timescale)?;
+        if self.version == 0 {
+            BigEndian::write_u32(writer, self.earliest_presentation_time as u32)?;
+            BigEndian::write_u32(writer, self.first_offset as u32)?;
+        } else {
+            BigEndian::write_u64(writer, self.earliest_presentation_time)?;
+            BigEndian::write_u64(writer, self.first_offset)?;
+        }
+        BigEndian::write_u16(writer, 0)?; // reserved
+
+        BigEndian::write_u16(writer, self.references.len() as u16)?;
+        for reference in &self.references {
+            let reference_type = match reference.reference_type {
+                SidxReferenceType::Media => 0,
+                SidxReferenceType::Index => 1 << 31,
+            };
+            BigEndian::write_u32(writer, reference_type | reference.referenced_size)?;
+            BigEndian::write_u32(writer, reference.subsegment_duration)?;
+            BigEndian::write_u32(
+                writer,
+                (reference.starts_with_sap as u32) << 31
+                    | (reference.sap_type as u32 & 0x7) << 28
+                    | reference.sap_delta_time & 0x0FFF_FFFF,
+            )?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{read, round_trip, write};
+
+    fn references() -> Vec<SidxReference> {
+        vec![
+            SidxReference {
+                reference_type: SidxReferenceType::Index,
+                referenced_size: 2000,
+                subsegment_duration: 90000,
+                starts_with_sap: true,
+                sap_type: 1,
+                sap_delta_time: 0,
+            },
+            SidxReference {
+                reference_type: SidxReferenceType::Media,
+                referenced_size: 0x7FFF_FFFF,
+                subsegment_duration: 45000,
+                starts_with_sap: false,
+                sap_type: 3,
+                sap_delta_time: 0x0FFF_FFFF,
+            },
+        ]
+    }
+
+    #[test]
+    fn sidx_v0_round_trip() {
+        let sidx = SidxBox {
+            reference_id: 1,
+            timescale: 90000,
+            earliest_presentation_time: 3000,
+            first_offset: 100,
+            references: references(),
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&sidx), sidx);
+        assert_eq!(
+            sidx.reference_ranges(),
+            [
+                (sidx.box_size() + 100..sidx.box_size() + 2100, 3000),
+                (
+                    sidx.box_size() + 2100..sidx.box_size() + 2100 + 0x7FFF_FFFF,
+                    93000
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn sidx_v1_round_trip() {
+        let sidx = SidxBox {
+            version: 1,
+            reference_id: 2,
+            timescale: 48000,
+            earliest_presentation_time: u32::MAX as u64 + 1,
+            first_offset: u32::MAX as u64 + 2,
+            references: references(),
+            ..Default::default()
+        };
+        assert_eq!(sidx.box_size(), sidx.references.len() as u64 * 12 + 40);
+        assert_eq!(round_trip(&sidx), sidx);
+
+        let mut data = write(&sidx);
+        data[8] = 2;
+        assert!(matches!(
+            read::<SidxBox>(&data),
+            Err(Error::UnsupportedVersion { version: 2, .. })
+        ));
+
+        // a reference count past the end of the box
+        let mut data = write(&sidx);
+        data[38..40].copy_from_slice(&3u16.to_be_bytes());
+        assert!(matches!(
+            read::<SidxBox>(&data),
+            Err(Error::InvalidValue { .. })
+        ));
+    }
+}
@@ -0,0 +1,529 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, FourCC, Mp4Box, ReadBox, ReadChild, Result, UnknownBox, WriteBox,
+    HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+// Protection scheme information of an encrypted (`encv`, `enca`) sample entry
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SinfBox {
+    pub frma: FrmaBox,
+    pub schm: Option<SchmBox>,
+    pub schi: Option<SchiBox>,
+    /// Children this crate doesn't model (`imif`, ...), written back as read
+    pub unknown: Vec<UnknownBox>,
+}
+
+impl SinfBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::SinfBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + self.frma.box_size();
+        if let Some(schm) = &self.schm {
+            size += schm.box_size();
+        }
+        if let Some(schi) = &self.schi {
+            size += schi.box_size();
+        }
+        size + self
+            .unknown
+            .iter()
+            .map(|unknown| unknown.box_size())
+            .sum::<u64>()
+    }
+
+    // The sample entry type before encryption, e.g. `avc1`
+    pub fn original_format(&self) -> FourCC {
+        self.frma.original_format
+    }
+
+    // `cenc`, `cens`, `cbc1` or `cbcs`
+    pub fn scheme_type(&self) -> Option<FourCC> {
+        self.schm.as_ref().map(|schm| schm.scheme_type)
+    }
+
+    pub fn tenc(&self) -> Option<&TencBox> {
+        self.schi.as_ref().and_then(|schi| schi.tenc.as_ref())
+    }
+}
+
+impl Mp4Box for SinfBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SinfBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut frma = None;
+        let mut schm = None;
+        let mut schi = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::FrmaBox => {
+                    frma.replace(FrmaBox::read_child(reader, &header)?);
+                }
+                BoxType::SchmBox => {
+                    schm.replace(SchmBox::read_child(reader, &header)?);
+                }
+                BoxType::SchiBox => {
+                    schi.replace(SchiBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(frma) = frma else {
+            return Err(Error::missing_box(BoxType::FrmaBox, start));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            frma,
+            schm,
+            schi,
+            unknown,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SinfBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        self.frma.write_box(writer)?;
+        if let Some(schm) = &self.schm {
+            schm.write_box(writer)?;
+        }
+        if let Some(schi) = &self.schi {
+            schi.write_box(writer)?;
+        }
+        for unknown in &self.unknown {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FrmaBox {
+    pub original_format: FourCC,
+}
+
+impl FrmaBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::FrmaBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + 4
+    }
+}
+
+impl Mp4Box for FrmaBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for FrmaBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let original_format = BigEndian::read_u32(reader)?.into();
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self { original_format })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for FrmaBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, self.original_format.into())?;
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchmBox {
+    pub version: u8,
+    pub flags: u32,
+    pub scheme_type: FourCC,
+    /// `0x00010000` for version 1.0 of the common encryption schemes
+    pub scheme_version: u32,
+    /// Present when `flags & 1`
+    pub scheme_uri: Option<String>,
+}
+
+impl SchmBox {
+    pub const FLAG_SCHEME_URI: u32 = 0x000001;
+
+    fn get_type(&self) -> BoxType {
+        BoxType::SchmBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 8;
+        if let Some(uri) = &self.scheme_uri {
+            size += uri.len() as u64 + 1;
+        }
+        size
+    }
+}
+
+impl Mp4Box for SchmBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SchmBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let scheme_type = BigEndian::read_u32(reader)?.into();
+        let scheme_version = BigEndian::read_u32(reader)?;
+
+        let scheme_uri = if flags & Self::FLAG_SCHEME_URI != 0 {
+            let len = size.saturating_sub(HEADER_SIZE + HEADER_EXT_SIZE + 8) as usize;
+            let mut buf = vec![0u8; len];
+            reader.read_exact(&mut buf)?;
+            let end = buf.iter().position(|byte| *byte == 0).unwrap_or(len);
+            Some(String::from_utf8_lossy(&buf[..end]).into_owned())
+        } else {
+            None
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            scheme_type,
+            scheme_version,
+            scheme_uri,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SchmBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.scheme_type.into())?;
+        BigEndian::write_u32(writer, self.scheme_version)?;
+        if let Some(uri) = &self.scheme_uri {
+            writer.write_all(uri.as_bytes())?;
+            BigEndian::write_u8(writer, 0)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SchiBox {
+    pub tenc: Option<TencBox>,
+    /// Children this crate doesn't model, written back as read
+    pub unknown: Vec<UnknownBox>,
+}
+
+impl SchiBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::SchiBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + self.tenc.as_ref().map_or(0, |tenc| tenc.box_size())
+            + self
+                .unknown
+                .iter()
+                .map(|unknown| unknown.box_size())
+                .sum::<u64>()
+    }
+}
+
+impl Mp4Box for SchiBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SchiBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut tenc = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::TencBox => {
+                    tenc.replace(TencBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self { tenc, unknown })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SchiBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        if let Some(tenc) = &self.tenc {
+            tenc.write_box(writer)?;
+        }
+        for unknown in &self.unknown {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+// Default encryption parameters of a track
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TencBox {
+    pub version: u8,
+    pub flags: u32,
+    /// Pattern encryption (`cens`, `cbcs`), version 1 only
+    pub default_crypt_byte_block: u8,
+    pub default_skip_byte_block: u8,
+    pub default_is_protected: bool,
+    /// 0, 8 or 16. With 0 every sample uses `default_constant_iv`
+    pub default_per_sample_iv_size: u8,
+    pub default_kid: [u8; 16],
+    pub default_constant_iv: Option<Vec<u8>>,
+}
+
+impl TencBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::TencBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + HEADER_EXT_SIZE + 20;
+        if let Some(iv) = &self.default_constant_iv {
+            size += 1 + iv.len() as u64;
+        }
+        size
+    }
+}
+
+impl Mp4Box for TencBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for TencBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let _ = BigEndian::read_u8(reader)?; // reserved
+        let pattern = BigEndian::read_u8(reader)?;
+        let (default_crypt_byte_block, default_skip_byte_block) = match version {
+            0 => (0, 0),
+            _ => (pattern >> 4, pattern & 0x0F),
+        };
+        let default_is_protected = BigEndian::read_u8(reader)? != 0;
+        let default_per_sample_iv_size = BigEndian::read_u8(reader)?;
+        let mut default_kid = [0u8; 16];
+        reader.read_exact(&mut default_kid)?;
+
+        let default_constant_iv = if default_is_protected && default_per_sample_iv_size == 0 {
+            let len = BigEndian::read_u8(reader)?;
+            let mut iv = vec![0u8; len as usize];
+            reader.read_exact(&mut iv)?;
+            Some(iv)
+        } else {
+            None
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            default_crypt_byte_block,
+            default_skip_byte_block,
+            default_is_protected,
+            default_per_sample_iv_size,
+            default_kid,
+            default_constant_iv,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for TencBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u8(writer, 0)?; // reserved
+        match self.version {
+            0 => BigEndian::write_u8(writer, 0)?, // reserved
+            _ => BigEndian::write_u8(
+                writer,
+                self.default_crypt_byte_block << 4 | self.default_skip_byte_block & 0x0F,
+            )?,
+        }
+        BigEndian::write_u8(writer, self.default_is_protected as u8)?;
+        BigEndian::write_u8(writer, self.default_per_sample_iv_size)?;
+        writer.write_all(&self.default_kid)?;
+
+        if let Some(iv) = &self.default_constant_iv {
+            BigEndian::write_u8(writer, iv.len() as u8)?;
+            writer.write_all(iv)?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{read, round_trip, write};
+
+    #[test]
+    fn sinf_round_trip() {
+        let sinf = SinfBox {
+            frma: FrmaBox {
+                original_format: (*b"avc1").into(),
+            },
+            schm: Some(SchmBox {
+                version: 0,
+                flags: SchmBox::FLAG_SCHEME_URI,
+                scheme_type: (*b"cbcs").into(),
+                scheme_version: 0x00010000,
+                scheme_uri: Some(String::from("urn:example")),
+            }),
+            schi: Some(SchiBox {
+                tenc: Some(TencBox {
+                    version: 1,
+                    flags: 0,
+                    default_crypt_byte_block: 1,
+                    default_skip_byte_block: 9,
+                    default_is_protected: true,
+                    default_per_sample_iv_size: 0,
+                    default_kid: [7; 16],
+                    default_constant_iv: Some(vec![3; 16]),
+                }),
+                unknown: vec![UnknownBox {
+                    fourcc: (*b"fake").into(),
+                    payload: vec![1, 2, 3],
+                }],
+            }),
+            unknown: vec![UnknownBox {
+                fourcc: (*b"imif").into(),
+                payload: vec![0; 4],
+            }],
+        };
+        assert_eq!(round_trip(&sinf), sinf);
+        assert_eq!(sinf.original_format(), (*b"avc1").into());
+        assert_eq!(sinf.scheme_type(), Some((*b"cbcs").into()));
+    }
+
+    #[test]
+    fn tenc_round_trip() {
+        let tenc = TencBox {
+            version: 0,
+            flags: 0,
+            default_crypt_byte_block: 0,
+            default_skip_byte_block: 0,
+            default_is_protected: true,
+            default_per_sample_iv_size: 8,
+            default_kid: [1; 16],
+            default_constant_iv: None,
+        };
+        assert_eq!(round_trip(&tenc), tenc);
+    }
+
+    #[test]
+    fn sinf_without_frma() {
+        let mut children = write(&SchmBox::default());
+        children.extend(write(&SchiBox::default()));
+        let mut data = (8 + children.len() as u32).to_be_bytes().to_vec();
+        data.extend(b"sinf");
+        data.extend(children);
+        assert!(matches!(
+            read::<SinfBox>(&data),
+            Err(Error::MissingRequiredBox {
+                box_type: BoxType::FrmaBox,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SsixRange {
+    pub level: u8,
+    /// 24 bits
+    pub range_size: u32,
+}
+
+// Splits every subsegment of the preceding `sidx` into byte ranges per level
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SsixBox {
+    pub version: u8,
+    pub flags: u32,
+    pub subsegments: Vec<Vec<SsixRange>>,
+}
+
+impl SsixBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::SsixBox
+    }
+
+    fn get_size(&self) -> u64 {
+        self.subsegments
+            .iter()
+            .fold(HEADER_SIZE + HEADER_EXT_SIZE + 4, |size, ranges| {
+                size + 4 + 4 * ranges.len() as u64
+            })
+    }
+}
+
+impl Mp4Box for SsixBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for SsixBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+        let end = start + size;
+
+        let subsegment_count = BigEndian::read_u32(reader)?;
+        if u64::from(subsegment_count) > (end - reader.stream_position()?) / 4 {
+            return Err(Error::invalid_value(
+                "ssix subsegment_count indicates more entries than could fit in the box",
+            ));
+        }
+
+        let mut subsegments = Vec::with_capacity(subsegment_count as usize);
+        for _ in 0..subsegment_count {
+            let range_count = BigEndian::read_u32(reader)?;
+            if u64::from(range_count) > (end - reader.stream_position()?) / 4 {
+                return Err(Error::invalid_value(
+                    "ssix range_count indicates more entries than could fit in the box",
+                ));
+            }
+
+            let mut ranges = Vec::with_capacity(range_count as usize);
+            for _ in 0..range_count {
+                ranges.push(SsixRange {
+                    level: BigEndian::read_u8(reader)?,
+                    range_size: BigEndian::read_u24(reader)?,
+                });
+            }
+            subsegments.push(ranges);
+        }
+
+        skip_bytes_to(reader, end)?;
+
+        Ok(Self {
+            version,
+            flags,
+            subsegments,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for SsixBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.subsegments.len() as u32)?;
+        for ranges in &self.subsegments {
+            BigEndian::write_u32(writer, ranges.len() as u32)?;
+            for range in ranges {
+                BigEndian::write_u8(writer, range.level)?;
+                BigEndian::write_u24(writer, range.range_size)?;
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::round_trip;
+
+    #[test]
+    fn ssix_round_trip() {
+        let range = |level, range_size| SsixRange { level, range_size };
+        let ssix = SsixBox {
+            subsegments: vec![
+                vec![range(0, 1200), range(1, 0xFF_FFFF)],
+                Vec::new(),
+                vec![range(2, 64)],
+            ],
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&ssix), ssix);
+    }
+}
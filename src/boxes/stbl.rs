@@ -0,0 +1,217 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start,
+    boxes::{
+        co64::Co64Box, ctts::CttsBox, saio::SaioBox, saiz::SaizBox, stco::StcoBox, stsc::StscBox,
+        stsd::StsdBox, stss::StssBox, stsz::StszBox, stts::SttsBox,
+    },
+    skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, ReadPayload, Result,
+    UnknownBox, WriteBox, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StblBox<B = Vec<u8>> {
+    pub stsd: StsdBox<B>,
+    pub stts: SttsBox,
+    pub ctts: Option<CttsBox>,
+    pub stss: Option<StssBox>,
+    pub stsc: StscBox,
+    pub stsz: StszBox,
+    pub stco: Option<StcoBox>,
+    pub co64: Option<Co64Box>,
+    pub saiz: Option<SaizBox>,
+    pub saio: Option<SaioBox>,
+    /// Children this crate doesn't model, written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+}
+
+impl<B: AsRef<[u8]>> StblBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::StblBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE;
+        size += self.stsd.box_size();
+        size += self.stts.box_size();
+
+        if let Some(ref ctts) = self.ctts {
+            size += ctts.box_size()
+        }
+        if let Some(ref stss) = self.stss {
+            size += stss.box_size()
+        }
+
+        size += self.stsc.box_size();
+        size += self.stsz.box_size();
+
+        if let Some(ref stco) = self.stco {
+            size += stco.box_size()
+        }
+        if let Some(ref co64) = self.co64 {
+            size += co64.box_size()
+        }
+        if let Some(ref saiz) = self.saiz {
+            size += saiz.box_size()
+        }
+        if let Some(ref saio) = self.saio {
+            size += saio.box_size()
+        }
+        for unknown in &self.unknown {
+            size += unknown.box_size();
+        }
+
+        size
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for StblBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R> ReadBox<&mut R> for StblBox<B>
+where
+    R: Read + Seek + ReadPayload<B>,
+{
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut stsd = None;
+        let mut stts = None;
+        let mut ctts = None;
+        let mut stss = None;
+        let mut stsc = None;
+        let mut stsz = None;
+        let mut stco = None;
+        let mut co64 = None;
+        let mut saiz = None;
+        let mut saio = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::StsdBox => {
+                    stsd.replace(StsdBox::read_child(reader, &header)?);
+                }
+                BoxType::SttsBox => {
+                    stts.replace(SttsBox::read_child(reader, &header)?);
+                }
+                BoxType::CttsBox => {
+                    ctts.replace(CttsBox::read_child(reader, &header)?);
+                }
+                BoxType::StssBox => {
+                    stss.replace(StssBox::read_child(reader, &header)?);
+                }
+                BoxType::StscBox => {
+                    stsc.replace(StscBox::read_child(reader, &header)?);
+                }
+                BoxType::StszBox => {
+                    stsz.replace(StszBox::read_child(reader, &header)?);
+                }
+                BoxType::StcoBox => {
+                    stco.replace(StcoBox::read_child(reader, &header)?);
+                }
+                BoxType::Co64Box => {
+                    co64.replace(Co64Box::read_child(reader, &header)?);
+                }
+                BoxType::SaizBox => {
+                    saiz.replace(SaizBox::read_child(reader, &header)?);
+                }
+                BoxType::SaioBox => {
+                    saio.replace(SaioBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+            current = reader.stream_position()?;
+        }
+
+        let Some(stsd) = stsd else {
+            return Err(Error::missing_box(BoxType::StsdBox, start));
+        };
+        let Some(stts) = stts else {
+            return Err(Error::missing_box(BoxType::SttsBox, start));
+        };
+        let Some(stsc) = stsc else {
+            return Err(Error::missing_box(BoxType::StscBox, start));
+        };
+        let Some(stsz) = stsz else {
+            return Err(Error::missing_box(BoxType::StszBox, start));
+        };
+        if stco.is_none() && co64.is_none() {
+            return Err(Error::missing_box(BoxType::StcoBox, start));
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            stsd,
+            stts,
+            ctts,
+            stss,
+            stsc,
+            stsz,
+            stco,
+            co64,
+            saiz,
+            saio,
+            unknown,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for StblBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        self.stsd.write_box(writer)?;
+        self.stts.write_box(writer)?;
+
+        if let Some(ref ctts) = self.ctts {
+            ctts.write_box(writer)?;
+        }
+        if let Some(ref stss) = self.stss {
+            stss.write_box(writer)?;
+        }
+
+        self.stsc.write_box(writer)?;
+        self.stsz.write_box(writer)?;
+
+        if let Some(ref stco) = self.stco {
+            stco.write_box(writer)?;
+        }
+        if let Some(ref co64) = self.co64 {
+            co64.write_box(writer)?;
+        }
+        if let Some(ref saiz) = self.saiz {
+            saiz.write_box(writer)?;
+        }
+        if let Some(ref saio) = self.saio {
+            saio.write_box(writer)?;
+        }
+
+        for unknown in &self.unknown {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
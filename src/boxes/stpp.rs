@@ -0,0 +1,103 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, skip_bytes_to, BigEndian, BoxHeader, BoxType, Mp4Box, ReadBox, Result, WriteBox,
+    HEADER_SIZE,
+};
+
+// XML subtitle sample entry, samples are TTML documents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StppBox {
+    pub data_reference_index: u16,
+    /// Space separated XML namespaces, e.g. `http://www.w3.org/ns/ttml`
+    pub namespace: String,
+    pub schema_location: String,
+    pub auxiliary_mime_types: String,
+}
+
+impl Default for StppBox {
+    fn default() -> Self {
+        Self {
+            data_reference_index: 1,
+            namespace: String::from("http://www.w3.org/ns/ttml"),
+            schema_location: String::new(),
+            auxiliary_mime_types: String::new(),
+        }
+    }
+}
+
+impl StppBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::StppBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + 8
+            + self.namespace.len() as u64
+            + self.schema_location.len() as u64
+            + self.auxiliary_mime_types.len() as u64
+            + 3 // null terminators
+    }
+}
+
+impl Mp4Box for StppBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for StppBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let _ = BigEndian::read_u16(reader)?; // reserved
+        let data_reference_index = BigEndian::read_u16(reader)?;
+
+        // three null terminated strings, optionally followed by a `btrt` box
+        let mut rest = vec![0u8; size.saturating_sub(HEADER_SIZE + 8) as usize];
+        reader.read_exact(&mut rest)?;
+        let mut strings = rest
+            .split(|byte| *byte == 0)
+            .map(|string| String::from_utf8_lossy(string).into_owned());
+        let namespace = strings.next().unwrap_or_default();
+        let schema_location = strings.next().unwrap_or_default();
+        let auxiliary_mime_types = strings.next().unwrap_or_default();
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            data_reference_index,
+            namespace,
+            schema_location,
+            auxiliary_mime_types,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for StppBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+
+        for string in [
+            &self.namespace,
+            &self.schema_location,
+            &self.auxiliary_mime_types,
+        ] {
+            writer.write_all(string.as_bytes())?;
+            BigEndian::write_u8(writer, 0)?;
+        }
+
+        Ok(size)
+    }
+}
@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -41,7 +41,7 @@ impl Mp4Box for StscBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for StscBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -57,8 +57,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for StscBox {
                 .saturating_sub(other_size as u64)
                 / entry_size as u64
         {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
+            return Err(Error::invalid_value(
                 "stsc entry_count indicates more entries than it could fit in the box",
             ));
         }
@@ -91,8 +90,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for StscBox {
                     .checked_sub(first_chunk)
                     .and_then(|n| n.checked_mul(samples_per_chunk))
                     .and_then(|n| n.checked_add(sample_id))
-                    .ok_or(io::Error::new(
-                        io::ErrorKind::InvalidData,
+                    .ok_or(Error::invalid_value(
                         "attempt to calculate stsc sample_id with overflow",
                     ))?;
             }
@@ -107,3 +105,21 @@ impl<R: Read + Seek> ReadBox<&mut R> for StscBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for StscBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.entries.len() as u32)?;
+
+        for entry in &self.entries {
+            BigEndian::write_u32(writer, entry.first_chunk)?;
+            BigEndian::write_u32(writer, entry.samples_per_chunk)?;
+            BigEndian::write_u32(writer, entry.sample_description_index)?;
+        }
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,501 @@
+use std::{
+    fmt::Write,
+    io::{self, Read, Seek, SeekFrom},
+};
+
+use crate::{
+    box_start,
+    boxes::{
+        av01::Av01Box,
+        avc1::Avc1Box,
+        c608::C608Box,
+        hevc::{HevcBox, HevcDecoderConfigurationRecord},
+        mp4a::Mp4aBox,
+        sinf::SinfBox,
+        stpp::StppBox,
+        tx3g::Tx3gBox,
+        vp08::Vp08Box,
+        vp09::Vp09Box,
+        wvtt::WvttBox,
+    },
+    read_box_header_ext, skip_box, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, ReadChild, ReadPayload, Result, TrackKind, UnknownBox,
+    WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StsdBoxContent<B = Vec<u8>> {
+    Av01(Av01Box<B>),
+    Avc1(Avc1Box<B>),
+    Hvc1(HevcBox<B>),
+    Hev1(HevcBox<B>),
+    Vp08(Vp08Box<B>),
+    Vp09(Vp09Box<B>),
+    Mp4a(Mp4aBox),
+    Tx3g(Tx3gBox),
+    Wvtt(WvttBox),
+    Stpp(StppBox),
+    C608(C608Box),
+    /// `encv` or `enca`, the sample entry before encryption with its protection scheme
+    Encrypted {
+        entry: Box<StsdBoxContent<B>>,
+        sinf: SinfBox,
+    },
+    /// An entry this crate doesn't model, kept so it's written back as read. Encrypted
+    /// entries of such a format keep their `encv`/`enca` box whole, `sinf` included.
+    Unknown(UnknownBox<B>),
+}
+
+impl<B: Default> Default for StsdBoxContent<B> {
+    fn default() -> Self {
+        Self::Unknown(UnknownBox::default())
+    }
+}
+
+impl<B> StsdBoxContent<B> {
+    // The entry as it was before encryption, `self` for clear entries
+    pub fn original(&self) -> &Self {
+        match self {
+            Self::Encrypted { entry, .. } => entry,
+            _ => self,
+        }
+    }
+
+    pub fn protection(&self) -> Option<&SinfBox> {
+        match self {
+            Self::Encrypted { sinf, .. } => Some(sinf),
+            _ => None,
+        }
+    }
+
+    pub fn bit_depth(&self) -> Option<u8> {
+        match self {
+            Self::Av01(bx) => Some(bx.av1c.bit_depth),
+            Self::Avc1(_) => None, // TODO: figure out bit_depth
+            Self::Hvc1(_) => None, // TODO: figure out bit_depth
+            Self::Hev1(_) => None, // TODO: figure out bit_depth
+            Self::Vp08(bx) => Some(bx.vpcc.bit_depth),
+            Self::Vp09(bx) => Some(bx.vpcc.bit_depth),
+            Self::Mp4a(_)
+            | Self::Tx3g(_)
+            | Self::Wvtt(_)
+            | Self::Stpp(_)
+            | Self::C608(_)
+            | Self::Unknown(_) => None, // Not aplicable
+            Self::Encrypted { entry, .. } => entry.bit_depth(),
+        }
+    }
+
+    pub fn codec_string(&self) -> Option<String> {
+        match self {
+            Self::Av01(av01) => {
+                let profile = av01.av1c.profile;
+                let level = av01.av1c.level;
+                let tier = if av01.av1c.tier == 0 { "M" } else { "H" };
+                let bit_depth = av01.av1c.bit_depth;
+
+                Some(format!("av01.{profile}.{level:02}{tier}.{bit_depth:02}"))
+            }
+            Self::Avc1(avc1) => {
+                let profile = avc1.avcc.avc_profile_indication;
+                let constraint = avc1.avcc.profile_compatibility;
+                let level = avc1.avcc.avc_level_indication;
+
+                Some(format!("avc1.{profile:02X}{constraint:02X}{level:02X}"))
+            }
+            Self::Hvc1(hevc) => Some(format!("hvc1{}", hevc_codec_details(&hevc.hvcc))),
+            Self::Hev1(hevc) => Some(format!("hev1{}", hevc_codec_details(&hevc.hvcc))),
+            Self::Vp08(vp08) => {
+                let profile = vp08.vpcc.profile;
+                let level = vp08.vpcc.level;
+                let bit_depth = vp08.vpcc.bit_depth;
+
+                Some(format!("vp08.{profile:02}.{level:02}.{bit_depth:02}"))
+            }
+            Self::Vp09(vp09) => {
+                let profile = vp09.vpcc.profile;
+                let level = vp09.vpcc.level;
+                let bit_depth = vp09.vpcc.bit_depth;
+
+                Some(format!("vp09.{profile:02}.{level:02}.{bit_depth:02}"))
+            }
+            Self::Mp4a(_)
+            | Self::Tx3g(_)
+            | Self::Wvtt(_)
+            | Self::Stpp(_)
+            | Self::C608(_)
+            | Self::Unknown(_) => None,
+            Self::Encrypted { entry, .. } => entry.codec_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StsdBox<B = Vec<u8>> {
+    pub version: u8,
+    pub flags: u32,
+    pub entries: Vec<StsdBoxContent<B>>,
+}
+
+impl<B: AsRef<[u8]>> StsdBoxContent<B> {
+    pub fn kind(&self) -> Option<TrackKind> {
+        match self {
+            Self::Av01(_)
+            | Self::Avc1(_)
+            | Self::Hvc1(_)
+            | Self::Hev1(_)
+            | Self::Vp08(_)
+            | Self::Vp09(_) => Some(TrackKind::Video),
+            Self::Mp4a(_) => Some(TrackKind::Audio),
+            Self::Tx3g(_) | Self::Wvtt(_) | Self::Stpp(_) | Self::C608(_) => {
+                Some(TrackKind::Subtitle)
+            }
+            Self::Encrypted { entry, .. } => entry.kind(),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    pub fn raw_codec_config(&self) -> Option<&[u8]> {
+        match self {
+            Self::Av01(content) => Some(content.av1c.raw.as_ref()),
+            Self::Avc1(content) => Some(content.avcc.raw.as_ref()),
+            Self::Hev1(content) | Self::Hvc1(content) => Some(content.hvcc.raw.as_ref()),
+            Self::Vp08(content) => Some(content.vpcc.raw.as_ref()),
+            Self::Vp09(content) => Some(content.vpcc.raw.as_ref()),
+            Self::Mp4a(_)
+            | Self::Tx3g(_)
+            | Self::Wvtt(_)
+            | Self::Stpp(_)
+            | Self::C608(_)
+            | Self::Unknown(_) => None,
+            Self::Encrypted { entry, .. } => entry.raw_codec_config(),
+        }
+    }
+
+    fn entry_size(&self) -> u64 {
+        match self {
+            Self::Av01(contents) => contents.box_size(),
+            Self::Avc1(contents) => contents.box_size(),
+            Self::Hvc1(contents) | Self::Hev1(contents) => contents.box_size(),
+            Self::Vp08(contents) => contents.box_size(),
+            Self::Vp09(contents) => contents.box_size(),
+            Self::Mp4a(contents) => contents.box_size(),
+            Self::Tx3g(contents) => contents.box_size(),
+            Self::Wvtt(contents) => contents.box_size(),
+            Self::Stpp(contents) => contents.box_size(),
+            Self::C608(contents) => contents.box_size(),
+            Self::Encrypted { entry, .. } if matches!(**entry, Self::Unknown(_)) => {
+                entry.entry_size()
+            }
+            Self::Encrypted { entry, sinf } => entry.entry_size() + sinf.box_size(),
+            Self::Unknown(unknown) => unknown.box_size(),
+        }
+    }
+
+    fn write_entry<W: io::Write>(&self, writer: &mut W) -> Result<u64> {
+        match self {
+            Self::Av01(contents) => contents.write_box(writer),
+            Self::Avc1(contents) => contents.write_box(writer),
+            Self::Hvc1(contents) => contents.write_entry(writer, BoxType::Hvc1Box),
+            Self::Hev1(contents) => contents.write_entry(writer, BoxType::Hev1Box),
+            Self::Vp08(contents) => contents.write_box(writer),
+            Self::Vp09(contents) => contents.write_box(writer),
+            Self::Mp4a(contents) => contents.write_box(writer),
+            Self::Tx3g(contents) => contents.write_box(writer),
+            Self::Wvtt(contents) => contents.write_box(writer),
+            Self::Stpp(contents) => contents.write_box(writer),
+            Self::C608(contents) => contents.write_box(writer),
+            Self::Encrypted { entry, .. } if matches!(**entry, Self::Unknown(_)) => {
+                entry.write_entry(writer)
+            }
+            Self::Encrypted { entry, sinf } => {
+                let mut buf = Vec::new();
+                entry.write_entry(&mut buf)?;
+
+                // the original entry renamed, with `sinf` appended to its children
+                let size = buf.len() as u64 + sinf.box_size();
+                let name = match entry.kind() {
+                    Some(TrackKind::Audio) => BoxType::EncaBox,
+                    _ => BoxType::EncvBox,
+                };
+                buf[..4].copy_from_slice(&(size as u32).to_be_bytes());
+                buf[4..8].copy_from_slice(&u32::from(name).to_be_bytes());
+                writer.write_all(&buf)?;
+                sinf.write_box(writer)?;
+
+                Ok(size)
+            }
+            Self::Unknown(unknown) => unknown.write_box(writer),
+        }
+    }
+}
+
+impl<B: AsRef<[u8]>> StsdBox<B> {
+    // All entries of a track share its handler, the first one we recognize decides
+    pub fn kind(&self) -> Option<TrackKind> {
+        self.entries.iter().find_map(|entry| entry.kind())
+    }
+
+    // `sample_description_index` as found in `stsc` and `tfhd`/`trex`, starting at 1
+    pub fn entry(&self, sample_description_index: u32) -> Option<&StsdBoxContent<B>> {
+        self.entries
+            .get((sample_description_index as usize).checked_sub(1)?)
+    }
+
+    fn get_type(&self) -> BoxType {
+        BoxType::StsdBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + HEADER_EXT_SIZE
+            + 4
+            + self
+                .entries
+                .iter()
+                .map(|entry| entry.entry_size())
+                .sum::<u64>()
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for StsdBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for StsdBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let entry_count = BigEndian::read_u32(reader)?;
+
+        let mut entries = Vec::new();
+        let end = start + size;
+        for _ in 0..entry_count {
+            if reader.stream_position()? + HEADER_SIZE > end {
+                break;
+            }
+
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            let entry = read_entry(reader, &header)?;
+            entries.push(entry);
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            version,
+            flags,
+            entries,
+        })
+    }
+}
+
+fn read_entry<B, R: Read + Seek + ReadPayload<B>>(
+    reader: &mut R,
+    header: &BoxHeader,
+) -> Result<StsdBoxContent<B>> {
+    Ok(match header.name {
+        BoxType::Av01Box => StsdBoxContent::Av01(Av01Box::read_child(reader, header)?),
+        //
+        // According to MPEG-4 part 15, sections 5.4.2.1.2 and 5.4.4
+        // -- or the whole 5.4 section in general --
+        // the Avc1Box and Avc3Box are identical,
+        // but the Avc3Box is used in some cases
+        //
+        BoxType::Avc1Box => StsdBoxContent::Avc1(Avc1Box::read_child(reader, header)?),
+        BoxType::Hvc1Box => StsdBoxContent::Hvc1(HevcBox::read_child(reader, header)?),
+        BoxType::Hev1Box => StsdBoxContent::Hev1(HevcBox::read_child(reader, header)?),
+        BoxType::Vp08Box => StsdBoxContent::Vp08(Vp08Box::read_child(reader, header)?),
+        BoxType::Vp09Box => StsdBoxContent::Vp09(Vp09Box::read_child(reader, header)?),
+        BoxType::Mp4aBox => StsdBoxContent::Mp4a(Mp4aBox::read_child(reader, header)?),
+        BoxType::Tx3gBox => StsdBoxContent::Tx3g(Tx3gBox::read_child(reader, header)?),
+        BoxType::WvttBox => StsdBoxContent::Wvtt(WvttBox::read_child(reader, header)?),
+        BoxType::StppBox => StsdBoxContent::Stpp(StppBox::read_child(reader, header)?),
+        BoxType::C608Box => StsdBoxContent::C608(C608Box::read_child(reader, header)?),
+        BoxType::EncvBox | BoxType::EncaBox => read_encrypted_entry(reader, header)?,
+        _ => StsdBoxContent::Unknown(UnknownBox::read_child(reader, header)?),
+    })
+}
+
+// An encrypted entry has the layout of its original format plus a `sinf` child.
+// The fixed fields are skipped to find `sinf`, then the entry is read again as
+// the format `frma` names.
+fn read_encrypted_entry<B, R: Read + Seek + ReadPayload<B>>(
+    reader: &mut R,
+    header: &BoxHeader,
+) -> Result<StsdBoxContent<B>> {
+    let body = reader.stream_position()?;
+    let start = body - HEADER_SIZE;
+    let end = start + header.size;
+
+    let fields = if header.name == BoxType::EncaBox {
+        // sound sample entry versions 1 and 2 from QuickTime are longer
+        reader.seek(SeekFrom::Start(body + 8))?;
+        match BigEndian::read_u16(reader)? {
+            1 => 44,
+            2 => 64,
+            _ => 28,
+        }
+    } else {
+        78
+    };
+    reader.seek(SeekFrom::Start(body + fields))?;
+
+    let mut sinf = None;
+    while reader.stream_position()? + HEADER_SIZE <= end {
+        let child = BoxHeader::read(reader)?;
+        if child.size > header.size {
+            return Err(Error::size_overflow(child.name, box_start(reader)?));
+        }
+        if child.name == BoxType::SinfBox {
+            sinf = Some(SinfBox::read_child(reader, &child)?);
+            break;
+        }
+        skip_box(reader, child.size)?;
+    }
+    let Some(sinf) = sinf else {
+        return Err(Error::missing_box(BoxType::SinfBox, start));
+    };
+
+    let original = BoxHeader::new(u32::from(sinf.original_format()).into(), header.size);
+    if matches!(original.name, BoxType::EncvBox | BoxType::EncaBox) {
+        return Err(Error::invalid_value("frma names an encrypted format"));
+    }
+    reader.seek(SeekFrom::Start(body))?;
+    let mut entry = read_entry(reader, &original)?;
+    if matches!(entry, StsdBoxContent::Unknown(_)) {
+        // `sinf` can sit anywhere among the children, keep the whole encrypted entry
+        reader.seek(SeekFrom::Start(body))?;
+        entry = StsdBoxContent::Unknown(UnknownBox::read_child(reader, header)?);
+    }
+    skip_bytes_to(reader, end)?;
+
+    Ok(StsdBoxContent::Encrypted {
+        entry: Box::new(entry),
+        sinf,
+    })
+}
+
+impl<B: AsRef<[u8]>, W: io::Write> WriteBox<&mut W> for StsdBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.entries.len() as u32)?;
+
+        for entry in &self.entries {
+            entry.write_entry(writer)?;
+        }
+
+        Ok(size)
+    }
+}
+
+fn hevc_codec_details<B>(hvcc: &HevcDecoderConfigurationRecord<B>) -> String {
+    let mut codec = String::new();
+    match hvcc.general_profile_space {
+        1 => codec.push_str(".A"),
+        2 => codec.push_str(".B"),
+        3 => codec.push_str(".C"),
+        _ => {}
+    }
+    write!(&mut codec, ".{}", hvcc.general_profile_idc).ok();
+
+    let mut val = hvcc.general_profile_compatibility_flags;
+    let mut reversed = 0;
+
+    for i in 0..32 {
+        reversed |= val & 1;
+        if i == 31 {
+            break;
+        }
+        reversed <<= 1;
+        val >>= 1;
+    }
+    write!(&mut codec, ".{reversed:X}").ok();
+
+    if hvcc.general_tier_flag {
+        codec.push_str(".H")
+    } else {
+        codec.push_str(".L")
+    }
+    write!(&mut codec, "{}", hvcc.general_level_idc).ok();
+
+    let mut constraint = [0u8; 6];
+    constraint.copy_from_slice(&hvcc.general_constraint_indicator_flag.to_be_bytes()[2..]);
+
+    let mut has_byte = false;
+    let mut i: isize = 5;
+
+    while i >= 0 {
+        let v = constraint[i as usize];
+        if v > 0 || has_byte {
+            write!(&mut codec, ".{v:00X}").ok();
+            has_byte = true;
+        }
+        i -= 1;
+    }
+
+    codec
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn unknown_entries_and_children_round_trip() {
+        let unknown = |fourcc: &[u8; 4], payload: &[u8]| UnknownBox {
+            fourcc: (*fourcc).into(),
+            payload: payload.to_vec(),
+        };
+        let stsd = StsdBox {
+            version: 0,
+            flags: 0,
+            entries: vec![
+                StsdBoxContent::Vp09(Vp09Box {
+                    unknown: vec![unknown(b"colr", b"nclx\0\x01\0\x01\0\x01\0")],
+                    ..Default::default()
+                }),
+                StsdBoxContent::Unknown(unknown(b"xyz1", &[7; 86])),
+            ],
+        };
+
+        let mut data = Vec::new();
+        let size = stsd.write_box(&mut data).unwrap();
+        assert_eq!(size, data.len() as u64);
+        assert_eq!(size, stsd.box_size());
+
+        let mut reader = Cursor::new(&data[..]);
+        let header = BoxHeader::read(&mut reader).unwrap();
+        let read = StsdBox::<Vec<u8>>::read_box(&mut reader, header.size).unwrap();
+        let [StsdBoxContent::Vp09(vp09), StsdBoxContent::Unknown(entry)] = &read.entries[..] else {
+            panic!("unexpected entries {:?}", read.entries);
+        };
+        assert_eq!(
+            vp09.unknown,
+            vec![unknown(b"colr", b"nclx\0\x01\0\x01\0\x01\0")]
+        );
+        assert_eq!(*entry, unknown(b"xyz1", &[7; 86]));
+
+        let mut written = Vec::new();
+        read.write_box(&mut written).unwrap();
+        assert_eq!(written, data);
+    }
+}
@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -33,7 +33,7 @@ impl Mp4Box for StssBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for StssBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -48,8 +48,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for StssBox {
                 .saturating_sub(other_size as u64)
                 / entry_size as u64
         {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
+            return Err(Error::invalid_value(
                 "stss entry_count indicates more entries than could fit in the box",
             ));
         }
@@ -70,3 +69,19 @@ impl<R: Read + Seek> ReadBox<&mut R> for StssBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for StssBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.entries.len() as u32)?;
+
+        for sample_number in &self.entries {
+            BigEndian::write_u32(writer, *sample_number)?;
+        }
+
+        Ok(size)
+    }
+}
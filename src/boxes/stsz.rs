@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -35,7 +35,7 @@ impl Mp4Box for StszBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for StszBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -57,8 +57,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for StszBox {
                     .saturating_sub(other_size as u64)
                     / stsz_item_size as u64
             {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
+                return Err(Error::invalid_value(
                     "stsz sample_count indicates more values than could fir in the box",
                 ));
             }
@@ -81,3 +80,28 @@ impl<R: Read + Seek> ReadBox<&mut R> for StszBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for StszBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.sample_size)?;
+        BigEndian::write_u32(writer, self.sample_count)?;
+
+        if self.sample_size == 0 {
+            if self.sample_count != self.sample_sizes.len() as u32 {
+                return Err(Error::invalid_value(
+                    "stsz sample_count doesn't match the number of sample sizes",
+                ));
+            }
+
+            for sample_size in &self.sample_sizes {
+                BigEndian::write_u32(writer, *sample_size)?;
+            }
+        }
+
+        Ok(size)
+    }
+}
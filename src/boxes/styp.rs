@@ -0,0 +1,61 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    BigEndian, BoxHeader, BoxType, FourCC, FtypBox, Mp4Box, ReadBox, Result, WriteBox, HEADER_SIZE,
+};
+
+// The `ftyp` of a media segment, same layout under another name
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StypBox {
+    pub major_brand: FourCC,
+    pub minor_version: u32,
+    pub compatible_brands: Vec<FourCC>,
+}
+
+impl StypBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::StypBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + 8 + (4 * self.compatible_brands.len() as u64)
+    }
+}
+
+impl Mp4Box for StypBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for StypBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let ftyp = FtypBox::read_box(reader, size)?;
+
+        Ok(Self {
+            major_brand: ftyp.major_brand,
+            minor_version: ftyp.minor_version,
+            compatible_brands: ftyp.compatible_brands,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for StypBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, self.major_brand.into())?;
+        BigEndian::write_u32(writer, self.minor_version)?;
+
+        for brand in &self.compatible_brands {
+            BigEndian::write_u32(writer, brand.into())?;
+        }
+
+        Ok(size)
+    }
+}
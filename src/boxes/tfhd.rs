@@ -1,8 +1,8 @@
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -64,7 +64,7 @@ impl Mp4Box for TfhdBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for TfhdBox {
-    fn read_box(reader: &mut R, size: u64) -> std::io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -110,3 +110,63 @@ impl<R: Read + Seek> ReadBox<&mut R> for TfhdBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for TfhdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.track_id)?;
+
+        if Self::FLAG_BASE_DATA_OFFSET & self.flags > 0 {
+            BigEndian::write_u64(writer, self.base_data_offset.unwrap_or(0))?;
+        }
+        if Self::FLAG_SAMPLE_DESCRIPTION_INDEX & self.flags > 0 {
+            BigEndian::write_u32(writer, self.sample_description_index.unwrap_or(0))?;
+        }
+        if Self::FLAG_DEFAULT_SAMPLE_DURATION & self.flags > 0 {
+            BigEndian::write_u32(writer, self.default_sample_duration.unwrap_or(0))?;
+        }
+        if Self::FLAG_DEFAULT_SAMPLE_SIZE & self.flags > 0 {
+            BigEndian::write_u32(writer, self.default_sample_size.unwrap_or(0))?;
+        }
+        if Self::FLAG_DEFAULT_SAMPLE_FLAGS & self.flags > 0 {
+            BigEndian::write_u32(writer, self.default_sample_flags.unwrap_or(0))?;
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::round_trip;
+
+    #[test]
+    fn tfhd_round_trip() {
+        let tfhd = TfhdBox {
+            version: 0,
+            flags: TfhdBox::FLAG_BASE_DATA_OFFSET
+                | TfhdBox::FLAG_SAMPLE_DESCRIPTION_INDEX
+                | TfhdBox::FLAG_DEFAULT_SAMPLE_DURATION
+                | TfhdBox::FLAG_DEFAULT_SAMPLE_SIZE
+                | TfhdBox::FLAG_DEFAULT_SAMPLE_FLAGS,
+            track_id: 2,
+            base_data_offset: Some(u32::MAX as u64 + 1),
+            sample_description_index: Some(1),
+            default_sample_duration: Some(1024),
+            default_sample_size: Some(371),
+            default_sample_flags: Some(0x0200_0000),
+        };
+        assert_eq!(round_trip(&tfhd), tfhd);
+
+        let tfhd = TfhdBox {
+            flags: TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF,
+            track_id: 1,
+            ..Default::default()
+        };
+        assert_eq!(round_trip(&tfhd), tfhd);
+    }
+}
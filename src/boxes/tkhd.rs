@@ -1,12 +1,15 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, FixedPointU16, FixedPointU8,
-    Matrix, Mp4Box, ReadBox, HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, FixedPointU16, FixedPointU8, Matrix, Mp4Box, ReadBox, Result, WriteBox,
+    HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
-enum TrackFlag {
+pub enum TrackFlag {
     TrackEnabled = 0x000001,
+    TrackInMovie = 0x000002,
+    TrackInPreview = 0x000004,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -80,7 +83,7 @@ impl Mp4Box for TkhdBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for TkhdBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
         let (creation_time, modification_time, track_id, _, duration) = if version == 1 {
@@ -100,10 +103,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for TkhdBox {
                 BigEndian::read_u32(reader)? as u64,
             )
         } else {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "version must be 0 or 1",
-            ));
+            return Err(Error::unsupported_version(BoxType::TkhdBox, version, start));
         };
 
         let _ = BigEndian::read_u64(reader)?; // reserved
@@ -136,3 +136,41 @@ impl<R: Read + Seek> ReadBox<&mut R> for TkhdBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for TkhdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        if self.version == 1 {
+            BigEndian::write_u64(writer, self.creation_time)?;
+            BigEndian::write_u64(writer, self.modification_time)?;
+            BigEndian::write_u32(writer, self.track_id)?;
+            BigEndian::write_u32(writer, 0)?; // reserved
+            BigEndian::write_u64(writer, self.duration)?;
+        } else if self.version == 0 {
+            BigEndian::write_u32(writer, self.creation_time as u32)?;
+            BigEndian::write_u32(writer, self.modification_time as u32)?;
+            BigEndian::write_u32(writer, self.track_id)?;
+            BigEndian::write_u32(writer, 0)?; // reserved
+            BigEndian::write_u32(writer, self.duration as u32)?;
+        } else {
+            return Err(Error::unsupported_version(self.box_type(), self.version, 0));
+        }
+
+        BigEndian::write_u64(writer, 0)?; // reserved
+
+        BigEndian::write_u16(writer, self.layer)?;
+        BigEndian::write_u16(writer, self.alternate_group)?;
+        BigEndian::write_u16(writer, self.volume.raw_value())?;
+
+        BigEndian::write_u16(writer, 0)?; // reserved
+
+        self.matrix.write_i32(writer)?;
+        BigEndian::write_u32(writer, self.width.raw_value())?;
+        BigEndian::write_u32(writer, self.height.raw_value())?;
+
+        Ok(size)
+    }
+}
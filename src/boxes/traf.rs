@@ -0,0 +1,155 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start,
+    boxes::{
+        saio::SaioBox, saiz::SaizBox, senc::SencBox, tfdt::TfdtBox, tfhd::TfhdBox, trun::TrunBox,
+    },
+    skip_box, skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, Result,
+    WriteBox, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrafBox {
+    pub tfhd: TfhdBox,
+    pub tfdt: Option<TfdtBox>,
+    pub truns: Vec<TrunBox>,
+    pub senc: Option<SencBox>,
+    pub saiz: Option<SaizBox>,
+    pub saio: Option<SaioBox>,
+}
+
+impl TrafBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::TrafBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE;
+        size += self.tfhd.box_size();
+
+        if let Some(ref tfdt) = self.tfdt {
+            size += tfdt.box_size()
+        }
+
+        for trun in &self.truns {
+            size += trun.box_size()
+        }
+
+        if let Some(ref senc) = self.senc {
+            size += senc.box_size()
+        }
+        if let Some(ref saiz) = self.saiz {
+            size += saiz.box_size()
+        }
+        if let Some(ref saio) = self.saio {
+            size += saio.box_size()
+        }
+
+        size
+    }
+}
+
+impl Mp4Box for TrafBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for TrafBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut tfhd = None;
+        let mut tfdt = None;
+        let mut truns = Vec::new();
+        let mut senc = None;
+        let mut saiz = None;
+        let mut saio = None;
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::TfhdBox => {
+                    tfhd.replace(TfhdBox::read_child(reader, &header)?);
+                }
+                BoxType::TfdtBox => {
+                    tfdt.replace(TfdtBox::read_child(reader, &header)?);
+                }
+                BoxType::TrunBox => {
+                    truns.push(TrunBox::read_child(reader, &header)?);
+                }
+                BoxType::SencBox => {
+                    senc.replace(SencBox::read_child(reader, &header)?);
+                }
+                BoxType::SaizBox => {
+                    saiz.replace(SaizBox::read_child(reader, &header)?);
+                }
+                BoxType::SaioBox => {
+                    saio.replace(SaioBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    skip_box(reader, header.size)?;
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(tfhd) = tfhd else {
+            return Err(Error::missing_box(BoxType::TfhdBox, start));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            tfhd,
+            tfdt,
+            truns,
+            senc,
+            saiz,
+            saio,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for TrafBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        self.tfhd.write_box(writer)?;
+
+        if let Some(ref tfdt) = self.tfdt {
+            tfdt.write_box(writer)?;
+        }
+
+        if let Some(ref saiz) = self.saiz {
+            saiz.write_box(writer)?;
+        }
+        if let Some(ref saio) = self.saio {
+            saio.write_box(writer)?;
+        }
+        if let Some(ref senc) = self.senc {
+            senc.write_box(writer)?;
+        }
+
+        for trun in &self.truns {
+            trun.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start,
+    boxes::{edts::EdtsBox, mdia::MdiaBox, meta::MetaBox, tkhd::TkhdBox, tref::TrefBox},
+    skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, ReadPayload, Result,
+    UnknownBox, WriteBox, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrakBox<B = Vec<u8>> {
+    pub tkhd: TkhdBox,
+    pub tref: Option<TrefBox>,
+    pub edts: Option<EdtsBox>,
+    pub meta: Option<MetaBox<B>>,
+    pub mdia: MdiaBox<B>,
+    /// Children this crate doesn't model, written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+}
+
+impl<B: AsRef<[u8]>> TrakBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::TrakBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE;
+        size += self.tkhd.box_size();
+
+        if let Some(ref tref) = self.tref {
+            size += tref.box_size()
+        }
+        if let Some(ref edts) = self.edts {
+            size += edts.box_size()
+        }
+        size += self.mdia.box_size();
+
+        if let Some(ref meta) = self.meta {
+            size += meta.box_size()
+        }
+        for unknown in &self.unknown {
+            size += unknown.box_size();
+        }
+
+        size
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for TrakBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for TrakBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut tkhd = None;
+        let mut tref = None;
+        let mut edts = None;
+        let mut meta = None;
+        let mut mdia = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::TkhdBox => {
+                    tkhd.replace(TkhdBox::read_child(reader, &header)?);
+                }
+                BoxType::TrefBox => {
+                    tref.replace(TrefBox::read_child(reader, &header)?);
+                }
+                BoxType::EdtsBox => {
+                    edts.replace(EdtsBox::read_child(reader, &header)?);
+                }
+                BoxType::MetaBox => {
+                    meta.replace(MetaBox::read_child(reader, &header)?);
+                }
+                BoxType::MdiaBox => {
+                    mdia.replace(MdiaBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(tkhd) = tkhd else {
+            return Err(Error::missing_box(BoxType::TkhdBox, start));
+        };
+        let Some(mdia) = mdia else {
+            return Err(Error::missing_box(BoxType::MdiaBox, start));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            tkhd,
+            tref,
+            edts,
+            meta,
+            mdia,
+            unknown,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for TrakBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        self.tkhd.write_box(writer)?;
+
+        if let Some(ref tref) = self.tref {
+            tref.write_box(writer)?;
+        }
+
+        if let Some(ref edts) = self.edts {
+            edts.write_box(writer)?;
+        }
+
+        self.mdia.write_box(writer)?;
+
+        if let Some(ref meta) = self.meta {
+            meta.write_box(writer)?;
+        }
+
+        for unknown in &self.unknown {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
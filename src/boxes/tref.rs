@@ -0,0 +1,241 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, skip_bytes_to, BigEndian, BoxHeader, BoxType, Error, FourCC, Mp4Box, ReadBox,
+    Result, WriteBox, HEADER_SIZE,
+};
+
+macro_rules! reference_types {
+    ($( $name:ident => $fourcc:expr ),*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum ReferenceType {
+            $( $name, )*
+            Other(FourCC),
+        }
+
+        impl From<FourCC> for ReferenceType {
+            fn from(fourcc: FourCC) -> Self {
+                match &fourcc.value {
+                    $( $fourcc => Self::$name, )*
+                    _ => Self::Other(fourcc),
+                }
+            }
+        }
+
+        impl From<ReferenceType> for FourCC {
+            fn from(reference_type: ReferenceType) -> Self {
+                match reference_type {
+                    $( ReferenceType::$name => FourCC::from(*$fourcc), )*
+                    ReferenceType::Other(fourcc) => fourcc,
+                }
+            }
+        }
+    };
+}
+
+reference_types! {
+    Hint => b"hint",
+    Chapter => b"chap",
+    ContentDescribes => b"cdsc",
+    Font => b"font",
+    HintDependency => b"hind",
+    AuxiliaryDepth => b"vdep",
+    AuxiliaryParallax => b"vplx",
+    Subtitle => b"subt",
+    Thumbnail => b"thmb",
+    Auxiliary => b"auxl",
+    ScalableBase => b"sbas",
+    Scalable => b"scal",
+    Fallback => b"fall",
+    Forced => b"forc",
+    ElementaryStream => b"mpod",
+    Sync => b"sync",
+    IpmpInfo => b"ipir",
+    Dependency => b"dpnd",
+    Timecode => b"tmcd"
+}
+
+impl Default for ReferenceType {
+    fn default() -> Self {
+        Self::Other(FourCC::default())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrackReference {
+    pub reference_type: ReferenceType,
+    pub track_ids: Vec<u32>,
+}
+
+impl TrackReference {
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE + 4 * self.track_ids.len() as u64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrefBox {
+    pub references: Vec<TrackReference>,
+}
+
+impl TrefBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::TrefBox
+    }
+
+    fn get_size(&self) -> u64 {
+        HEADER_SIZE
+            + self
+                .references
+                .iter()
+                .map(TrackReference::get_size)
+                .sum::<u64>()
+    }
+
+    pub fn track_ids(&self, reference_type: ReferenceType) -> &[u32] {
+        self.references
+            .iter()
+            .find(|reference| reference.reference_type == reference_type)
+            .map_or(&[], |reference| &reference.track_ids)
+    }
+
+    // Appends to the reference of that type if there is one
+    pub fn add(&mut self, reference_type: ReferenceType, track_id: u32) {
+        match self
+            .references
+            .iter_mut()
+            .find(|reference| reference.reference_type == reference_type)
+        {
+            Some(reference) => reference.track_ids.push(track_id),
+            None => self.references.push(TrackReference {
+                reference_type,
+                track_ids: vec![track_id],
+            }),
+        }
+    }
+}
+
+impl Mp4Box for TrefBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for TrefBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let mut references = Vec::new();
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            // Every child is a plain list of track ids named after the reference type
+            let count = header.size.saturating_sub(HEADER_SIZE) / 4;
+            let mut track_ids = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                track_ids.push(BigEndian::read_u32(reader)?);
+            }
+            skip_bytes_to(reader, current + header.size)?;
+
+            references.push(TrackReference {
+                reference_type: ReferenceType::from(FourCC::from(header.name)),
+                track_ids,
+            });
+
+            current = reader.stream_position()?;
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self { references })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for TrefBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        for reference in &self.references {
+            let name = BoxType::from(u32::from(FourCC::from(reference.reference_type)));
+            BoxHeader::new(name, reference.get_size()).write(writer)?;
+            for track_id in &reference.track_ids {
+                BigEndian::write_u32(writer, *track_id)?;
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{read, round_trip};
+
+    #[test]
+    fn tref_round_trip() {
+        let tref = TrefBox {
+            references: vec![
+                TrackReference {
+                    reference_type: ReferenceType::Chapter,
+                    track_ids: vec![3],
+                },
+                TrackReference {
+                    reference_type: ReferenceType::AuxiliaryDepth,
+                    track_ids: vec![1, 2],
+                },
+                TrackReference {
+                    reference_type: ReferenceType::Other(FourCC::from(*b"xref")),
+                    track_ids: vec![0, 4],
+                },
+            ],
+        };
+        assert_eq!(round_trip(&tref), tref);
+        assert_eq!(tref.track_ids(ReferenceType::AuxiliaryDepth), [1, 2]);
+        assert!(tref.track_ids(ReferenceType::Hint).is_empty());
+    }
+
+    #[test]
+    fn reference_types() {
+        let mut data = vec![0, 0, 0, 36];
+        data.extend(b"tref");
+        for (name, track_id) in [(b"cdsc", 1u32), (b"fall", 2), (b"dpnd", 5)] {
+            data.extend([0, 0, 0, 12]);
+            data.extend(name);
+            data.extend(track_id.to_be_bytes());
+        }
+        let mut tref = read::<TrefBox>(&data).unwrap();
+        let types: Vec<_> = tref
+            .references
+            .iter()
+            .map(|reference| reference.reference_type)
+            .collect();
+        assert_eq!(
+            types,
+            [
+                ReferenceType::ContentDescribes,
+                ReferenceType::Fallback,
+                ReferenceType::Dependency,
+            ]
+        );
+
+        tref.add(ReferenceType::Fallback, 3);
+        tref.add(ReferenceType::Timecode, 4);
+        assert_eq!(tref.track_ids(ReferenceType::Fallback), [2, 3]);
+        assert_eq!(tref.track_ids(ReferenceType::Timecode), [4]);
+        assert_eq!(
+            FourCC::from(ReferenceType::Timecode),
+            FourCC::from(*b"tmcd")
+        );
+    }
+}
@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -37,7 +37,7 @@ impl Mp4Box for TrexBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for TrexBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -60,3 +60,19 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrexBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for TrexBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.track_id)?;
+        BigEndian::write_u32(writer, self.default_sample_description_index)?;
+        BigEndian::write_u32(writer, self.default_sample_duration)?;
+        BigEndian::write_u32(writer, self.default_sample_size)?;
+        BigEndian::write_u32(writer, self.default_sample_flags)?;
+
+        Ok(size)
+    }
+}
@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Error, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -40,16 +40,16 @@ impl TrunBox {
             sum += 0b100;
         }
         if Self::FLAG_SAMPLE_DURATION & self.flags > 0 {
-            sum += 0b100;
+            sum += 0b100 * self.sample_count as u64;
         }
         if Self::FLAG_SAMPLE_SIZE & self.flags > 0 {
-            sum += 0b100;
+            sum += 0b100 * self.sample_count as u64;
         }
         if Self::FLAG_SAMPLE_FLAGS & self.flags > 0 {
-            sum += 0b100;
+            sum += 0b100 * self.sample_count as u64;
         }
         if Self::FLAG_SAMPLE_CTS & self.flags > 0 {
-            sum += 0b100;
+            sum += 0b100 * self.sample_count as u64;
         }
 
         sum
@@ -67,7 +67,7 @@ impl Mp4Box for TrunBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for TrunBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -104,8 +104,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrunBox {
                 .saturating_sub(header_size)
                 .saturating_sub(other_size as u64)
         {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
+            return Err(Error::invalid_value(
                 "trun sample_count indicates more values than could fit in the box",
             ));
         }
@@ -157,3 +156,86 @@ impl<R: Read + Seek> ReadBox<&mut R> for TrunBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for TrunBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u32(writer, self.sample_count)?;
+
+        if Self::FLAG_DATA_OFFSET & self.flags > 0 {
+            BigEndian::write_i32(writer, self.data_offset.unwrap_or(0))?;
+        }
+        if Self::FLAG_FIRST_SAMPLE_FLAGS & self.flags > 0 {
+            BigEndian::write_u32(writer, self.first_sample_flags.unwrap_or(0))?;
+        }
+
+        for i in 0..self.sample_count as usize {
+            if Self::FLAG_SAMPLE_DURATION & self.flags > 0 {
+                BigEndian::write_u32(writer, sample_value(&self.sample_duration, i)?)?;
+            }
+            if Self::FLAG_SAMPLE_SIZE & self.flags > 0 {
+                BigEndian::write_u32(writer, sample_value(&self.sample_sizes, i)?)?;
+            }
+            if Self::FLAG_SAMPLE_FLAGS & self.flags > 0 {
+                BigEndian::write_u32(writer, sample_value(&self.sample_flags, i)?)?;
+            }
+            if Self::FLAG_SAMPLE_CTS & self.flags > 0 {
+                BigEndian::write_u32(writer, sample_value(&self.sample_cts, i)?)?;
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+fn sample_value(values: &[u32], index: usize) -> Result<u32> {
+    values.get(index).copied().ok_or(Error::invalid_value(
+        "trun sample_count indicates more values than the box holds",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::round_trip;
+
+    #[test]
+    fn trun_v0_round_trip() {
+        let trun = TrunBox {
+            version: 0,
+            flags: TrunBox::FLAG_DATA_OFFSET
+                | TrunBox::FLAG_FIRST_SAMPLE_FLAGS
+                | TrunBox::FLAG_SAMPLE_DURATION
+                | TrunBox::FLAG_SAMPLE_SIZE
+                | TrunBox::FLAG_SAMPLE_FLAGS
+                | TrunBox::FLAG_SAMPLE_CTS,
+            sample_count: 3,
+            data_offset: Some(120),
+            first_sample_flags: Some(0x0200_0000),
+            sample_duration: vec![1000, 1000, 1001],
+            sample_sizes: vec![5000, 300, 280],
+            sample_flags: vec![0x0200_0000, 0x0101_0000, 0x0101_0000],
+            sample_cts: vec![2000, 0, 1000],
+        };
+        assert_eq!(round_trip(&trun), trun);
+    }
+
+    #[test]
+    fn trun_v1_round_trip() {
+        let trun = TrunBox {
+            version: 1,
+            flags: TrunBox::FLAG_DATA_OFFSET | TrunBox::FLAG_SAMPLE_SIZE | TrunBox::FLAG_SAMPLE_CTS,
+            sample_count: 2,
+            data_offset: Some(-8),
+            sample_sizes: vec![10, 20],
+            sample_cts: vec![(-1000i32) as u32, 1000],
+            ..Default::default()
+        };
+        let read = round_trip(&trun);
+        assert_eq!(read, trun);
+        assert_eq!(read.sample_cts[0] as i32, -1000);
+    }
+}
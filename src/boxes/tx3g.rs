@@ -1,7 +1,11 @@
-use std::io::{self, Read, Seek};
+use std::{
+    borrow::Cow,
+    io::{Read, Seek, Write},
+};
 
 use crate::{
-    box_start, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox, RgbaColor, HEADER_SIZE,
+    box_start, skip_bytes_to, BigEndian, BoxHeader, BoxType, Mp4Box, ReadBox, Result, RgbaColor,
+    WriteBox, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -42,6 +46,36 @@ impl Tx3gBox {
     fn get_size(&self) -> u64 {
         HEADER_SIZE + 6 + 32
     }
+
+    // A sample is the text prefixed with its 16 bit length, optionally followed by
+    // modifier boxes (styles, karaoke, ...) which are ignored here. The text is
+    // UTF-8 unless it starts with a UTF-16 byte order mark.
+    pub fn sample_text(data: &[u8]) -> Cow<'_, str> {
+        let len = match data {
+            [high, low, ..] => u16::from_be_bytes([*high, *low]) as usize,
+            _ => return Cow::Borrowed(""),
+        };
+        let text = &data[2..(2 + len).min(data.len())];
+
+        match text {
+            [0xfe, 0xff, rest @ ..] => {
+                let units = rest
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                    .collect::<Vec<_>>();
+                Cow::Owned(String::from_utf16_lossy(&units))
+            }
+            _ => String::from_utf8_lossy(text),
+        }
+    }
+
+    pub fn encode_sample(text: &str) -> Vec<u8> {
+        let text = &text.as_bytes()[..text.len().min(u16::MAX as usize)];
+        let mut data = Vec::with_capacity(2 + text.len());
+        data.extend((text.len() as u16).to_be_bytes());
+        data.extend(text);
+        data
+    }
 }
 
 impl Mp4Box for Tx3gBox {
@@ -55,7 +89,7 @@ impl Mp4Box for Tx3gBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for Tx3gBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
 
         let _ = BigEndian::read_u32(reader)?; // reserved
@@ -105,3 +139,30 @@ impl<R: Read + Seek> ReadBox<&mut R> for Tx3gBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for Tx3gBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+
+        BigEndian::write_u32(writer, self.display_flags)?;
+        BigEndian::write_i8(writer, self.horizontal_justification)?;
+        BigEndian::write_i8(writer, self.vertical_justification)?;
+        BigEndian::write_u8(writer, self.bg_color_rgba.red)?;
+        BigEndian::write_u8(writer, self.bg_color_rgba.green)?;
+        BigEndian::write_u8(writer, self.bg_color_rgba.blue)?;
+        BigEndian::write_u8(writer, self.bg_color_rgba.alpha)?;
+
+        for n in self.box_record {
+            BigEndian::write_i16(writer, n)?;
+        }
+
+        writer.write_all(&self.style_record)?;
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start,
+    boxes::{chpl::ChplBox, meta::MetaBox},
+    skip_bytes_to, BoxHeader, BoxType, Error, Mp4Box, ReadBox, ReadChild, ReadPayload, Result,
+    UnknownBox, WriteBox, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UdtaBox<B = Vec<u8>> {
+    pub meta: Option<MetaBox<B>>,
+    pub chpl: Option<ChplBox>,
+    /// Children this crate doesn't model, written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+}
+
+impl<B: AsRef<[u8]>> UdtaBox<B> {
+    fn get_type(&self) -> BoxType {
+        BoxType::UdtaBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE;
+
+        if let Some(ref meta) = self.meta {
+            size += meta.box_size()
+        }
+
+        if let Some(ref chpl) = self.chpl {
+            size += chpl.box_size()
+        }
+        for unknown in &self.unknown {
+            size += unknown.box_size();
+        }
+
+        size
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for UdtaBox<B> {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for UdtaBox<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let mut meta = None;
+        let mut chpl = None;
+        let mut unknown = Vec::new();
+
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::MetaBox => {
+                    meta.replace(MetaBox::read_child(reader, &header)?);
+                }
+                BoxType::ChplBox => {
+                    chpl.replace(ChplBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            meta,
+            chpl,
+            unknown,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for UdtaBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        if let Some(ref meta) = self.meta {
+            meta.write_box(writer)?;
+        }
+
+        if let Some(ref chpl) = self.chpl {
+            chpl.write_box(writer)?;
+        }
+
+        for unknown in &self.unknown {
+            unknown.write_box(writer)?;
+        }
+
+        Ok(size)
+    }
+}
@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox, RgbColor,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Mp4Box, ReadBox, Result, RgbColor, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -34,7 +34,7 @@ impl Mp4Box for VmhdBox {
 }
 
 impl<R: Read + Seek> ReadBox<&mut R> for VmhdBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
         let graphics_mode = BigEndian::read_u16(reader)?;
@@ -54,3 +54,18 @@ impl<R: Read + Seek> ReadBox<&mut R> for VmhdBox {
         })
     }
 }
+
+impl<W: Write> WriteBox<&mut W> for VmhdBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u16(writer, self.graphics_mode)?;
+        BigEndian::write_u16(writer, self.op_color.red)?;
+        BigEndian::write_u16(writer, self.op_color.green)?;
+        BigEndian::write_u16(writer, self.op_color.blue)?;
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,202 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, boxes::vpcc::VpccBox, read_box_header_ext, skip_box, skip_bytes_to,
+    write_box_header_ext, write_children, BigEndian, BoxHeader, BoxType, Error, Mp4Box, RawBox,
+    ReadBox, ReadChild, ReadPayload, Result, UnknownBox, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Vp08Box<B = Vec<u8>> {
+    pub version: u8,
+    pub flags: u32,
+    pub start_code: u16,
+    pub data_reference_index: u16,
+    pub reserved0: [u8; 16],
+    pub width: u16,
+    pub height: u16,
+    pub horizresolution: (u16, u16),
+    pub vertresolution: (u16, u16),
+    pub reserved1: [u8; 4],
+    pub frame_count: u16,
+    pub compressorname: [u8; 32],
+    pub depth: u16, // This is usually 24, even for HDR with bit_depth=10
+    pub end_code: u16,
+    pub vpcc: RawBox<VpccBox, B>,
+    /// Children this crate doesn't model (`pasp`, `colr`, `btrt`, ...), written back as read
+    pub unknown: Vec<UnknownBox<B>>,
+    /// How many of the `unknown` children come before `vpcc`
+    pub unknown_before_vpcc: usize,
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for Vp08Box<B> {
+    fn box_type(&self) -> BoxType {
+        BoxType::Vp08Box
+    }
+
+    fn box_size(&self) -> u64 {
+        HEADER_SIZE
+            + HEADER_EXT_SIZE
+            + 74
+            + self.vpcc.box_size()
+            + self
+                .unknown
+                .iter()
+                .map(|unknown| unknown.box_size())
+                .sum::<u64>()
+    }
+}
+
+impl<B, R: Read + Seek + ReadPayload<B>> ReadBox<&mut R> for Vp08Box<B> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+        let (version, flags) = read_box_header_ext(reader)?;
+
+        let start_code = BigEndian::read_u16(reader)?;
+        let data_reference_index = BigEndian::read_u16(reader)?;
+        let reserved0 = {
+            let mut buf = [0u8; 16];
+            reader.read_exact(&mut buf)?;
+            buf
+        };
+        let width = BigEndian::read_u16(reader)?;
+        let height = BigEndian::read_u16(reader)?;
+        let horizresolution = (BigEndian::read_u16(reader)?, BigEndian::read_u16(reader)?);
+        let vertresolution = (BigEndian::read_u16(reader)?, BigEndian::read_u16(reader)?);
+        let reserved1 = {
+            let mut buf = [0u8; 4];
+            reader.read_exact(&mut buf)?;
+            buf
+        };
+        let frame_count = BigEndian::read_u16(reader)?;
+        let compressorname = {
+            let mut buf = [0u8; 32];
+            reader.read_exact(&mut buf)?;
+            buf
+        };
+        let depth = BigEndian::read_u16(reader)?;
+        let end_code = BigEndian::read_u16(reader)?;
+
+        let end = start + size;
+        let mut vpcc = None;
+        let mut unknown = Vec::new();
+        let mut unknown_before_vpcc = 0;
+        let mut current = reader.stream_position()?;
+        while current + HEADER_SIZE <= end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+            if header.size == 0 {
+                break;
+            }
+
+            match header.name {
+                BoxType::VpccBox => {
+                    vpcc.replace(RawBox::read_child(reader, &header)?);
+                    unknown_before_vpcc = unknown.len();
+                }
+                // the protection of encrypted entries is kept by `StsdBoxContent::Encrypted`
+                BoxType::SinfBox => {
+                    skip_box(reader, header.size)?;
+                }
+                _ => {
+                    unknown.push(UnknownBox::read_child(reader, &header)?);
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(vpcc) = vpcc else {
+            return Err(Error::missing_box(BoxType::VpccBox, start));
+        };
+        skip_bytes_to(reader, end)?;
+
+        Ok(Self {
+            version,
+            flags,
+            start_code,
+            data_reference_index,
+            reserved0,
+            width,
+            height,
+            horizresolution,
+            vertresolution,
+            reserved1,
+            frame_count,
+            compressorname,
+            depth,
+            end_code,
+            vpcc,
+            unknown,
+            unknown_before_vpcc,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for Vp08Box<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u16(writer, self.start_code)?;
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+        writer.write_all(&self.reserved0)?;
+        BigEndian::write_u16(writer, self.width)?;
+        BigEndian::write_u16(writer, self.height)?;
+        BigEndian::write_u16(writer, self.horizresolution.0)?;
+        BigEndian::write_u16(writer, self.horizresolution.1)?;
+        BigEndian::write_u16(writer, self.vertresolution.0)?;
+        BigEndian::write_u16(writer, self.vertresolution.1)?;
+        writer.write_all(&self.reserved1)?;
+        BigEndian::write_u16(writer, self.frame_count)?;
+        writer.write_all(&self.compressorname)?;
+        BigEndian::write_u16(writer, self.depth)?;
+        BigEndian::write_u16(writer, self.end_code)?;
+
+        write_children(writer, &self.vpcc, &self.unknown, self.unknown_before_vpcc)?;
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boxes::tests::{round_trip, unknown};
+
+    #[test]
+    fn vp08_round_trip() {
+        let vp08 = Vp08Box {
+            data_reference_index: 1,
+            width: 640,
+            height: 360,
+            horizresolution: (0x48, 0),
+            vertresolution: (0x48, 0),
+            frame_count: 1,
+            depth: 24,
+            end_code: 0xFFFF,
+            vpcc: RawBox {
+                contents: VpccBox {
+                    version: 1,
+                    bit_depth: 8,
+                    chroma_subsampling: 1,
+                    color_primaries: 1,
+                    transfer_characteristics: 1,
+                    matrix_coefficients: 1,
+                    ..Default::default()
+                },
+                raw: Vec::new(),
+            },
+            unknown: vec![unknown(b"colr", b"nclx\0\x01\0\x01\0\x01\0")],
+            unknown_before_vpcc: 1,
+            ..Default::default()
+        };
+        let read = round_trip(&vp08);
+        assert_eq!(read.vpcc.contents, vp08.vpcc.contents);
+        assert_eq!(read.unknown, vp08.unknown);
+        assert_eq!(read.unknown_before_vpcc, 1);
+    }
+}
@@ -1,8 +1,8 @@
-use std::io::{self, Read, Seek};
+use std::io::{Read, Seek, Write};
 
 use crate::{
-    box_start, read_box_header_ext, skip_bytes_to, BigEndian, BoxType, Mp4Box, ReadBox,
-    HEADER_EXT_SIZE, HEADER_SIZE,
+    box_start, read_box_header_ext, skip_bytes_to, write_box_header_ext, BigEndian, BoxHeader,
+    BoxType, Mp4Box, ReadBox, Result, WriteBox, HEADER_EXT_SIZE, HEADER_SIZE,
 };
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -35,8 +35,31 @@ impl Mp4Box for VpccBox {
     }
 }
 
+impl<W: Write> WriteBox<&mut W> for VpccBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        write_box_header_ext(writer, self.version, self.flags)?;
+
+        BigEndian::write_u8(writer, self.profile)?;
+        BigEndian::write_u8(writer, self.level)?;
+        BigEndian::write_u8(
+            writer,
+            (self.bit_depth << 4)
+                | ((self.chroma_subsampling & 0b111) << 1)
+                | self.video_full_range_flag as u8,
+        )?;
+        BigEndian::write_u8(writer, self.color_primaries)?;
+        BigEndian::write_u8(writer, self.transfer_characteristics)?;
+        BigEndian::write_u8(writer, self.matrix_coefficients)?;
+        BigEndian::write_u16(writer, 0)?; // codec_initialization_data_size
+
+        Ok(size)
+    }
+}
+
 impl<R: Read + Seek> ReadBox<&mut R> for VpccBox {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = box_start(reader)?;
         let (version, flags) = read_box_header_ext(reader)?;
 
@@ -48,6 +71,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for VpccBox {
             (b >> 4, b << 4 >> 5, b & 0x01 == 1)
         };
 
+        let color_primaries = BigEndian::read_u8(reader)?;
         let transfer_characteristics = BigEndian::read_u8(reader)?;
         let matrix_coefficients = BigEndian::read_u8(reader)?;
         let codec_initialization_data_size = BigEndian::read_u16(reader)?;
@@ -62,7 +86,7 @@ impl<R: Read + Seek> ReadBox<&mut R> for VpccBox {
             bit_depth,
             chroma_subsampling,
             video_full_range_flag,
-            color_primaries: 0,
+            color_primaries,
             transfer_characteristics,
             matrix_coefficients,
             codec_initialization_data_size,
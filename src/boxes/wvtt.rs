@@ -0,0 +1,125 @@
+use std::io::{Read, Seek, Write};
+
+use crate::{
+    box_start, skip_box, skip_bytes_to, BigEndian, BoxHeader, BoxType, Error, Mp4Box, ReadBox,
+    Result, WriteBox, HEADER_SIZE,
+};
+
+// WebVTT sample entry from ISO/IEC 14496-30
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WvttBox {
+    pub data_reference_index: u16,
+    /// `vttC`, the WebVTT file header up to the first cue
+    pub config: String,
+    /// `vlab`, a unique label for the source of the track
+    pub label: Option<String>,
+}
+
+impl Default for WvttBox {
+    fn default() -> Self {
+        Self {
+            data_reference_index: 1,
+            config: String::from("WEBVTT"),
+            label: None,
+        }
+    }
+}
+
+impl WvttBox {
+    fn get_type(&self) -> BoxType {
+        BoxType::WvttBox
+    }
+
+    fn get_size(&self) -> u64 {
+        let mut size = HEADER_SIZE + 8 + HEADER_SIZE + self.config.len() as u64;
+        if let Some(label) = &self.label {
+            size += HEADER_SIZE + label.len() as u64;
+        }
+        size
+    }
+}
+
+impl Mp4Box for WvttBox {
+    fn box_type(&self) -> BoxType {
+        self.get_type()
+    }
+
+    fn box_size(&self) -> u64 {
+        self.get_size()
+    }
+}
+
+fn read_string<R: Read>(reader: &mut R, header: &BoxHeader) -> Result<String> {
+    let mut buf = vec![0u8; header.size.saturating_sub(HEADER_SIZE) as usize];
+    reader.read_exact(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+impl<R: Read + Seek> ReadBox<&mut R> for WvttBox {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
+        let start = box_start(reader)?;
+
+        let _ = BigEndian::read_u32(reader)?; // reserved
+        let _ = BigEndian::read_u16(reader)?; // reserved
+        let data_reference_index = BigEndian::read_u16(reader)?;
+
+        let mut config = None;
+        let mut label = None;
+        let mut current = reader.stream_position()?;
+        let end = start + size;
+
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, box_start(reader)?));
+            }
+
+            match header.name {
+                BoxType::VttCBox => {
+                    config.replace(read_string(reader, &header)?);
+                }
+                BoxType::VlabBox => {
+                    label.replace(read_string(reader, &header)?);
+                }
+                _ => {
+                    skip_box(reader, header.size)?;
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(config) = config else {
+            return Err(Error::missing_box(BoxType::VttCBox, start));
+        };
+
+        skip_bytes_to(reader, start + size)?;
+
+        Ok(Self {
+            data_reference_index,
+            config,
+            label,
+        })
+    }
+}
+
+impl<W: Write> WriteBox<&mut W> for WvttBox {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+
+        BigEndian::write_u32(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, 0)?; // reserved
+        BigEndian::write_u16(writer, self.data_reference_index)?;
+
+        BoxHeader::new(BoxType::VttCBox, HEADER_SIZE + self.config.len() as u64).write(writer)?;
+        writer.write_all(self.config.as_bytes())?;
+
+        if let Some(label) = &self.label {
+            BoxHeader::new(BoxType::VlabBox, HEADER_SIZE + label.len() as u64).write(writer)?;
+            writer.write_all(label.as_bytes())?;
+        }
+
+        Ok(size)
+    }
+}
@@ -0,0 +1,914 @@
+use std::{
+    io::{Read, Seek},
+    time::Duration,
+};
+
+use crate::{Cue, Mp4, Result, StsdBoxContent, Track};
+
+const ROWS: usize = 15;
+const COLUMNS: usize = 32;
+
+const AVC_NAL_SEI: u8 = 6;
+const HEVC_NAL_PREFIX_SEI: u8 = 39;
+const HEVC_NAL_SUFFIX_SEI: u8 = 40;
+const SEI_USER_DATA_REGISTERED_ITU_T_T35: u32 = 4;
+
+const T35_COUNTRY_USA: u8 = 0xB5;
+const T35_PROVIDER_ATSC: u16 = 0x0031;
+const T35_PROVIDER_DIRECTV: u16 = 0x002F;
+const ATSC_USER_IDENTIFIER: &[u8; 4] = b"GA94";
+const USER_DATA_TYPE_CC_DATA: u8 = 0x03;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CcType {
+    /// CEA-608 byte pair of field 1, channels CC1 and CC2
+    Field1,
+    /// CEA-608 byte pair of field 2, channels CC3 and CC4
+    Field2,
+    /// CEA-708 DTVCC packet data
+    DtvccData,
+    /// CEA-708 DTVCC packet start
+    DtvccStart,
+}
+
+impl From<u8> for CcType {
+    fn from(cc_type: u8) -> Self {
+        match cc_type & 0x03 {
+            0 => Self::Field1,
+            1 => Self::Field2,
+            2 => Self::DtvccData,
+            _ => Self::DtvccStart,
+        }
+    }
+}
+
+// A valid `cc_data` triplet, bytes are as transmitted including the 608 parity bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CcData {
+    pub cc_type: CcType,
+    pub data: [u8; 2],
+}
+
+// The caption data carried by one sample
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptionData {
+    /// Presentation time of the sample
+    pub time: Duration,
+    pub cc_data: Vec<CcData>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptionChannel {
+    #[default]
+    Cc1,
+    Cc2,
+    Cc3,
+    Cc4,
+}
+
+impl CaptionChannel {
+    fn cc_type(self) -> CcType {
+        match self {
+            Self::Cc1 | Self::Cc2 => CcType::Field1,
+            Self::Cc3 | Self::Cc4 => CcType::Field2,
+        }
+    }
+
+    // Control codes of the second data channel of a field have bit 3 set
+    fn second(self) -> bool {
+        matches!(self, Self::Cc2 | Self::Cc4)
+    }
+}
+
+// Drops emulation prevention bytes, `00 00 03` becomes `00 00`
+fn unescape_rbsp(nal: &[u8]) -> Vec<u8> {
+    let mut rbsp = Vec::with_capacity(nal.len());
+    let mut zeros = 0;
+    for &byte in nal {
+        if zeros >= 2 && byte == 0x03 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if byte == 0 { zeros + 1 } else { 0 };
+        rbsp.push(byte);
+    }
+    rbsp
+}
+
+// `ff` extended values of SEI payload type and size
+fn read_sei_value(data: &mut &[u8]) -> Option<u32> {
+    let mut value = 0u32;
+    loop {
+        let (&byte, rest) = data.split_first()?;
+        *data = rest;
+        value += byte as u32;
+        if byte != 0xFF {
+            return Some(value);
+        }
+    }
+}
+
+// The `cc_data` of an ATSC A/53 (or DirecTV) `user_data_registered_itu_t_t35` payload
+fn t35_cc_data(payload: &[u8], cc_data: &mut Vec<CcData>) -> Option<()> {
+    let (&country, mut rest) = payload.split_first()?;
+    if country != T35_COUNTRY_USA {
+        return None;
+    }
+
+    let provider = u16::from_be_bytes(rest.get(..2)?.try_into().ok()?);
+    rest = &rest[2..];
+    match provider {
+        T35_PROVIDER_ATSC => {
+            if rest.get(..4)? != ATSC_USER_IDENTIFIER {
+                return None;
+            }
+            rest = &rest[4..];
+        }
+        T35_PROVIDER_DIRECTV => {}
+        _ => return None,
+    }
+
+    if *rest.first()? != USER_DATA_TYPE_CC_DATA {
+        return None;
+    }
+    // process_cc_data_flag, cc_count, then em_data
+    let flags = *rest.get(1)?;
+    if flags & 0x40 == 0 {
+        return None;
+    }
+    let count = (flags & 0x1F) as usize;
+
+    let triplets = rest.get(3..)?.chunks_exact(3).take(count);
+    cc_data.extend(
+        triplets
+            .filter(|triplet| triplet[0] & 0x04 != 0)
+            .map(|triplet| CcData {
+                cc_type: CcType::from(triplet[0]),
+                data: [triplet[1], triplet[2]],
+            }),
+    );
+
+    Some(())
+}
+
+// Caption data in the SEI NAL units of a length prefixed H.264 or H.265 sample
+fn sei_cc_data(sample: &[u8], length_size: usize, hevc: bool) -> Vec<CcData> {
+    let mut cc_data = Vec::new();
+    let mut rest = sample;
+
+    while rest.len() > length_size {
+        let len = rest[..length_size]
+            .iter()
+            .fold(0usize, |len, byte| len << 8 | *byte as usize);
+        let Some(nal) = rest.get(length_size..length_size + len) else {
+            break;
+        };
+        rest = &rest[length_size + len..];
+
+        let header_size = if hevc { 2 } else { 1 };
+        let is_sei = match nal.first() {
+            Some(header) if hevc => {
+                let nal_type = header >> 1 & 0x3F;
+                nal_type == HEVC_NAL_PREFIX_SEI || nal_type == HEVC_NAL_SUFFIX_SEI
+            }
+            Some(header) => header & 0x1F == AVC_NAL_SEI,
+            None => false,
+        };
+        if !is_sei || nal.len() <= header_size {
+            continue;
+        }
+
+        let rbsp = unescape_rbsp(&nal[header_size..]);
+        let mut messages = rbsp.as_slice();
+        // stop at the trailing bits
+        while messages.len() > 1 {
+            let (Some(payload_type), Some(payload_size)) =
+                (read_sei_value(&mut messages), read_sei_value(&mut messages))
+            else {
+                break;
+            };
+            let Some(payload) = messages.get(..payload_size as usize) else {
+                break;
+            };
+            if payload_type == SEI_USER_DATA_REGISTERED_ITU_T_T35 {
+                t35_cc_data(payload, &mut cc_data);
+            }
+            messages = &messages[payload_size as usize..];
+        }
+    }
+
+    cc_data
+}
+
+// `cdat` and `cdt2` boxes of a QuickTime `c608` sample
+fn c608_cc_data(sample: &[u8]) -> Vec<CcData> {
+    let mut cc_data = Vec::new();
+    let mut rest = sample;
+
+    while let Some(header) = rest.get(..8) {
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        if size < 8 || size > rest.len() {
+            break;
+        }
+        let cc_type = match &header[4..8] {
+            b"cdat" => Some(CcType::Field1),
+            b"cdt2" => Some(CcType::Field2),
+            _ => None,
+        };
+        if let Some(cc_type) = cc_type {
+            cc_data.extend(rest[8..size].chunks_exact(2).map(|pair| CcData {
+                cc_type,
+                data: [pair[0], pair[1]],
+            }));
+        }
+        rest = &rest[size..];
+    }
+
+    cc_data
+}
+
+// Standard characters that differ from ASCII
+fn basic_char(byte: u8) -> char {
+    match byte {
+        0x2A => 'á',
+        0x5C => 'é',
+        0x5E => 'í',
+        0x5F => 'ó',
+        0x60 => 'ú',
+        0x7B => 'ç',
+        0x7C => '÷',
+        0x7D => 'Ñ',
+        0x7E => 'ñ',
+        0x7F => '█',
+        byte => byte as char,
+    }
+}
+
+const SPECIAL_CHARS: [char; 16] = [
+    '®', '°', '½', '¿', '™', '¢', '£', '♪', 'à', ' ', 'è', 'â', 'ê', 'î', 'ô', 'û',
+];
+
+// Spanish, French and miscellaneous (`0x12`), then Portuguese, German and Danish
+// (`0x13`) extended characters
+const EXTENDED_CHARS: [[char; 32]; 2] = [
+    [
+        'Á', 'É', 'Ó', 'Ú', 'Ü', 'ü', '‘', '¡', '*', '\'', '—', '©', '℠', '•', '“', '”', 'À', 'Â',
+        'Ç', 'È', 'Ê', 'Ë', 'ë', 'Î', 'Ï', 'ï', 'Ô', 'Ù', 'ù', 'Û', '«', '»',
+    ],
+    [
+        'Ã', 'ã', 'Í', 'Ì', 'ì', 'Ò', 'ò', 'Õ', 'õ', '{', '}', '\\', '^', '_', '|', '~', 'Ä', 'ä',
+        'Ö', 'ö', 'ß', '¥', '¤', '¦', 'Å', 'å', 'Ø', 'ø', '┌', '┐', '└', '┘',
+    ],
+];
+
+// First row of each preamble address code, by the low bits of its first byte
+const PAC_ROWS: [usize; 8] = [10, 0, 2, 11, 13, 4, 6, 8];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    PopOn,
+    RollUp(usize),
+    PaintOn,
+    // text service, not captions
+    Text,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Screen {
+    rows: [[Option<char>; COLUMNS]; ROWS],
+}
+
+impl Screen {
+    // Rows top to bottom, positioning spaces trimmed
+    fn text(&self) -> String {
+        let lines: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|c| c.unwrap_or(' ')).collect::<String>())
+            .map(|line| line.trim().to_owned())
+            .filter(|line| !line.is_empty())
+            .collect();
+        lines.join("\n")
+    }
+}
+
+// Decodes one CEA-608 channel into cues. Pop-on captions become a cue from the
+// `EOC` that shows them until they're replaced or erased, roll-up captions a cue
+// for every carriage return and paint-on captions a cue per update.
+#[derive(Debug, Clone)]
+pub struct Cea608Decoder {
+    channel: CaptionChannel,
+    mode: Mode,
+    displayed: Screen,
+    non_displayed: Screen,
+    row: usize,
+    column: usize,
+    // whether the last control code selected our data channel
+    selected: bool,
+    last_control: Option<[u8; 2]>,
+    // start and text of the cue on screen
+    showing: Option<(Duration, String)>,
+    cues: Vec<Cue>,
+}
+
+impl Cea608Decoder {
+    pub fn new(channel: CaptionChannel) -> Self {
+        Self {
+            channel,
+            mode: Mode::PopOn,
+            displayed: Screen::default(),
+            non_displayed: Screen::default(),
+            row: ROWS - 1,
+            column: 0,
+            selected: false,
+            last_control: None,
+            showing: None,
+            cues: Vec::new(),
+        }
+    }
+
+    // Feeds the caption data of a sample, triplets of other fields are ignored
+    pub fn decode(&mut self, time: Duration, cc_data: &[CcData]) {
+        let cc_type = self.channel.cc_type();
+        for cc in cc_data.iter().filter(|cc| cc.cc_type == cc_type) {
+            self.decode_pair(time, cc.data[0] & 0x7F, cc.data[1] & 0x7F);
+        }
+
+        if self.mode == Mode::PaintOn {
+            self.commit(time);
+        }
+    }
+
+    // Closes the cue still on screen at `time`
+    pub fn finish(mut self, time: Duration) -> Vec<Cue> {
+        self.displayed = Screen::default();
+        self.commit(time);
+        self.cues
+    }
+
+    fn decode_pair(&mut self, time: Duration, first: u8, second: u8) {
+        match first {
+            0x00 => {}
+            // extended data services, no captions until the next control code
+            0x01..=0x0F => {
+                self.selected = false;
+                self.last_control = None;
+            }
+            0x10..=0x1F => {
+                // control codes are sent twice in a row for redundancy
+                if self.last_control == Some([first, second]) {
+                    self.last_control = None;
+                    return;
+                }
+                self.last_control = Some([first, second]);
+
+                self.selected = (first & 0x08 != 0) == self.channel.second();
+                if self.selected {
+                    self.control(time, first & 0x77, second);
+                }
+            }
+            _ => {
+                self.last_control = None;
+                if self.selected {
+                    self.put(basic_char(first));
+                    if second >= 0x20 {
+                        self.put(basic_char(second));
+                    }
+                }
+            }
+        }
+    }
+
+    fn control(&mut self, time: Duration, first: u8, second: u8) {
+        match (first, second) {
+            // miscellaneous control codes, `0x15` in field 2
+            (0x14 | 0x15, 0x20..=0x2F) => self.command(time, second),
+            // tab offsets
+            (0x17, 0x21..=0x23) => {
+                self.column = (self.column + (second & 0x03) as usize).min(COLUMNS - 1);
+            }
+            // mid-row style changes take up a space
+            (0x11, 0x20..=0x2F) => self.put(' '),
+            (0x11, 0x30..=0x3F) => self.put(SPECIAL_CHARS[(second & 0x0F) as usize]),
+            // extended characters replace the standard one sent before them as a fallback
+            (0x12 | 0x13, 0x20..=0x3F) => {
+                self.backspace();
+                self.put(EXTENDED_CHARS[(first & 0x01) as usize][(second & 0x1F) as usize]);
+            }
+            (_, 0x40..=0x7F) => self.preamble(first, second),
+            _ => {}
+        }
+    }
+
+    fn command(&mut self, time: Duration, command: u8) {
+        match command {
+            // resume caption loading
+            0x20 => self.mode = Mode::PopOn,
+            // backspace
+            0x21 => self.backspace(),
+            // delete to end of row
+            0x24 => {
+                let (row, column) = (self.row, self.column.min(COLUMNS));
+                if let Some(screen) = self.screen() {
+                    screen.rows[row][column..].fill(None);
+                }
+            }
+            // roll-up captions, 2 to 4 rows
+            0x25..=0x27 => {
+                let rows = (command - 0x23) as usize;
+                if !matches!(self.mode, Mode::RollUp(_)) {
+                    self.displayed = Screen::default();
+                    self.non_displayed = Screen::default();
+                    self.row = ROWS - 1;
+                    self.commit(time);
+                }
+                self.mode = Mode::RollUp(rows);
+                self.column = 0;
+            }
+            // resume direct captioning
+            0x29 => self.mode = Mode::PaintOn,
+            // text restart, resume text display
+            0x2A | 0x2B => self.mode = Mode::Text,
+            // erase displayed memory
+            0x2C => {
+                self.displayed = Screen::default();
+                self.commit(time);
+            }
+            // carriage return
+            0x2D => {
+                if let Mode::RollUp(rows) = self.mode {
+                    self.commit(time);
+                    let top = (self.row + 1).saturating_sub(rows);
+                    self.displayed.rows.copy_within(top + 1..=self.row, top);
+                    self.displayed.rows[self.row] = [None; COLUMNS];
+                    self.column = 0;
+                }
+            }
+            // erase non-displayed memory
+            0x2E => self.non_displayed = Screen::default(),
+            // end of caption, flip memories
+            0x2F => {
+                std::mem::swap(&mut self.displayed, &mut self.non_displayed);
+                self.mode = Mode::PopOn;
+                self.commit(time);
+            }
+            // alarm and flash codes
+            _ => {}
+        }
+    }
+
+    fn preamble(&mut self, first: u8, second: u8) {
+        let mut row = PAC_ROWS[(first & 0x07) as usize];
+        if second & 0x20 != 0 && first & 0x07 != 0 {
+            row += 1;
+        }
+
+        // a roll-up window moves along with its base row
+        if let Mode::RollUp(rows) = self.mode {
+            if row != self.row {
+                let window = rows.min(row + 1).min(self.row + 1);
+                let mut moved = Screen::default();
+                for offset in 0..window {
+                    moved.rows[row - offset] = self.displayed.rows[self.row - offset];
+                }
+                self.displayed = moved;
+            }
+        }
+
+        self.row = row;
+        self.column = if second & 0x10 != 0 {
+            ((second & 0x0E) >> 1) as usize * 4
+        } else {
+            0
+        };
+    }
+
+    // The memory characters are written to in the current mode
+    fn screen(&mut self) -> Option<&mut Screen> {
+        match self.mode {
+            Mode::PopOn => Some(&mut self.non_displayed),
+            Mode::RollUp(_) | Mode::PaintOn => Some(&mut self.displayed),
+            Mode::Text => None,
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        let (row, column) = (self.row, self.column.min(COLUMNS - 1));
+        if let Some(screen) = self.screen() {
+            screen.rows[row][column] = Some(c);
+            self.column = column + 1;
+        }
+    }
+
+    fn backspace(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+            let (row, column) = (self.row, self.column.min(COLUMNS - 1));
+            if let Some(screen) = self.screen() {
+                screen.rows[row][column] = None;
+            }
+        }
+    }
+
+    // Ends the cue on screen if the displayed text changed and starts the next one
+    fn commit(&mut self, time: Duration) {
+        let text = self.displayed.text();
+        if self
+            .showing
+            .as_ref()
+            .map_or("", |(_, shown)| shown.as_str())
+            == text
+        {
+            return;
+        }
+
+        if let Some((start, shown)) = self.showing.take() {
+            if time > start {
+                self.cues.push(Cue {
+                    start,
+                    end: time,
+                    text: shown,
+                    ..Default::default()
+                });
+            }
+        }
+        if !text.is_empty() {
+            self.showing = Some((time, text));
+        }
+    }
+}
+
+impl Track {
+    // The caption data of every sample in presentation order, from the SEI messages
+    // of H.264 and H.265 tracks or the samples of a `c608` track
+    pub fn read_cc_data<B: AsRef<[u8]>, R: Read + Seek>(
+        &self,
+        mp4: &Mp4<B>,
+        reader: R,
+    ) -> Result<Vec<CaptionData>> {
+        let mut captions = Vec::new();
+
+        for sample in self.read_samples(mp4, reader) {
+            let (sample, data) = sample?;
+            // SEI NAL units stay in the clear of protected samples
+            let entry = self
+                .sample_description(mp4, &sample)
+                .map(StsdBoxContent::original);
+            let cc_data = match entry {
+                Some(StsdBoxContent::Avc1(avc1)) => {
+                    let length_size = (avc1.avcc.length_size_minus_one & 0x3) as usize + 1;
+                    sei_cc_data(&data, length_size, false)
+                }
+                Some(StsdBoxContent::Hvc1(hevc) | StsdBoxContent::Hev1(hevc)) => {
+                    let length_size = (hevc.hvcc.length_size_minus_one & 0x3) as usize + 1;
+                    sei_cc_data(&data, length_size, true)
+                }
+                Some(StsdBoxContent::C608(_)) => c608_cc_data(&data),
+                _ => continue,
+            };
+
+            if !cc_data.is_empty() {
+                captions.push(CaptionData {
+                    time: self.to_duration(sample.composition_timestamp),
+                    cc_data,
+                });
+            }
+        }
+
+        // B-frames carry their captions in decode order
+        captions.sort_by_key(|caption| caption.time);
+        Ok(captions)
+    }
+
+    // CEA-608 captions of one channel, 708 services aren't decoded
+    pub fn read_captions<B: AsRef<[u8]>, R: Read + Seek>(
+        &self,
+        mp4: &Mp4<B>,
+        reader: R,
+        channel: CaptionChannel,
+    ) -> Result<Vec<Cue>> {
+        let mut decoder = Cea608Decoder::new(channel);
+        for caption in self.read_cc_data(mp4, reader)? {
+            decoder.decode(caption.time, &caption.cc_data);
+        }
+
+        let end = self
+            .track_samples(mp4)
+            .iter()
+            .map(|sample| sample.composition_timestamp + sample.duration as i64)
+            .max()
+            .unwrap_or_default();
+        Ok(decoder.finish(self.to_duration(end)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        boxes::{
+            avc1::{Avc1Box, AvcCBox},
+            c608::C608Box,
+        },
+        Mp4Writer, RawBox, Sample, TrackConfig,
+    };
+
+    const RCL: [u8; 2] = [0x14, 0x20];
+    const EOC: [u8; 2] = [0x14, 0x2F];
+    const EDM: [u8; 2] = [0x14, 0x2C];
+    const RU2: [u8; 2] = [0x14, 0x25];
+    const CR: [u8; 2] = [0x14, 0x2D];
+    // row 15, column 0
+    const PAC: [u8; 2] = [0x14, 0x60];
+
+    fn field1(pairs: &[[u8; 2]]) -> Vec<CcData> {
+        pairs
+            .iter()
+            .map(|data| CcData {
+                cc_type: CcType::Field1,
+                data: *data,
+            })
+            .collect()
+    }
+
+    // Control codes are sent twice
+    fn twice(code: [u8; 2]) -> [[u8; 2]; 2] {
+        [code, code]
+    }
+
+    // ATSC A/53 payload, `cc_valid` set on every triplet but those of type 0xF8
+    fn t35(triplets: &[[u8; 3]]) -> Vec<u8> {
+        let mut payload = vec![0xB5, 0x00, 0x31];
+        payload.extend(b"GA94");
+        payload.extend([0x03, 0x40 | triplets.len() as u8, 0xFF]);
+        payload.extend(triplets.concat());
+        payload.push(0xFF);
+        payload
+    }
+
+    // An SEI NAL unit with emulation prevention bytes inserted
+    fn sei_nal(header: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut rbsp = vec![
+            SEI_USER_DATA_REGISTERED_ITU_T_T35 as u8,
+            payload.len() as u8,
+        ];
+        rbsp.extend(payload);
+        rbsp.push(0x80);
+
+        let mut nal = header.to_vec();
+        let mut zeros = 0;
+        for byte in rbsp {
+            if zeros >= 2 && byte <= 0x03 {
+                nal.push(0x03);
+                zeros = 0;
+            }
+            zeros = if byte == 0 { zeros + 1 } else { 0 };
+            nal.push(byte);
+        }
+        nal
+    }
+
+    fn length_prefixed(nals: &[Vec<u8>], length_size: usize) -> Vec<u8> {
+        let mut sample = Vec::new();
+        for nal in nals {
+            sample.extend(&(nal.len() as u32).to_be_bytes()[4 - length_size..]);
+            sample.extend(nal);
+        }
+        sample
+    }
+
+    fn cue(start: u64, end: u64, text: &str) -> Cue {
+        Cue {
+            start: Duration::from_secs(start),
+            end: Duration::from_secs(end),
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn avc_sei() {
+        let payload = t35(&[
+            [0xFC, 0x94, 0x20],
+            [0xFC, 0x00, 0x00],
+            [0xF8, 0x41, 0x42],
+            [0xFF, 0x02, 0x21],
+        ]);
+        let slice = vec![0x65, 0x88, 0x84];
+        let sample = length_prefixed(&[slice.clone(), sei_nal(&[0x06], &payload), slice], 2);
+
+        assert_eq!(
+            sei_cc_data(&sample, 2, false),
+            [
+                CcData {
+                    cc_type: CcType::Field1,
+                    data: [0x94, 0x20],
+                },
+                CcData {
+                    cc_type: CcType::Field1,
+                    data: [0x00, 0x00],
+                },
+                CcData {
+                    cc_type: CcType::DtvccStart,
+                    data: [0x02, 0x21],
+                },
+            ]
+        );
+
+        assert_eq!(
+            unescape_rbsp(&[0x01, 0, 0, 0x03, 0x01, 0, 0, 0x03]),
+            [0x01, 0, 0, 0x01, 0, 0]
+        );
+
+        // other countries, providers and user data types aren't captions
+        let mut other = payload.clone();
+        other[0] = 0x26;
+        let mut afd = payload;
+        afd[7] = 0x06;
+        for payload in [other, afd] {
+            let sample = length_prefixed(&[sei_nal(&[0x06], &payload)], 4);
+            assert!(sei_cc_data(&sample, 4, false).is_empty());
+        }
+    }
+
+    #[test]
+    fn hevc_sei() {
+        let payload = t35(&[[0xFD, 0x15, 0x2C]]);
+        // prefix SEI, then a suffix SEI
+        let nals = [
+            sei_nal(&[HEVC_NAL_PREFIX_SEI << 1, 0x01], &payload),
+            sei_nal(&[HEVC_NAL_SUFFIX_SEI << 1, 0x01], &payload),
+        ];
+        let sample = length_prefixed(&nals, 4);
+        let cc_data = sei_cc_data(&sample, 4, true);
+        assert_eq!(cc_data.len(), 2);
+        assert_eq!(cc_data[0].cc_type, CcType::Field2);
+        assert_eq!(cc_data[0].data, [0x15, 0x2C]);
+
+        // read as H.264, the first byte is no SEI header
+        assert!(sei_cc_data(&sample, 4, false).is_empty());
+    }
+
+    #[test]
+    fn c608_sample() {
+        let mut sample = vec![0, 0, 0, 12];
+        sample.extend(b"cdat\x94\x20\xC8\xC9");
+        sample.extend([0, 0, 0, 10]);
+        sample.extend(b"cdt2\x15\x2C");
+        sample.extend([0, 0, 0, 10]);
+        sample.extend(b"free\x00\x00");
+        assert_eq!(
+            c608_cc_data(&sample),
+            [
+                CcData {
+                    cc_type: CcType::Field1,
+                    data: [0x94, 0x20],
+                },
+                CcData {
+                    cc_type: CcType::Field1,
+                    data: [0xC8, 0xC9],
+                },
+                CcData {
+                    cc_type: CcType::Field2,
+                    data: [0x15, 0x2C],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pop_on() {
+        let mut decoder = Cea608Decoder::new(CaptionChannel::Cc1);
+        let second = Duration::from_secs;
+        let mut loading = Vec::new();
+        loading.extend(twice(RCL));
+        loading.extend(twice(PAC));
+        // parity bits are ignored, `0x11 0x37` is a music note
+        loading.extend([
+            [0xC8, 0x45],
+            *b"LL",
+            [b'O', 0x80],
+            [0x11, 0x37],
+            [0x11, 0x37],
+        ]);
+        decoder.decode(second(0), &field1(&loading));
+        decoder.decode(second(1), &field1(&twice(EOC)));
+
+        let mut next = Vec::new();
+        next.extend(twice(RCL));
+        next.extend(twice(PAC));
+        // `e` replaced by the extended `É`
+        next.extend([*b"BY", *b"e\0", [0x12, 0x21], [0x12, 0x21]]);
+        decoder.decode(second(2), &field1(&next));
+        // CC2 codes have bit 3 set
+        decoder.decode(second(2), &field1(&twice([0x1C, 0x2C])));
+        decoder.decode(second(3), &field1(&twice(EOC)));
+        decoder.decode(second(4), &field1(&twice(EDM)));
+
+        assert_eq!(
+            decoder.finish(second(5)),
+            [cue(1, 3, "HELLO♪"), cue(3, 4, "BYÉ")]
+        );
+    }
+
+    #[test]
+    fn roll_up() {
+        let mut decoder = Cea608Decoder::new(CaptionChannel::Cc1);
+        let second = Duration::from_secs;
+        let mut first = Vec::new();
+        first.extend(twice(RU2));
+        first.extend(twice(PAC));
+        first.extend([*b"ON", *b"E\0"]);
+        decoder.decode(second(1), &field1(&first));
+        decoder.decode(second(2), &field1(&twice(CR)));
+        decoder.decode(second(3), &field1(&[*b"TW", *b"O\0"]));
+        decoder.decode(second(4), &field1(&twice(CR)));
+        decoder.decode(second(5), &field1(&[*b"TH", *b"RE", *b"E\0"]));
+        decoder.decode(second(6), &field1(&twice(CR)));
+
+        assert_eq!(
+            decoder.finish(second(7)),
+            [
+                cue(2, 4, "ONE"),
+                cue(4, 6, "ONE\nTWO"),
+                cue(6, 7, "TWO\nTHREE"),
+            ]
+        );
+    }
+
+    #[test]
+    fn video_track_captions() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let avc1 = Avc1Box {
+            avcc: RawBox {
+                contents: AvcCBox::new(&[0x67, 0x64, 0, 0x28], &[0x68, 0xEE]),
+                raw: Vec::new(),
+            },
+            ..Default::default()
+        };
+        let video = writer
+            .add_track(TrackConfig::new(1000, StsdBoxContent::Avc1(avc1)))
+            .unwrap();
+        let c608 = writer
+            .add_track(TrackConfig::new(
+                1000,
+                StsdBoxContent::C608(C608Box::default()),
+            ))
+            .unwrap();
+
+        let mut loading = Vec::new();
+        loading.extend(twice(RCL));
+        loading.extend(twice(PAC));
+        loading.extend([*b"HI"]);
+        let pairs = [
+            loading,
+            twice(EOC).to_vec(),
+            Vec::new(),
+            twice(EDM).to_vec(),
+        ];
+        for (id, pairs) in pairs.iter().enumerate() {
+            let sample = Sample {
+                id: id as u32,
+                is_sync: true,
+                timescale: 1000,
+                decode_timestamp: id as i64 * 1000,
+                composition_timestamp: id as i64 * 1000,
+                duration: 1000,
+                ..Default::default()
+            };
+            let triplets: Vec<_> = pairs.iter().map(|[a, b]| [0xFC, *a, *b]).collect();
+            let mut nals = vec![vec![0x65, 0x88, 0x84]];
+            if !triplets.is_empty() {
+                nals.push(sei_nal(&[0x06], &t35(&triplets)));
+            }
+            // the default avcC has 4 byte lengths
+            let data = length_prefixed(&nals, 4);
+            writer.write_sample(video, &sample, &data).unwrap();
+
+            let mut cdat = (8 + 2 * pairs.len() as u32).to_be_bytes().to_vec();
+            cdat.extend(b"cdat");
+            cdat.extend(pairs.concat());
+            writer.write_sample(c608, &sample, &cdat).unwrap();
+        }
+        let file = writer.finish().unwrap().into_inner();
+
+        let mp4 = Mp4::read(Cursor::new(&file[..]), file.len() as u64).unwrap();
+        for track_id in [video, c608] {
+            let track = &mp4.tracks()[&track_id];
+            let cc_data = track.read_cc_data(&mp4, Cursor::new(&file[..])).unwrap();
+            let times: Vec<_> = cc_data.iter().map(|caption| caption.time).collect();
+            assert_eq!(times, [0, 1, 3].map(Duration::from_secs));
+
+            let cues = track
+                .read_captions(&mp4, Cursor::new(&file[..]), CaptionChannel::Cc1)
+                .unwrap();
+            assert_eq!(cues, [cue(1, 3, "HI")]);
+            let cues = track
+                .read_captions(&mp4, Cursor::new(&file[..]), CaptionChannel::Cc3)
+                .unwrap();
+            assert!(cues.is_empty());
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    io::{Read, Seek},
+    time::Duration,
+};
+
+use crate::{boxes::tref::ReferenceType, Mp4, Result, TrackId, Tx3gBox};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    pub start: Duration,
+    pub title: String,
+}
+
+impl<B: AsRef<[u8]>> Mp4<B> {
+    // The QuickTime chapter track, a text track another track points at with `tref/chap`
+    pub fn chapter_track_id(&self) -> Option<TrackId> {
+        self.moov
+            .traks
+            .iter()
+            .filter_map(|trak| trak.tref.as_ref())
+            .flat_map(|tref| tref.track_ids(ReferenceType::Chapter))
+            .copied()
+            .find(|track_id| self.tracks.contains_key(track_id))
+    }
+
+    // Chapters of the QuickTime chapter track if there is one, the Nero `udta/chpl`
+    // list otherwise. Only the chapter track needs `reader`, its titles are samples.
+    pub fn chapters<R: Read + Seek>(&self, reader: &mut R) -> Result<Vec<Chapter>> {
+        if let Some(track_id) = self.chapter_track_id() {
+            let track = &self.tracks[&track_id];
+            let mut chapters = Vec::new();
+
+            for sample in track.read_samples(self, &mut *reader) {
+                let (sample, data) = sample?;
+                chapters.push(Chapter {
+                    start: track.to_duration(sample.composition_timestamp),
+                    title: Tx3gBox::sample_text(&data).into_owned(),
+                });
+            }
+
+            return Ok(chapters);
+        }
+
+        let chpl = self.moov.udta.as_ref().and_then(|udta| udta.chpl.as_ref());
+        Ok(chpl
+            .map(|chpl| {
+                chpl.entries
+                    .iter()
+                    .map(|entry| Chapter {
+                        start: Duration::from_nanos(entry.start.saturating_mul(100)),
+                        title: entry.title.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{boxes::mp4a::Mp4aBox, Mp4Writer, Sample, StsdBoxContent, TrackConfig};
+
+    #[test]
+    fn written_chapters() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(1000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let main = writer.add_track(config.clone()).unwrap();
+        let fallback = writer.add_track(config).unwrap();
+        writer
+            .add_track_reference(fallback, ReferenceType::Fallback, main)
+            .unwrap();
+        for track_id in [main, fallback] {
+            for id in 0..30u32 {
+                let sample = Sample {
+                    id,
+                    is_sync: true,
+                    timescale: 1000,
+                    decode_timestamp: id as i64 * 100,
+                    composition_timestamp: id as i64 * 100,
+                    duration: 100,
+                    ..Default::default()
+                };
+                writer.write_sample(track_id, &sample, &[0; 4]).unwrap();
+            }
+        }
+        let chapters = vec![
+            Chapter {
+                start: Duration::from_millis(1500),
+                title: String::from("Second"),
+            },
+            Chapter {
+                start: Duration::ZERO,
+                title: String::from("First"),
+            },
+        ];
+        writer.set_chapters(chapters.clone());
+        let file = writer.finish().unwrap().into_inner();
+
+        let mut reader = Cursor::new(&file[..]);
+        let mut mp4 = Mp4::read(&mut reader, file.len() as u64).unwrap();
+        let chapter_track = mp4.chapter_track_id().unwrap();
+        let references = mp4.track_references();
+        assert_eq!(
+            references.references(fallback),
+            [
+                (ReferenceType::Fallback, main),
+                (ReferenceType::Chapter, chapter_track)
+            ]
+        );
+        assert_eq!(
+            references.references(main),
+            [(ReferenceType::Chapter, chapter_track)]
+        );
+
+        let mut sorted = chapters;
+        sorted.reverse();
+        assert_eq!(mp4.chapters(&mut reader).unwrap(), sorted);
+
+        // without the reference only the `chpl` list is left
+        for trak in &mut mp4.moov.traks {
+            trak.tref = None;
+        }
+        assert_eq!(mp4.chapter_track_id(), None);
+        let chpl = mp4.moov.udta.as_ref().unwrap().chpl.as_ref().unwrap();
+        assert_eq!(chpl.entries.len(), 2);
+        assert_eq!(mp4.chapters(&mut reader).unwrap(), sorted);
+    }
+}
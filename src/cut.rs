@@ -0,0 +1,330 @@
+use std::{
+    io::{Read, Seek, Write},
+    time::Duration,
+};
+
+use crate::{
+    boxes::{meta::MetaBox, udta::UdtaBox},
+    copy_box, Chapter, Edit, Error, Mp4, Mp4Writer, Result, Sample, StsdBox, StsdBoxContent,
+    TrackConfig,
+};
+
+// Decode order indices of the samples to copy: from the sync sample the frame shown at
+// `start` depends on, to the last sample shown before `end`
+fn kept_samples(samples: &[Sample], start: i64, end: i64) -> Option<(usize, usize)> {
+    let shown = samples
+        .iter()
+        .enumerate()
+        .filter(|(_, sample)| sample.composition_timestamp <= start)
+        .max_by_key(|(_, sample)| sample.composition_timestamp)
+        .map_or(0, |(index, _)| index);
+    let first = samples[..=shown]
+        .iter()
+        .rposition(|sample| sample.is_sync)
+        .unwrap_or(0);
+
+    let last = samples
+        .iter()
+        .rposition(|sample| sample.composition_timestamp < end)?;
+
+    (last >= first).then_some((first, last))
+}
+
+impl<B: AsRef<[u8]>> Mp4<B> {
+    // Copies `start..end` of the movie without re-encoding. Every track starts at the
+    // sync sample before `start`, and an edit list hides what comes before `start` so
+    // all tracks still begin presenting together. Chapters are moved along, track
+    // headers, handler names and the movie metadata are kept.
+    pub fn cut<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: W,
+        start: Duration,
+        end: Duration,
+    ) -> Result<W> {
+        if end <= start {
+            return Err(Error::invalid_input("cut ends before it starts"));
+        }
+
+        let mut mp4_writer = Mp4Writer::new(writer)?;
+        let chapter_track = self.chapter_track_id();
+        let mut order = Vec::new();
+
+        for (&track_id, track) in &self.tracks {
+            if Some(track_id) == chapter_track {
+                continue;
+            }
+
+            let table = track.sample_table(self);
+            let table_samples = table.iter().take(track.table_samples as usize);
+            let samples: Vec<_> = table_samples.chain(track.samples.iter().cloned()).collect();
+
+            let start = track.from_duration(start);
+            let end = track.from_duration(end);
+            let Some((first, last)) = kept_samples(&samples, start, end) else {
+                continue;
+            };
+            let kept = &samples[first..=last];
+
+            let trak = track.trak(self);
+            let stsd: StsdBox = copy_box(&trak.mdia.minf.stbl.stsd)?;
+            let entries = &stsd.entries;
+            if entries.is_empty() {
+                continue;
+            }
+            if entries
+                .iter()
+                .any(|entry| matches!(entry, StsdBoxContent::Unknown(_)))
+            {
+                return Err(Error::invalid_input(format!(
+                    "track {track_id} has a sample description of unknown format"
+                )));
+            }
+            if entries.iter().any(|entry| entry.protection().is_some()) {
+                return Err(Error::invalid_input(format!(
+                    "track {track_id} is encrypted, decrypt it before cutting"
+                )));
+            }
+
+            // the copied samples are decoded from 0, shown from `media_start` on
+            let decode_start = kept[0].decode_timestamp;
+            let kept_end = kept
+                .iter()
+                .map(|sample| sample.composition_timestamp + sample.duration as i64)
+                .max()
+                .unwrap_or(end);
+            let media_start = kept
+                .iter()
+                .map(|sample| sample.composition_timestamp)
+                .min()
+                .map_or(start, |first_shown| first_shown.max(start));
+            let media_end = end.min(kept_end);
+            if media_end <= media_start {
+                continue;
+            }
+
+            let output_id = mp4_writer.add_track(TrackConfig {
+                timescale: track.time_scale as u32,
+                language: trak.mdia.mdhd.language.clone(),
+                contents: entries[0].clone(),
+            })?;
+            for entry in &entries[1..] {
+                mp4_writer.add_sample_description(output_id, entry.clone())?;
+            }
+            mp4_writer.set_track_header(output_id, trak.tkhd.clone())?;
+            mp4_writer.set_handler_name(output_id, trak.mdia.hdlr.name.clone())?;
+
+            let mut edits = Vec::new();
+            if media_start > start {
+                edits.push(Edit {
+                    presentation_time: 0,
+                    duration: (media_start - start) as u64,
+                    media_time: None,
+                    media_rate: 1,
+                });
+            }
+            edits.push(Edit {
+                presentation_time: (media_start - start) as u64,
+                duration: (media_end - media_start) as u64,
+                media_time: Some(media_start - decode_start),
+                media_rate: 1,
+            });
+            mp4_writer.set_edits(output_id, edits)?;
+
+            for sample in kept {
+                let time = track.to_duration(sample.decode_timestamp);
+                order.push((time, track_id, sample.id, output_id));
+            }
+        }
+
+        // interleave the tracks by decode time
+        order.sort_by_key(|&(time, track_id, index, _)| (time, track_id, index));
+        for (_, track_id, sample_id, output_id) in order {
+            let data = self.read_sample(reader, track_id, sample_id)?;
+            let sample = self.sample(track_id, sample_id)?;
+            mp4_writer.write_sample(output_id, &sample, &data)?;
+        }
+
+        // the chapter running at `start` becomes the first one
+        let mut chapters = self.chapters(reader)?;
+        chapters.sort_by_key(|chapter| chapter.start);
+        let current = chapters
+            .iter()
+            .rposition(|chapter| chapter.start <= start)
+            .unwrap_or(0);
+        let chapters = chapters
+            .into_iter()
+            .skip(current)
+            .take_while(|chapter| chapter.start < end)
+            .map(|chapter| Chapter {
+                start: chapter.start.saturating_sub(start),
+                title: chapter.title,
+            })
+            .collect();
+        mp4_writer.set_chapters(chapters);
+
+        let meta: Option<MetaBox> = self.moov.meta.as_ref().map(copy_box).transpose()?;
+        let udta: Option<UdtaBox> = self.moov.udta.as_ref().map(copy_box).transpose()?;
+        // the `chpl` is rebuilt from the chapters set above
+        let udta = udta
+            .map(|udta| UdtaBox { chpl: None, ..udta })
+            .filter(|udta| udta.meta.is_some() || !udta.unknown.is_empty());
+        mp4_writer.set_metadata(meta, udta);
+
+        mp4_writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        boxes::{mp4a::Mp4aBox, tests::write, tkhd::TkhdBox},
+        Matrix, Metadata, MetadataKey, MetadataValue, MoovBox, UnknownBox,
+    };
+
+    fn write_movie(second_entry: StsdBoxContent) -> Vec<u8> {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        writer
+            .add_sample_description(track_id, second_entry)
+            .unwrap();
+        let tkhd = TkhdBox {
+            flags: 0x3,
+            alternate_group: 1,
+            matrix: Matrix {
+                a: 0,
+                b: 0x10000,
+                c: -0x10000,
+                d: 0,
+                ..TkhdBox::default().matrix
+            },
+            ..Default::default()
+        };
+        writer.set_track_header(track_id, tkhd).unwrap();
+        writer
+            .set_handler_name(track_id, String::from("Core Media Audio"))
+            .unwrap();
+        let mut moov = MoovBox::default();
+        moov.ilst_mut()
+            .set(MetadataKey::Title, MetadataValue::Text("title".into()));
+        writer.set_metadata(None, moov.udta);
+        for id in 0..10u32 {
+            let sample = Sample {
+                id,
+                is_sync: id % 4 == 0,
+                timescale: 48000,
+                decode_timestamp: id as i64 * 1024,
+                composition_timestamp: id as i64 * 1024,
+                duration: 1024,
+                sample_description_index: if id < 5 { 1 } else { 2 },
+                ..Default::default()
+            };
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 16])
+                .unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn cut_from_sync_sample() {
+        let mono = StsdBoxContent::Mp4a(Mp4aBox {
+            channelcount: 1,
+            ..Default::default()
+        });
+        let file = write_movie(mono);
+        let mut reader = Cursor::new(&file[..]);
+        let mp4 = Mp4::read(&mut reader, file.len() as u64).unwrap();
+
+        // samples 6 to 8 are shown, decoding starts at the sync sample 4
+        let (start, end) = (Duration::from_millis(128), Duration::from_millis(192));
+        let cut = mp4
+            .cut(&mut reader, Cursor::new(Vec::new()), start, end)
+            .unwrap()
+            .into_inner();
+        let mut cut_reader = Cursor::new(&cut[..]);
+        let cut = Mp4::read(&mut cut_reader, cut.len() as u64).unwrap();
+
+        let track = &cut.tracks()[&1];
+        let (trak, input) = (track.trak(&cut), mp4.tracks()[&1].trak(&mp4));
+        assert_eq!(
+            write(&trak.mdia.minf.stbl.stsd),
+            write(&input.mdia.minf.stbl.stsd)
+        );
+        assert_eq!(trak.tkhd.flags, input.tkhd.flags);
+        assert_eq!(trak.tkhd.alternate_group, 1);
+        assert_eq!(trak.tkhd.matrix, input.tkhd.matrix);
+        assert_eq!(trak.mdia.hdlr.name, "Core Media Audio");
+        assert_eq!(cut.title().as_deref(), Some("title"));
+        assert_eq!(
+            cut.moov.udta.as_ref().unwrap().meta,
+            mp4.moov.udta.unwrap().meta
+        );
+        let indices: Vec<_> = track
+            .samples
+            .iter()
+            .map(|sample| sample.sample_description_index)
+            .collect();
+        assert_eq!(indices, [1, 2, 2, 2, 2]);
+        for (sample, id) in track.samples.iter().zip(4u8..) {
+            let data = cut.read_sample(&mut cut_reader, 1, sample.id).unwrap();
+            assert_eq!(&data[..], &[id; 16]);
+        }
+        assert!(track.samples[0].is_sync);
+        assert_eq!(
+            track.edits(),
+            [Edit {
+                presentation_time: 0,
+                duration: 3 * 1024,
+                media_time: Some(2 * 1024),
+                media_rate: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn cut_borrowed() {
+        let file = write_movie(StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let mp4 = Mp4::from_bytes(&file).unwrap();
+
+        let cut = mp4
+            .cut(
+                &mut Cursor::new(&file[..]),
+                Cursor::new(Vec::new()),
+                Duration::from_millis(128),
+                Duration::from_millis(192),
+            )
+            .unwrap()
+            .into_inner();
+        let cut = Mp4::from_bytes(&cut).unwrap();
+        let track = &cut.tracks()[&1];
+        assert_eq!(track.samples.len(), 5);
+        assert_eq!(
+            write(&track.trak(&cut).mdia.minf.stbl.stsd),
+            write(&mp4.tracks()[&1].trak(&mp4).mdia.minf.stbl.stsd)
+        );
+    }
+
+    #[test]
+    fn cut_unknown_entry() {
+        let unknown = StsdBoxContent::Unknown(UnknownBox {
+            fourcc: (*b"abcd").into(),
+            payload: vec![0; 8],
+        });
+        let file = write_movie(unknown);
+        let mut reader = Cursor::new(&file[..]);
+        let mp4 = Mp4::read(&mut reader, file.len() as u64).unwrap();
+
+        let cut = mp4.cut(
+            &mut reader,
+            Cursor::new(Vec::new()),
+            Duration::ZERO,
+            Duration::from_secs(1),
+        );
+        assert!(matches!(cut, Err(Error::InvalidInput(_))));
+    }
+}
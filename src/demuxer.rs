@@ -0,0 +1,622 @@
+use std::{collections::BTreeMap, io::Cursor};
+
+use crate::{
+    encryption::read_traf_aux_info, BoxHeader, BoxType, EmsgBox, Error, FtypBox, MoofBox, MoovBox,
+    Mp4, ReadChild, Result, Sample, SidxBox, StypBox, TrackId, HEADER_SIZE,
+};
+
+// Boxes are buffered whole before they're parsed, an `mdat` holds all samples of a fragment
+const DEFAULT_MAX_BOX_SIZE: u64 = 128 << 20;
+
+#[derive(Debug)]
+pub enum DemuxEvent {
+    /// `ftyp` and `moov` were parsed, see [`Mp4Demuxer::mp4`]
+    InitSegment,
+    Fragment(Fragment),
+    Emsg(EmsgBox),
+    /// Start of a media segment
+    Styp(StypBox),
+    Sidx(SidxBox),
+}
+
+#[derive(Debug)]
+pub struct Fragment {
+    pub moof: MoofBox,
+    pub samples: BTreeMap<TrackId, Vec<Sample>>,
+    /// stream offset of the first byte of `data`
+    pub data_offset: u64,
+    pub data: Vec<u8>,
+}
+
+impl Fragment {
+    pub fn sample_data(&self, sample: &Sample) -> Option<&[u8]> {
+        let start = sample.offset.checked_sub(self.data_offset)? as usize;
+        self.data.get(start..start + sample.size as usize)
+    }
+}
+
+#[derive(Debug)]
+pub struct Mp4Demuxer {
+    buffer: Vec<u8>,
+    // stream offset of `buffer[0]`
+    position: u64,
+    // bytes of a box that isn't kept still to be dropped
+    skip: u64,
+    max_box_size: u64,
+    ftyp: Option<FtypBox>,
+    mp4: Option<Mp4>,
+    last_run_position: u64,
+    next_sample_ids: BTreeMap<TrackId, u32>,
+    pending: Option<PendingFragment>,
+}
+
+// A moof waiting for its mdat
+#[derive(Debug)]
+struct PendingFragment {
+    moof: MoofBox,
+    samples: BTreeMap<TrackId, Vec<Sample>>,
+    bytes: Vec<u8>,
+}
+
+impl Default for Mp4Demuxer {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            position: 0,
+            skip: 0,
+            max_box_size: DEFAULT_MAX_BOX_SIZE,
+            ftyp: None,
+            mp4: None,
+            last_run_position: 0,
+            next_sample_ids: BTreeMap::new(),
+            pending: None,
+        }
+    }
+}
+
+impl Mp4Demuxer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Boxes that are parsed are buffered until they're complete, larger ones fail `push`
+    // instead of growing the buffer. Boxes that aren't parsed are dropped as they come.
+    pub fn set_max_box_size(&mut self, size: u64) {
+        self.max_box_size = size;
+    }
+
+    pub fn mp4(&self) -> Option<&Mp4> {
+        self.mp4.as_ref()
+    }
+
+    pub fn push(&mut self, data: &[u8]) -> Result<Vec<DemuxEvent>> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.extend_from_slice(data);
+
+        let mut events = Vec::new();
+        let mut consumed = 0;
+
+        let result = loop {
+            let skipped = self.skip.min((buffer.len() - consumed) as u64);
+            consumed += skipped as usize;
+            self.skip -= skipped;
+            if self.skip > 0 {
+                break Ok(());
+            }
+
+            let (name, size) = match peek_box(&buffer[consumed..]) {
+                Ok(Some(next)) => next,
+                Ok(None) => break Ok(()),
+                Err(err) => break Err(err.shifted(self.position + consumed as u64)),
+            };
+            let start = self.position + consumed as u64;
+            if !self.is_parsed(name) {
+                self.skip = size;
+                continue;
+            }
+            if size > self.max_box_size {
+                let message = format!(
+                    "box of {size} bytes is larger than the limit of {}",
+                    self.max_box_size
+                );
+                break Err(Error::invalid_value(message).within(name, start));
+            }
+            if ((buffer.len() - consumed) as u64) < size {
+                break Ok(());
+            }
+
+            let bytes = &buffer[consumed..consumed + size as usize];
+            consumed += size as usize;
+
+            match self.handle_box(name, start, bytes) {
+                Ok(Some(event)) => events.push(event),
+                Ok(None) => {}
+                // boxes are parsed from their own buffer, move offsets onto the stream
+                Err(err) => break Err(err.shifted(start)),
+            }
+        };
+
+        buffer.drain(..consumed);
+        self.buffer = buffer;
+        self.position += consumed as u64;
+
+        result.map(|_| events)
+    }
+
+    fn is_parsed(&self, name: BoxType) -> bool {
+        match name {
+            BoxType::FtypBox
+            | BoxType::MoovBox
+            | BoxType::MoofBox
+            | BoxType::EmsgBox
+            | BoxType::StypBox
+            | BoxType::SidxBox => true,
+            // only the mdat following a moof has samples we know of
+            BoxType::MdatBox => self.pending.is_some(),
+            _ => false,
+        }
+    }
+
+    fn handle_box(
+        &mut self,
+        name: BoxType,
+        start: u64,
+        bytes: &[u8],
+    ) -> Result<Option<DemuxEvent>> {
+        let mut reader = Cursor::new(bytes);
+        let header = BoxHeader::read(&mut reader)?;
+
+        match name {
+            BoxType::FtypBox => {
+                self.ftyp
+                    .replace(FtypBox::read_child(&mut reader, &header)?);
+                Ok(None)
+            }
+            BoxType::MoovBox => {
+                let Some(ftyp) = self.ftyp.take() else {
+                    return Err(Error::missing_box(BoxType::FtypBox, 0));
+                };
+                let moov = MoovBox::read_child(&mut reader, &header)?;
+
+                let mut mp4 = Mp4 {
+                    ftyp,
+                    moov,
+                    moofs: Vec::new(),
+                    emsgs: Vec::new(),
+                    styp: None,
+                    sidxs: Vec::new(),
+                    ssixs: Vec::new(),
+                    mfra: None,
+                    tracks: Default::default(),
+                };
+                mp4.tracks = mp4.build_tracks(true);
+                mp4.update_tracks();
+
+                self.next_sample_ids = mp4
+                    .tracks
+                    .iter()
+                    .map(|(track_id, track)| (*track_id, track.samples.len() as u32))
+                    .collect();
+                self.mp4 = Some(mp4);
+
+                Ok(Some(DemuxEvent::InitSegment))
+            }
+            BoxType::MoofBox => {
+                let Some(mp4) = &mut self.mp4 else {
+                    return Err(Error::missing_box(BoxType::MoovBox, 0));
+                };
+                let mut moof = MoofBox::read_child(&mut reader, &header)?;
+                moof.start = start;
+
+                let samples = merge_fragment(
+                    mp4,
+                    &moof,
+                    &mut self.last_run_position,
+                    &mut self.next_sample_ids,
+                )?;
+                self.pending = Some(PendingFragment {
+                    moof,
+                    samples,
+                    bytes: bytes.to_vec(),
+                });
+
+                Ok(None)
+            }
+            BoxType::MdatBox => {
+                let (Some(mp4), Some(pending)) = (&self.mp4, self.pending.take()) else {
+                    return Ok(None);
+                };
+                let PendingFragment {
+                    moof,
+                    mut samples,
+                    bytes: mut stream,
+                } = pending;
+                let header_size = reader.position();
+
+                if moof
+                    .trafs
+                    .iter()
+                    .any(|traf| traf.senc.is_none() && traf.saio.is_some())
+                {
+                    // `saio` points into the moof or the mdat right after it
+                    if moof.start + stream.len() as u64 == start {
+                        stream.extend_from_slice(bytes);
+                    }
+                    let mut stream = Cursor::new(stream);
+                    let mut first_samples = BTreeMap::new();
+                    for traf in &moof.trafs {
+                        let track_id = traf.tfhd.track_id;
+                        let first_sample = first_samples.entry(track_id).or_insert(0);
+                        let first = *first_sample;
+                        *first_sample += traf
+                            .truns
+                            .iter()
+                            .map(|trun| trun.sample_count as usize)
+                            .sum::<usize>();
+
+                        let (Some(trak), Some(samples)) = (
+                            mp4.moov
+                                .traks
+                                .iter()
+                                .find(|trak| trak.tkhd.track_id == track_id),
+                            samples.get_mut(&track_id),
+                        ) else {
+                            continue;
+                        };
+                        read_traf_aux_info(
+                            &mut stream,
+                            moof.start,
+                            trak,
+                            &moof,
+                            traf,
+                            first,
+                            samples,
+                        )?;
+                    }
+                }
+
+                Ok(Some(DemuxEvent::Fragment(Fragment {
+                    moof,
+                    samples,
+                    data_offset: start + header_size,
+                    data: bytes[header_size as usize..].to_vec(),
+                })))
+            }
+            BoxType::EmsgBox => Ok(Some(DemuxEvent::Emsg(EmsgBox::read_child(
+                &mut reader,
+                &header,
+            )?))),
+            BoxType::StypBox => Ok(Some(DemuxEvent::Styp(StypBox::read_child(
+                &mut reader,
+                &header,
+            )?))),
+            BoxType::SidxBox => {
+                let mut sidx = SidxBox::read_child(&mut reader, &header)?;
+                sidx.start = start;
+                Ok(Some(DemuxEvent::Sidx(sidx)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+// Only the last sample of every track is kept around, the fragment reader needs
+// it to continue the decode timeline when a traf carries no tfdt.
+fn merge_fragment(
+    mp4: &mut Mp4,
+    moof: &MoofBox,
+    last_run_position: &mut u64,
+    next_sample_ids: &mut BTreeMap<TrackId, u32>,
+) -> Result<BTreeMap<TrackId, Vec<Sample>>> {
+    let previous = mp4
+        .tracks
+        .iter()
+        .map(|(track_id, track)| (*track_id, track.samples.len()))
+        .collect::<BTreeMap<_, _>>();
+
+    Mp4::merge_moof(&mp4.moov, &mut mp4.tracks, moof, last_run_position)?;
+
+    let mut samples = BTreeMap::new();
+    for (track_id, track) in mp4.tracks.iter_mut() {
+        let mut new_samples = track.samples.split_off(previous[track_id]);
+        if new_samples.is_empty() {
+            continue;
+        }
+
+        let next_id = next_sample_ids.entry(*track_id).or_default();
+        for sample in &mut new_samples {
+            sample.id = *next_id;
+            *next_id += 1;
+        }
+
+        track.samples = new_samples.last().cloned().into_iter().collect();
+        samples.insert(*track_id, new_samples);
+    }
+
+    Ok(samples)
+}
+
+fn peek_box(buffer: &[u8]) -> Result<Option<(BoxType, u64)>> {
+    if (buffer.len() as u64) < HEADER_SIZE {
+        return Ok(None);
+    }
+
+    let size = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as u64;
+    let name = BoxType::from(u32::from_be_bytes([
+        buffer[4], buffer[5], buffer[6], buffer[7],
+    ]));
+
+    let size = match size {
+        0 => {
+            return Err(Error::invalid_value(
+                "boxes extending to the end of the stream are not supported",
+            )
+            .within(name, 0))
+        }
+        1 => {
+            if (buffer.len() as u64) < HEADER_SIZE + 8 {
+                return Ok(None);
+            }
+            let mut largesize = [0u8; 8];
+            largesize.copy_from_slice(&buffer[8..16]);
+            u64::from_be_bytes(largesize)
+        }
+        size => size,
+    };
+
+    if size < HEADER_SIZE {
+        return Err(Error::invalid_value("box size is smaller than its header").within(name, 0));
+    }
+
+    Ok(Some((name, size)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        boxes::{
+            mfhd::MfhdBox,
+            mp4a::Mp4aBox,
+            saio::SaioBox,
+            saiz::SaizBox,
+            sinf::{FrmaBox, SchiBox, SchmBox},
+            tests::write,
+            tfdt::TfdtBox,
+        },
+        FourCC, FragmentedMp4Writer, Mp4Box, SampleEncryption, SinfBox, StsdBoxContent, TencBox,
+        TfhdBox, TrackConfig, TrafBox, TrunBox, WriteBox,
+    };
+
+    fn audio_sample(id: u32) -> Sample {
+        Sample {
+            id,
+            is_sync: true,
+            timescale: 48000,
+            decode_timestamp: id as i64 * 1024,
+            composition_timestamp: id as i64 * 1024,
+            duration: 1024,
+            ..Default::default()
+        }
+    }
+
+    // Splits a stream into its top level boxes
+    fn boxes(mut data: &[u8]) -> Vec<&[u8]> {
+        let mut boxes = Vec::new();
+        while !data.is_empty() {
+            let size = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+            let (bx, rest) = data.split_at(size);
+            boxes.push(bx);
+            data = rest;
+        }
+        boxes
+    }
+
+    fn push_chunked(demuxer: &mut Mp4Demuxer, data: &[u8], chunk: usize) -> Vec<DemuxEvent> {
+        let mut events = Vec::new();
+        for chunk in data.chunks(chunk) {
+            events.extend(demuxer.push(chunk).unwrap());
+        }
+        events
+    }
+
+    #[test]
+    fn chunked_segments() {
+        let mut writer = FragmentedMp4Writer::new(Vec::new());
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        writer.write_init_segment().unwrap();
+        for id in 0..6u32 {
+            let data = vec![id as u8; 10 + id as usize];
+            writer
+                .write_sample(track_id, &audio_sample(id), &data)
+                .unwrap();
+            if id == 2 {
+                writer.flush_segment().unwrap();
+            }
+        }
+        let file = writer.finish().unwrap();
+
+        // ftyp moov, then a styp, sidx and free box ahead of each moof mdat pair
+        let styp = StypBox {
+            major_brand: FourCC::from(*b"msdh"),
+            minor_version: 0,
+            compatible_brands: vec![FourCC::from(*b"msdh"), FourCC::from(*b"msix")],
+        };
+        let sidx = SidxBox {
+            reference_id: 1,
+            timescale: 48000,
+            ..Default::default()
+        };
+        let free = [&[0, 0, 0x08, 0x00][..], b"free", &[0; 2040]].concat();
+        let boxes = boxes(&file);
+        let mut stream = boxes[..2].concat();
+        for segment in boxes[2..].chunks(2) {
+            stream.extend(write(&styp));
+            stream.extend(write(&sidx));
+            stream.extend(&free);
+            stream.extend(segment.concat());
+        }
+
+        let mut demuxer = Mp4Demuxer::new();
+        // boxes that are dropped aren't held to the limit
+        demuxer.set_max_box_size(1024);
+        let events = push_chunked(&mut demuxer, &stream, 7);
+        assert!(demuxer.buffer.is_empty());
+        assert!(demuxer.mp4().is_some());
+
+        assert!(matches!(events[0], DemuxEvent::InitSegment));
+        let mut fragments = Vec::new();
+        for segment in events[1..].chunks(3) {
+            let [DemuxEvent::Styp(read_styp), DemuxEvent::Sidx(read_sidx), DemuxEvent::Fragment(fragment)] =
+                segment
+            else {
+                panic!("unexpected events {segment:?}");
+            };
+            assert_eq!(read_styp, &styp);
+            assert_eq!(read_sidx.timescale, 48000);
+            fragments.push(fragment);
+        }
+        assert_eq!(fragments.len(), 2);
+
+        let mut offset = 0;
+        for (fragment, ids) in fragments.iter().zip([0..3, 3..6]) {
+            let samples = &fragment.samples[&track_id];
+            assert_eq!(
+                samples.iter().map(|s| s.id).collect::<Vec<_>>(),
+                Vec::from_iter(ids.clone())
+            );
+            for (sample, id) in samples.iter().zip(ids) {
+                assert_eq!(sample.decode_timestamp, id as i64 * 1024);
+                let data = fragment.sample_data(sample).unwrap();
+                assert_eq!(data, &vec![id as u8; 10 + id as usize][..]);
+            }
+            assert!(fragment.data_offset > offset);
+            offset = fragment.data_offset;
+        }
+    }
+
+    #[test]
+    fn max_box_size() {
+        let mut writer = FragmentedMp4Writer::new(Vec::new());
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        writer.write_init_segment().unwrap();
+        writer
+            .write_sample(track_id, &audio_sample(0), &[0; 4096])
+            .unwrap();
+        let file = writer.finish().unwrap();
+
+        let mut demuxer = Mp4Demuxer::new();
+        demuxer.set_max_box_size(1024);
+        let boxes = boxes(&file);
+        let init = boxes[..2].concat();
+        let events = demuxer.push(&init).unwrap();
+        assert!(matches!(events[..], [DemuxEvent::InitSegment]));
+        assert!(demuxer.push(boxes[2]).unwrap().is_empty());
+
+        // only the mdat header is needed to tell it's too large
+        let err = demuxer.push(&boxes[3][..8]).unwrap_err();
+        assert!(matches!(err, Error::InvalidValue { .. }));
+        assert_eq!(err.offset(), Some((init.len() + boxes[2].len()) as u64));
+    }
+
+    #[test]
+    fn auxiliary_info() {
+        let tenc = TencBox {
+            default_is_protected: true,
+            default_per_sample_iv_size: 8,
+            default_kid: [1; 16],
+            ..Default::default()
+        };
+        let entry = StsdBoxContent::Encrypted {
+            entry: Box::new(StsdBoxContent::Mp4a(Mp4aBox::default())),
+            sinf: SinfBox {
+                frma: FrmaBox {
+                    original_format: FourCC::from(*b"mp4a"),
+                },
+                schm: Some(SchmBox {
+                    scheme_type: FourCC::from(*b"cenc"),
+                    scheme_version: 0x10000,
+                    ..Default::default()
+                }),
+                schi: Some(SchiBox {
+                    tenc: Some(tenc),
+                    unknown: Vec::new(),
+                }),
+                unknown: Vec::new(),
+            },
+        };
+        let mut writer = FragmentedMp4Writer::new(Vec::new());
+        writer.add_track(TrackConfig::new(48000, entry)).unwrap();
+        writer.write_init_segment().unwrap();
+        let mut stream = writer.finish().unwrap();
+
+        // the IVs are at the start of the mdat, ahead of the samples
+        let ivs: Vec<u8> = (1..=3).flat_map(|id| [id; 8]).collect();
+        let mut moof = MoofBox {
+            mfhd: MfhdBox {
+                version: 0,
+                flags: 0,
+                sequence_number: 1,
+            },
+            trafs: vec![TrafBox {
+                tfhd: TfhdBox {
+                    flags: TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF,
+                    track_id: 1,
+                    ..Default::default()
+                },
+                tfdt: Some(TfdtBox::default()),
+                truns: vec![TrunBox {
+                    flags: TrunBox::FLAG_DATA_OFFSET
+                        | TrunBox::FLAG_SAMPLE_DURATION
+                        | TrunBox::FLAG_SAMPLE_SIZE,
+                    sample_count: 3,
+                    data_offset: Some(0),
+                    sample_duration: vec![1024; 3],
+                    sample_sizes: vec![16; 3],
+                    ..Default::default()
+                }],
+                saiz: Some(SaizBox {
+                    default_sample_info_size: 8,
+                    sample_count: 3,
+                    ..Default::default()
+                }),
+                saio: Some(SaioBox {
+                    offsets: vec![0],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let aux_offset = moof.box_size() + HEADER_SIZE;
+        let traf = &mut moof.trafs[0];
+        traf.saio.as_mut().unwrap().offsets[0] = aux_offset;
+        traf.truns[0].data_offset = Some((aux_offset + ivs.len() as u64) as i32);
+        moof.write_box(&mut stream).unwrap();
+        let mdat = [&ivs[..], &[0; 48]].concat();
+        BoxHeader::new(BoxType::MdatBox, HEADER_SIZE + mdat.len() as u64)
+            .write(&mut stream)
+            .unwrap();
+        stream.extend(mdat);
+
+        let events = push_chunked(&mut Mp4Demuxer::new(), &stream, 5);
+        let [DemuxEvent::InitSegment, DemuxEvent::Fragment(fragment)] = &events[..] else {
+            panic!("unexpected events {events:?}");
+        };
+        let encryption: Vec<_> = fragment.samples[&1]
+            .iter()
+            .map(|sample| sample.encryption.clone())
+            .collect();
+        let expected: Vec<_> = (1..=3)
+            .map(|id| {
+                Some(SampleEncryption {
+                    iv: vec![id; 8],
+                    subsamples: Vec::new(),
+                })
+            })
+            .collect();
+        assert_eq!(encryption, expected);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{boxes::elst::ElstBox, Mp4, Sample, Track};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edit {
+    /// Start of the edit on the presentation timeline, in the track timescale
+    pub presentation_time: u64,
+    /// Length of the edit in the track timescale, 0 means "until the end of the media"
+    pub duration: u64,
+    /// Composition time the edit starts at, `None` for empty edits
+    pub media_time: Option<i64>,
+    /// 0 for dwell edits that hold a single frame
+    pub media_rate: i16,
+}
+
+impl Edit {
+    pub fn is_empty(&self) -> bool {
+        self.media_time.is_none()
+    }
+
+    pub fn is_dwell(&self) -> bool {
+        self.media_time.is_some() && self.media_rate == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresentedSample {
+    pub sample_id: u32,
+    /// In the track timescale
+    pub presentation_time: u64,
+    /// Visible part of the sample, shorter than `Sample::duration` when an edit cuts into it
+    pub duration: u64,
+}
+
+pub(crate) fn build_edits(elst: &ElstBox, movie_timescale: u32, track_timescale: u64) -> Vec<Edit> {
+    let mut presentation_time = 0;
+
+    elst.entries
+        .iter()
+        .map(|entry| {
+            let duration = match movie_timescale {
+                0 => 0,
+                _ => {
+                    (entry.segment_duration as u128 * track_timescale as u128
+                        / movie_timescale as u128) as u64
+                }
+            };
+            let edit = Edit {
+                presentation_time,
+                duration,
+                media_time: (entry.media_time != -1).then_some(entry.media_time),
+                media_rate: entry.media_rate,
+            };
+            presentation_time += duration;
+            edit
+        })
+        .collect()
+}
+
+impl Track {
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    // Which samples are shown, when, and for how long once the edit list is applied.
+    // Samples may be listed more than once (repeated edits) or not at all (trimmed).
+    pub fn presentation_timeline<B>(&self, mp4: &Mp4<B>) -> Vec<PresentedSample> {
+        let mut samples = self.track_samples(mp4).iter().collect::<Vec<_>>();
+        samples.sort_by_key(|sample| sample.composition_timestamp);
+
+        if self.edits.is_empty() {
+            return samples
+                .into_iter()
+                .filter(|sample| sample.composition_timestamp >= 0)
+                .map(|sample| PresentedSample {
+                    sample_id: sample.id,
+                    presentation_time: sample.composition_timestamp as u64,
+                    duration: sample.duration,
+                })
+                .collect();
+        }
+
+        // sample timestamps were shifted onto the presentation timeline, edits refer to media time
+        let media_start = |sample: &Sample| sample.composition_timestamp - self.presentation_offset;
+
+        let mut timeline = Vec::new();
+        for edit in &self.edits {
+            let Some(media_time) = edit.media_time else {
+                continue;
+            };
+
+            if edit.is_dwell() {
+                let shown = samples
+                    .iter()
+                    .rev()
+                    .find(|sample| media_start(sample) <= media_time)
+                    .or(samples.first());
+                if let Some(sample) = shown {
+                    timeline.push(PresentedSample {
+                        sample_id: sample.id,
+                        presentation_time: edit.presentation_time,
+                        duration: edit.duration,
+                    });
+                }
+                continue;
+            }
+
+            if edit.media_rate < 0 {
+                continue;
+            }
+            let rate = edit.media_rate as i64;
+            let media_end = match edit.duration {
+                0 => i64::MAX,
+                duration => media_time.saturating_add(duration as i64 * rate),
+            };
+
+            for sample in &samples {
+                let start = media_start(sample);
+                let end = start + sample.duration as i64;
+                if end <= media_time || start >= media_end {
+                    continue;
+                }
+
+                let visible_start = start.max(media_time);
+                let visible_end = end.min(media_end);
+                timeline.push(PresentedSample {
+                    sample_id: sample.id,
+                    presentation_time: edit.presentation_time
+                        + ((visible_start - media_time) / rate) as u64,
+                    duration: ((visible_end - visible_start) / rate) as u64,
+                });
+            }
+        }
+
+        timeline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        boxes::{elst::ElstEntry, mp4a::Mp4aBox},
+        Mp4Writer, StsdBoxContent, TrackConfig,
+    };
+
+    fn edit(duration: u64, media_time: Option<i64>, media_rate: i16) -> Edit {
+        Edit {
+            presentation_time: 0,
+            duration,
+            media_time,
+            media_rate,
+        }
+    }
+
+    fn presented(sample_id: u32, presentation_time: u64, duration: u64) -> PresentedSample {
+        PresentedSample {
+            sample_id,
+            presentation_time,
+            duration,
+        }
+    }
+
+    #[test]
+    fn empty_dwell_and_rate_edits() {
+        // ten 100 tick samples, in a timescale matching the movie one
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(1000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        for id in 0..10u32 {
+            let sample = Sample {
+                id,
+                is_sync: true,
+                timescale: 1000,
+                decode_timestamp: id as i64 * 100,
+                composition_timestamp: id as i64 * 100,
+                duration: 100,
+                ..Default::default()
+            };
+            writer.write_sample(track_id, &sample, &[0; 4]).unwrap();
+        }
+        let edits = vec![
+            edit(500, None, 1),
+            // cuts into the samples at both ends
+            edit(300, Some(250), 1),
+            edit(200, Some(720), 0),
+            edit(200, Some(0), 2),
+        ];
+        writer.set_edits(track_id, edits).unwrap();
+        let file = writer.finish().unwrap().into_inner();
+
+        let mp4 = Mp4::read(Cursor::new(&file[..]), file.len() as u64).unwrap();
+        let track = &mp4.tracks()[&track_id];
+        let presentation_times: Vec<_> = track
+            .edits()
+            .iter()
+            .map(|edit| edit.presentation_time)
+            .collect();
+        assert_eq!(presentation_times, [0, 500, 800, 1000]);
+        assert!(track.edits()[0].is_empty());
+        assert!(track.edits()[2].is_dwell());
+
+        // the first media edit places media time 250 at 500
+        assert_eq!(track.samples[2].composition_timestamp, 450);
+
+        assert_eq!(
+            track.presentation_timeline(&mp4),
+            [
+                presented(2, 500, 50),
+                presented(3, 550, 100),
+                presented(4, 650, 100),
+                presented(5, 750, 50),
+                presented(7, 800, 200),
+                presented(0, 1000, 50),
+                presented(1, 1050, 50),
+                presented(2, 1100, 50),
+                presented(3, 1150, 50),
+            ]
+        );
+    }
+
+    #[test]
+    fn edits_in_track_timescale() {
+        let elst = ElstBox::default();
+        assert!(build_edits(&elst, 1000, 48000).is_empty());
+
+        let mut elst = ElstBox::default();
+        elst.entries.push(ElstEntry {
+            segment_duration: 1500,
+            media_time: 1024,
+            media_rate: 1,
+            media_rate_fraction: 0,
+        });
+        assert_eq!(
+            build_edits(&elst, 1000, 48000),
+            [Edit {
+                presentation_time: 0,
+                duration: 72000,
+                media_time: Some(1024),
+                media_rate: 1,
+            }]
+        );
+    }
+}
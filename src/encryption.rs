@@ -0,0 +1,913 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    io::{Read, Seek, SeekFrom, Write},
+    ops::Range,
+};
+
+use crate::{
+    aes::{Aes128, BLOCK_SIZE},
+    boxes::{saiz::SaizBox, stsc::StscBox},
+    Edit, Error, FourCC, MoofBox, Mp4, Mp4Writer, Result, Sample, SampleEncryption, StsdBoxContent,
+    TencBox, TfhdBox, Track, TrackConfig, TrackId, TrafBox, TrakBox,
+};
+
+// Auxiliary information types of the common encryption schemes
+const CENC_SCHEMES: [[u8; 4]; 4] = [*b"cenc", *b"cens", *b"cbc1", *b"cbcs"];
+
+const SCHEME_CENC: [u8; 4] = *b"cenc";
+const SCHEME_CBCS: [u8; 4] = *b"cbcs";
+
+// Encryption defaults of a sample description, `None` when it isn't protected
+fn protected_tenc<B: AsRef<[u8]>>(
+    trak: &TrakBox<B>,
+    sample_description_index: u32,
+) -> Option<&TencBox> {
+    trak.mdia
+        .minf
+        .stbl
+        .stsd
+        .entry(sample_description_index)?
+        .protection()?
+        .tenc()
+        .filter(|tenc| tenc.default_is_protected)
+}
+
+// Samples of tracks using a constant IV carry none of their own
+fn with_constant_iv(mut entry: SampleEncryption, tenc: &TencBox) -> SampleEncryption {
+    if entry.iv.is_empty() {
+        if let Some(iv) = &tenc.default_constant_iv {
+            entry.iv.clone_from(iv);
+        }
+    }
+    entry
+}
+
+// Hands the `senc` entries of a track fragment to its samples
+pub(crate) fn apply_senc<B: AsRef<[u8]>>(
+    trak: &TrakBox<B>,
+    traf: &TrafBox,
+    samples: &mut [Sample],
+) -> Result<()> {
+    let Some(senc) = &traf.senc else {
+        return Ok(());
+    };
+    let Some(tenc) = samples
+        .first()
+        .and_then(|sample| protected_tenc(trak, sample.sample_description_index))
+    else {
+        return Ok(());
+    };
+
+    let entries = senc.entries(tenc.default_per_sample_iv_size)?;
+    for (sample, entry) in samples.iter_mut().zip(entries) {
+        sample.encryption = Some(with_constant_iv(entry, tenc));
+    }
+
+    Ok(())
+}
+
+// First and end sample of every chunk
+fn chunk_sample_ranges(stsc: &StscBox, chunk_count: usize) -> Vec<Range<usize>> {
+    let mut ranges = Vec::with_capacity(chunk_count);
+    let mut sample = 0;
+    let mut run = 0;
+
+    for chunk in 1..=chunk_count as u32 {
+        while run + 1 < stsc.entries.len() && stsc.entries[run + 1].first_chunk <= chunk {
+            run += 1;
+        }
+        let samples_per_chunk = stsc
+            .entries
+            .get(run)
+            .map_or(0, |entry| entry.samples_per_chunk as usize);
+        ranges.push(sample..sample + samples_per_chunk);
+        sample += samples_per_chunk;
+    }
+
+    ranges
+}
+
+fn is_cenc_info(aux_info_type: Option<FourCC>) -> bool {
+    aux_info_type.is_none_or(|aux_info_type| CENC_SCHEMES.contains(&aux_info_type.value))
+}
+
+// Reads the auxiliary information of runs of samples stored contiguously at the
+// given offsets. `saiz` indexes samples from `first_sample`.
+fn read_runs<B: AsRef<[u8]>, R: Read + Seek>(
+    reader: &mut R,
+    trak: &TrakBox<B>,
+    saiz: &SaizBox,
+    first_sample: usize,
+    runs: impl IntoIterator<Item = (u64, Range<usize>)>,
+    samples: &mut [Sample],
+) -> Result<()> {
+    for (offset, range) in runs {
+        let range = range.start.min(samples.len())..range.end.min(samples.len());
+        let sizes: Vec<usize> = range
+            .clone()
+            .map(|index| saiz.sample_info_size(index - first_sample) as usize)
+            .collect();
+
+        let mut data = vec![0u8; sizes.iter().sum()];
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut data)?;
+
+        let mut position = 0;
+        for (index, size) in range.zip(sizes) {
+            let info = &data[position..position + size];
+            position += size;
+
+            let sample = &mut samples[index];
+            let Some(tenc) = protected_tenc(trak, sample.sample_description_index) else {
+                continue;
+            };
+            let iv_size = tenc.default_per_sample_iv_size;
+            if let Some((entry, _)) =
+                SampleEncryption::parse(info, iv_size, size > iv_size as usize)
+            {
+                sample.encryption = Some(with_constant_iv(entry, tenc));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl<B: AsRef<[u8]>> Mp4<B> {
+    // Auxiliary information `saiz`/`saio` point at, for tracks without fragments and
+    // for track fragments without `senc`
+    pub(crate) fn read_auxiliary_info<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        tracks: &mut BTreeMap<TrackId, Track>,
+    ) -> Result<()> {
+        // index of the first sample of the next fragment of every track
+        let mut next_sample = BTreeMap::new();
+
+        for trak in &self.moov.traks {
+            let track_id = trak.tkhd.track_id;
+            let stbl = &trak.mdia.minf.stbl;
+            next_sample.insert(track_id, stbl.stsz.sample_count as usize);
+
+            let (Some(saiz), Some(saio), Some(track)) =
+                (&stbl.saiz, &stbl.saio, tracks.get_mut(&track_id))
+            else {
+                continue;
+            };
+            // samples left in the sample tables carry no encryption info
+            if !is_cenc_info(saiz.aux_info_type) || track.table_samples > 0 {
+                continue;
+            }
+
+            // a single offset for all samples, one per chunk otherwise
+            let runs: Vec<(u64, Range<usize>)> = match saio.offsets[..] {
+                [offset] => vec![(offset, 0..stbl.stsz.sample_count as usize)],
+                _ => saio
+                    .offsets
+                    .iter()
+                    .copied()
+                    .zip(chunk_sample_ranges(&stbl.stsc, saio.offsets.len()))
+                    .collect(),
+            };
+            read_runs(reader, trak, saiz, 0, runs, &mut track.samples)?;
+        }
+
+        for moof in &self.moofs {
+            for traf in &moof.trafs {
+                let track_id = traf.tfhd.track_id;
+                let Some(first_sample) = next_sample.get_mut(&track_id) else {
+                    continue;
+                };
+                let start = *first_sample;
+                let count: usize = traf
+                    .truns
+                    .iter()
+                    .map(|trun| trun.sample_count as usize)
+                    .sum();
+                *first_sample += count;
+
+                let (Some(trak), Some(track)) = (
+                    self.moov
+                        .traks
+                        .iter()
+                        .find(|trak| trak.tkhd.track_id == track_id),
+                    tracks.get_mut(&track_id),
+                ) else {
+                    continue;
+                };
+                let start = start.saturating_sub(track.table_samples as usize);
+                read_traf_aux_info(reader, 0, trak, moof, traf, start, &mut track.samples)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Auxiliary information of a track fragment without `senc`, for its samples starting at
+// `samples[first_sample]`. `reader` holds the stream from `origin` on.
+pub(crate) fn read_traf_aux_info<B: AsRef<[u8]>, R: Read + Seek>(
+    reader: &mut R,
+    origin: u64,
+    trak: &TrakBox<B>,
+    moof: &MoofBox,
+    traf: &TrafBox,
+    first_sample: usize,
+    samples: &mut [Sample],
+) -> Result<()> {
+    let (None, Some(saiz), Some(saio)) = (&traf.senc, &traf.saiz, &traf.saio) else {
+        return Ok(());
+    };
+    if !is_cenc_info(saiz.aux_info_type) {
+        return Ok(());
+    }
+
+    let base = match traf.tfhd.flags & TfhdBox::FLAG_BASE_DATA_OFFSET {
+        0 => moof.start,
+        _ => traf.tfhd.base_data_offset.unwrap_or(moof.start),
+    };
+    // a single offset for the whole fragment, one per track run otherwise
+    let mut runs = Vec::new();
+    let mut run_start = first_sample;
+    for (index, offset) in saio.offsets.iter().enumerate() {
+        let run_count = match saio.offsets.len() {
+            1 => traf
+                .truns
+                .iter()
+                .map(|trun| trun.sample_count as usize)
+                .sum(),
+            _ => traf
+                .truns
+                .get(index)
+                .map_or(0, |trun| trun.sample_count as usize),
+        };
+        let Some(offset) = (base + offset).checked_sub(origin) else {
+            return Err(Error::invalid_value(
+                "auxiliary information lies before the fragment",
+            ));
+        };
+        runs.push((offset, run_start..run_start + run_count));
+        run_start += run_count;
+    }
+
+    read_runs(reader, trak, saiz, first_sample, runs, samples)
+}
+
+// Byte ranges of a sample that are protected, everything when there is no subsample map
+fn protected_ranges(encryption: &SampleEncryption, size: usize) -> Result<Vec<Range<usize>>> {
+    if encryption.subsamples.is_empty() {
+        return Ok(std::iter::once(0..size).collect());
+    }
+
+    let mut ranges = Vec::with_capacity(encryption.subsamples.len());
+    let mut position = 0usize;
+    for subsample in &encryption.subsamples {
+        let start = position + subsample.clear_bytes as usize;
+        position = start + subsample.encrypted_bytes as usize;
+        if position > size {
+            return Err(Error::invalid_value(
+                "subsample map covers more bytes than the sample has",
+            ));
+        }
+        ranges.push(start..position);
+    }
+
+    Ok(ranges)
+}
+
+// `cenc`: AES-CTR, a single key stream runs over all protected ranges
+fn decrypt_ctr(cipher: &Aes128, iv: [u8; BLOCK_SIZE], data: &mut [u8], ranges: &[Range<usize>]) {
+    let mut counter = iv;
+    let mut key_stream = [0u8; BLOCK_SIZE];
+    let mut used = BLOCK_SIZE;
+
+    for range in ranges {
+        for byte in &mut data[range.clone()] {
+            if used == BLOCK_SIZE {
+                key_stream = counter;
+                cipher.encrypt_block(&mut key_stream);
+                // only the lower 64 bits count blocks
+                let block_counter = u64::from_be_bytes(counter[8..].try_into().unwrap());
+                counter[8..].copy_from_slice(&block_counter.wrapping_add(1).to_be_bytes());
+                used = 0;
+            }
+            *byte ^= key_stream[used];
+            used += 1;
+        }
+    }
+}
+
+// `cbcs`: AES-CBC restarting from the IV in every protected range, of which only
+// `crypt` out of every `crypt + skip` blocks are encrypted. Trailing partial blocks
+// stay in the clear.
+fn decrypt_cbc_pattern(
+    cipher: &Aes128,
+    iv: [u8; BLOCK_SIZE],
+    data: &mut [u8],
+    ranges: &[Range<usize>],
+    crypt: usize,
+    skip: usize,
+) {
+    // a zero pattern (as used for audio) protects every block
+    let (crypt, skip) = if crypt == 0 { (1, 0) } else { (crypt, skip) };
+
+    for range in ranges {
+        let mut chain = iv;
+        let blocks = data[range.clone()].chunks_exact_mut(BLOCK_SIZE);
+        for (index, block) in blocks.enumerate() {
+            if index % (crypt + skip) >= crypt {
+                continue;
+            }
+            let block: &mut [u8; BLOCK_SIZE] = block.try_into().unwrap();
+            let ciphertext = *block;
+            cipher.decrypt_block(block);
+            for (byte, previous) in block.iter_mut().zip(chain) {
+                *byte ^= previous;
+            }
+            chain = ciphertext;
+        }
+    }
+}
+
+// Decrypts a sample in place with the scheme of its sample description
+fn decrypt_sample(
+    scheme: FourCC,
+    tenc: &TencBox,
+    encryption: &SampleEncryption,
+    key: &[u8; BLOCK_SIZE],
+    data: &mut [u8],
+) -> Result<()> {
+    if !matches!(encryption.iv.len(), 8 | BLOCK_SIZE) {
+        return Err(Error::invalid_value("sample IV must be 8 or 16 bytes"));
+    }
+    // 8 byte IVs are padded with zeros
+    let mut iv = [0u8; BLOCK_SIZE];
+    iv[..encryption.iv.len()].copy_from_slice(&encryption.iv);
+
+    let ranges = protected_ranges(encryption, data.len())?;
+    let cipher = Aes128::new(key);
+    match scheme.value {
+        SCHEME_CENC => decrypt_ctr(&cipher, iv, data, &ranges),
+        SCHEME_CBCS => decrypt_cbc_pattern(
+            &cipher,
+            iv,
+            data,
+            &ranges,
+            tenc.default_crypt_byte_block as usize,
+            tenc.default_skip_byte_block as usize,
+        ),
+        _ => {
+            return Err(Error::invalid_value(format!(
+                "unsupported protection scheme {scheme}"
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+impl<B: AsRef<[u8]>> Mp4<B> {
+    // Like `read_sample`, but with protected samples decrypted with `key`. Samples
+    // that aren't protected are returned as stored.
+    pub fn decrypted_sample<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        track_id: TrackId,
+        sample_id: u32,
+        key: &[u8; 16],
+    ) -> Result<Vec<u8>> {
+        let mut data = self.read_sample(reader, track_id, sample_id)?;
+
+        let sample = self.sample(track_id, sample_id)?;
+        let track = &self.tracks[&track_id];
+        let trak = track.trak(self);
+        let protection = trak
+            .mdia
+            .minf
+            .stbl
+            .stsd
+            .entry(sample.sample_description_index)
+            .and_then(StsdBoxContent::protection);
+
+        let tenc = protected_tenc(trak, sample.sample_description_index);
+        if tenc.is_some() && sample_id < track.table_samples {
+            return Err(Error::invalid_input(
+                "samples read with `read_compact` carry no encryption parameters",
+            ));
+        }
+
+        if let (Some(sinf), Some(tenc)) = (protection, tenc) {
+            // without `senc` or auxiliary information the whole sample is protected
+            // with the constant IV
+            let encryption = match (&sample.encryption, &tenc.default_constant_iv) {
+                (Some(encryption), _) => Cow::Borrowed(encryption),
+                (None, Some(iv)) => Cow::Owned(SampleEncryption {
+                    iv: iv.clone(),
+                    subsamples: Vec::new(),
+                }),
+                (None, None) => {
+                    return Err(Error::invalid_value(format!(
+                        "sample {sample_id} of track {track_id} has no IV"
+                    )))
+                }
+            };
+            let scheme = sinf.scheme_type().unwrap_or(FourCC::from(SCHEME_CENC));
+            decrypt_sample(scheme, tenc, &encryption, key, &mut data)?;
+        }
+
+        Ok(data)
+    }
+}
+
+impl Mp4 {
+    // Writes a copy of the movie with every protected sample decrypted and the sample
+    // descriptions restored to their original format. `keys` maps key IDs to keys, the
+    // ID of a sample description is the default one of its `tenc`. Edit lists, chapters
+    // and metadata are carried over.
+    pub fn write_decrypted<R: Read + Seek, W: Write + Seek>(
+        &self,
+        reader: &mut R,
+        writer: W,
+        keys: &BTreeMap<[u8; 16], [u8; 16]>,
+    ) -> Result<W> {
+        let mut mp4_writer = Mp4Writer::new(writer)?;
+        let chapter_track = self.chapter_track_id();
+        let mut order = Vec::new();
+
+        for (&track_id, track) in &self.tracks {
+            if Some(track_id) == chapter_track {
+                continue;
+            }
+
+            let trak = track.trak(self);
+            let entries = &trak.mdia.minf.stbl.stsd.entries;
+            if entries.is_empty() {
+                continue;
+            }
+            if entries
+                .iter()
+                .any(|entry| matches!(entry.original(), StsdBoxContent::Unknown(_)))
+            {
+                return Err(Error::invalid_input(format!(
+                    "track {track_id} has a sample description of unknown format"
+                )));
+            }
+
+            // the key of every sample description, by `sample_description_index - 1`
+            let mut description_keys = Vec::with_capacity(entries.len());
+            for index in 1..=entries.len() as u32 {
+                let key = match protected_tenc(trak, index) {
+                    Some(tenc) => Some(*keys.get(&tenc.default_kid).ok_or_else(|| {
+                        Error::invalid_input(format!("no key for track {track_id}"))
+                    })?),
+                    None => None,
+                };
+                description_keys.push(key);
+            }
+
+            let output_id = mp4_writer.add_track(TrackConfig {
+                timescale: track.time_scale as u32,
+                language: trak.mdia.mdhd.language.clone(),
+                contents: entries[0].original().clone(),
+            })?;
+            for entry in &entries[1..] {
+                mp4_writer.add_sample_description(output_id, entry.original().clone())?;
+            }
+
+            let table = track.sample_table(self);
+            let table_samples = table.iter().take(track.table_samples as usize);
+            let samples: Vec<_> = table_samples.chain(track.samples.iter().cloned()).collect();
+
+            // the copy is decoded from 0, fragments may start later. Sample times are on
+            // the presentation timeline, edits on the media one.
+            let decode_start = samples.first().map_or(0, |sample| {
+                sample.decode_timestamp - track.presentation_offset
+            });
+            let edits = track
+                .edits()
+                .iter()
+                .map(|edit| Edit {
+                    media_time: edit.media_time.map(|time| time - decode_start),
+                    ..*edit
+                })
+                .collect();
+            mp4_writer.set_edits(output_id, edits)?;
+
+            for sample in samples {
+                let key = (sample.sample_description_index as usize)
+                    .checked_sub(1)
+                    .and_then(|index| description_keys.get(index).copied())
+                    .flatten();
+                let time = track.to_duration(sample.decode_timestamp);
+                order.push((time, track_id, sample.id, output_id, key));
+            }
+        }
+
+        // interleave the tracks by decode time
+        order.sort_by_key(|&(time, track_id, index, ..)| (time, track_id, index));
+        for (_, track_id, sample_id, output_id, key) in order {
+            let data = match &key {
+                Some(key) => self.decrypted_sample(reader, track_id, sample_id, key)?,
+                None => self.read_sample(reader, track_id, sample_id)?,
+            };
+            let sample = self.sample(track_id, sample_id)?;
+            mp4_writer.write_sample(output_id, &sample, &data)?;
+        }
+
+        mp4_writer.set_metadata(self.moov.meta.clone(), self.moov.udta.clone());
+        if chapter_track.is_some() {
+            mp4_writer.set_chapters(self.chapters(reader)?);
+        }
+
+        mp4_writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, time::Duration};
+
+    use super::*;
+    use crate::{
+        boxes::{
+            mfhd::MfhdBox,
+            mp4a::Mp4aBox,
+            senc::{SencBox, Subsample},
+            sinf::{FrmaBox, SchiBox, SchmBox},
+            tfdt::TfdtBox,
+        },
+        BoxHeader, BoxType, Chapter, FragmentedMp4Writer, Metadata, MetadataKey, MetadataValue,
+        MoofBox, MoovBox, Mp4Box, SinfBox, TrunBox, WriteBox, HEADER_SIZE,
+    };
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    const KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const PLAINTEXT: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51";
+
+    fn key() -> [u8; BLOCK_SIZE] {
+        hex(KEY).try_into().unwrap()
+    }
+
+    fn subsample(clear_bytes: u16, encrypted_bytes: u32) -> Subsample {
+        Subsample {
+            clear_bytes,
+            encrypted_bytes,
+        }
+    }
+
+    #[test]
+    fn cenc() {
+        // NIST SP 800-38A F.5.1, the key stream runs on across subsamples
+        let ciphertext = hex("874d6191b620e3261bef6864990db6ce9806f66b7970fdff8617187bb9fffdff");
+        let mut data = [&[1; 3][..], &ciphertext[..10], &[2; 4], &ciphertext[10..]].concat();
+        let encryption = SampleEncryption {
+            iv: hex("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"),
+            subsamples: vec![subsample(3, 10), subsample(4, 22)],
+        };
+        let scheme = FourCC::from(SCHEME_CENC);
+        decrypt_sample(scheme, &TencBox::default(), &encryption, &key(), &mut data).unwrap();
+
+        let plaintext = hex(PLAINTEXT);
+        let expected = [&[1; 3][..], &plaintext[..10], &[2; 4], &plaintext[10..]].concat();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn cbcs() {
+        // NIST SP 800-38A F.2.2 with a 1:1 pattern, the skipped block and the trailing
+        // partial block stay as they are
+        let ciphertext = hex("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2");
+        let mut data = [&ciphertext[..16], &[3; 16], &ciphertext[16..], &[4; 5]].concat();
+        let encryption = SampleEncryption {
+            iv: hex("000102030405060708090a0b0c0d0e0f"),
+            subsamples: Vec::new(),
+        };
+        let tenc = TencBox {
+            default_crypt_byte_block: 1,
+            default_skip_byte_block: 1,
+            ..Default::default()
+        };
+        let scheme = FourCC::from(SCHEME_CBCS);
+        decrypt_sample(scheme, &tenc, &encryption, &key(), &mut data).unwrap();
+
+        let plaintext = hex(PLAINTEXT);
+        let expected = [&plaintext[..16], &[3; 16], &plaintext[16..], &[4; 5]].concat();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn iv_size() {
+        let encryption = SampleEncryption {
+            iv: vec![0; 12],
+            subsamples: Vec::new(),
+        };
+        let scheme = FourCC::from(SCHEME_CENC);
+        let mut data = [0; 32];
+        assert!(
+            decrypt_sample(scheme, &TencBox::default(), &encryption, &key(), &mut data).is_err()
+        );
+    }
+
+    #[test]
+    fn write_decrypted_keeps_the_movie() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let stereo = StsdBoxContent::Mp4a(Mp4aBox::default());
+        let mono = StsdBoxContent::Mp4a(Mp4aBox {
+            channelcount: 1,
+            ..Default::default()
+        });
+        let track_id = writer
+            .add_track(TrackConfig::new(48000, stereo.clone()))
+            .unwrap();
+        assert_eq!(
+            writer
+                .add_sample_description(track_id, mono.clone())
+                .unwrap(),
+            2
+        );
+        // priming: the first 1024 ticks are hidden
+        let edits = vec![Edit {
+            presentation_time: 0,
+            duration: 5 * 1024,
+            media_time: Some(1024),
+            media_rate: 1,
+        }];
+        writer.set_edits(track_id, edits.clone()).unwrap();
+        for id in 0..6u32 {
+            let sample = Sample {
+                id,
+                is_sync: true,
+                timescale: 48000,
+                decode_timestamp: id as i64 * 1024,
+                composition_timestamp: id as i64 * 1024,
+                duration: 1024,
+                sample_description_index: if id < 3 { 1 } else { 2 },
+                ..Default::default()
+            };
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 4])
+                .unwrap();
+        }
+        let mut moov = MoovBox::default();
+        moov.ilst_mut()
+            .set(MetadataKey::Title, MetadataValue::Text(Cow::from("title")));
+        writer.set_metadata(None, moov.udta);
+        writer.set_chapters(vec![Chapter {
+            start: Duration::ZERO,
+            title: String::from("chapter"),
+        }]);
+        let file = writer.finish().unwrap().into_inner();
+
+        let mut reader = Cursor::new(&file[..]);
+        let mp4 = Mp4::read(&mut reader, file.len() as u64).unwrap();
+        let copy = mp4
+            .write_decrypted(&mut reader, Cursor::new(Vec::new()), &BTreeMap::new())
+            .unwrap()
+            .into_inner();
+
+        let mut copy_reader = Cursor::new(&copy[..]);
+        let copy = Mp4::read(&mut copy_reader, copy.len() as u64).unwrap();
+        let track = &copy.tracks()[&track_id];
+        assert_eq!(
+            track.trak(&copy).mdia.minf.stbl.stsd.entries,
+            [stereo, mono]
+        );
+        // the elst of both is in movie ticks, the input rounded the duration already
+        assert_eq!(track.edits(), mp4.tracks()[&track_id].edits());
+        assert_eq!(track.edits()[0].media_time, edits[0].media_time);
+        for sample in &track.samples {
+            let index = if sample.id < 3 { 1 } else { 2 };
+            assert_eq!(sample.sample_description_index, index);
+            let data = copy
+                .read_sample(&mut copy_reader, track_id, sample.id)
+                .unwrap();
+            assert_eq!(&data[..], &[sample.id as u8; 4]);
+        }
+        assert_eq!(copy.moov.title().as_deref(), Some("title"));
+        let chapters = copy.chapters(&mut copy_reader).unwrap();
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].title, "chapter");
+    }
+
+    const KID: [u8; 16] = [7; 16];
+
+    fn plaintext(id: u32, size: usize) -> Vec<u8> {
+        (0..size)
+            .map(|byte| (byte * 7 + id as usize) as u8)
+            .collect()
+    }
+
+    fn protected_mp4a(scheme: [u8; 4], tenc: TencBox) -> StsdBoxContent {
+        StsdBoxContent::Encrypted {
+            entry: Box::new(StsdBoxContent::Mp4a(Mp4aBox::default())),
+            sinf: SinfBox {
+                frma: FrmaBox {
+                    original_format: FourCC::from(*b"mp4a"),
+                },
+                schm: Some(SchmBox {
+                    scheme_type: FourCC::from(scheme),
+                    scheme_version: 0x10000,
+                    ..Default::default()
+                }),
+                schi: Some(SchiBox {
+                    tenc: Some(tenc),
+                    unknown: Vec::new(),
+                }),
+                unknown: Vec::new(),
+            },
+        }
+    }
+
+    // Encrypts like a packager would, one key stream block after the other
+    fn encrypt_ctr(iv: &[u8], data: &mut [u8], ranges: &[Range<usize>]) {
+        let cipher = Aes128::new(&key());
+        let mut counter = [0u8; BLOCK_SIZE];
+        counter[..iv.len()].copy_from_slice(iv);
+        let counter = u128::from_be_bytes(counter);
+
+        let protected: Vec<usize> = ranges.iter().flat_map(|range| range.clone()).collect();
+        for (block, indices) in protected.chunks(BLOCK_SIZE).enumerate() {
+            let mut key_stream = (counter + block as u128).to_be_bytes();
+            cipher.encrypt_block(&mut key_stream);
+            for (&index, key_byte) in indices.iter().zip(key_stream) {
+                data[index] ^= key_byte;
+            }
+        }
+    }
+
+    fn encrypt_cbc_pattern(iv: &[u8], data: &mut [u8], crypt: usize, skip: usize) {
+        let cipher = Aes128::new(&key());
+        let mut chain: [u8; BLOCK_SIZE] = iv.try_into().unwrap();
+        for (index, block) in data.chunks_exact_mut(BLOCK_SIZE).enumerate() {
+            if index % (crypt + skip) >= crypt {
+                continue;
+            }
+            let block: &mut [u8; BLOCK_SIZE] = block.try_into().unwrap();
+            for (byte, previous) in block.iter_mut().zip(chain) {
+                *byte ^= previous;
+            }
+            cipher.encrypt_block(block);
+            chain = *block;
+        }
+    }
+
+    fn check_decrypted(file: &[u8], samples: &[Vec<u8>]) {
+        let mut reader = Cursor::new(file);
+        let mp4 = Mp4::read(&mut reader, file.len() as u64).unwrap();
+        for (id, plaintext) in (0..).zip(samples) {
+            let data = mp4.decrypted_sample(&mut reader, 1, id, &key()).unwrap();
+            assert_eq!(&data, plaintext);
+        }
+
+        let keys = BTreeMap::from([(KID, key())]);
+        let copy = mp4
+            .write_decrypted(&mut reader, Cursor::new(Vec::new()), &keys)
+            .unwrap()
+            .into_inner();
+        let mut copy_reader = Cursor::new(&copy[..]);
+        let copy = Mp4::read(&mut copy_reader, copy.len() as u64).unwrap();
+        assert_eq!(
+            copy.tracks()[&1].trak(&copy).mdia.minf.stbl.stsd.entries,
+            [StsdBoxContent::Mp4a(Mp4aBox::default())]
+        );
+        for (id, plaintext) in (0..).zip(samples) {
+            let data = copy.read_sample(&mut copy_reader, 1, id).unwrap();
+            assert_eq!(&data, plaintext);
+        }
+    }
+
+    #[test]
+    fn cenc_fragment() {
+        let tenc = TencBox {
+            default_is_protected: true,
+            default_per_sample_iv_size: 8,
+            default_kid: KID,
+            ..Default::default()
+        };
+        let mut writer = FragmentedMp4Writer::new(Vec::new());
+        writer
+            .add_track(TrackConfig::new(48000, protected_mp4a(SCHEME_CENC, tenc)))
+            .unwrap();
+        writer.write_init_segment().unwrap();
+        let mut file = writer.finish().unwrap();
+
+        let samples: Vec<_> = (0..3).map(|id| plaintext(id, 100)).collect();
+        let mut mdat = Vec::new();
+        let mut entries = Vec::new();
+        for (id, sample) in (0u8..).zip(&samples) {
+            let encryption = SampleEncryption {
+                iv: vec![id + 1; 8],
+                subsamples: vec![subsample(5, 40), subsample(3, 52)],
+            };
+            let mut data = sample.clone();
+            encrypt_ctr(&encryption.iv, &mut data, &[5..45, 48..100]);
+            mdat.extend(data);
+            entries.push(encryption);
+        }
+
+        let mut moof = MoofBox {
+            mfhd: MfhdBox {
+                version: 0,
+                flags: 0,
+                sequence_number: 1,
+            },
+            trafs: vec![TrafBox {
+                tfhd: TfhdBox {
+                    flags: TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF,
+                    track_id: 1,
+                    ..Default::default()
+                },
+                tfdt: Some(TfdtBox::default()),
+                truns: vec![TrunBox {
+                    flags: TrunBox::FLAG_DATA_OFFSET
+                        | TrunBox::FLAG_SAMPLE_DURATION
+                        | TrunBox::FLAG_SAMPLE_SIZE,
+                    sample_count: 3,
+                    data_offset: Some(0),
+                    sample_duration: vec![1024; 3],
+                    sample_sizes: vec![100; 3],
+                    ..Default::default()
+                }],
+                senc: Some(SencBox::new(&entries)),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        moof.trafs[0].truns[0].data_offset = Some((moof.box_size() + HEADER_SIZE) as i32);
+        moof.write_box(&mut file).unwrap();
+        BoxHeader::new(BoxType::MdatBox, HEADER_SIZE + mdat.len() as u64)
+            .write(&mut file)
+            .unwrap();
+        file.extend(mdat);
+
+        check_decrypted(&file, &samples);
+    }
+
+    fn write_cbcs_movie(constant_iv: Option<Vec<u8>>, samples: &[Vec<u8>]) -> Vec<u8> {
+        let tenc = TencBox {
+            version: 1,
+            default_crypt_byte_block: 1,
+            default_skip_byte_block: 9,
+            default_is_protected: true,
+            default_per_sample_iv_size: 0,
+            default_kid: KID,
+            default_constant_iv: constant_iv.clone(),
+            ..Default::default()
+        };
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        writer
+            .add_track(TrackConfig::new(48000, protected_mp4a(SCHEME_CBCS, tenc)))
+            .unwrap();
+        for (id, sample) in (0..).zip(samples) {
+            let mut data = sample.clone();
+            if let Some(iv) = &constant_iv {
+                encrypt_cbc_pattern(iv, &mut data, 1, 9);
+            }
+            let sample = Sample {
+                id,
+                is_sync: true,
+                timescale: 48000,
+                decode_timestamp: id as i64 * 1024,
+                composition_timestamp: id as i64 * 1024,
+                duration: 1024,
+                ..Default::default()
+            };
+            writer.write_sample(1, &sample, &data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn cbcs_constant_iv() {
+        // 25 whole blocks of which 0, 10 and 20 are encrypted, and a partial one
+        let samples: Vec<_> = (0..3).map(|id| plaintext(id, 410)).collect();
+        let file = write_cbcs_movie(Some(hex("000102030405060708090a0b0c0d0e0f")), &samples);
+        let mp4 = Mp4::read(&mut Cursor::new(&file[..]), file.len() as u64).unwrap();
+        assert_eq!(mp4.tracks()[&1].samples[0].encryption, None);
+        assert_ne!(
+            mp4.read_sample(&mut Cursor::new(&file[..]), 1, 0).unwrap(),
+            samples[0]
+        );
+
+        check_decrypted(&file, &samples);
+    }
+
+    #[test]
+    fn no_iv() {
+        let samples = [plaintext(0, 32)];
+        let file = write_cbcs_movie(None, &samples);
+        let mut reader = Cursor::new(&file[..]);
+        let mp4 = Mp4::read(&mut reader, file.len() as u64).unwrap();
+        assert!(matches!(
+            mp4.decrypted_sample(&mut reader, 1, 0, &key()),
+            Err(Error::InvalidValue { .. })
+        ));
+    }
+}
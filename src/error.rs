@@ -0,0 +1,415 @@
+use std::{borrow::Cow, fmt, io};
+
+use crate::{BoxType, TrackId};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+// Chain of boxes from the top level down to the box an error occurred in
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoxPath(Vec<BoxType>);
+
+impl BoxPath {
+    pub fn boxes(&self) -> &[BoxType] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// Just the four characters, without the numeric code `BoxType` displays with
+fn name(box_type: BoxType) -> String {
+    String::from_utf8_lossy(&u32::from(box_type).to_be_bytes()).into_owned()
+}
+
+impl fmt::Display for BoxPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, box_type) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            write!(f, "{}", name(*box_type))?;
+        }
+        Ok(())
+    }
+}
+
+// Errors found while parsing carry the byte offset of the box they occurred in and
+// the path leading to it. Both are only known for failures inside a box, they are
+// 0 and empty otherwise (e.g. for errors returned by the writers).
+#[derive(Debug)]
+pub enum Error {
+    /// The input ended before the end of the box
+    Truncated {
+        box_type: BoxType,
+        offset: u64,
+        path: BoxPath,
+    },
+    /// A box claims to be larger than the box containing it, `offset` points at the child
+    SizeOverflow {
+        box_type: BoxType,
+        offset: u64,
+        path: BoxPath,
+    },
+    /// A box the specification requires is absent, `offset` points at its parent
+    MissingRequiredBox {
+        box_type: BoxType,
+        offset: u64,
+        path: BoxPath,
+    },
+    UnsupportedVersion {
+        box_type: BoxType,
+        version: u8,
+        offset: u64,
+        path: BoxPath,
+    },
+    InvalidValue {
+        message: Cow<'static, str>,
+        offset: u64,
+        path: BoxPath,
+    },
+    Io {
+        source: io::Error,
+        offset: u64,
+        path: BoxPath,
+    },
+    TrackNotFound(TrackId),
+    SampleNotFound {
+        track_id: TrackId,
+        sample_id: u32,
+    },
+    /// The API was used in a way that can't produce a valid file
+    InvalidInput(Cow<'static, str>),
+}
+
+impl Error {
+    pub(crate) fn size_overflow(box_type: BoxType, offset: u64) -> Self {
+        Self::SizeOverflow {
+            box_type,
+            offset,
+            path: BoxPath::default(),
+        }
+    }
+
+    pub(crate) fn truncated(box_type: BoxType, offset: u64) -> Self {
+        Self::Truncated {
+            box_type,
+            offset,
+            path: BoxPath::default(),
+        }
+    }
+
+    pub(crate) fn missing_box(box_type: BoxType, offset: u64) -> Self {
+        Self::MissingRequiredBox {
+            box_type,
+            offset,
+            path: BoxPath::default(),
+        }
+    }
+
+    pub(crate) fn unsupported_version(box_type: BoxType, version: u8, offset: u64) -> Self {
+        Self::UnsupportedVersion {
+            box_type,
+            version,
+            offset,
+            path: BoxPath::default(),
+        }
+    }
+
+    pub(crate) fn invalid_value(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::InvalidValue {
+            message: message.into(),
+            offset: 0,
+            path: BoxPath::default(),
+        }
+    }
+
+    pub(crate) fn invalid_input(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::InvalidInput(message.into())
+    }
+
+    // Called on the way out of every box read, the innermost box provides the offset
+    // (unless the error already points at a child) and each level prepends its type
+    pub(crate) fn within(self, box_type: BoxType, offset: u64) -> Self {
+        let mut err = match self {
+            Self::Io { source, path, .. }
+                if path.is_empty() && source.kind() == io::ErrorKind::UnexpectedEof =>
+            {
+                Self::Truncated {
+                    box_type,
+                    offset,
+                    path,
+                }
+            }
+            err => err,
+        };
+
+        match &mut err {
+            Self::Truncated {
+                offset: at, path, ..
+            }
+            | Self::MissingRequiredBox {
+                offset: at, path, ..
+            }
+            | Self::UnsupportedVersion {
+                offset: at, path, ..
+            }
+            | Self::InvalidValue {
+                offset: at, path, ..
+            }
+            | Self::Io {
+                offset: at, path, ..
+            } => {
+                if path.is_empty() {
+                    *at = offset;
+                }
+                path.0.insert(0, box_type);
+            }
+            Self::SizeOverflow { path, .. } => path.0.insert(0, box_type),
+            Self::TrackNotFound(_) | Self::SampleNotFound { .. } | Self::InvalidInput(_) => {}
+        }
+
+        err
+    }
+
+    // Moves the offset of an error found in a box that was parsed on its own, starting
+    // at position 0, to where that box is located in the stream
+    pub(crate) fn shifted(mut self, base: u64) -> Self {
+        match &mut self {
+            Self::Truncated { offset, .. }
+            | Self::SizeOverflow { offset, .. }
+            | Self::MissingRequiredBox { offset, .. }
+            | Self::UnsupportedVersion { offset, .. }
+            | Self::InvalidValue { offset, .. }
+            | Self::Io { offset, .. } => *offset += base,
+            Self::TrackNotFound(_) | Self::SampleNotFound { .. } | Self::InvalidInput(_) => {}
+        }
+        self
+    }
+
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::Truncated { offset, .. }
+            | Self::SizeOverflow { offset, .. }
+            | Self::MissingRequiredBox { offset, .. }
+            | Self::UnsupportedVersion { offset, .. }
+            | Self::InvalidValue { offset, .. }
+            | Self::Io { offset, .. } => Some(*offset),
+            Self::TrackNotFound(_) | Self::SampleNotFound { .. } | Self::InvalidInput(_) => None,
+        }
+    }
+
+    pub fn path(&self) -> Option<&BoxPath> {
+        match self {
+            Self::Truncated { path, .. }
+            | Self::SizeOverflow { path, .. }
+            | Self::MissingRequiredBox { path, .. }
+            | Self::UnsupportedVersion { path, .. }
+            | Self::InvalidValue { path, .. }
+            | Self::Io { path, .. } => Some(path),
+            Self::TrackNotFound(_) | Self::SampleNotFound { .. } | Self::InvalidInput(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { box_type, .. } => write!(f, "{} box is truncated", name(*box_type))?,
+            Self::SizeOverflow { box_type, .. } => write!(
+                f,
+                "{} box is larger than the box containing it",
+                name(*box_type)
+            )?,
+            Self::MissingRequiredBox { box_type, .. } => {
+                write!(f, "required {} box is missing", name(*box_type))?
+            }
+            Self::UnsupportedVersion {
+                box_type, version, ..
+            } => write!(
+                f,
+                "{} box version {version} is not supported",
+                name(*box_type)
+            )?,
+            Self::InvalidValue { message, .. } => write!(f, "{message}")?,
+            Self::Io { source, .. } => write!(f, "{source}")?,
+            Self::TrackNotFound(track_id) => {
+                return write!(f, "track with id {track_id} not found")
+            }
+            Self::SampleNotFound {
+                track_id,
+                sample_id,
+            } => {
+                return write!(
+                    f,
+                    "sample with id {sample_id} not found in track with id {track_id}"
+                )
+            }
+            Self::InvalidInput(message) => return write!(f, "{message}"),
+        }
+
+        if let (Some(offset), Some(path)) = (self.offset(), self.path()) {
+            if !path.is_empty() {
+                write!(f, " (in {path} at offset {offset})")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Self::Io {
+            source,
+            offset: 0,
+            path: BoxPath::default(),
+        }
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match &err {
+            Error::Io { source, .. } => source.kind(),
+            Error::Truncated { .. } => io::ErrorKind::UnexpectedEof,
+            Error::TrackNotFound(_) | Error::SampleNotFound { .. } => io::ErrorKind::NotFound,
+            Error::InvalidInput(_) => io::ErrorKind::InvalidInput,
+            Error::SizeOverflow { .. }
+            | Error::MissingRequiredBox { .. }
+            | Error::UnsupportedVersion { .. }
+            | Error::InvalidValue { .. } => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{boxes::mp4a::Mp4aBox, Mp4, Mp4Writer, Sample, StsdBoxContent, TrackConfig};
+
+    // A small file and the offset of its moov, mvhd is the first child
+    fn file() -> (Vec<u8>, usize) {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        let sample = Sample {
+            is_sync: true,
+            timescale: 48000,
+            duration: 1024,
+            ..Default::default()
+        };
+        writer.write_sample(track_id, &sample, &[0; 4]).unwrap();
+        let file = writer.finish().unwrap().into_inner();
+        let moov = file.windows(4).position(|name| name == b"moov").unwrap() - 4;
+        assert_eq!(&file[moov + 12..moov + 16], b"mvhd");
+        (file, moov)
+    }
+
+    fn read(file: &[u8]) -> Error {
+        Mp4::read(Cursor::new(file), file.len() as u64).unwrap_err()
+    }
+
+    fn path(boxes: &[BoxType]) -> BoxPath {
+        BoxPath(boxes.to_vec())
+    }
+
+    #[test]
+    fn truncated() {
+        let (file, moov) = file();
+        // the stream ends before the size given
+        let stream = &file[..moov + 30];
+        let err = Mp4::read(Cursor::new(stream), file.len() as u64).unwrap_err();
+        let mvhd = moov as u64 + 8;
+        assert!(matches!(
+            &err,
+            Error::Truncated { box_type: BoxType::MvhdBox, offset, path: at }
+                if *offset == mvhd && *at == path(&[BoxType::MoovBox, BoxType::MvhdBox])
+        ));
+        assert_eq!(
+            err.to_string(),
+            format!("mvhd box is truncated (in moov/mvhd at offset {mvhd})")
+        );
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::UnexpectedEof);
+
+        // a cut file has a moov larger than the file
+        let err = read(stream);
+        assert!(matches!(
+            &err,
+            Error::SizeOverflow { box_type: BoxType::MoovBox, offset, path: at }
+                if *offset == moov as u64 && at.is_empty()
+        ));
+    }
+
+    #[test]
+    fn size_overflow() {
+        let (mut file, moov) = file();
+        file[moov + 8..moov + 12].copy_from_slice(&u32::MAX.to_be_bytes());
+        let err = read(&file);
+        assert!(matches!(
+            &err,
+            Error::SizeOverflow { box_type: BoxType::MvhdBox, offset, path: at }
+                if *offset == moov as u64 + 8 && *at == path(&[BoxType::MoovBox])
+        ));
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let (mut file, moov) = file();
+        file[moov + 16] = 2;
+        let err = read(&file);
+        assert!(matches!(
+            &err,
+            Error::UnsupportedVersion { box_type: BoxType::MvhdBox, version: 2, offset, path: at }
+                if *offset == moov as u64 + 8
+                    && *at == path(&[BoxType::MoovBox, BoxType::MvhdBox])
+        ));
+    }
+
+    #[test]
+    fn missing_box() {
+        let (mut file, moov) = file();
+        file[moov + 12..moov + 16].copy_from_slice(b"free");
+        let err = read(&file);
+        assert!(matches!(
+            &err,
+            Error::MissingRequiredBox { box_type: BoxType::MvhdBox, offset, path: at }
+                if *offset == moov as u64 && *at == path(&[BoxType::MoovBox])
+        ));
+        assert_eq!(err.path().unwrap().boxes(), [BoxType::MoovBox]);
+        assert_eq!(err.offset(), Some(moov as u64));
+
+        let err = read(&file[..moov]);
+        assert!(matches!(
+            err,
+            Error::MissingRequiredBox {
+                box_type: BoxType::MoovBox,
+                offset: 0,
+                ..
+            }
+        ));
+        assert_eq!(err.to_string(), "required moov box is missing");
+    }
+
+    #[test]
+    fn api_errors() {
+        let err = Error::TrackNotFound(3);
+        assert_eq!(err.offset(), None);
+        assert_eq!(err.to_string(), "track with id 3 not found");
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::NotFound);
+    }
+}
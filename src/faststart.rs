@@ -0,0 +1,350 @@
+use std::{
+    io::{self, Cursor, Read, Seek, SeekFrom, Write},
+    ops::Range,
+};
+
+use crate::{
+    box_start,
+    boxes::{co64::Co64Box, saio::SaioBox, stco::StcoBox},
+    BoxHeader, BoxType, Error, Mp4, ReadChild, Result, WriteBox, HEADER_SIZE,
+};
+
+// Top-level boxes in file order, with the range each one covers
+type Layout = Vec<(BoxType, Range<u64>)>;
+
+fn read_layout<R: Read + Seek>(reader: &mut R, size: u64) -> Result<Layout> {
+    let mut layout = Vec::new();
+
+    let mut current = reader.stream_position()?;
+    while current < size {
+        let header = BoxHeader::read(reader)?;
+
+        if header.size > size {
+            return Err(Error::size_overflow(header.name, box_start(reader)?));
+        }
+        if header.name == BoxType::MoofBox {
+            return Err(Error::invalid_input(
+                "fragmented files can't be rewritten for faststart",
+            ));
+        }
+
+        // a size of 0 runs to the end of the file
+        let end = match header.size {
+            0 => size,
+            _ => box_start(reader)? + header.size,
+        };
+
+        layout.push((header.name, current..end));
+        current = end;
+        reader.seek(SeekFrom::Start(current))?;
+    }
+
+    Ok(layout)
+}
+
+fn write_container(name: BoxType, body: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    let mut size = HEADER_SIZE + body.len() as u64;
+    if size > u32::MAX as u64 {
+        size += 8;
+    }
+    BoxHeader::new(name, size).write(out)?;
+    out.extend_from_slice(body);
+    Ok(())
+}
+
+// Copies the boxes up to `end` to `out` as they are, except for the absolute offsets
+// of the tables which move to where their box lands. `stco` turns into `co64` where
+// the moved offsets don't fit 32 bits anymore, the containers on the way are resized.
+fn relocate_children<R: Read + Seek>(
+    reader: &mut R,
+    end: u64,
+    out: &mut Vec<u8>,
+    relocate_offset: &impl Fn(u64) -> Result<u64>,
+) -> Result<()> {
+    let mut current = reader.stream_position()?;
+    while current + HEADER_SIZE <= end {
+        let header = BoxHeader::read(reader)?;
+        let box_end = match header.size {
+            0 => end,
+            size => box_start(reader)? + size,
+        };
+        if box_end > end {
+            return Err(Error::size_overflow(header.name, box_start(reader)?));
+        }
+
+        match header.name {
+            BoxType::MoovBox
+            | BoxType::TrakBox
+            | BoxType::MdiaBox
+            | BoxType::MinfBox
+            | BoxType::StblBox => {
+                let mut body = Vec::new();
+                relocate_children(reader, box_end, &mut body, relocate_offset)
+                    .map_err(|err| err.within(header.name, current))?;
+                write_container(header.name, &body, out)?;
+            }
+            BoxType::StcoBox => {
+                let stco = StcoBox::read_child(reader, &header)?;
+                let entries = stco
+                    .entries
+                    .iter()
+                    .map(|&offset| relocate_offset(offset as u64))
+                    .collect::<Result<Vec<_>>>()?;
+
+                if entries.iter().all(|&offset| offset <= u32::MAX as u64) {
+                    let entries = entries.into_iter().map(|offset| offset as u32).collect();
+                    StcoBox { entries, ..stco }.write_box(out)?;
+                } else {
+                    Co64Box {
+                        version: 0,
+                        flags: 0,
+                        entries,
+                    }
+                    .write_box(out)?;
+                }
+            }
+            BoxType::Co64Box => {
+                let mut co64 = Co64Box::read_child(reader, &header)?;
+                for offset in &mut co64.entries {
+                    *offset = relocate_offset(*offset)?;
+                }
+                co64.write_box(out)?;
+            }
+            BoxType::SaioBox => {
+                let mut saio = SaioBox::read_child(reader, &header)?;
+                for offset in &mut saio.offsets {
+                    *offset = relocate_offset(*offset)?;
+                }
+                if saio.version == 0 && saio.offsets.iter().any(|&offset| offset > u32::MAX as u64)
+                {
+                    saio.version = 1;
+                }
+                saio.write_box(out)?;
+            }
+            _ => {
+                reader.seek(SeekFrom::Start(current))?;
+                reader
+                    .take(box_end - current)
+                    .read_to_end(out)
+                    .map_err(|err| Error::from(err).within(header.name, current))?;
+            }
+        }
+
+        current = box_end;
+        reader.seek(SeekFrom::Start(current))?;
+    }
+
+    Ok(())
+}
+
+impl Mp4 {
+    // Copies the file to `writer` with `moov` ahead of the first `mdat`, so playback can
+    // start before the whole file is downloaded. Every other box is copied as is and
+    // the chunk offsets follow the data they point at.
+    pub fn faststart<R: Read + Seek, W: Write>(
+        reader: &mut R,
+        size: u64,
+        mut writer: W,
+    ) -> Result<W> {
+        let start = reader.stream_position()?;
+        let mut layout = read_layout(reader, size)?;
+
+        let Some(moov_index) = layout
+            .iter()
+            .position(|(name, _)| *name == BoxType::MoovBox)
+        else {
+            return Err(Error::missing_box(BoxType::MoovBox, start));
+        };
+        let (_, moov_range) = layout.remove(moov_index);
+        let mdat_index = layout
+            .iter()
+            .position(|(name, _)| *name == BoxType::MdatBox)
+            .unwrap_or(layout.len());
+        let moov_index = moov_index.min(mdat_index);
+
+        // only the offset tables are rewritten, the rest of moov is copied byte for byte
+        let mut original = Vec::new();
+        reader.seek(SeekFrom::Start(moov_range.start))?;
+        reader
+            .take(moov_range.end - moov_range.start)
+            .read_to_end(&mut original)?;
+
+        // promoting stco to co64 grows moov, which can push more offsets past 32 bits
+        let mut moov_size = original.len() as u64;
+        let moov = loop {
+            let mut new_starts = Vec::with_capacity(layout.len());
+            let mut position = start;
+            for (index, (_, range)) in layout.iter().enumerate() {
+                if index == moov_index {
+                    position += moov_size;
+                }
+                new_starts.push(position);
+                position += range.end - range.start;
+            }
+
+            let relocate_offset = |offset| {
+                layout
+                    .iter()
+                    .zip(&new_starts)
+                    .find(|((_, range), _)| range.contains(&offset))
+                    .map(|((_, range), new_start)| new_start + (offset - range.start))
+                    .ok_or_else(|| {
+                        Error::invalid_value(format!(
+                            "offset {offset} points outside of the boxes of the file"
+                        ))
+                    })
+            };
+            let mut relocated = Vec::with_capacity(original.len());
+            let mut moov_reader = Cursor::new(&original[..]);
+            relocate_children(
+                &mut moov_reader,
+                original.len() as u64,
+                &mut relocated,
+                &relocate_offset,
+            )
+            .map_err(|err| err.shifted(moov_range.start))?;
+
+            if relocated.len() as u64 == moov_size {
+                break relocated;
+            }
+            moov_size = relocated.len() as u64;
+        };
+
+        for (index, (_, range)) in layout.iter().enumerate() {
+            if index == moov_index {
+                writer.write_all(&moov)?;
+            }
+            reader.seek(SeekFrom::Start(range.start))?;
+            io::copy(&mut reader.take(range.end - range.start), &mut writer)?;
+        }
+        if moov_index == layout.len() {
+            writer.write_all(&moov)?;
+        }
+
+        Ok(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        boxes::{mp4a::Mp4aBox, udta::UdtaBox},
+        Mp4Writer, Sample, StsdBoxContent, TrackConfig, UnknownBox,
+    };
+
+    fn nested(leaf: impl for<'a> WriteBox<&'a mut Vec<u8>>) -> Vec<u8> {
+        let mut body = Vec::new();
+        leaf.write_box(&mut body).unwrap();
+        for name in [
+            BoxType::StblBox,
+            BoxType::MinfBox,
+            BoxType::MdiaBox,
+            BoxType::TrakBox,
+            BoxType::MoovBox,
+        ] {
+            let mut container = Vec::new();
+            write_container(name, &body, &mut container).unwrap();
+            body = container;
+        }
+        body
+    }
+
+    #[test]
+    fn stco_past_4gib_becomes_co64() {
+        let stco = StcoBox {
+            version: 0,
+            flags: 0,
+            entries: vec![100, 200],
+        };
+        let moov = nested(stco);
+
+        let mut relocated = Vec::new();
+        let relocate_offset = |offset| Ok(offset + (5 << 30));
+        let mut reader = Cursor::new(&moov[..]);
+        relocate_children(
+            &mut reader,
+            moov.len() as u64,
+            &mut relocated,
+            &relocate_offset,
+        )
+        .unwrap();
+
+        let co64 = Co64Box {
+            version: 0,
+            flags: 0,
+            entries: vec![100 + (5 << 30), 200 + (5 << 30)],
+        };
+        assert_eq!(relocated, nested(co64));
+    }
+
+    #[test]
+    fn moov_moves_ahead_of_mdat() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        for id in 0..8u32 {
+            let sample = Sample {
+                id,
+                is_sync: true,
+                timescale: 48000,
+                decode_timestamp: id as i64 * 1024,
+                composition_timestamp: id as i64 * 1024,
+                duration: 1024,
+                ..Default::default()
+            };
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 32])
+                .unwrap();
+        }
+        // a box this crate doesn't model has to survive the rewrite
+        let udta = UdtaBox {
+            unknown: vec![UnknownBox {
+                fourcc: (*b"xtra").into(),
+                payload: b"kept as is".to_vec(),
+            }],
+            ..Default::default()
+        };
+        writer.set_metadata(None, Some(udta));
+        let file = writer.finish().unwrap().into_inner();
+
+        let mut reader = Cursor::new(&file[..]);
+        let output = Mp4::faststart(&mut reader, file.len() as u64, Vec::new()).unwrap();
+        assert_eq!(output.len(), file.len());
+
+        let layout = read_layout(&mut Cursor::new(&file[..]), file.len() as u64).unwrap();
+        let moved = read_layout(&mut Cursor::new(&output[..]), output.len() as u64).unwrap();
+        let names = |layout: &Layout| layout.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        let (ftyp, mdat, moov) = (BoxType::FtypBox, BoxType::MdatBox, BoxType::MoovBox);
+        assert_eq!(names(&layout), [ftyp, mdat, moov]);
+        assert_eq!(names(&moved), [ftyp, moov, mdat]);
+
+        // only the chunk offsets differ, by the size of moov
+        let moov_size = moved[1].1.end - moved[1].1.start;
+        let moov = &file[layout[2].1.start as usize..];
+        let moved_moov = &output[moved[1].1.start as usize..moved[1].1.end as usize];
+        let stco = moov.windows(4).position(|name| name == b"stco").unwrap() - 4;
+        let stco_size = u32::from_be_bytes(moov[stco..stco + 4].try_into().unwrap()) as usize;
+        let stco_entries = stco + 16..stco + stco_size;
+        for (index, (a, b)) in moov.iter().zip(moved_moov).enumerate() {
+            assert!(
+                a == b || stco_entries.contains(&index),
+                "moov differs at {index}"
+            );
+        }
+
+        let mp4 = Mp4::read(&mut Cursor::new(&file[..]), file.len() as u64).unwrap();
+        let mut moved_reader = Cursor::new(&output[..]);
+        let moved_mp4 = Mp4::read(&mut moved_reader, output.len() as u64).unwrap();
+        let samples = &mp4.tracks()[&track_id].samples;
+        let moved_samples = &moved_mp4.tracks()[&track_id].samples;
+        for (sample, moved) in samples.iter().zip(moved_samples) {
+            assert_eq!(moved.offset, sample.offset + moov_size);
+            let data = moved_mp4
+                .read_sample(&mut moved_reader, track_id, moved.id)
+                .unwrap();
+            assert_eq!(&data[..], &[moved.id as u8; 32]);
+        }
+        assert_eq!(moved_samples.len(), 8);
+    }
+}
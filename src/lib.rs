@@ -1,64 +1,76 @@
-mod av01;
-mod avc1;
-mod co64;
-mod ctts;
-mod data;
-mod dinf;
-mod edts;
-mod elst;
-mod emsg;
-mod ftyp;
-mod hdlr;
-mod hevc;
-mod ilst;
-mod mdhd;
-mod mdia;
-mod mehd;
-mod meta;
-mod mfhd;
-mod minf;
-mod moof;
-mod moov;
-mod mp4a;
-mod mvex;
-mod mvhd;
-mod smhd;
-mod stbl;
-mod stco;
-mod stsc;
-mod stsd;
-mod stss;
-mod stsz;
-mod stts;
-mod tfdt;
-mod tfhd;
-mod tkhd;
-mod traf;
-mod trak;
-mod trex;
-mod trun;
-mod tx3g;
-mod udta;
-mod vmhd;
-mod vp08;
-mod vp09;
-mod vpcc;
+mod aes;
+pub mod boxes;
+mod captions;
+mod chapters;
+mod cut;
+mod demuxer;
+mod edit;
+mod encryption;
+mod error;
+mod faststart;
+mod metadata;
+mod random_access;
+mod sample_reader;
+mod sample_table;
+mod seek;
+mod subtitles;
+mod track_references;
+mod writer;
 
 use std::{
     borrow::Cow,
     collections::BTreeMap,
-    io::{self, Read, Seek},
+    io::{self, Read, Seek, Write},
     str::FromStr,
 };
 
-use emsg::EmsgBox;
-use ftyp::FtypBox;
-use moof::MoofBox;
-use moov::MoovBox;
-use stbl::StblBox;
-use tfhd::TfhdBox;
-use trak::TrakBox;
-use trun::TrunBox;
+pub use boxes::{
+    av01::{Av01Box, Av1CBox},
+    avc1::{Avc1Box, AvcCBox, NalUnit},
+    c608::C608Box,
+    emsg::EmsgBox,
+    ftyp::FtypBox,
+    hevc::{HevcBox, HevcDecoderConfigurationRecord},
+    mdia::MdiaBox,
+    mfra::{MfraBox, MfroBox, TfraBox, TfraEntry},
+    minf::MinfBox,
+    moof::MoofBox,
+    moov::MoovBox,
+    mp4a::{EsdsBox, Mp4aBox},
+    pssh::PsshBox,
+    senc::{SampleEncryption, Subsample},
+    sidx::{SidxBox, SidxReference, SidxReferenceType, Subsegment},
+    sinf::{SinfBox, TencBox},
+    ssix::{SsixBox, SsixRange},
+    stbl::StblBox,
+    stpp::StppBox,
+    stsd::{StsdBox, StsdBoxContent},
+    styp::StypBox,
+    tfhd::TfhdBox,
+    traf::TrafBox,
+    trak::TrakBox,
+    tref::{ReferenceType, TrackReference, TrefBox},
+    trun::TrunBox,
+    tx3g::Tx3gBox,
+    vp08::Vp08Box,
+    vp09::Vp09Box,
+    wvtt::WvttBox,
+};
+pub use captions::{CaptionChannel, CaptionData, CcData, CcType, Cea608Decoder};
+pub use chapters::Chapter;
+pub use demuxer::{DemuxEvent, Fragment, Mp4Demuxer};
+pub use edit::{Edit, PresentedSample};
+pub use error::{BoxPath, Error, Result};
+pub use metadata::{Location, Metadata, MetadataKey, MetadataValue};
+pub use sample_reader::SampleReader;
+pub use sample_table::{SampleTable, SampleTableIter};
+pub use seek::{rescale_time, Rounding};
+pub use subtitles::{
+    cue_samples, parse_srt, parse_ttml, parse_webvtt, to_srt, to_webvtt, Cue, Karaoke,
+    KaraokeEntry, TextStyle,
+};
+pub use track_references::TrackReferences;
+pub use writer::{FragmentedMp4Writer, Mp4Writer, TrackConfig};
 
 const HEADER_SIZE: u64 = 0b1000;
 const HEADER_EXT_SIZE: u64 = 0b0100;
@@ -76,6 +88,17 @@ const HANDLER_TYPE_AUDIO_FOURCC: [u8; 4] = *b"soun";
 const HANDLER_TYPE_SUBTITLE: &str = "sbtl";
 const HANDLER_TYPE_SUBTITLE_FOURCC: [u8; 4] = *b"sbtl";
 
+// ISO/IEC 14496-30 subtitles (`wvtt`, `stpp`) and QuickTime text tracks
+const HANDLER_TYPE_SUBT: &str = "subt";
+const HANDLER_TYPE_SUBT_FOURCC: [u8; 4] = *b"subt";
+
+const HANDLER_TYPE_TEXT: &str = "text";
+const HANDLER_TYPE_TEXT_FOURCC: [u8; 4] = *b"text";
+
+// QuickTime closed captions (`c608`)
+const HANDLER_TYPE_CLCP: &str = "clcp";
+const HANDLER_TYPE_CLCP_FOURCC: [u8; 4] = *b"clcp";
+
 pub type TrackId = u32;
 
 macro_rules! boxtype {
@@ -117,6 +140,12 @@ boxtype! {
     MehdBox => 0x6d656864,
     TrexBox => 0x74726578,
     EmsgBox => 0x656d7367,
+    StypBox => 0x73747970,
+    SidxBox => 0x73696478,
+    SsixBox => 0x73736978,
+    MfraBox => 0x6d667261,
+    TfraBox => 0x74667261,
+    MfroBox => 0x6d66726f,
     MoofBox => 0x6d6f6f66,
     TkhdBox => 0x746b6864,
     TfhdBox => 0x74666864,
@@ -142,6 +171,9 @@ boxtype! {
     TrunBox => 0x7472756E,
     UdtaBox => 0x75647461,
     MetaBox => 0x6d657461,
+    KeysBox => 0x6b657973,
+    TrefBox => 0x74726566,
+    ChplBox => 0x6368706c,
     DinfBox => 0x64696e66,
     DrefBox => 0x64726566,
     UrlBox  => 0x75726C20,
@@ -157,6 +189,22 @@ boxtype! {
     Mp4aBox => 0x6d703461,
     EsdsBox => 0x65736473,
     Tx3gBox => 0x74783367,
+    WvttBox => 0x77767474,
+    VttCBox => 0x76747443,
+    VlabBox => 0x766c6162,
+    StppBox => 0x73747070,
+    C608Box => 0x63363038,
+    EncvBox => 0x656e6376,
+    EncaBox => 0x656e6361,
+    SinfBox => 0x73696e66,
+    FrmaBox => 0x66726d61,
+    SchmBox => 0x7363686d,
+    SchiBox => 0x73636869,
+    TencBox => 0x74656e63,
+    PsshBox => 0x70737368,
+    SencBox => 0x73656e63,
+    SaizBox => 0x7361697a,
+    SaioBox => 0x7361696f,
     VpccBox => 0x76706343,
     Vp08Box => 0x76703038,
     Vp09Box => 0x76703039,
@@ -166,6 +214,9 @@ boxtype! {
     DayBox => 0xa9646179,
     CovrBox => 0x636f7672,
     DescBox => 0x64657363,
+    FreeformBox => 0x2d2d2d2d,
+    MeanBox => 0x6d65616e,
+    FreeformNameBox => 0x6e616d65,
     WideBox => 0x77696465,
     WaveBox => 0x77617665
 }
@@ -231,7 +282,7 @@ pub enum AudioObjectType {
 }
 
 impl TryFrom<u8> for AudioObjectType {
-    type Error = io::Error;
+    type Error = Error;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
@@ -277,10 +328,7 @@ impl TryFrom<u8> for AudioObjectType {
             44 => Ok(Self::LowDelayMpegSurround),
             45 => Ok(Self::SpatialAudioObjectCodingDialogueEnhancement),
             46 => Ok(Self::AudioSync),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "invalid audio object type",
-            )),
+            _ => Err(Error::invalid_value("invalid audio object type")),
         }
     }
 }
@@ -354,7 +402,7 @@ pub enum SampleFreqIndex {
 }
 
 impl TryFrom<u8> for SampleFreqIndex {
-    type Error = io::Error;
+    type Error = Error;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0x0 => Ok(Self::Freq96000),
@@ -370,10 +418,7 @@ impl TryFrom<u8> for SampleFreqIndex {
             0xa => Ok(Self::Freq11025),
             0xb => Ok(Self::Freq8000),
             0xc => Ok(Self::Freq7350),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "invalid sampling frequency index",
-            )),
+            _ => Err(Error::invalid_value("invalid sampling frequency index")),
         }
     }
 }
@@ -410,7 +455,7 @@ pub enum ChannelConfig {
 }
 
 impl TryFrom<u8> for ChannelConfig {
-    type Error = io::Error;
+    type Error = Error;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0x1 => Ok(Self::Mono),
@@ -420,10 +465,7 @@ impl TryFrom<u8> for ChannelConfig {
             0x5 => Ok(Self::Five),
             0x6 => Ok(Self::FiveOne),
             0x7 => Ok(Self::SevenOne),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "invalid channel configuration",
-            )),
+            _ => Err(Error::invalid_value("invalid channel configuration")),
         }
     }
 }
@@ -443,44 +485,40 @@ impl std::fmt::Display for ChannelConfig {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum DataType {
+    #[default]
     Binary = 0x000000,
     Text = 0x000001,
+    Utf16 = 0x000002,
     Image = 0x00000D,
+    Png = 0x00000E,
     TempoCpil = 0x000015,
-}
-
-impl Default for DataType {
-    fn default() -> Self {
-        Self::Binary
-    }
+    UnsignedInteger = 0x000016,
+    Float32 = 0x000017,
+    Float64 = 0x000018,
+    Bmp = 0x00001B,
 }
 
 impl TryFrom<u32> for DataType {
-    type Error = std::io::Error;
+    type Error = Error;
     fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
             0x000000 => Ok(Self::Binary),
             0x000001 => Ok(Self::Text),
+            0x000002 => Ok(Self::Utf16),
             0x00000D => Ok(Self::Image),
+            0x00000E => Ok(Self::Png),
             0x000015 => Ok(Self::TempoCpil),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "invalid data type",
-            )),
+            0x000016 => Ok(Self::UnsignedInteger),
+            0x000017 => Ok(Self::Float32),
+            0x000018 => Ok(Self::Float64),
+            0x00001B => Ok(Self::Bmp),
+            _ => Err(Error::invalid_value("invalid data type")),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum MetadataKey {
-    Title,
-    Year,
-    Poster,
-    Summary,
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TrackKind {
     Video,
@@ -500,31 +538,30 @@ impl std::fmt::Display for TrackKind {
 }
 
 impl TryFrom<&str> for TrackKind {
-    type Error = io::Error;
+    type Error = Error;
     fn try_from(handler: &str) -> Result<Self, Self::Error> {
         match handler {
             HANDLER_TYPE_VIDEO => Ok(Self::Video),
             HANDLER_TYPE_AUDIO => Ok(Self::Audio),
-            HANDLER_TYPE_SUBTITLE => Ok(Self::Subtitle),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "unsupported handler type",
-            )),
+            HANDLER_TYPE_SUBTITLE | HANDLER_TYPE_SUBT | HANDLER_TYPE_TEXT | HANDLER_TYPE_CLCP => {
+                Ok(Self::Subtitle)
+            }
+            _ => Err(Error::invalid_value("unsupported handler type")),
         }
     }
 }
 
 impl TryFrom<&FourCC> for TrackKind {
-    type Error = io::Error;
+    type Error = Error;
     fn try_from(fourcc: &FourCC) -> Result<Self, Self::Error> {
         match fourcc.value {
             HANDLER_TYPE_VIDEO_FOURCC => Ok(Self::Video),
             HANDLER_TYPE_AUDIO_FOURCC => Ok(Self::Audio),
-            HANDLER_TYPE_SUBTITLE_FOURCC => Ok(Self::Subtitle),
-            _ => Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "unsupported handler type",
-            )),
+            HANDLER_TYPE_SUBTITLE_FOURCC
+            | HANDLER_TYPE_SUBT_FOURCC
+            | HANDLER_TYPE_TEXT_FOURCC
+            | HANDLER_TYPE_CLCP_FOURCC => Ok(Self::Subtitle),
+            _ => Err(Error::invalid_value("unsupported handler type")),
         }
     }
 }
@@ -633,6 +670,16 @@ impl Matrix {
             w: arr[8],
         })
     }
+
+    fn write_i32<W: Write>(&self, writer: &mut W) -> io::Result<u64> {
+        for num in [
+            self.a, self.b, self.u, self.c, self.d, self.v, self.x, self.y, self.w,
+        ] {
+            BigEndian::write_i32(writer, num)?;
+        }
+
+        Ok(36)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -747,11 +794,11 @@ struct BoxHeader {
 }
 
 impl BoxHeader {
-    // fn new(name: BoxType, size: u64) -> Self {
-    //     Self { name, size }
-    // }
+    fn new(name: BoxType, size: u64) -> Self {
+        Self { name, size }
+    }
 
-    fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
         let mut buf = [0u8; 8];
         reader.read_exact(&mut buf)?;
 
@@ -770,12 +817,7 @@ impl BoxHeader {
                 name: BoxType::from(type_),
                 size: match largesize {
                     0 => 0,
-                    1..=15 => {
-                        return Err(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            "64-bit box size too small",
-                        ))
-                    }
+                    1..=15 => return Err(Error::invalid_value("64-bit box size too small")),
                     16..=u64::MAX => largesize - 8,
                 },
             })
@@ -786,15 +828,28 @@ impl BoxHeader {
             })
         }
     }
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<u64> {
+        if self.size > u32::MAX as u64 {
+            BigEndian::write_u32(writer, 1)?;
+            BigEndian::write_u32(writer, self.name.into())?;
+            BigEndian::write_u64(writer, self.size)?;
+            Ok(16)
+        } else {
+            BigEndian::write_u32(writer, self.size as u32)?;
+            BigEndian::write_u32(writer, self.name.into())?;
+            Ok(8)
+        }
+    }
 }
 
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub struct FourCC {
     value: [u8; 4],
 }
 
 impl FromStr for FourCC {
-    type Err = io::Error;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let [a, b, c, d] = s.as_bytes() {
@@ -802,8 +857,7 @@ impl FromStr for FourCC {
                 value: [*a, *b, *c, *d],
             })
         } else {
-            Err(io::Error::new(
-                io::ErrorKind::InvalidData,
+            Err(Error::invalid_value(
                 "expected exactly four bytes in string",
             ))
         }
@@ -852,12 +906,12 @@ impl std::fmt::Display for FourCC {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
-pub struct RawBox<T> {
+pub struct RawBox<T, B = Vec<u8>> {
     pub contents: T,
-    pub raw: Vec<u8>,
+    pub raw: B,
 }
 
-impl<T> std::ops::Deref for RawBox<T> {
+impl<T, B> std::ops::Deref for RawBox<T, B> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -865,29 +919,116 @@ impl<T> std::ops::Deref for RawBox<T> {
     }
 }
 
-impl<T> std::ops::DerefMut for RawBox<T> {
+impl<T, B> std::ops::DerefMut for RawBox<T, B> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.contents
     }
 }
 
-impl<R, T> ReadBox<&mut R> for RawBox<T>
+impl<R, T, B> ReadBox<&mut R> for RawBox<T, B>
 where
-    R: Read + Seek,
+    R: Read + Seek + ReadPayload<B>,
     T: for<'a> ReadBox<&'a mut R>,
 {
-    fn read_box(reader: &mut R, size: u64) -> io::Result<Self> {
+    fn read_box(reader: &mut R, size: u64) -> Result<Self> {
         let start = reader.stream_position()?;
         let contents = T::read_box(reader, size)?;
         let end = reader.stream_position()?;
-        let mut raw = vec![0u8; (end - start) as _];
         reader.seek(io::SeekFrom::Start(start))?;
-        reader.read_exact(&mut raw[..])?;
+        let raw = reader.read_payload(end - start)?;
 
         Ok(Self { contents, raw })
     }
 }
 
+impl<W, T, B> WriteBox<&mut W> for RawBox<T, B>
+where
+    W: Write,
+    T: for<'a> WriteBox<&'a mut W>,
+{
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        self.contents.write_box(writer)
+    }
+}
+
+// A box this crate doesn't model, kept as its payload so it's written back as read
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UnknownBox<B = Vec<u8>> {
+    pub fourcc: FourCC,
+    pub payload: B,
+}
+
+impl<B> UnknownBox<B> {
+    fn read_child<R: Read + Seek + ReadPayload<B>>(
+        reader: &mut R,
+        header: &BoxHeader,
+    ) -> Result<Self> {
+        let Some(payload_size) = header.size.checked_sub(HEADER_SIZE) else {
+            return Err(Error::invalid_value("box is smaller than its header")
+                .within(header.name, box_start(reader)?));
+        };
+        let payload = reader.read_payload(payload_size)?;
+
+        Ok(Self {
+            fourcc: header.name.into(),
+            payload,
+        })
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4Box for UnknownBox<B> {
+    fn box_type(&self) -> BoxType {
+        u32::from(self.fourcc).into()
+    }
+
+    fn box_size(&self) -> u64 {
+        HEADER_SIZE + self.payload.as_ref().len() as u64
+    }
+}
+
+impl<B: AsRef<[u8]>, W: Write> WriteBox<&mut W> for UnknownBox<B> {
+    fn write_box(&self, writer: &mut W) -> Result<u64> {
+        let size = self.box_size();
+        BoxHeader::new(self.box_type(), size).write(writer)?;
+        writer.write_all(self.payload.as_ref())?;
+
+        Ok(size)
+    }
+}
+
+// Writes the children of a sample entry in the order they were read, the first
+// `unknown_before` unknown children come ahead of the modelled `config` box
+fn write_children<W: Write, B: AsRef<[u8]>>(
+    writer: &mut W,
+    config: &impl for<'a> WriteBox<&'a mut W>,
+    unknown: &[UnknownBox<B>],
+    unknown_before: usize,
+) -> Result<()> {
+    let (before, after) = unknown.split_at(unknown_before.min(unknown.len()));
+    for unknown in before {
+        unknown.write_box(writer)?;
+    }
+    config.write_box(writer)?;
+    for unknown in after {
+        unknown.write_box(writer)?;
+    }
+    Ok(())
+}
+
+// Copies a box by writing it out and reading it back, e.g. to turn one holding
+// borrowed payloads into one that owns them
+fn copy_box<T, U>(bx: &T) -> Result<U>
+where
+    T: for<'a> WriteBox<&'a mut Vec<u8>>,
+    U: for<'a> ReadBox<&'a mut io::Cursor<Vec<u8>>>,
+{
+    let mut buf = Vec::new();
+    bx.write_box(&mut buf)?;
+    let mut reader = io::Cursor::new(buf);
+    let header = BoxHeader::read(&mut reader)?;
+    U::read_box(&mut reader, header.size)
+}
+
 pub struct BigEndian;
 
 impl BigEndian {
@@ -960,48 +1101,41 @@ impl BigEndian {
         reader.read_exact(&mut buf)?;
         Ok(u64::from_be_bytes(buf))
     }
-}
 
-pub trait Metadata<'a> {
-    fn title(&self) -> Option<Cow<'_, str>>;
-    fn year(&self) -> Option<u32>;
-    fn poster(&self) -> Option<&[u8]>;
-    fn summary(&self) -> Option<Cow<'_, str>>;
-}
+    pub fn write_i8<W: Write>(writer: &mut W, n: i8) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes())
+    }
 
-impl<'a, T: Metadata<'a>> Metadata<'a> for &'a T {
-    fn title(&self) -> Option<Cow<'_, str>> {
-        (**self).title()
+    pub fn write_u8<W: Write>(writer: &mut W, n: u8) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes())
     }
 
-    fn year(&self) -> Option<u32> {
-        (**self).year()
+    pub fn write_i16<W: Write>(writer: &mut W, n: i16) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes())
     }
 
-    fn poster(&self) -> Option<&[u8]> {
-        (**self).poster()
+    pub fn write_u16<W: Write>(writer: &mut W, n: u16) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes())
     }
 
-    fn summary(&self) -> Option<Cow<'_, str>> {
-        (**self).summary()
+    pub fn write_u24<W: Write>(writer: &mut W, n: u32) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes()[1..])
     }
-}
 
-impl<'a, T: Metadata<'a>> Metadata<'a> for Option<T> {
-    fn title(&self) -> Option<Cow<'_, str>> {
-        self.as_ref().and_then(|t| t.title())
+    pub fn write_i32<W: Write>(writer: &mut W, n: i32) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes())
     }
 
-    fn year(&self) -> Option<u32> {
-        self.as_ref().and_then(|t| t.year())
+    pub fn write_u32<W: Write>(writer: &mut W, n: u32) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes())
     }
 
-    fn poster(&self) -> Option<&[u8]> {
-        self.as_ref().and_then(|t| t.poster())
+    fn write_u48<W: Write>(writer: &mut W, n: u64) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes()[2..])
     }
 
-    fn summary(&self) -> Option<Cow<'_, str>> {
-        self.as_ref().and_then(|t| t.summary())
+    pub fn write_u64<W: Write>(writer: &mut W, n: u64) -> io::Result<()> {
+        writer.write_all(&n.to_be_bytes())
     }
 }
 
@@ -1011,7 +1145,57 @@ pub trait Mp4Box: Sized {
 }
 
 pub trait ReadBox<T>: Sized {
-    fn read_box(_: T, size: u64) -> io::Result<Self>;
+    fn read_box(_: T, size: u64) -> Result<Self>;
+}
+
+pub trait WriteBox<T>: Sized {
+    fn write_box(&self, _: T) -> Result<u64>;
+}
+
+// Reads a box whose header was just consumed. Errors coming out of it get the box
+// type and offset attached, so they end up with the full path to where they occurred.
+trait ReadChild<R>: Sized {
+    fn read_child(reader: &mut R, header: &BoxHeader) -> Result<Self>;
+}
+
+impl<R: Seek, T: for<'a> ReadBox<&'a mut R>> ReadChild<R> for T {
+    fn read_child(reader: &mut R, header: &BoxHeader) -> Result<Self> {
+        let offset = box_start(reader)?;
+        Self::read_box(reader, header.size).map_err(|err| err.within(header.name, offset))
+    }
+}
+
+// Reads the byte payloads of a box (parameter sets, cover art, message data, ...).
+// Any reader can produce owned `Vec<u8>` payloads, a `Cursor` over a byte slice can
+// also hand out borrowed `&[u8]` payloads, which is what `Mp4::from_bytes` uses.
+pub trait ReadPayload<B> {
+    fn read_payload(&mut self, size: u64) -> io::Result<B>;
+}
+
+impl<R: Read> ReadPayload<Vec<u8>> for R {
+    fn read_payload(&mut self, size: u64) -> io::Result<Vec<u8>> {
+        let mut payload = vec![0u8; size as usize];
+        self.read_exact(&mut payload)?;
+        Ok(payload)
+    }
+}
+
+impl<'a> ReadPayload<&'a [u8]> for io::Cursor<&'a [u8]> {
+    fn read_payload(&mut self, size: u64) -> io::Result<&'a [u8]> {
+        let bytes: &'a [u8] = self.get_ref();
+        let start = self.position();
+        let Some(payload) = start
+            .checked_add(size)
+            .and_then(|end| bytes.get(start as usize..end as usize))
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "payload extends past the end of the buffer",
+            ));
+        };
+        self.set_position(start + size);
+        Ok(payload)
+    }
 }
 
 fn read_box_header_ext<R: Read>(reader: &mut R) -> io::Result<(u8, u32)> {
@@ -1021,6 +1205,13 @@ fn read_box_header_ext<R: Read>(reader: &mut R) -> io::Result<(u8, u32)> {
     Ok((version, flag))
 }
 
+fn write_box_header_ext<W: Write>(writer: &mut W, version: u8, flags: u32) -> io::Result<u64> {
+    BigEndian::write_u8(writer, version)?;
+    BigEndian::write_u24(writer, flags)?;
+
+    Ok(HEADER_EXT_SIZE)
+}
+
 fn box_start<R: Seek>(seeker: &mut R) -> io::Result<u64> {
     Ok(seeker.stream_position()? - HEADER_SIZE)
 }
@@ -1041,7 +1232,7 @@ fn skip_box<S: Seek>(seeker: &mut S, size: u64) -> io::Result<()> {
     Ok(())
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone)]
 pub struct Sample {
     pub id: u32,
     pub is_sync: bool,
@@ -1051,6 +1242,10 @@ pub struct Sample {
     pub decode_timestamp: i64,
     pub composition_timestamp: i64,
     pub duration: u64,
+    /// 1-based index into `stsd` entries, from `stsc` or `tfhd`/`trex`
+    pub sample_description_index: u32,
+    /// IV and subsample map of a protected sample, see `StsdBoxContent::protection`
+    pub encryption: Option<SampleEncryption>,
 }
 
 impl std::fmt::Debug for Sample {
@@ -1062,6 +1257,8 @@ impl std::fmt::Debug for Sample {
             .field("decode_timestamp", &self.decode_timestamp)
             .field("composition_timestamp", &self.composition_timestamp)
             .field("duration", &self.duration)
+            .field("sample_description_index", &self.sample_description_index)
+            .field("encryption", &self.encryption)
             .finish()
     }
 }
@@ -1074,6 +1271,13 @@ impl Sample {
 
 pub struct Track {
     first_traf_merged: bool,
+    // added to media timestamps to place them on the presentation timeline
+    presentation_offset: i64,
+    // largest CTS - DTS over all samples, bounds the seek scan
+    max_composition_offset: i64,
+    // samples of the sample tables left out of `samples`, see `Mp4::read_compact`
+    table_samples: u32,
+    edits: Vec<Edit>,
     pub width: u16,
     pub height: u16,
     pub track_id: u32,
@@ -1087,6 +1291,9 @@ impl std::fmt::Debug for Track {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Track")
             .field("first_traf_merged", &self.first_traf_merged)
+            .field("presentation_offset", &self.presentation_offset)
+            .field("max_composition_offset", &self.max_composition_offset)
+            .field("edits", &self.edits)
             .field("kind", &self.kind)
             .field("timescale", &self.time_scale)
             .field("duration", &self.duration)
@@ -1095,7 +1302,7 @@ impl std::fmt::Debug for Track {
 }
 
 impl Track {
-    pub fn trak<'a>(&self, mp4: &'a Mp4) -> &'a TrakBox {
+    pub fn trak<'a, B>(&self, mp4: &'a Mp4<B>) -> &'a TrakBox<B> {
         let Some(trak) = mp4
             .moov
             .traks
@@ -1108,39 +1315,127 @@ impl Track {
         trak
     }
 
-    pub fn raw_codec_config(&self, mp4: &Mp4) -> Option<Vec<u8>> {
-        let sample_description = &self.trak(mp4).mdia.minf.stbl.stsd;
+    // The sample entry of the track's first sample description, see
+    // `sample_description` for tracks switching between several
+    pub fn raw_codec_config<B: AsRef<[u8]>>(&self, mp4: &Mp4<B>) -> Option<Vec<u8>> {
+        let entry = self.trak(mp4).mdia.minf.stbl.stsd.entry(1)?;
+        entry.raw_codec_config().map(|raw| raw.to_vec())
+    }
 
-        match &sample_description.contents {
-            stsd::StsdBoxContent::Av01(content) => Some(content.av1c.raw.clone()),
-            stsd::StsdBoxContent::Avc1(content) => Some(content.avcc.raw.clone()),
-            stsd::StsdBoxContent::Hev1(content) | stsd::StsdBoxContent::Hvc1(content) => {
-                Some(content.hvcc.raw.clone())
-            }
-            stsd::StsdBoxContent::Vp08(content) => Some(content.vpcc.raw.clone()),
-            stsd::StsdBoxContent::Vp09(content) => Some(content.vpcc.raw.clone()),
-            stsd::StsdBoxContent::Mp4a(_)
-            | stsd::StsdBoxContent::Tx3g(_)
-            | stsd::StsdBoxContent::Unknown(_) => None,
-        }
+    pub fn codec_string<B: AsRef<[u8]>>(&self, mp4: &Mp4<B>) -> Option<String> {
+        self.trak(mp4).mdia.minf.stbl.stsd.entry(1)?.codec_string()
+    }
+
+    pub fn sample_description<'a, B: AsRef<[u8]>>(
+        &self,
+        mp4: &'a Mp4<B>,
+        sample: &Sample,
+    ) -> Option<&'a StsdBoxContent<B>> {
+        let stsd = &self.trak(mp4).mdia.minf.stbl.stsd;
+        stsd.entry(sample.sample_description_index)
     }
 
-    pub fn codec_string(&self, mp4: &Mp4) -> Option<String> {
-        self.trak(mp4).mdia.minf.stbl.stsd.contents.codec_string()
+    pub fn sample_raw_codec_config<B: AsRef<[u8]>>(
+        &self,
+        mp4: &Mp4<B>,
+        sample: &Sample,
+    ) -> Option<Vec<u8>> {
+        let entry = self.sample_description(mp4, sample)?;
+        entry.raw_codec_config().map(|raw| raw.to_vec())
+    }
+
+    pub fn sample_codec_string<B: AsRef<[u8]>>(
+        &self,
+        mp4: &Mp4<B>,
+        sample: &Sample,
+    ) -> Option<String> {
+        self.sample_description(mp4, sample)?.codec_string()
+    }
+
+    // Yields `(Sample, bytes)` in decode order, contiguous samples are fetched
+    // with a single read.
+    pub fn read_samples<'a, B, R: Read + Seek>(
+        &'a self,
+        mp4: &'a Mp4<B>,
+        reader: R,
+    ) -> SampleReader<'a, R, B> {
+        SampleReader::new(reader, mp4, self)
     }
 }
 
 #[derive(Debug)]
-pub struct Mp4 {
+pub struct Mp4<B = Vec<u8>> {
     pub ftyp: FtypBox,
-    pub moov: MoovBox,
+    pub moov: MoovBox<B>,
     pub moofs: Vec<MoofBox>,
-    pub emsgs: Vec<EmsgBox>,
+    pub emsgs: Vec<EmsgBox<B>>,
+    pub styp: Option<StypBox>,
+    pub sidxs: Vec<SidxBox>,
+    pub ssixs: Vec<SsixBox>,
+    pub mfra: Option<MfraBox>,
     tracks: BTreeMap<TrackId, Track>,
 }
 
 impl Mp4 {
-    pub fn read<R: Read + Seek>(mut reader: R, size: u64) -> io::Result<Self> {
+    pub fn read<R: Read + Seek>(reader: R, size: u64) -> Result<Self> {
+        Self::read_boxes(reader, size, None, true)
+    }
+
+    // Like `read`, but the samples of the sample tables aren't expanded into
+    // `Track::samples`, which then only holds the samples of fragments. The others are
+    // resolved through `Track::sample_table`.
+    pub fn read_compact<R: Read + Seek>(reader: R, size: u64) -> Result<Self> {
+        Self::read_boxes(reader, size, None, false)
+    }
+
+    // Parses a DASH/CMAF media segment, which has neither `ftyp` nor `moov`, with the
+    // movie of its init segment. Sample offsets are relative to the segment.
+    pub fn read_media_segment<R: Read + Seek>(init: &Mp4, reader: R, size: u64) -> Result<Self> {
+        Self::read_boxes(reader, size, Some(init), true)
+    }
+}
+
+impl<'a> Mp4<&'a [u8]> {
+    // Payloads such as parameter sets, cover art and emsg data borrow from `bytes`
+    // instead of being copied.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self> {
+        Self::read_boxes(io::Cursor::new(bytes), bytes.len() as u64, None, true)
+    }
+}
+
+impl<'a, B: AsRef<[u8]>> Metadata<'a> for Mp4<B> {
+    fn title(&self) -> Option<Cow<'_, str>> {
+        self.moov.title()
+    }
+
+    fn year(&self) -> Option<u32> {
+        self.moov.year()
+    }
+
+    fn poster(&self) -> Option<&[u8]> {
+        self.moov.poster()
+    }
+
+    fn summary(&self) -> Option<Cow<'_, str>> {
+        self.moov.summary()
+    }
+
+    fn value(&self, key: &MetadataKey) -> Option<MetadataValue<'_>> {
+        self.moov.value(key)
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4<B> {
+    fn read_boxes<R>(
+        mut reader: R,
+        size: u64,
+        init: Option<&Self>,
+        expand_samples: bool,
+    ) -> Result<Self>
+    where
+        R: Read + Seek + ReadPayload<B>,
+        B: Clone,
+    {
         let start = reader.stream_position()?;
 
         let mut ftyp = None;
@@ -1148,16 +1443,17 @@ impl Mp4 {
         let mut moofs = Vec::new();
         let mut moof_offsets = Vec::new();
         let mut emsgs = Vec::new();
+        let mut styp = None;
+        let mut sidxs = Vec::new();
+        let mut ssixs = Vec::new();
+        let mut mfra = None;
 
         let mut current = start;
         while current < size {
             let header = BoxHeader::read(&mut reader)?;
 
             if header.size > size {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "file contains a box with a larger scale than it",
-                ));
+                return Err(Error::size_overflow(header.name, box_start(&mut reader)?));
             }
 
             if header.size == 0 {
@@ -1166,7 +1462,7 @@ impl Mp4 {
 
             match header.name {
                 BoxType::FtypBox => {
-                    ftyp.replace(FtypBox::read_box(&mut reader, header.size)?);
+                    ftyp.replace(FtypBox::read_child(&mut reader, &header)?);
                 }
                 BoxType::FreeBox => {
                     skip_box(&mut reader, header.size)?;
@@ -1175,18 +1471,30 @@ impl Mp4 {
                     skip_box(&mut reader, header.size)?;
                 }
                 BoxType::MoovBox => {
-                    moov.replace(MoovBox::read_box(&mut reader, header.size)?);
+                    moov.replace(MoovBox::read_child(&mut reader, &header)?);
                 }
                 BoxType::MoofBox => {
                     let moof_offset = reader.stream_position()?;
-                    let moof = MoofBox::read_box(&mut reader, header.size)?;
+                    let moof = MoofBox::read_child(&mut reader, &header)?;
                     moofs.push(moof);
                     moof_offsets.push(moof_offset);
                 }
                 BoxType::EmsgBox => {
-                    let emsg = EmsgBox::read_box(&mut reader, header.size)?;
+                    let emsg = EmsgBox::read_child(&mut reader, &header)?;
                     emsgs.push(emsg)
                 }
+                BoxType::StypBox => {
+                    styp.replace(StypBox::read_child(&mut reader, &header)?);
+                }
+                BoxType::SidxBox => {
+                    sidxs.push(SidxBox::read_child(&mut reader, &header)?);
+                }
+                BoxType::SsixBox => {
+                    ssixs.push(SsixBox::read_child(&mut reader, &header)?);
+                }
+                BoxType::MfraBox => {
+                    mfra.replace(MfraBox::read_child(&mut reader, &header)?);
+                }
                 _ => {
                     skip_box(&mut reader, header.size)?;
                 }
@@ -1195,18 +1503,12 @@ impl Mp4 {
             current = reader.stream_position()?;
         }
 
-        let Some(ftyp) = ftyp else {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                "ftyp box is not found",
-            ));
+        let Some(ftyp) = ftyp.or_else(|| init.map(|init| init.ftyp.clone())) else {
+            return Err(Error::missing_box(BoxType::FtypBox, start));
         };
 
-        let Some(moov) = moov else {
-            return Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                "moov box is not found",
-            ));
+        let Some(moov) = moov.or_else(|| init.map(|init| init.moov.clone())) else {
+            return Err(Error::missing_box(BoxType::MoovBox, start));
         };
 
         let mut this = Self {
@@ -1214,11 +1516,16 @@ impl Mp4 {
             moov,
             moofs,
             emsgs,
+            styp,
+            sidxs,
+            ssixs,
+            mfra,
             tracks: Default::default(),
         };
 
-        let mut tracks = this.build_tracks();
+        let mut tracks = this.build_tracks(expand_samples);
         this.update_sample_list(&mut tracks)?;
+        this.read_auxiliary_info(&mut reader, &mut tracks)?;
         this.tracks = tracks;
         this.update_tracks();
 
@@ -1229,182 +1536,138 @@ impl Mp4 {
         &self.tracks
     }
 
-    fn build_tracks(&mut self) -> BTreeMap<TrackId, Track> {
-        let mut tracks = BTreeMap::new();
-
-        for trak in &self.moov.traks {
-            let mut sample_n = 0usize;
-            let mut chunk_index = 1u64;
-            let mut chunk_run_index = 0usize;
-            let mut last_sample_in_chunk = 0u64;
-            let mut offset_in_chunk = 0u64;
-            let mut last_chunk_in_run = 0u64;
-            let mut last_sample_in_stts_run = -1i64;
-            let mut stts_run_index = -1i64;
-            let mut last_stss_index = 0;
-            let mut last_sample_in_ctts_run = -1i64;
-            let mut ctts_run_index = -1i64;
-            let mut dts_shift = 0;
-
-            // The smallest presentation timestap observed on this stream
-            //
-            // This is typically 0, but in the pressence of sample reordering (cused by AVC/HVC b-frames), it may be non-zero.
-            // In fact, many formats don't require this to be zero, but video players typically
-            // normalize the shown time to start at zero
-            let mut min_composition_timestamp = i64::MAX;
-
-            let mut samples = Vec::<Sample>::new();
-
-            fn get_sample_chunk_offset(stbl: &StblBox, chunk_index: u64) -> u64 {
-                if let Some(stco) = &stbl.stco {
-                    stco.entries[chunk_index as usize - 1] as u64
-                } else if let Some(co64) = &stbl.co64 {
-                    co64.entries[chunk_index as usize - 1]
-                } else {
-                    panic!()
-                }
+    // Media subsegments of the segment index, with `sidx` hierarchies resolved. A
+    // `sidx` referenced by another one isn't walked on its own.
+    pub fn subsegments(&self) -> Vec<Subsegment> {
+        let referenced: Vec<u64> = self
+            .sidxs
+            .iter()
+            .flat_map(|sidx| sidx.reference_ranges().into_iter().zip(&sidx.references))
+            .filter(|(_, reference)| reference.reference_type == SidxReferenceType::Index)
+            .map(|((range, _), _)| range.start)
+            .collect();
+
+        let mut subsegments = Vec::new();
+        for sidx in &self.sidxs {
+            if !referenced.contains(&sidx.start) {
+                self.collect_subsegments(sidx, &mut subsegments);
             }
+        }
 
-            let stbl = &trak.mdia.minf.stbl;
-            let stsc = &stbl.stsc;
-            let stsz = &stbl.stsz;
-            let stts = &stbl.stts;
-
-            while sample_n < stsz.sample_sizes.len() {
-                // compute offset
-                if sample_n == 0 {
-                    chunk_index = 1;
-                    chunk_run_index = 0;
-                    last_sample_in_chunk = stsc.entries[chunk_run_index].samples_per_chunk as u64;
-                    offset_in_chunk = 0;
-
-                    if chunk_run_index + 1 < stsc.entries.len() {
-                        last_chunk_in_run =
-                            stsc.entries[chunk_run_index + 1].first_chunk as u64 - 1;
-                    } else {
-                        last_chunk_in_run = u64::MAX;
-                    }
-                } else if sample_n < last_sample_in_chunk as usize {
-                    /* ... */
-                } else {
-                    chunk_index += 1;
-                    offset_in_chunk = 0;
-                    if chunk_index > last_chunk_in_run {
-                        chunk_run_index += 1;
-                        if chunk_run_index + 1 < stsc.entries.len() {
-                            last_chunk_in_run =
-                                stsc.entries[chunk_run_index + 1].first_chunk as u64 - 1;
-                        } else {
-                            last_chunk_in_run = u64::MAX;
-                        }
+        subsegments
+    }
+
+    // References only point forward, so the recursion ends
+    fn collect_subsegments(&self, sidx: &SidxBox, subsegments: &mut Vec<Subsegment>) {
+        let ranges = sidx.reference_ranges();
+        for ((range, time), reference) in ranges.into_iter().zip(&sidx.references) {
+            match reference.reference_type {
+                SidxReferenceType::Media => subsegments.push(Subsegment {
+                    reference_id: sidx.reference_id,
+                    timescale: sidx.timescale,
+                    earliest_presentation_time: time,
+                    duration: reference.subsegment_duration,
+                    range,
+                    starts_with_sap: reference.starts_with_sap,
+                    sap_type: reference.sap_type,
+                    sap_delta_time: reference.sap_delta_time,
+                }),
+                SidxReferenceType::Index => {
+                    if let Some(child) = self.sidxs.iter().find(|child| child.start == range.start)
+                    {
+                        self.collect_subsegments(child, subsegments);
                     }
-
-                    last_sample_in_chunk += stsc.entries[chunk_run_index].samples_per_chunk as u64;
                 }
+            }
+        }
+    }
 
-                // compute timestamp, duration, is_sync
+    pub fn read_sample<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        track_id: TrackId,
+        sample_id: u32,
+    ) -> Result<Vec<u8>> {
+        let sample = self.sample(track_id, sample_id)?;
 
-                if sample_n as i64 > last_sample_in_stts_run {
-                    stts_run_index += 1;
-                    if last_sample_in_stts_run < 0 {
-                        last_sample_in_stts_run = 0;
-                    }
-                    last_sample_in_stts_run +=
-                        stts.entries[stts_run_index as usize].sample_count as i64;
-                }
+        let mut data = vec![0u8; sample.size as usize];
+        reader.seek(io::SeekFrom::Start(sample.offset))?;
+        reader.read_exact(&mut data)?;
 
-                let timescale = trak.mdia.mdhd.timescale as u64;
-                let size = stsz.sample_sizes[sample_n] as u64;
-                let offset = get_sample_chunk_offset(stbl, chunk_index) + offset_in_chunk;
-                offset_in_chunk += size;
-
-                let decode_timestamp = if sample_n > 0 {
-                    samples[sample_n - 1].duration =
-                        stts.entries[stts_run_index as usize].sample_delta as u64;
-                    samples[sample_n - 1].decode_timestamp + samples[sample_n - 1].duration as i64
-                } else {
-                    0
-                };
-
-                let composition_timestamp = if let Some(ctts) = &stbl.ctts {
-                    if sample_n as i64 >= last_sample_in_ctts_run {
-                        ctts_run_index += 1;
-                        if last_sample_in_ctts_run < 0 {
-                            last_sample_in_ctts_run = 0;
-                        }
-                        last_sample_in_ctts_run +=
-                            ctts.entries[ctts_run_index as usize].sample_count as i64;
-                    }
+        Ok(data)
+    }
 
-                    // dts shift is determined by the smallest negative sample offset
-                    let offset = ctts.entries[ctts_run_index as usize].sample_offset as i64;
-                    if offset < 0 {
-                        dts_shift = dts_shift.max(-offset);
-                    }
+    // Samples left in the sample tables by `read_compact` are resolved from there
+    pub(crate) fn sample(&self, track_id: TrackId, sample_id: u32) -> Result<Cow<'_, Sample>> {
+        let Some(track) = self.tracks.get(&track_id) else {
+            return Err(Error::TrackNotFound(track_id));
+        };
+        let sample = match sample_id.checked_sub(track.table_samples) {
+            Some(index) => track.samples.get(index as usize).map(Cow::Borrowed),
+            None => track.sample_table(self).get(sample_id).map(Cow::Owned),
+        };
 
-                    decode_timestamp + offset
-                } else {
-                    decode_timestamp
-                };
+        sample.ok_or(Error::SampleNotFound {
+            track_id,
+            sample_id,
+        })
+    }
 
-                min_composition_timestamp = min_composition_timestamp.min(composition_timestamp);
+    fn build_tracks(&mut self, expand_samples: bool) -> BTreeMap<TrackId, Track> {
+        let mut tracks = BTreeMap::new();
 
-                let is_sync = if let Some(stss) = &stbl.stss {
-                    if last_stss_index < stss.entries.len()
-                        && sample_n == stss.entries[last_stss_index] as usize - 1
-                    {
-                        last_stss_index += 1;
-                        true
-                    } else {
-                        false
-                    }
-                } else {
-                    true
-                };
-
-                samples.push(Sample {
-                    id: samples.len() as u32,
-                    is_sync,
-                    size,
-                    offset,
-                    timescale,
-                    decode_timestamp,
-                    composition_timestamp,
-                    duration: 0, // filled once next sample timestamp is known
+        for trak in &self.moov.traks {
+            let mut table = SampleTable::new(trak);
+
+            let edits = trak
+                .edts
+                .as_ref()
+                .and_then(|edts| edts.elst.as_ref())
+                .map(|elst| {
+                    edit::build_edits(
+                        elst,
+                        self.moov.mvhd.timescale,
+                        trak.mdia.mdhd.timescale as u64,
+                    )
+                })
+                .unwrap_or_default();
+
+            // The first non-empty edit decides where the media starts on the presentation
+            // timeline, without one both DTS & CTS are shifted by the smallest CTS
+            let presentation_offset = edits
+                .iter()
+                .find_map(|edit| Some(edit.presentation_time as i64 - edit.media_time?))
+                .unwrap_or(match table.is_empty() {
+                    true => 0,
+                    false => -table.min_composition_timestamp(),
                 });
+            table.presentation_offset = presentation_offset;
 
-                sample_n += 1;
-            }
-
-            if let Some(last_sample) = samples.last_mut() {
-                last_sample.duration =
-                    trak.mdia.mdhd.duration - last_sample.decode_timestamp as u64;
-            }
-
-            if dts_shift > 0 {
-                for sample in &mut samples {
-                    sample.decode_timestamp -= dts_shift;
-                }
-            }
-
-            // Shift both DTS & CTS by the smallest CTS
-            if min_composition_timestamp != 0 {
-                for sample in &mut samples {
-                    sample.decode_timestamp -= min_composition_timestamp;
-                    sample.composition_timestamp -= min_composition_timestamp;
-                }
-            }
+            let (samples, table_samples) = match expand_samples {
+                true => (table.iter().collect(), 0),
+                false => (Vec::new(), table.len() as u32),
+            };
 
             tracks.insert(
                 trak.tkhd.track_id,
                 Track {
                     first_traf_merged: false,
+                    presentation_offset,
+                    max_composition_offset: table.max_composition_offset(),
+                    table_samples,
+                    edits,
                     width: trak.tkhd.width.value(),
                     height: trak.tkhd.height.value(),
                     track_id: trak.tkhd.track_id,
                     time_scale: trak.mdia.mdhd.timescale as u64,
                     duration: trak.mdia.mdhd.duration,
-                    kind: trak.mdia.minf.stbl.stsd.kind(),
+                    kind: trak
+                        .mdia
+                        .minf
+                        .stbl
+                        .stsd
+                        .kind()
+                        .or_else(|| TrackKind::try_from(&trak.mdia.hdlr.handler_type).ok()),
                     samples,
                 },
             );
@@ -1415,143 +1678,161 @@ impl Mp4 {
 
     // In case the input file is fragmented, it will contain one or more `moof` boxes,
     // which must be processed to obtain the full list of samples for each track.
-    fn update_sample_list(&mut self, tracks: &mut BTreeMap<TrackId, Track>) -> io::Result<()> {
+    fn update_sample_list(&mut self, tracks: &mut BTreeMap<TrackId, Track>) -> Result<()> {
         let mut last_run_position = 0;
 
         for moof in &self.moofs {
-            for traf in &moof.trafs {
-                let track_id = traf.tfhd.track_id;
-                let track = tracks.get_mut(&track_id).ok_or(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("trak box with id {} not found", track_id).as_str(),
-                ))?;
-                let trak = self
-                    .moov
-                    .traks
+            Self::merge_moof(&self.moov, tracks, moof, &mut last_run_position)?;
+        }
+
+        Ok(())
+    }
+
+    fn merge_moof(
+        moov: &MoovBox<B>,
+        tracks: &mut BTreeMap<TrackId, Track>,
+        moof: &MoofBox,
+        last_run_position: &mut u64,
+    ) -> Result<()> {
+        for traf in &moof.trafs {
+            let track_id = traf.tfhd.track_id;
+            let track = tracks
+                .get_mut(&track_id)
+                .ok_or(Error::TrackNotFound(track_id))?;
+            let first_sample = track.samples.len();
+            let trak = moov
+                .traks
+                .iter()
+                .find(|trak| trak.tkhd.track_id == track_id)
+                .ok_or(Error::TrackNotFound(track_id))?;
+            let trex = if let Some(mvex) = &moov.mvex {
+                mvex.trexs
                     .iter()
-                    .find(|trak| trak.tkhd.track_id == track_id)
-                    .ok_or(io::Error::new(
-                        io::ErrorKind::NotFound,
-                        format!("trak box with id {} not found", track_id).as_str(),
-                    ))?;
-                let trex = if let Some(mvex) = &self.moov.mvex {
-                    mvex.trexs
-                        .iter()
-                        .find(|trex| trex.track_id == track_id)
-                        .ok_or(io::Error::new(
-                            io::ErrorKind::NotFound,
-                            format!("trak box with id {} not found", track_id).as_str(),
-                        ))?
-                        .clone()
-                } else {
-                    Default::default()
-                };
-
-                let default_sample_duration = traf
-                    .tfhd
-                    .default_sample_duration
-                    .unwrap_or(trex.default_sample_duration);
-                let default_sample_size = traf
-                    .tfhd
-                    .default_sample_size
-                    .unwrap_or(trex.default_sample_size);
-                let default_sample_flags = traf
-                    .tfhd
-                    .default_sample_flags
-                    .unwrap_or(trex.default_sample_flags);
-
-                for (traf_idx, trun) in traf.truns.iter().enumerate() {
-                    for sample_n in 0..trun.sample_count as usize {
-                        let mut sample_flags = default_sample_flags;
-                        if trun.flags & TrunBox::FLAG_SAMPLE_FLAGS != 0 {
-                            sample_flags = trun
-                                .sample_flags
-                                .get(sample_n)
-                                .copied()
-                                .unwrap_or(sample_flags);
-                        } else if sample_n == 0
-                            && (trun.flags & TrunBox::FLAG_FIRST_SAMPLE_FLAGS != 0)
-                        {
-                            sample_flags = trun.first_sample_flags.unwrap_or(sample_flags);
-                        }
+                    .find(|trex| trex.track_id == track_id)
+                    .ok_or(Error::TrackNotFound(track_id))?
+                    .clone()
+            } else {
+                Default::default()
+            };
+
+            let default_sample_duration = traf
+                .tfhd
+                .default_sample_duration
+                .unwrap_or(trex.default_sample_duration);
+            let default_sample_size = traf
+                .tfhd
+                .default_sample_size
+                .unwrap_or(trex.default_sample_size);
+            let default_sample_flags = traf
+                .tfhd
+                .default_sample_flags
+                .unwrap_or(trex.default_sample_flags);
+            let sample_description_index = traf
+                .tfhd
+                .sample_description_index
+                .unwrap_or(trex.default_sample_description_index);
+
+            for (traf_idx, trun) in traf.truns.iter().enumerate() {
+                for sample_n in 0..trun.sample_count as usize {
+                    let mut sample_flags = default_sample_flags;
+                    if trun.flags & TrunBox::FLAG_SAMPLE_FLAGS != 0 {
+                        sample_flags = trun
+                            .sample_flags
+                            .get(sample_n)
+                            .copied()
+                            .unwrap_or(sample_flags);
+                    } else if sample_n == 0 && (trun.flags & TrunBox::FLAG_FIRST_SAMPLE_FLAGS != 0)
+                    {
+                        sample_flags = trun.first_sample_flags.unwrap_or(sample_flags);
+                    }
 
-                        let mut decode_timestamp = 0;
-                        if track.first_traf_merged || sample_n > 0 {
-                            let prev = &track.samples[track.samples.len() - 1];
-                            decode_timestamp = prev.decode_timestamp + prev.duration as i64;
-                        } else {
-                            if let Some(tfdt) = &traf.tfdt {
-                                decode_timestamp = tfdt.base_media_decode_time as i64;
-                            }
-                            track.first_traf_merged = true;
+                    let mut decode_timestamp = 0;
+                    if track.first_traf_merged || sample_n > 0 {
+                        let prev = &track.samples[track.samples.len() - 1];
+                        decode_timestamp = prev.decode_timestamp + prev.duration as i64;
+                    } else {
+                        if let Some(tfdt) = &traf.tfdt {
+                            decode_timestamp =
+                                tfdt.base_media_decode_time as i64 + track.presentation_offset;
                         }
+                        track.first_traf_merged = true;
+                    }
 
-                        let composition_timestamp = if trun.flags & TrunBox::FLAG_SAMPLE_CTS != 0 {
-                            decode_timestamp
-                                + trun.sample_cts.get(sample_n).copied().unwrap_or(0) as i64
+                    let composition_timestamp = if trun.flags & TrunBox::FLAG_SAMPLE_CTS != 0 {
+                        let sample_cts = trun.sample_cts.get(sample_n).copied().unwrap_or(0);
+                        if trun.version == 1 {
+                            decode_timestamp + sample_cts as i32 as i64
                         } else {
-                            decode_timestamp
-                        };
+                            decode_timestamp + sample_cts as i64
+                        }
+                    } else {
+                        decode_timestamp
+                    };
 
-                        let duration = trun
-                            .sample_duration
+                    let duration =
+                        trun.sample_duration
                             .get(sample_n)
                             .copied()
-                            .unwrap_or(default_sample_duration)
-                            as u64;
-
-                        let base_data_offset_present =
-                            traf.tfhd.flags & TfhdBox::FLAG_BASE_DATA_OFFSET != 0;
-                        let default_base_is_moof =
-                            traf.tfhd.flags & TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF != 0;
-                        let data_offset_present = trun.flags & TrunBox::FLAG_DATA_OFFSET != 0;
-                        let base_data_offset = if !base_data_offset_present {
-                            if !default_base_is_moof {
-                                if sample_n == 0 {
-                                    // the first sample in the track fragment
-                                    moof.start // the position of the first byte of the enclosing Movie Fragment Box
-                                } else {
-                                    last_run_position // the offset of the previous sample
-                                }
+                            .unwrap_or(default_sample_duration) as u64;
+
+                    let base_data_offset_present =
+                        traf.tfhd.flags & TfhdBox::FLAG_BASE_DATA_OFFSET != 0;
+                    let default_base_is_moof =
+                        traf.tfhd.flags & TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF != 0;
+                    let data_offset_present = trun.flags & TrunBox::FLAG_DATA_OFFSET != 0;
+                    let base_data_offset = if !base_data_offset_present {
+                        if !default_base_is_moof {
+                            if sample_n == 0 {
+                                // the first sample in the track fragment
+                                moof.start // the position of the first byte of the enclosing Movie Fragment Box
                             } else {
-                                moof.start
+                                *last_run_position // the offset of the previous sample
                             }
                         } else {
-                            traf.tfhd.base_data_offset.unwrap_or(moof.start)
-                        };
-
-                        let sample_size =
-                            trun.sample_sizes
-                                .get(sample_n)
-                                .copied()
-                                .unwrap_or(default_sample_size) as u64;
-
-                        let sample_offset = if traf_idx == 0 && sample_n == 0 {
-                            if data_offset_present {
-                                base_data_offset
-                                    .saturating_add_signed(trun.data_offset.unwrap_or(0) as i64)
-                            } else {
-                                base_data_offset
-                            }
+                            moof.start
+                        }
+                    } else {
+                        traf.tfhd.base_data_offset.unwrap_or(moof.start)
+                    };
+
+                    let sample_size =
+                        trun.sample_sizes
+                            .get(sample_n)
+                            .copied()
+                            .unwrap_or(default_sample_size) as u64;
+
+                    let sample_offset = if traf_idx == 0 && sample_n == 0 {
+                        if data_offset_present {
+                            base_data_offset
+                                .saturating_add_signed(trun.data_offset.unwrap_or(0) as i64)
                         } else {
-                            last_run_position
-                        };
-
-                        last_run_position = sample_offset + sample_size;
-
-                        track.samples.push(Sample {
-                            id: track.samples.len() as u32,
-                            is_sync: (sample_flags >> 16) & 0x1 != 0,
-                            size: sample_size,
-                            offset: sample_offset,
-                            timescale: trak.mdia.mdhd.timescale as u64,
-                            decode_timestamp,
-                            composition_timestamp,
-                            duration,
-                        });
-                    }
+                            base_data_offset
+                        }
+                    } else {
+                        *last_run_position
+                    };
+
+                    *last_run_position = sample_offset + sample_size;
+
+                    track.max_composition_offset = track
+                        .max_composition_offset
+                        .max(composition_timestamp - decode_timestamp);
+                    track.samples.push(Sample {
+                        id: track.table_samples + track.samples.len() as u32,
+                        is_sync: (sample_flags >> 16) & 0x1 == 0, // sample_is_non_sync_sample
+                        size: sample_size,
+                        offset: sample_offset,
+                        timescale: trak.mdia.mdhd.timescale as u64,
+                        decode_timestamp,
+                        composition_timestamp,
+                        duration,
+                        sample_description_index,
+                        encryption: None,
+                    });
                 }
             }
+
+            encryption::apply_senc(trak, traf, &mut track.samples[first_sample..])?;
         }
 
         Ok(())
@@ -1569,3 +1850,255 @@ impl Mp4 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::boxes::{ilst::IlstBox, meta::MetaBox, tests::write, udta::UdtaBox};
+
+    // Whether `bytes` points into `file` rather than at a copy
+    fn borrows(file: &[u8], bytes: &[u8]) -> bool {
+        file.as_ptr_range().contains(&bytes.as_ptr())
+    }
+
+    fn sample_positions<B: AsRef<[u8]>>(
+        mp4: &Mp4<B>,
+        track_id: TrackId,
+    ) -> Vec<(u64, u64, i64, bool)> {
+        mp4.tracks()[&track_id]
+            .samples
+            .iter()
+            .map(|sample| {
+                (
+                    sample.offset,
+                    sample.size,
+                    sample.decode_timestamp,
+                    sample.is_sync,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn from_bytes_matches_read() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let avcc = AvcCBox {
+            length_size_minus_one: 3,
+            ..AvcCBox::new(&[0x67, 0x64, 0, 0x28, 0xAC], &[0x68, 0xEE, 0x3C, 0x80])
+        };
+        let config = TrackConfig::new(
+            90000,
+            StsdBoxContent::Avc1(Avc1Box {
+                avcc: RawBox {
+                    contents: avcc,
+                    raw: Vec::new(),
+                },
+                ..Default::default()
+            }),
+        );
+        let track_id = writer.add_track(config).unwrap();
+        for id in 0..10u32 {
+            let sample = Sample {
+                id,
+                is_sync: id % 5 == 0,
+                timescale: 90000,
+                decode_timestamp: id as i64 * 3000,
+                composition_timestamp: id as i64 * 3000,
+                duration: 3000,
+                ..Default::default()
+            };
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 16])
+                .unwrap();
+        }
+        let mut ilst = IlstBox::default();
+        ilst.set(
+            MetadataKey::Poster,
+            MetadataValue::Image {
+                data_type: DataType::Png,
+                data: b"\x89PNG",
+            },
+        );
+        writer.set_metadata(
+            None,
+            Some(UdtaBox {
+                meta: Some(MetaBox::Mdir { ilst: Some(ilst) }),
+                ..Default::default()
+            }),
+        );
+        let mut file = writer.finish().unwrap().into_inner();
+        let emsg = EmsgBox {
+            timescale: 90000,
+            presentation_time_delta: Some(0),
+            scheme_id_uri: "https://aomedia.org/emsg/ID3".into(),
+            message_data: b"ID3".to_vec(),
+            ..Default::default()
+        };
+        emsg.write_box(&mut file).unwrap();
+
+        let read = Mp4::read(&mut Cursor::new(&file[..]), file.len() as u64).unwrap();
+        let borrowed = Mp4::from_bytes(&file).unwrap();
+        assert_eq!(write(&borrowed.moov), write(&read.moov));
+        assert_eq!(write(&borrowed.emsgs[0]), write(&read.emsgs[0]));
+
+        assert_eq!(
+            sample_positions(&borrowed, track_id),
+            sample_positions(&read, track_id)
+        );
+
+        // the payloads point into `file`
+        assert!(borrows(&file, borrowed.poster().unwrap()));
+        assert!(borrows(&file, borrowed.emsgs[0].message_data));
+        let StsdBoxContent::Avc1(avc1) = &borrowed.moov.traks[0].mdia.minf.stbl.stsd.entries[0]
+        else {
+            panic!("expected avc1");
+        };
+        let avcc = &avc1.avcc;
+        assert!(borrows(&file, avcc.raw));
+        assert!(borrows(
+            &file,
+            avcc.contents.sequence_parameter_sets[0].bytes
+        ));
+        assert!(!borrows(&file, read.poster().unwrap()));
+    }
+
+    #[test]
+    fn media_segment() {
+        let mut writer = FragmentedMp4Writer::new(Cursor::new(Vec::new()));
+        let config = TrackConfig::new(1000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        writer.write_init_segment().unwrap();
+        for id in 0..15u32 {
+            let sample = Sample {
+                id,
+                is_sync: true,
+                timescale: 1000,
+                decode_timestamp: id as i64 * 100,
+                composition_timestamp: id as i64 * 100,
+                duration: 100,
+                ..Default::default()
+            };
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 6])
+                .unwrap();
+            if id % 5 == 4 {
+                writer.flush_segment().unwrap();
+            }
+        }
+        let file = writer.finish().unwrap().into_inner();
+        let written = Mp4::read(Cursor::new(&file[..]), file.len() as u64).unwrap();
+        let starts: Vec<_> = written
+            .moofs
+            .iter()
+            .map(|moof| moof.start as usize)
+            .collect();
+        let fragments = [
+            &file[starts[0]..starts[1]],
+            &file[starts[1]..starts[2]],
+            &file[starts[2]..],
+        ];
+        let init = Mp4::read(Cursor::new(&file[..starts[0]]), starts[0] as u64).unwrap();
+        assert!(init.tracks()[&track_id].samples.is_empty());
+
+        // styp, a sidx indexing a sidx of the first two fragments and the last one,
+        // then the level ranges of the inner sidx
+        let media = |fragment: &[u8]| SidxReference {
+            referenced_size: fragment.len() as u32,
+            subsegment_duration: 500,
+            starts_with_sap: true,
+            sap_type: 1,
+            ..Default::default()
+        };
+        let ssix = SsixBox {
+            subsegments: vec![vec![SsixRange {
+                level: 0,
+                range_size: fragments[0].len() as u32,
+            }]],
+            ..Default::default()
+        };
+        let inner = SidxBox {
+            reference_id: track_id,
+            timescale: 1000,
+            first_offset: ssix.box_size(),
+            references: vec![media(fragments[0]), media(fragments[1])],
+            ..Default::default()
+        };
+        let indexed =
+            inner.box_size() + ssix.box_size() + (fragments[0].len() + fragments[1].len()) as u64;
+        let outer = SidxBox {
+            version: 1,
+            reference_id: track_id,
+            timescale: 1000,
+            references: vec![
+                SidxReference {
+                    reference_type: SidxReferenceType::Index,
+                    referenced_size: indexed as u32,
+                    subsegment_duration: 1000,
+                    ..Default::default()
+                },
+                media(fragments[2]),
+            ],
+            ..Default::default()
+        };
+        let styp = StypBox {
+            major_brand: FourCC::from(*b"msdh"),
+            minor_version: 0,
+            compatible_brands: vec![FourCC::from(*b"msdh"), FourCC::from(*b"msix")],
+        };
+        let mut segment = Vec::new();
+        styp.write_box(&mut segment).unwrap();
+        outer.write_box(&mut segment).unwrap();
+        inner.write_box(&mut segment).unwrap();
+        ssix.write_box(&mut segment).unwrap();
+        let first_fragment = segment.len() as u64;
+        segment.extend(fragments.concat());
+
+        let mp4 = Mp4::read_media_segment(&init, Cursor::new(&segment[..]), segment.len() as u64)
+            .unwrap();
+        assert_eq!(mp4.styp, Some(styp));
+        assert_eq!(mp4.sidxs.len(), 2);
+        assert_eq!(mp4.ssixs, [ssix]);
+        assert_eq!(mp4.moov.mvhd.timescale, init.moov.mvhd.timescale);
+
+        let subsegments = mp4.subsegments();
+        let ranges: Vec<_> = subsegments
+            .iter()
+            .map(|subsegment| {
+                (
+                    subsegment.earliest_presentation_time,
+                    subsegment.range.clone(),
+                )
+            })
+            .collect();
+        let mut start = first_fragment;
+        let mut expected = Vec::new();
+        for (index, fragment) in fragments.iter().enumerate() {
+            let end = start + fragment.len() as u64;
+            expected.push((index as u64 * 500, start..end));
+            start = end;
+        }
+        assert_eq!(ranges, expected);
+        assert!(subsegments
+            .iter()
+            .all(|subsegment| subsegment.starts_with_sap));
+
+        let track = &mp4.tracks()[&track_id];
+        assert_eq!(track.samples.len(), 15);
+        for (id, sample) in track.samples.iter().enumerate() {
+            assert_eq!(sample.decode_timestamp, id as i64 * 100);
+            let offset = sample.offset as usize;
+            assert_eq!(segment[offset..offset + 6], [id as u8; 6]);
+        }
+
+        // without the init segment there is no movie
+        assert!(matches!(
+            Mp4::read(Cursor::new(&segment[..]), segment.len() as u64),
+            Err(Error::MissingRequiredBox {
+                box_type: BoxType::FtypBox,
+                ..
+            })
+        ));
+    }
+}
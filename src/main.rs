@@ -1,7 +1,7 @@
-use parse_mp4::Mp4;
+use parse_mp4::{Mp4, Result};
 use std::io;
 
-fn main() -> io::Result<()> {
+fn main() -> Result<()> {
     let bytes = std::fs::read("../vid1.mp4")?;
     let len = bytes.len();
     let reader = io::Cursor::new(bytes);
@@ -0,0 +1,581 @@
+use std::borrow::Cow;
+
+use crate::{DataType, FourCC};
+
+macro_rules! metadata_keys {
+    ($( $name:ident => $fourcc:expr ),*) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum MetadataKey {
+            $( $name, )*
+            /// `----` item, identified by its `mean` (reverse DNS owner) and `name`
+            Freeform { mean: String, name: String },
+            Other(FourCC),
+        }
+
+        impl MetadataKey {
+            // `----` for freeform items
+            pub fn fourcc(&self) -> FourCC {
+                match self {
+                    $( Self::$name => FourCC::from(*$fourcc), )*
+                    Self::Freeform { .. } => FourCC::from(*b"----"),
+                    Self::Other(fourcc) => *fourcc,
+                }
+            }
+        }
+
+        impl From<FourCC> for MetadataKey {
+            fn from(fourcc: FourCC) -> Self {
+                $( if fourcc == FourCC::from(*$fourcc) {
+                    return Self::$name;
+                } )*
+                Self::Other(fourcc)
+            }
+        }
+    };
+}
+
+metadata_keys! {
+    Title => b"\xa9nam",
+    Artist => b"\xa9ART",
+    AlbumArtist => b"aART",
+    Album => b"\xa9alb",
+    Grouping => b"\xa9grp",
+    Composer => b"\xa9wrt",
+    Comment => b"\xa9cmt",
+    Genre => b"\xa9gen",
+    GenreId => b"gnre",
+    StoreGenreId => b"geID",
+    Year => b"\xa9day",
+    TrackNumber => b"trkn",
+    DiskNumber => b"disk",
+    Tempo => b"tmpo",
+    Compilation => b"cpil",
+    Gapless => b"pgap",
+    Rating => b"rtng",
+    Encoder => b"\xa9too",
+    Copyright => b"cprt",
+    Lyrics => b"\xa9lyr",
+    Summary => b"desc",
+    LongDescription => b"ldes",
+    Poster => b"covr",
+    SortTitle => b"sonm",
+    SortArtist => b"soar",
+    SortAlbumArtist => b"soaa",
+    SortAlbum => b"soal",
+    SortComposer => b"soco",
+    SortShow => b"sosn",
+    TvShow => b"tvsh",
+    TvNetwork => b"tvnn",
+    TvEpisodeId => b"tven",
+    TvSeason => b"tvsn",
+    TvEpisode => b"tves",
+    MediaType => b"stik",
+    PurchaseDate => b"purd",
+    AccountId => b"apID",
+    AccountType => b"akID",
+    StoreCountry => b"sfID",
+    ContentId => b"cnID"
+}
+
+impl MetadataKey {
+    // Byte width iTunes uses for integer items, others get the smallest width that fits
+    pub(crate) fn integer_width(&self) -> Option<usize> {
+        match self {
+            Self::Compilation
+            | Self::Gapless
+            | Self::Rating
+            | Self::MediaType
+            | Self::AccountType => Some(1),
+            Self::Tempo | Self::GenreId => Some(2),
+            Self::TvSeason
+            | Self::TvEpisode
+            | Self::StoreCountry
+            | Self::StoreGenreId
+            | Self::ContentId => Some(4),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue<'a> {
+    Text(Cow<'a, str>),
+    Integer(i64),
+    Float(f64),
+    /// `trkn` and `disk`, a total of 0 means unknown
+    Pair {
+        number: u16,
+        total: u16,
+    },
+    Image {
+        data_type: DataType,
+        data: &'a [u8],
+    },
+    Binary(&'a [u8]),
+}
+
+impl<'a> MetadataValue<'a> {
+    pub fn into_text(self) -> Option<Cow<'a, str>> {
+        match self {
+            Self::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Self::Float(value) => Some(*value),
+            Self::Integer(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_pair(&self) -> Option<(u16, u16)> {
+        match self {
+            Self::Pair { number, total } => Some((*number, *total)),
+            _ => None,
+        }
+    }
+}
+
+// ID3v1 genres including the Winamp extensions, `gnre` stores the index + 1
+const GENRES: [&str; 126] = [
+    "Blues",
+    "Classic Rock",
+    "Country",
+    "Dance",
+    "Disco",
+    "Funk",
+    "Grunge",
+    "Hip-Hop",
+    "Jazz",
+    "Metal",
+    "New Age",
+    "Oldies",
+    "Other",
+    "Pop",
+    "R&B",
+    "Rap",
+    "Reggae",
+    "Rock",
+    "Techno",
+    "Industrial",
+    "Alternative",
+    "Ska",
+    "Death Metal",
+    "Pranks",
+    "Soundtrack",
+    "Euro-Techno",
+    "Ambient",
+    "Trip-Hop",
+    "Vocal",
+    "Jazz+Funk",
+    "Fusion",
+    "Trance",
+    "Classical",
+    "Instrumental",
+    "Acid",
+    "House",
+    "Game",
+    "Sound Clip",
+    "Gospel",
+    "Noise",
+    "AlternRock",
+    "Bass",
+    "Soul",
+    "Punk",
+    "Space",
+    "Meditative",
+    "Instrumental Pop",
+    "Instrumental Rock",
+    "Ethnic",
+    "Gothic",
+    "Darkwave",
+    "Techno-Industrial",
+    "Electronic",
+    "Pop-Folk",
+    "Eurodance",
+    "Dream",
+    "Southern Rock",
+    "Comedy",
+    "Cult",
+    "Gangsta",
+    "Top 40",
+    "Christian Rap",
+    "Pop/Funk",
+    "Jungle",
+    "Native American",
+    "Cabaret",
+    "New Wave",
+    "Psychadelic",
+    "Rave",
+    "Showtunes",
+    "Trailer",
+    "Lo-Fi",
+    "Tribal",
+    "Acid Punk",
+    "Acid Jazz",
+    "Polka",
+    "Retro",
+    "Musical",
+    "Rock & Roll",
+    "Hard Rock",
+    "Folk",
+    "Folk-Rock",
+    "National Folk",
+    "Swing",
+    "Fast Fusion",
+    "Bebob",
+    "Latin",
+    "Revival",
+    "Celtic",
+    "Bluegrass",
+    "Avantgarde",
+    "Gothic Rock",
+    "Progressive Rock",
+    "Psychedelic Rock",
+    "Symphonic Rock",
+    "Slow Rock",
+    "Big Band",
+    "Chorus",
+    "Easy Listening",
+    "Acoustic",
+    "Humour",
+    "Speech",
+    "Chanson",
+    "Opera",
+    "Chamber Music",
+    "Sonata",
+    "Symphony",
+    "Booty Bass",
+    "Primus",
+    "Porn Groove",
+    "Satire",
+    "Slow Jam",
+    "Club",
+    "Tango",
+    "Samba",
+    "Folklore",
+    "Ballad",
+    "Power Ballad",
+    "Rhythmic Soul",
+    "Freestyle",
+    "Duet",
+    "Punk Rock",
+    "Drum Solo",
+    "A capella",
+    "Euro-House",
+    "Dance Hall",
+];
+
+pub trait Metadata<'a> {
+    fn title(&self) -> Option<Cow<'_, str>>;
+    fn year(&self) -> Option<u32>;
+    fn poster(&self) -> Option<&[u8]>;
+    fn summary(&self) -> Option<Cow<'_, str>>;
+
+    // Every other item is looked up through here, the typed accessors below build on it
+    fn value(&self, _key: &MetadataKey) -> Option<MetadataValue<'_>> {
+        None
+    }
+
+    fn text(&self, key: &MetadataKey) -> Option<Cow<'_, str>> {
+        self.value(key)?.into_text()
+    }
+
+    fn integer(&self, key: &MetadataKey) -> Option<i64> {
+        self.value(key)?.as_integer()
+    }
+
+    fn artist(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Artist)
+    }
+
+    fn album_artist(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::AlbumArtist)
+    }
+
+    fn album(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Album)
+    }
+
+    fn grouping(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Grouping)
+    }
+
+    fn composer(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Composer)
+    }
+
+    fn comment(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Comment)
+    }
+
+    // `©gen` if present, otherwise the name of the standard `gnre` genre
+    fn genre(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Genre).or_else(|| {
+            let index = self.genre_id()?.checked_sub(1)?;
+            GENRES
+                .get(index as usize)
+                .map(|genre| Cow::Borrowed(*genre))
+        })
+    }
+
+    fn genre_id(&self) -> Option<u16> {
+        self.integer(&MetadataKey::GenreId)
+            .and_then(|id| u16::try_from(id).ok())
+    }
+
+    /// `(number, total)`, a total of 0 means unknown
+    fn track_number(&self) -> Option<(u16, u16)> {
+        self.value(&MetadataKey::TrackNumber)?.as_pair()
+    }
+
+    fn disk_number(&self) -> Option<(u16, u16)> {
+        self.value(&MetadataKey::DiskNumber)?.as_pair()
+    }
+
+    fn tempo(&self) -> Option<u16> {
+        self.integer(&MetadataKey::Tempo)
+            .and_then(|tempo| u16::try_from(tempo).ok())
+    }
+
+    fn compilation(&self) -> Option<bool> {
+        self.integer(&MetadataKey::Compilation)
+            .map(|value| value != 0)
+    }
+
+    fn gapless(&self) -> Option<bool> {
+        self.integer(&MetadataKey::Gapless).map(|value| value != 0)
+    }
+
+    /// 0 = none, 1 = explicit, 2 = clean
+    fn rating(&self) -> Option<u8> {
+        self.integer(&MetadataKey::Rating)
+            .and_then(|rating| u8::try_from(rating).ok())
+    }
+
+    fn encoder(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Encoder)
+    }
+
+    fn copyright(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Copyright)
+    }
+
+    fn lyrics(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Lyrics)
+    }
+
+    fn long_description(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::LongDescription)
+    }
+
+    fn sort_title(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::SortTitle)
+    }
+
+    fn sort_artist(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::SortArtist)
+    }
+
+    fn sort_album_artist(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::SortAlbumArtist)
+    }
+
+    fn sort_album(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::SortAlbum)
+    }
+
+    fn sort_composer(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::SortComposer)
+    }
+
+    fn sort_show(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::SortShow)
+    }
+
+    fn tv_show(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::TvShow)
+    }
+
+    fn tv_network(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::TvNetwork)
+    }
+
+    fn tv_episode_id(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::TvEpisodeId)
+    }
+
+    fn tv_season(&self) -> Option<u32> {
+        self.integer(&MetadataKey::TvSeason)
+            .and_then(|season| u32::try_from(season).ok())
+    }
+
+    fn tv_episode(&self) -> Option<u32> {
+        self.integer(&MetadataKey::TvEpisode)
+            .and_then(|episode| u32::try_from(episode).ok())
+    }
+
+    /// `stik`, e.g. 1 = music, 6 = music video, 9 = movie, 10 = TV show
+    fn media_type(&self) -> Option<u8> {
+        self.integer(&MetadataKey::MediaType)
+            .and_then(|media_type| u8::try_from(media_type).ok())
+    }
+
+    fn purchase_date(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::PurchaseDate)
+    }
+
+    fn account_id(&self) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::AccountId)
+    }
+
+    fn freeform(&self, mean: &str, name: &str) -> Option<Cow<'_, str>> {
+        self.text(&MetadataKey::Freeform {
+            mean: mean.to_owned(),
+            name: name.to_owned(),
+        })
+    }
+}
+
+impl<'a, T: Metadata<'a>> Metadata<'a> for &'a T {
+    fn title(&self) -> Option<Cow<'_, str>> {
+        (**self).title()
+    }
+
+    fn year(&self) -> Option<u32> {
+        (**self).year()
+    }
+
+    fn poster(&self) -> Option<&[u8]> {
+        (**self).poster()
+    }
+
+    fn summary(&self) -> Option<Cow<'_, str>> {
+        (**self).summary()
+    }
+
+    fn value(&self, key: &MetadataKey) -> Option<MetadataValue<'_>> {
+        (**self).value(key)
+    }
+}
+
+impl<'a, T: Metadata<'a>> Metadata<'a> for Option<T> {
+    fn title(&self) -> Option<Cow<'_, str>> {
+        self.as_ref().and_then(|t| t.title())
+    }
+
+    fn year(&self) -> Option<u32> {
+        self.as_ref().and_then(|t| t.year())
+    }
+
+    fn poster(&self) -> Option<&[u8]> {
+        self.as_ref().and_then(|t| t.poster())
+    }
+
+    fn summary(&self) -> Option<Cow<'_, str>> {
+        self.as_ref().and_then(|t| t.summary())
+    }
+
+    fn value(&self, key: &MetadataKey) -> Option<MetadataValue<'_>> {
+        self.as_ref().and_then(|t| t.value(key))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    /// Degrees, positive is north
+    pub latitude: f64,
+    /// Degrees, positive is east
+    pub longitude: f64,
+    /// Meters
+    pub altitude: Option<f64>,
+}
+
+impl Location {
+    // Parses the decimal degree form phones write, e.g. `+37.3349-122.0090+012.345/`.
+    // A trailing coordinate reference system like `CRSWGS_84/` is ignored.
+    pub fn from_iso6709(value: &str) -> Option<Self> {
+        let end = value
+            .find(|c: char| !matches!(c, '+' | '-' | '.' | '0'..='9'))
+            .unwrap_or(value.len());
+        let value = &value[..end];
+
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for (index, c) in value.char_indices().skip(1) {
+            if c == '+' || c == '-' {
+                parts.push(&value[start..index]);
+                start = index;
+            }
+        }
+        parts.push(&value[start..]);
+
+        let parse = |part: &str| part.parse::<f64>().ok();
+        let (latitude, longitude, altitude) = match parts[..] {
+            [latitude, longitude] => (parse(latitude)?, parse(longitude)?, None),
+            [latitude, longitude, altitude] => {
+                (parse(latitude)?, parse(longitude)?, Some(parse(altitude)?))
+            }
+            _ => return None,
+        };
+
+        if latitude.abs() > 90.0 || longitude.abs() > 180.0 {
+            return None;
+        }
+
+        Some(Self {
+            latitude,
+            longitude,
+            altitude,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(latitude: f64, longitude: f64, altitude: Option<f64>) -> Option<Location> {
+        Some(Location {
+            latitude,
+            longitude,
+            altitude,
+        })
+    }
+
+    #[test]
+    fn iso6709() {
+        assert_eq!(
+            Location::from_iso6709("+48.8577+002.2950/"),
+            location(48.8577, 2.295, None)
+        );
+        assert_eq!(
+            Location::from_iso6709("-33.8688+151.2093-005.000/"),
+            location(-33.8688, 151.2093, Some(-5.0))
+        );
+        assert_eq!(
+            Location::from_iso6709("+37.3349-122.0090+012.345CRSWGS_84/"),
+            location(37.3349, -122.009, Some(12.345))
+        );
+        assert_eq!(
+            Location::from_iso6709("+00.0000+000.0000"),
+            location(0.0, 0.0, None)
+        );
+
+        assert_eq!(Location::from_iso6709("+91.0000+000.0000/"), None);
+        assert_eq!(Location::from_iso6709("+45.0000+180.5000/"), None);
+        assert_eq!(Location::from_iso6709("+45.0000/"), None);
+        assert_eq!(Location::from_iso6709("+1+2+3+4/"), None);
+        assert_eq!(Location::from_iso6709("+4.5.0+2.0/"), None);
+        assert_eq!(Location::from_iso6709(""), None);
+    }
+}
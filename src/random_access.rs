@@ -0,0 +1,399 @@
+use std::{
+    io::{Read, Seek, SeekFrom},
+    ops::Range,
+    time::Duration,
+};
+
+use crate::{
+    skip_box, BoxHeader, BoxType, Error, FtypBox, MfraBox, MfroBox, MoofBox, MoovBox, Mp4,
+    ReadChild, Result, Track,
+};
+
+// Finds `mfra` through the `mfro` closing the file
+fn read_mfra<R: Read + Seek>(reader: &mut R, end: u64) -> Result<MfraBox> {
+    let Some(mfro_start) = end.checked_sub(MfroBox::SIZE) else {
+        return Err(Error::missing_box(BoxType::MfraBox, 0));
+    };
+    reader.seek(SeekFrom::Start(mfro_start))?;
+    let header = BoxHeader::read(reader)?;
+    if header.name != BoxType::MfroBox {
+        return Err(Error::missing_box(BoxType::MfraBox, 0));
+    }
+    let mfro = MfroBox::read_child(reader, &header)?;
+
+    let Some(mfra_start) = end.checked_sub(mfro.parent_size as u64) else {
+        return Err(Error::invalid_value(
+            "mfro size points before the start of the file",
+        ));
+    };
+    reader.seek(SeekFrom::Start(mfra_start))?;
+    let header = BoxHeader::read(reader)?;
+    if header.name != BoxType::MfraBox {
+        return Err(Error::invalid_value(
+            "mfro size doesn't point at an mfra box",
+        ));
+    }
+
+    MfraBox::read_child(reader, &header)
+}
+
+// Without a tfdt, the first fragment read gets its decode times from the tfra entry
+// of a sync sample in it, else they start at 0
+fn seed_from_tfra(
+    mfra: &MfraBox,
+    moof: &MoofBox,
+    traf_index: usize,
+    first_sample: usize,
+    track: &mut Track,
+) {
+    let traf = &moof.trafs[traf_index];
+    let Some(entry) = mfra
+        .tfras
+        .iter()
+        .filter(|tfra| tfra.track_id == traf.tfhd.track_id)
+        .flat_map(|tfra| &tfra.entries)
+        .find(|entry| {
+            entry.moof_offset == moof.start && entry.traf_number as usize == traf_index + 1
+        })
+    else {
+        return;
+    };
+    let run_start: usize = traf
+        .truns
+        .iter()
+        .take(entry.trun_number.saturating_sub(1) as usize)
+        .map(|trun| trun.sample_count as usize)
+        .sum();
+    let index = first_sample + run_start + entry.sample_number.saturating_sub(1) as usize;
+    let Some(sample) = track.samples.get(index) else {
+        return;
+    };
+
+    let shift = entry.time as i64 + track.presentation_offset - sample.composition_timestamp;
+    for sample in &mut track.samples[first_sample..] {
+        sample.decode_timestamp += shift;
+        sample.composition_timestamp += shift;
+    }
+}
+
+impl Mp4 {
+    // Reads `ftyp`, `moov` and the `mfra` found from the end of the file, but none
+    // of the fragments. `read_fragments` parses the ones of a time range.
+    pub fn read_lazy<R: Read + Seek>(reader: &mut R, size: u64) -> Result<Self> {
+        let start = reader.stream_position()?;
+
+        let mut ftyp = None;
+        let mut moov = None;
+
+        let mut current = start;
+        while current < size && moov.is_none() {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size {
+                return Err(Error::size_overflow(header.name, current));
+            }
+
+            if header.size == 0 {
+                break;
+            }
+
+            match header.name {
+                BoxType::FtypBox => {
+                    ftyp.replace(FtypBox::read_child(reader, &header)?);
+                }
+                BoxType::MoovBox => {
+                    moov.replace(MoovBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    skip_box(reader, header.size)?;
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        let Some(ftyp) = ftyp else {
+            return Err(Error::missing_box(BoxType::FtypBox, start));
+        };
+
+        let Some(moov) = moov else {
+            return Err(Error::missing_box(BoxType::MoovBox, start));
+        };
+
+        let mfra = read_mfra(reader, size)?;
+
+        let mut this = Self {
+            ftyp,
+            moov,
+            moofs: Vec::new(),
+            emsgs: Vec::new(),
+            styp: None,
+            sidxs: Vec::new(),
+            ssixs: Vec::new(),
+            mfra: Some(mfra),
+            tracks: Default::default(),
+        };
+        this.tracks = this.build_tracks(true);
+        this.update_tracks();
+
+        Ok(this)
+    }
+}
+
+impl<B: AsRef<[u8]>> Mp4<B> {
+    // Parses the fragments presenting `range`, starting at the one with the last sync
+    // sample at or before its start, and replaces the samples of previously read ones
+    // with theirs. Sample ids count from the first sample read.
+    pub fn read_fragments<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        size: u64,
+        range: Range<Duration>,
+    ) -> Result<()> {
+        let Some(mfra) = &self.mfra else {
+            return Err(Error::missing_box(BoxType::MfraBox, 0));
+        };
+
+        let mut first_moof: Option<u64> = None;
+        // None reads up to the end of the file
+        let mut end_moof: Option<Option<u64>> = None;
+
+        for tfra in &mfra.tfras {
+            let Some(track) = self.tracks.get(&tfra.track_id) else {
+                continue;
+            };
+            let entries = &tfra.entries;
+            if entries.is_empty() {
+                continue;
+            }
+
+            // tfra times don't include the edit list shift of the samples
+            let start = track.from_duration(range.start) - track.presentation_offset;
+            let end = track.from_duration(range.end) - track.presentation_offset;
+
+            let before = entries.partition_point(|entry| entry.time as i64 <= start);
+            let from = entries[before.saturating_sub(1)].moof_offset;
+
+            // tfra lists every sync sample, several can share a fragment
+            let after = entries.partition_point(|entry| (entry.time as i64) < end);
+            let last = entries[..after]
+                .last()
+                .map_or(from, |entry| entry.moof_offset);
+            let to = entries[after..]
+                .iter()
+                .find(|entry| entry.moof_offset > last.max(from))
+                .map(|entry| entry.moof_offset);
+
+            first_moof = Some(first_moof.map_or(from, |first| first.min(from)));
+            end_moof = Some(match (end_moof, to) {
+                (Some(Some(end)), Some(to)) => Some(end.max(to)),
+                (None, to) => to,
+                _ => None,
+            });
+        }
+
+        let Some(first_moof) = first_moof else {
+            return Err(Error::invalid_value(
+                "mfra has no random access entries for the tracks",
+            ));
+        };
+        let end = end_moof.flatten().unwrap_or(size).min(size);
+
+        let mut moofs = Vec::new();
+        let mut current = reader.seek(SeekFrom::Start(first_moof))?;
+        while current < end {
+            let header = BoxHeader::read(reader)?;
+
+            if header.size > size - current {
+                return Err(Error::size_overflow(header.name, current));
+            }
+
+            if header.size == 0 || header.name == BoxType::MfraBox {
+                break;
+            }
+
+            match header.name {
+                BoxType::MoofBox => {
+                    moofs.push(MoofBox::read_child(reader, &header)?);
+                }
+                _ => {
+                    skip_box(reader, header.size)?;
+                }
+            }
+
+            current = reader.stream_position()?;
+        }
+
+        self.moofs = moofs;
+        let mut tracks = self.build_tracks(true);
+        let mut last_run_position = 0;
+        for moof in &self.moofs {
+            // the fragments read needn't follow the ones before, restart from tfdt
+            let mut unseeded = Vec::new();
+            for (traf_index, traf) in moof.trafs.iter().enumerate() {
+                let Some(track) = tracks.get_mut(&traf.tfhd.track_id) else {
+                    continue;
+                };
+                if traf.tfdt.is_some() {
+                    track.first_traf_merged = false;
+                } else if !track.first_traf_merged {
+                    unseeded.push((traf_index, track.samples.len()));
+                }
+            }
+            Self::merge_moof(&self.moov, &mut tracks, moof, &mut last_run_position)?;
+            for (traf_index, first_sample) in unseeded {
+                let track_id = moof.trafs[traf_index].tfhd.track_id;
+                if let (Some(mfra), Some(track)) = (&self.mfra, tracks.get_mut(&track_id)) {
+                    seed_from_tfra(mfra, moof, traf_index, first_sample, track);
+                }
+            }
+        }
+        self.read_auxiliary_info(reader, &mut tracks)?;
+        self.tracks = tracks;
+        self.update_tracks();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        boxes::avc1::Avc1Box, FragmentedMp4Writer, Mp4Box, Sample, StsdBoxContent, TfraBox,
+        TfraEntry, TrackConfig, WriteBox,
+    };
+
+    // Four fragments of five 100ms video samples, the first one sync, closed by an
+    // mfra listing them. Without tfdt the fragments only carry their durations.
+    fn fragmented_file(tfdt: bool) -> Vec<u8> {
+        let mut writer = FragmentedMp4Writer::new(Cursor::new(Vec::new()));
+        let config = TrackConfig::new(1000, StsdBoxContent::Avc1(Avc1Box::default()));
+        let track_id = writer.add_track(config).unwrap();
+        writer.write_init_segment().unwrap();
+        for id in 0..20u32 {
+            let sample = Sample {
+                id,
+                is_sync: id % 5 == 0,
+                timescale: 1000,
+                decode_timestamp: id as i64 * 100,
+                composition_timestamp: id as i64 * 100,
+                duration: 100,
+                ..Default::default()
+            };
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 8])
+                .unwrap();
+            if id % 5 == 4 {
+                writer.flush_segment().unwrap();
+            }
+        }
+        let written = writer.finish().unwrap().into_inner();
+        let mp4 = Mp4::read(&mut Cursor::new(&written[..]), written.len() as u64).unwrap();
+
+        let mut file = written[..mp4.moofs[0].start as usize].to_vec();
+        let mut entries = Vec::new();
+        for (index, moof) in mp4.moofs.iter().enumerate() {
+            let mdat_end = mp4
+                .moofs
+                .get(index + 1)
+                .map_or(written.len(), |next| next.start as usize);
+            let mdat = &written[(moof.start + moof.box_size()) as usize..mdat_end];
+
+            let mut moof = moof.clone();
+            if !tfdt {
+                let traf = &mut moof.trafs[0];
+                let tfdt_size = traf.tfdt.take().unwrap().box_size() as i32;
+                let data_offset = traf.truns[0].data_offset.as_mut().unwrap();
+                *data_offset -= tfdt_size;
+            }
+            entries.push(TfraEntry {
+                time: index as u64 * 500,
+                moof_offset: file.len() as u64,
+                traf_number: 1,
+                trun_number: 1,
+                sample_number: 1,
+            });
+            moof.write_box(&mut file).unwrap();
+            file.extend_from_slice(mdat);
+        }
+
+        let mfra = MfraBox {
+            tfras: vec![TfraBox {
+                version: 1,
+                flags: 0,
+                track_id,
+                length_size_of_traf_num: 0,
+                length_size_of_trun_num: 0,
+                length_size_of_sample_num: 0,
+                entries,
+            }],
+            mfro: MfroBox {
+                version: 0,
+                flags: 0,
+                parent_size: 0,
+            },
+        };
+        mfra.write_box(&mut file).unwrap();
+        file
+    }
+
+    fn decode_times(mp4: &Mp4) -> Vec<i64> {
+        let track = mp4.tracks().values().next().unwrap();
+        track
+            .samples
+            .iter()
+            .map(|sample| sample.decode_timestamp)
+            .collect()
+    }
+
+    #[test]
+    fn read_fragments_range() {
+        let file = fragmented_file(true);
+        let size = file.len() as u64;
+        let mut reader = Cursor::new(&file[..]);
+        let mut mp4 = Mp4::read_lazy(&mut reader, size).unwrap();
+        assert!(mp4.moofs.is_empty());
+        assert_eq!(mp4.mfra.as_ref().unwrap().tfras[0].entries.len(), 4);
+
+        // only the fragment holding the range
+        let range = Duration::from_millis(600)..Duration::from_millis(900);
+        mp4.read_fragments(&mut reader, size, range).unwrap();
+        assert_eq!(mp4.moofs.len(), 1);
+        assert_eq!(decode_times(&mp4), vec![500, 600, 700, 800, 900]);
+        let track = mp4.tracks().values().next().unwrap();
+        assert!(track.samples[0].is_sync);
+        let offset = track.samples[2].offset as usize;
+        assert_eq!(file[offset..offset + 8], [7; 8]);
+
+        // from the last sync sample before the start to the end of the file
+        let range = Duration::from_millis(1200)..Duration::from_millis(1600);
+        mp4.read_fragments(&mut reader, size, range).unwrap();
+        assert_eq!(mp4.moofs.len(), 2);
+        assert_eq!(
+            decode_times(&mp4),
+            (10..20).map(|id| id * 100).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn read_fragments_without_tfdt() {
+        let file = fragmented_file(false);
+        let size = file.len() as u64;
+        let mut reader = Cursor::new(&file[..]);
+        let mut mp4 = Mp4::read_lazy(&mut reader, size).unwrap();
+
+        let range = Duration::from_millis(1100)..Duration::from_millis(1700);
+        mp4.read_fragments(&mut reader, size, range).unwrap();
+        assert!(mp4.moofs.iter().all(|moof| moof.trafs[0].tfdt.is_none()));
+        assert_eq!(
+            decode_times(&mp4),
+            (10..20).map(|id| id * 100).collect::<Vec<_>>()
+        );
+        let track = mp4.tracks().values().next().unwrap();
+        let offset = track.samples[9].offset as usize;
+        assert_eq!(file[offset..offset + 8], [19; 8]);
+    }
+}
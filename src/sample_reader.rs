@@ -0,0 +1,182 @@
+use std::io::{self, Read, Seek};
+
+use crate::{sample_table::TrackSamples, Mp4, Result, Sample, Track};
+
+// Upper bound for a single coalesced read, runs of contiguous samples larger
+// than this are split over several reads.
+const MAX_COALESCED_READ: u64 = 4 * 1024 * 1024;
+
+pub struct SampleReader<'a, R, B = Vec<u8>> {
+    reader: R,
+    samples: TrackSamples<'a, B>,
+    next: usize,
+    buffer: Vec<u8>,
+    // stream offset of `buffer[0]`
+    buffer_offset: u64,
+}
+
+impl<'a, R: Read + Seek, B> SampleReader<'a, R, B> {
+    pub fn new(reader: R, mp4: &'a Mp4<B>, track: &'a Track) -> Self {
+        Self {
+            reader,
+            samples: track.track_samples(mp4),
+            next: 0,
+            buffer: Vec::new(),
+            buffer_offset: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn buffered(&self, sample: &Sample) -> Option<&[u8]> {
+        let start = sample.offset.checked_sub(self.buffer_offset)? as usize;
+        self.buffer.get(start..start + sample.size as usize)
+    }
+
+    fn fill_buffer(&mut self, first: &Sample, from: usize) -> Result<()> {
+        let mut end = first.offset + first.size;
+
+        for index in from + 1..self.samples.len() {
+            let Some(sample) = self.samples.get(index) else {
+                break;
+            };
+            if sample.offset != end
+                || sample.offset + sample.size - first.offset > MAX_COALESCED_READ
+            {
+                break;
+            }
+            end = sample.offset + sample.size;
+        }
+
+        self.buffer.resize((end - first.offset) as usize, 0);
+        self.reader.seek(io::SeekFrom::Start(first.offset))?;
+        self.reader.read_exact(&mut self.buffer)?;
+        self.buffer_offset = first.offset;
+
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek, B> Iterator for SampleReader<'_, R, B> {
+    type Item = Result<(Sample, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.samples.get(self.next)?.into_owned();
+
+        if self.buffered(&sample).is_none() {
+            if let Err(err) = self.fill_buffer(&sample, self.next) {
+                // don't retry the same sample forever
+                self.next = self.samples.len();
+                return Some(Err(err));
+            }
+        }
+        self.next += 1;
+
+        let data = self.buffered(&sample).map(|data| data.to_vec())?;
+        Some(Ok((sample, data)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, SeekFrom};
+
+    use super::*;
+    use crate::{boxes::mp4a::Mp4aBox, Mp4Writer, StsdBoxContent, TrackConfig, TrackId};
+
+    // Counts the reads reaching the file
+    struct CountingReader<R> {
+        inner: R,
+        reads: usize,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            self.inner.read(buf)
+        }
+    }
+
+    impl<R: Seek> Seek for CountingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    fn sample(id: u32) -> Sample {
+        Sample {
+            id,
+            is_sync: true,
+            timescale: 48000,
+            decode_timestamp: id as i64 * 1024,
+            composition_timestamp: id as i64 * 1024,
+            duration: 1024,
+            ..Default::default()
+        }
+    }
+
+    // Reads every sample of `track_id`, checking each holds `payload(id)`
+    fn read_track(file: &[u8], track_id: TrackId, payload: impl Fn(u32) -> Vec<u8>) -> usize {
+        let mp4 = Mp4::read(Cursor::new(file), file.len() as u64).unwrap();
+        let track = &mp4.tracks()[&track_id];
+        let mut reader = CountingReader {
+            inner: Cursor::new(file),
+            reads: 0,
+        };
+
+        let mut count = 0;
+        for (index, read) in track.read_samples(&mp4, &mut reader).enumerate() {
+            let (sample, data) = read.unwrap();
+            assert_eq!(sample.id as usize, index);
+            assert_eq!(data, payload(index as u32));
+            count += 1;
+        }
+        assert_eq!(count, track.samples.len());
+        reader.reads
+    }
+
+    #[test]
+    fn coalesced_per_chunk() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let first = writer.add_track(config.clone()).unwrap();
+        let second = writer.add_track(config).unwrap();
+        // interleaved chunks of 3 and 2 samples
+        let payload = |track: u8, id: u32| vec![track << 4 | id as u8; 10 + id as usize];
+        for chunk in 0..3u32 {
+            for id in chunk * 3..chunk * 3 + 3 {
+                writer
+                    .write_sample(first, &sample(id), &payload(1, id))
+                    .unwrap();
+            }
+            for id in chunk * 2..chunk * 2 + 2 {
+                writer
+                    .write_sample(second, &sample(id), &payload(2, id))
+                    .unwrap();
+            }
+        }
+        let file = writer.finish().unwrap().into_inner();
+
+        assert_eq!(read_track(&file, first, |id| payload(1, id)), 3);
+        assert_eq!(read_track(&file, second, |id| payload(2, id)), 3);
+    }
+
+    #[test]
+    fn coalesced_reads_are_capped() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        let payload = |id: u32| vec![id as u8; MAX_COALESCED_READ as usize / 2];
+        for id in 0..3 {
+            writer
+                .write_sample(track_id, &sample(id), &payload(id))
+                .unwrap();
+        }
+        let file = writer.finish().unwrap().into_inner();
+
+        // a single chunk, the first two samples fill a read
+        assert_eq!(read_track(&file, track_id, payload), 2);
+    }
+}
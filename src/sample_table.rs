@@ -0,0 +1,485 @@
+use std::borrow::Cow;
+
+use crate::{Mp4, Sample, StblBox, Track, TrakBox};
+
+// Resolves the samples of a `stbl` on demand. Only the first sample of every table
+// run is kept, so the memory used stays close to the size of the tables in the file.
+// Samples come out as in `Track::samples`, except that `encryption` is never set.
+// Fragments aren't covered, their samples are always in `Track::samples`.
+#[derive(Debug, Clone)]
+pub struct SampleTable<'a, B = Vec<u8>> {
+    stbl: &'a StblBox<B>,
+    timescale: u64,
+    media_duration: u64,
+    sample_count: u64,
+    // first sample and decode time of every stts run
+    stts_starts: Vec<(u64, i64)>,
+    // first sample of every ctts run
+    ctts_starts: Vec<u64>,
+    // first sample and chunk of every stsc run
+    stsc_starts: Vec<(u64, u64)>,
+    // subtracted from decode times so no composition time comes before them
+    dts_shift: i64,
+    pub(crate) presentation_offset: i64,
+}
+
+// Position in the tables while iterating
+#[derive(Debug, Clone, Copy, Default)]
+struct Cursor {
+    stts_run: usize,
+    ctts_run: usize,
+    stsc_run: usize,
+    // chunk and offset right after the previous sample
+    next_offset: Option<(u64, u64)>,
+}
+
+fn advance<T>(starts: &[T], run: &mut usize, sample: u64, first_sample: impl Fn(&T) -> u64) {
+    while *run + 1 < starts.len() && first_sample(&starts[*run + 1]) <= sample {
+        *run += 1;
+    }
+}
+
+fn run_of<T>(starts: &[T], sample: u64, first_sample: impl Fn(&T) -> u64) -> usize {
+    starts
+        .partition_point(|start| first_sample(start) <= sample)
+        .saturating_sub(1)
+}
+
+impl<'a, B> SampleTable<'a, B> {
+    pub(crate) fn new(trak: &'a TrakBox<B>) -> Self {
+        let stbl = &trak.mdia.minf.stbl;
+        let sample_count = stbl.stsz.sample_count as u64;
+
+        let mut stts_starts = Vec::with_capacity(stbl.stts.entries.len());
+        let (mut sample, mut decode_timestamp) = (0u64, 0i64);
+        for entry in &stbl.stts.entries {
+            stts_starts.push((sample, decode_timestamp));
+            sample += entry.sample_count as u64;
+            decode_timestamp += entry.sample_count as i64 * entry.sample_delta as i64;
+        }
+
+        let mut ctts_starts = Vec::new();
+        let mut dts_shift = 0;
+        if let Some(ctts) = &stbl.ctts {
+            ctts_starts.reserve(ctts.entries.len());
+            let mut sample = 0u64;
+            for entry in &ctts.entries {
+                // only runs holding samples count towards the shift
+                if sample < sample_count {
+                    dts_shift = dts_shift.max(-(entry.sample_offset as i64));
+                }
+                ctts_starts.push(sample);
+                sample += entry.sample_count as u64;
+            }
+        }
+
+        let mut stsc_starts = Vec::with_capacity(stbl.stsc.entries.len());
+        let mut sample = 0u64;
+        for (index, entry) in stbl.stsc.entries.iter().enumerate() {
+            // the first run starts at chunk 1, whatever it says
+            let first_chunk = match index {
+                0 => 1,
+                _ => entry.first_chunk as u64,
+            };
+            if let Some(&(_, previous_chunk)) = stsc_starts.last() {
+                let previous = &stbl.stsc.entries[index - 1];
+                sample +=
+                    first_chunk.saturating_sub(previous_chunk) * previous.samples_per_chunk as u64;
+            }
+            stsc_starts.push((sample, first_chunk));
+        }
+
+        Self {
+            stbl,
+            timescale: trak.mdia.mdhd.timescale as u64,
+            media_duration: trak.mdia.mdhd.duration,
+            sample_count,
+            stts_starts,
+            ctts_starts,
+            stsc_starts,
+            dts_shift,
+            presentation_offset: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sample_count as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sample_count == 0
+    }
+
+    pub fn get(&self, sample_id: u32) -> Option<Sample> {
+        let sample = sample_id as u64;
+        if sample >= self.sample_count {
+            return None;
+        }
+
+        let mut cursor = Cursor {
+            stts_run: run_of(&self.stts_starts, sample, |start| start.0),
+            ctts_run: run_of(&self.ctts_starts, sample, |start| *start),
+            stsc_run: run_of(&self.stsc_starts, sample, |start| start.0),
+            next_offset: None,
+        };
+        Some(self.resolve(sample, &mut cursor))
+    }
+
+    pub fn iter(&self) -> SampleTableIter<'_, 'a, B> {
+        SampleTableIter {
+            table: self,
+            sample: 0,
+            cursor: Cursor::default(),
+        }
+    }
+
+    fn sample_size(&self, sample: u64) -> u64 {
+        match self.stbl.stsz.sample_size {
+            0 => self
+                .stbl
+                .stsz
+                .sample_sizes
+                .get(sample as usize)
+                .copied()
+                .unwrap_or(0) as u64,
+            size => size as u64,
+        }
+    }
+
+    fn chunk_offset(&self, chunk: u64) -> u64 {
+        let index = chunk as usize - 1;
+        if let Some(stco) = &self.stbl.stco {
+            stco.entries.get(index).copied().unwrap_or(0) as u64
+        } else if let Some(co64) = &self.stbl.co64 {
+            co64.entries.get(index).copied().unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    // Decode time before the shift, and the stts delta of the sample
+    fn decode_time(&self, sample: u64, run: usize) -> (i64, u64) {
+        let (Some(&(first_sample, first_time)), Some(entry)) =
+            (self.stts_starts.get(run), self.stbl.stts.entries.get(run))
+        else {
+            return (0, 0);
+        };
+        let delta = entry.sample_delta as u64;
+        (first_time + ((sample - first_sample) * delta) as i64, delta)
+    }
+
+    fn composition_offset(&self, run: usize) -> i64 {
+        self.stbl
+            .ctts
+            .as_ref()
+            .and_then(|ctts| ctts.entries.get(run))
+            .map_or(0, |entry| entry.sample_offset as i64)
+    }
+
+    // The smallest composition time before any offset is applied
+    //
+    // This is typically 0, but in the presence of sample reordering (caused by AVC/HVC
+    // b-frames), it may be non-zero.
+    pub(crate) fn min_composition_timestamp(&self) -> i64 {
+        // decode times only grow within a run, so the minimum is at the start of one
+        let mut starts: Vec<u64> = std::iter::once(0)
+            .chain(self.stts_starts.iter().map(|start| start.0))
+            .chain(self.ctts_starts.iter().copied())
+            .filter(|&sample| sample < self.sample_count)
+            .collect();
+        starts.sort_unstable();
+        starts.dedup();
+
+        starts
+            .into_iter()
+            .map(|sample| {
+                let stts_run = run_of(&self.stts_starts, sample, |start| start.0);
+                let ctts_run = run_of(&self.ctts_starts, sample, |start| *start);
+                self.decode_time(sample, stts_run).0 + self.composition_offset(ctts_run)
+            })
+            .min()
+            .unwrap_or(i64::MAX)
+    }
+
+    // Largest composition time minus decode time of all samples
+    pub(crate) fn max_composition_offset(&self) -> i64 {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let max_offset = match &self.stbl.ctts {
+            Some(ctts) => ctts
+                .entries
+                .iter()
+                .zip(&self.ctts_starts)
+                .filter(|(_, &first_sample)| first_sample < self.sample_count)
+                .map(|(entry, _)| entry.sample_offset as i64)
+                .max()
+                .unwrap_or(0),
+            None => 0,
+        };
+        (max_offset + self.dts_shift).max(0)
+    }
+
+    fn resolve(&self, sample: u64, cursor: &mut Cursor) -> Sample {
+        let (raw_decode_timestamp, delta) = self.decode_time(sample, cursor.stts_run);
+        let duration = if sample + 1 == self.sample_count {
+            self.media_duration
+                .saturating_sub(raw_decode_timestamp as u64)
+        } else {
+            delta
+        };
+        let composition_timestamp = raw_decode_timestamp + self.composition_offset(cursor.ctts_run);
+
+        let (first_sample, first_chunk) = self
+            .stsc_starts
+            .get(cursor.stsc_run)
+            .copied()
+            .unwrap_or((0, 1));
+        let entry = self.stbl.stsc.entries.get(cursor.stsc_run);
+        let samples_per_chunk = entry.map_or(0, |entry| entry.samples_per_chunk as u64);
+        let (chunk, index_in_chunk) = match samples_per_chunk {
+            0 => (first_chunk, 0),
+            _ => (
+                first_chunk + (sample - first_sample) / samples_per_chunk,
+                (sample - first_sample) % samples_per_chunk,
+            ),
+        };
+
+        let size = self.sample_size(sample);
+        let offset = match cursor.next_offset {
+            Some((next_chunk, offset)) if next_chunk == chunk && index_in_chunk > 0 => offset,
+            _ => {
+                let before: u64 = (sample - index_in_chunk..sample)
+                    .map(|sample| self.sample_size(sample))
+                    .sum();
+                self.chunk_offset(chunk) + before
+            }
+        };
+        cursor.next_offset = Some((chunk, offset + size));
+
+        let is_sync = match &self.stbl.stss {
+            Some(stss) => stss.entries.binary_search(&(sample as u32 + 1)).is_ok(),
+            None => true,
+        };
+
+        Sample {
+            id: sample as u32,
+            is_sync,
+            size,
+            offset,
+            timescale: self.timescale,
+            decode_timestamp: raw_decode_timestamp - self.dts_shift + self.presentation_offset,
+            composition_timestamp: composition_timestamp + self.presentation_offset,
+            duration,
+            sample_description_index: entry.map_or(1, |entry| entry.sample_description_index),
+            encryption: None,
+        }
+    }
+}
+
+// Walks the tables with a cursor per table, in constant time per sample
+pub struct SampleTableIter<'t, 'a, B> {
+    table: &'t SampleTable<'a, B>,
+    sample: u64,
+    cursor: Cursor,
+}
+
+impl<B> Iterator for SampleTableIter<'_, '_, B> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        let table = self.table;
+        if self.sample >= table.sample_count {
+            return None;
+        }
+
+        let sample = self.sample;
+        advance(
+            &table.stts_starts,
+            &mut self.cursor.stts_run,
+            sample,
+            |start| start.0,
+        );
+        advance(
+            &table.ctts_starts,
+            &mut self.cursor.ctts_run,
+            sample,
+            |start| *start,
+        );
+        advance(
+            &table.stsc_starts,
+            &mut self.cursor.stsc_run,
+            sample,
+            |start| start.0,
+        );
+        self.sample += 1;
+
+        Some(table.resolve(sample, &mut self.cursor))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.table.sample_count - self.sample) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<B> ExactSizeIterator for SampleTableIter<'_, '_, B> {}
+
+impl<'t, 'a, B> IntoIterator for &'t SampleTable<'a, B> {
+    type Item = Sample;
+    type IntoIter = SampleTableIter<'t, 'a, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Track {
+    // The samples of the track's `stbl`, resolved on demand. Building the view walks
+    // the runs of the tables once, keep it around rather than building it per sample.
+    pub fn sample_table<'a, B>(&self, mp4: &'a Mp4<B>) -> SampleTable<'a, B> {
+        let mut table = SampleTable::new(self.trak(mp4));
+        table.presentation_offset = self.presentation_offset;
+        table
+    }
+}
+
+// The samples of a track however it was read, the ones `Mp4::read_compact` left in the
+// sample tables come from `SampleTable`, the others from `Track::samples`
+#[derive(Debug, Clone)]
+pub(crate) struct TrackSamples<'a, B> {
+    table: Option<SampleTable<'a, B>>,
+    table_samples: usize,
+    samples: &'a [Sample],
+}
+
+impl<'a, B> TrackSamples<'a, B> {
+    pub(crate) fn len(&self) -> usize {
+        self.table_samples + self.samples.len()
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<Cow<'a, Sample>> {
+        match index.checked_sub(self.table_samples) {
+            Some(index) => self.samples.get(index).map(Cow::Borrowed),
+            None => self.table.as_ref()?.get(index as u32).map(Cow::Owned),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = Cow<'a, Sample>> + '_ {
+        let table_samples = self.table.iter().flat_map(|table| table.iter());
+        table_samples
+            .take(self.table_samples)
+            .map(Cow::Owned)
+            .chain(self.samples.iter().map(Cow::Borrowed))
+    }
+}
+
+impl Track {
+    pub(crate) fn track_samples<'a, B>(&'a self, mp4: &'a Mp4<B>) -> TrackSamples<'a, B> {
+        TrackSamples {
+            table: (self.table_samples > 0).then(|| self.sample_table(mp4)),
+            table_samples: self.table_samples as usize,
+            samples: &self.samples,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Cursor, time::Duration};
+
+    use super::*;
+    use crate::{
+        boxes::mp4a::Mp4aBox, Chapter, Edit, Mp4Writer, Rounding, StsdBoxContent, TrackConfig,
+    };
+
+    #[test]
+    fn compact_matches_expanded() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(1000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        let edits = vec![Edit {
+            presentation_time: 0,
+            duration: 0,
+            media_time: Some(100),
+            media_rate: 1,
+        }];
+        writer.set_edits(track_id, edits).unwrap();
+        // I P B B pattern, shown 0 3 1 2
+        let offsets = [100, 300, 0, 0];
+        for id in 0..12u32 {
+            let sample = Sample {
+                id,
+                is_sync: id % 4 == 0,
+                timescale: 1000,
+                decode_timestamp: id as i64 * 100,
+                composition_timestamp: id as i64 * 100 + offsets[id as usize % 4],
+                duration: 100,
+                ..Default::default()
+            };
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 8])
+                .unwrap();
+        }
+        writer.set_chapters(vec![
+            Chapter {
+                start: Duration::ZERO,
+                title: String::from("one"),
+            },
+            Chapter {
+                start: Duration::from_millis(600),
+                title: String::from("two"),
+            },
+        ]);
+        let file = writer.finish().unwrap().into_inner();
+
+        let mut reader = Cursor::new(&file[..]);
+        let expanded = Mp4::read(&mut reader, file.len() as u64).unwrap();
+        let compact = Mp4::read_compact(Cursor::new(&file[..]), file.len() as u64).unwrap();
+        let expanded_track = &expanded.tracks()[&track_id];
+        let compact_track = &compact.tracks()[&track_id];
+        assert!(compact_track.samples.is_empty());
+
+        for pts in (-50..1400).step_by(50) {
+            for rounding in [Rounding::Down, Rounding::Up, Rounding::Nearest] {
+                assert_eq!(
+                    compact_track
+                        .sample_at_time(&compact, pts, rounding)
+                        .map(|sample| sample.id),
+                    expanded_track
+                        .sample_at_time(&expanded, pts, rounding)
+                        .map(|sample| sample.id),
+                );
+            }
+            assert_eq!(
+                compact_track
+                    .keyframe_before(&compact, pts)
+                    .map(|sample| sample.id),
+                expanded_track
+                    .keyframe_before(&expanded, pts)
+                    .map(|sample| sample.id),
+            );
+        }
+        assert_eq!(
+            compact_track.presentation_timeline(&compact),
+            expanded_track.presentation_timeline(&expanded),
+        );
+        assert_eq!(expanded_track.presentation_timeline(&expanded).len(), 11);
+
+        let read = |mp4, track: &Track| {
+            track
+                .read_samples(mp4, Cursor::new(&file[..]))
+                .map(|sample| sample.map(|(sample, data)| (sample.id, data)))
+                .collect::<crate::Result<Vec<_>>>()
+                .unwrap()
+        };
+        let samples = read(&expanded, expanded_track);
+        assert_eq!(read(&compact, compact_track), samples);
+        assert_eq!(samples.len(), 12);
+
+        let chapters = compact.chapters(&mut reader).unwrap();
+        assert_eq!(chapters, expanded.chapters(&mut reader).unwrap());
+        assert_eq!(chapters.len(), 2);
+    }
+}
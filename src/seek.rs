@@ -0,0 +1,228 @@
+use std::{borrow::Cow, time::Duration};
+
+use crate::{sample_table::TrackSamples, Mp4, Sample, Track};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Last sample presented at or before the timestamp
+    Down,
+    /// First sample presented at or after the timestamp
+    Up,
+    /// Whichever of the two is closer, ties go down
+    Nearest,
+}
+
+pub fn rescale_time(value: i64, from_timescale: u64, to_timescale: u64) -> i64 {
+    if from_timescale == 0 {
+        return 0;
+    }
+    (value as i128 * to_timescale as i128 / from_timescale as i128) as i64
+}
+
+impl<B> Mp4<B> {
+    pub fn movie_time_to_duration(&self, timestamp: i64) -> Duration {
+        let nanos = rescale_time(
+            timestamp.max(0),
+            self.moov.mvhd.timescale as u64,
+            1_000_000_000,
+        );
+        Duration::from_nanos(nanos as u64)
+    }
+
+    pub fn duration_to_movie_time(&self, duration: Duration) -> i64 {
+        let nanos = duration.as_nanos().min(i64::MAX as u128) as i64;
+        rescale_time(nanos, 1_000_000_000, self.moov.mvhd.timescale as u64)
+    }
+}
+
+impl Track {
+    pub fn to_duration(&self, timestamp: i64) -> Duration {
+        let nanos = rescale_time(timestamp.max(0), self.time_scale, 1_000_000_000);
+        Duration::from_nanos(nanos as u64)
+    }
+
+    pub fn from_duration(&self, duration: Duration) -> i64 {
+        let nanos = duration.as_nanos().min(i64::MAX as u128) as i64;
+        rescale_time(nanos, 1_000_000_000, self.time_scale)
+    }
+
+    pub fn to_movie_time(&self, timestamp: i64, movie_timescale: u32) -> i64 {
+        rescale_time(timestamp, self.time_scale, movie_timescale as u64)
+    }
+
+    pub fn from_movie_time(&self, timestamp: i64, movie_timescale: u32) -> i64 {
+        rescale_time(timestamp, movie_timescale as u64, self.time_scale)
+    }
+
+    // Samples are stored in decode order, so composition timestamps are only sorted
+    // up to the reorder window. Decode timestamps are binary searched and the scan
+    // around that point is bounded by the largest composition offset of the track.
+    pub fn sample_at_time<'a, B>(
+        &'a self,
+        mp4: &'a Mp4<B>,
+        pts: i64,
+        rounding: Rounding,
+    ) -> Option<Cow<'a, Sample>> {
+        let samples = self.track_samples(mp4);
+        let pts_of = |index| {
+            samples
+                .get(index)
+                .map(|sample| sample.composition_timestamp)
+        };
+        let index = match rounding {
+            Rounding::Down => self.sample_index_down(&samples, pts),
+            Rounding::Up => self.sample_index_up(&samples, pts),
+            Rounding::Nearest => match (
+                self.sample_index_down(&samples, pts),
+                self.sample_index_up(&samples, pts),
+            ) {
+                (Some(down), Some(up)) => {
+                    let down_distance = pts - pts_of(down)?;
+                    let up_distance = pts_of(up)? - pts;
+                    Some(if up_distance < down_distance {
+                        up
+                    } else {
+                        down
+                    })
+                }
+                (down, up) => down.or(up),
+            },
+        }?;
+
+        samples.get(index)
+    }
+
+    // The sync sample decoding has to start from to present the frame at `pts`.
+    pub fn keyframe_before<'a, B>(&'a self, mp4: &'a Mp4<B>, pts: i64) -> Option<Cow<'a, Sample>> {
+        let samples = self.track_samples(mp4);
+        let target = self.sample_index_down(&samples, pts)?;
+        (0..=target)
+            .rev()
+            .filter_map(|index| samples.get(index))
+            .find(|sample| sample.is_sync)
+    }
+
+    fn sample_index_down<B>(&self, samples: &TrackSamples<'_, B>, pts: i64) -> Option<usize> {
+        let end = partition_point(samples, |sample| sample.decode_timestamp <= pts);
+
+        let mut best: Option<(usize, i64)> = None;
+        for index in (0..end).rev() {
+            let sample = samples.get(index)?;
+            if let Some((_, best_pts)) = best {
+                if sample.decode_timestamp + self.max_composition_offset < best_pts {
+                    break;
+                }
+            }
+
+            if sample.composition_timestamp <= pts
+                && best.is_none_or(|(_, best_pts)| sample.composition_timestamp > best_pts)
+            {
+                best = Some((index, sample.composition_timestamp));
+            }
+        }
+
+        best.map(|(index, _)| index)
+    }
+
+    fn sample_index_up<B>(&self, samples: &TrackSamples<'_, B>, pts: i64) -> Option<usize> {
+        let start = partition_point(samples, |sample| {
+            sample.decode_timestamp + self.max_composition_offset < pts
+        });
+
+        let mut best: Option<(usize, i64)> = None;
+        for index in start..samples.len() {
+            let sample = samples.get(index)?;
+            if let Some((_, best_pts)) = best {
+                if sample.decode_timestamp > best_pts {
+                    break;
+                }
+            }
+
+            if sample.composition_timestamp >= pts
+                && best.is_none_or(|(_, best_pts)| sample.composition_timestamp < best_pts)
+            {
+                best = Some((index, sample.composition_timestamp));
+            }
+        }
+
+        best.map(|(index, _)| index)
+    }
+}
+
+// `slice::partition_point` over samples that may have to be resolved from the tables
+fn partition_point<B>(samples: &TrackSamples<'_, B>, pred: impl Fn(&Sample) -> bool) -> usize {
+    let (mut low, mut high) = (0, samples.len());
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match samples.get(middle) {
+            Some(sample) if pred(&sample) => low = middle + 1,
+            _ => high = middle,
+        }
+    }
+    low
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{boxes::avc1::Avc1Box, Mp4Writer, StsdBoxContent, TrackConfig};
+
+    #[test]
+    fn reordered_samples() {
+        // GOPs of I P B B in decode order, presented as I B B P
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(1000, StsdBoxContent::Avc1(Avc1Box::default()));
+        let track_id = writer.add_track(config).unwrap();
+        for id in 0..12u32 {
+            let decode_timestamp = id as i64 * 100;
+            let offset = [100, 300, 0, 0][id as usize % 4];
+            let sample = Sample {
+                id,
+                is_sync: id % 4 == 0,
+                timescale: 1000,
+                decode_timestamp,
+                composition_timestamp: decode_timestamp + offset,
+                duration: 100,
+                ..Default::default()
+            };
+            writer.write_sample(track_id, &sample, &[0; 4]).unwrap();
+        }
+        let file = writer.finish().unwrap().into_inner();
+
+        let mp4 = Mp4::read(Cursor::new(&file[..]), file.len() as u64).unwrap();
+        let track = &mp4.tracks()[&track_id];
+        // without edits the first frame is presented at 0
+        assert_eq!(track.samples[0].decode_timestamp, -100);
+        assert_eq!(track.samples[0].composition_timestamp, 0);
+
+        let at = |pts, rounding| {
+            track
+                .sample_at_time(&mp4, pts, rounding)
+                .map(|sample| sample.id)
+        };
+        assert_eq!(at(450, Rounding::Down), Some(4));
+        assert_eq!(at(450, Rounding::Up), Some(6));
+        assert_eq!(at(450, Rounding::Nearest), Some(4));
+        assert_eq!(at(480, Rounding::Nearest), Some(6));
+        assert_eq!(at(650, Rounding::Down), Some(7));
+        assert_eq!(at(650, Rounding::Up), Some(5));
+        assert_eq!(at(700, Rounding::Down), Some(5));
+        assert_eq!(at(-1, Rounding::Down), None);
+        assert_eq!(at(-1, Rounding::Up), Some(0));
+        assert_eq!(at(1101, Rounding::Up), None);
+        assert_eq!(at(5000, Rounding::Nearest), Some(9));
+
+        let keyframe = |pts| track.keyframe_before(&mp4, pts).map(|sample| sample.id);
+        assert_eq!(keyframe(399), Some(0));
+        assert_eq!(keyframe(400), Some(4));
+        assert_eq!(keyframe(1150), Some(8));
+        assert_eq!(keyframe(-1), None);
+
+        let pts = track.from_duration(Duration::from_millis(650));
+        assert_eq!(pts, 650);
+        assert_eq!(track.to_duration(pts), Duration::from_millis(650));
+        assert_eq!(track.to_movie_time(pts, 90000), 58500);
+    }
+}
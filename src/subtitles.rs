@@ -0,0 +1,1086 @@
+use std::{
+    io::{Read, Seek},
+    ops::Range,
+    time::Duration,
+};
+
+use crate::{Error, Mp4, Result, RgbaColor, Sample, StsdBoxContent, Track, Tx3gBox};
+
+const FACE_BOLD: u8 = 0x01;
+const FACE_ITALIC: u8 = 0x02;
+const FACE_UNDERLINE: u8 = 0x04;
+
+// Style of a character range of a cue, from a 3GPP `styl` record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextStyle {
+    pub start_char: u16,
+    pub end_char: u16,
+    pub font_id: u16,
+    /// Bold = 1, italic = 2, underline = 4
+    pub face_style_flags: u8,
+    pub font_size: u8,
+    pub text_color: RgbaColor,
+}
+
+impl TextStyle {
+    // Font, size and colour of the default `Tx3gBox` style record
+    pub fn new(chars: Range<u16>, face_style_flags: u8) -> Self {
+        Self {
+            start_char: chars.start,
+            end_char: chars.end,
+            font_id: 1,
+            face_style_flags,
+            font_size: 16,
+            text_color: RgbaColor {
+                red: 255,
+                green: 255,
+                blue: 255,
+                alpha: 255,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KaraokeEntry {
+    /// Relative to the start of the cue, in the track timescale
+    pub end_time: u32,
+    pub start_char: u16,
+    pub end_char: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Karaoke {
+    pub start_time: u32,
+    pub entries: Vec<KaraokeEntry>,
+}
+
+// A decoded timed text sample. Character offsets count characters, not bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Cue {
+    pub start: Duration,
+    pub end: Duration,
+    pub text: String,
+    pub styles: Vec<TextStyle>,
+    /// Character range from `hlit`
+    pub highlight: Option<Range<u16>>,
+    pub karaoke: Option<Karaoke>,
+    /// `tbox` override of the sample entry's `box_record`: top, left, bottom, right
+    pub text_box: Option<[i16; 4]>,
+    /// WebVTT cue identifier
+    pub id: Option<String>,
+    /// WebVTT cue settings, e.g. `line:0 align:start`
+    pub settings: Option<String>,
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn parse_styles(body: &[u8]) -> Option<Vec<TextStyle>> {
+    let count = read_u16(body, 0)? as usize;
+    let records = body.get(2..)?.chunks_exact(12).take(count);
+
+    Some(
+        records
+            .map(|record| TextStyle {
+                start_char: u16::from_be_bytes([record[0], record[1]]),
+                end_char: u16::from_be_bytes([record[2], record[3]]),
+                font_id: u16::from_be_bytes([record[4], record[5]]),
+                face_style_flags: record[6],
+                font_size: record[7],
+                text_color: RgbaColor {
+                    red: record[8],
+                    green: record[9],
+                    blue: record[10],
+                    alpha: record[11],
+                },
+            })
+            .collect(),
+    )
+}
+
+fn parse_karaoke(body: &[u8]) -> Option<Karaoke> {
+    let start_time = read_u32(body, 0)?;
+    let count = read_u16(body, 4)? as usize;
+    let entries = body
+        .get(6..)?
+        .chunks_exact(8)
+        .take(count)
+        .map(|entry| KaraokeEntry {
+            end_time: u32::from_be_bytes([entry[0], entry[1], entry[2], entry[3]]),
+            start_char: u16::from_be_bytes([entry[4], entry[5]]),
+            end_char: u16::from_be_bytes([entry[6], entry[7]]),
+        })
+        .collect();
+
+    Some(Karaoke {
+        start_time,
+        entries,
+    })
+}
+
+// The (type, body) pairs of a run of boxes, stopping at the first malformed one
+fn child_boxes(mut data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        let size = read_u32(data, 0)? as usize;
+        if size < 8 || size > data.len() {
+            return None;
+        }
+        let (name, body) = (&data[4..8], &data[8..size]);
+        data = &data[size..];
+        Some((name, body))
+    })
+}
+
+fn write_modifier(data: &mut Vec<u8>, name: &[u8; 4], body: &[u8]) {
+    data.extend((8 + body.len() as u32).to_be_bytes());
+    data.extend(name);
+    data.extend(body);
+}
+
+impl Cue {
+    // Decodes a 3GPP timed text sample, unknown modifier boxes (`hclr`, `dlay`,
+    // `href`, `blnk`, `twrp`) are skipped
+    pub fn from_sample(start: Duration, end: Duration, data: &[u8]) -> Self {
+        let mut cue = Self {
+            start,
+            end,
+            text: Tx3gBox::sample_text(data).into_owned(),
+            ..Default::default()
+        };
+
+        let text_len = read_u16(data, 0).unwrap_or_default() as usize;
+        let modifiers = data.get(2 + text_len..).unwrap_or_default();
+
+        for (name, body) in child_boxes(modifiers) {
+            match name {
+                b"styl" => cue.styles = parse_styles(body).unwrap_or_default(),
+                b"hlit" => {
+                    cue.highlight = read_u16(body, 0)
+                        .zip(read_u16(body, 2))
+                        .map(|(start, end)| start..end)
+                }
+                b"krok" => cue.karaoke = parse_karaoke(body),
+                b"tbox" => {
+                    if let [a, b, c, d, e, f, g, h, ..] = *body {
+                        cue.text_box = Some([
+                            i16::from_be_bytes([a, b]),
+                            i16::from_be_bytes([c, d]),
+                            i16::from_be_bytes([e, f]),
+                            i16::from_be_bytes([g, h]),
+                        ]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        cue
+    }
+
+    // A `wvtt` sample has a `vttc` box for each cue active during the whole sample,
+    // or a single `vtte` when there are none. Cues spanning several samples are
+    // repeated in each of them.
+    pub fn from_wvtt_sample(start: Duration, end: Duration, data: &[u8]) -> Vec<Self> {
+        child_boxes(data)
+            .filter(|(name, _)| *name == b"vttc")
+            .map(|(_, body)| {
+                let mut cue = Self {
+                    start,
+                    end,
+                    ..Default::default()
+                };
+
+                for (name, body) in child_boxes(body) {
+                    let value = String::from_utf8_lossy(body);
+                    match name {
+                        b"iden" => cue.id = Some(value.into_owned()),
+                        b"sttg" => cue.settings = Some(value.into_owned()),
+                        b"payl" => (cue.text, cue.styles) = parse_markup(&value, true),
+                        _ => {}
+                    }
+                }
+
+                cue
+            })
+            .collect()
+    }
+
+    pub fn to_sample(&self) -> Vec<u8> {
+        let mut data = Tx3gBox::encode_sample(&self.text);
+
+        if !self.styles.is_empty() {
+            let mut body = Vec::with_capacity(2 + 12 * self.styles.len());
+            body.extend((self.styles.len() as u16).to_be_bytes());
+            for style in &self.styles {
+                body.extend(style.start_char.to_be_bytes());
+                body.extend(style.end_char.to_be_bytes());
+                body.extend(style.font_id.to_be_bytes());
+                body.extend([style.face_style_flags, style.font_size]);
+                let color = &style.text_color;
+                body.extend([color.red, color.green, color.blue, color.alpha]);
+            }
+            write_modifier(&mut data, b"styl", &body);
+        }
+
+        if let Some(highlight) = &self.highlight {
+            let mut body = highlight.start.to_be_bytes().to_vec();
+            body.extend(highlight.end.to_be_bytes());
+            write_modifier(&mut data, b"hlit", &body);
+        }
+
+        if let Some(karaoke) = &self.karaoke {
+            let mut body = karaoke.start_time.to_be_bytes().to_vec();
+            body.extend((karaoke.entries.len() as u16).to_be_bytes());
+            for entry in &karaoke.entries {
+                body.extend(entry.end_time.to_be_bytes());
+                body.extend(entry.start_char.to_be_bytes());
+                body.extend(entry.end_char.to_be_bytes());
+            }
+            write_modifier(&mut data, b"krok", &body);
+        }
+
+        if let Some(text_box) = self.text_box {
+            let body: Vec<u8> = text_box.iter().flat_map(|n| n.to_be_bytes()).collect();
+            write_modifier(&mut data, b"tbox", &body);
+        }
+
+        data
+    }
+
+    // Face style of every character, `styl` ranges are end exclusive
+    fn char_flags(&self) -> Vec<u8> {
+        let mut flags = vec![0u8; self.text.chars().count()];
+        for style in &self.styles {
+            let end = (style.end_char as usize).min(flags.len());
+            for flag in flags.iter_mut().take(end).skip(style.start_char as usize) {
+                *flag |= style.face_style_flags;
+            }
+        }
+        flags
+    }
+
+    // The text with bold, italic and underline as `<b>`, `<i>` and `<u>` tags
+    fn markup(&self, escape: bool) -> String {
+        let flags = self.char_flags();
+        let mut out = String::with_capacity(self.text.len());
+        let mut current = 0u8;
+
+        for (c, flag) in self.text.chars().zip(flags.iter().copied()) {
+            if flag != current {
+                close_tags(&mut out, current);
+                open_tags(&mut out, flag);
+                current = flag;
+            }
+
+            match c {
+                '&' if escape => out.push_str("&amp;"),
+                '<' if escape => out.push_str("&lt;"),
+                '>' if escape => out.push_str("&gt;"),
+                c => out.push(c),
+            }
+        }
+        close_tags(&mut out, current);
+
+        out
+    }
+}
+
+fn open_tags(out: &mut String, flags: u8) {
+    for (flag, tag) in [
+        (FACE_BOLD, "<b>"),
+        (FACE_ITALIC, "<i>"),
+        (FACE_UNDERLINE, "<u>"),
+    ] {
+        if flags & flag != 0 {
+            out.push_str(tag);
+        }
+    }
+}
+
+fn close_tags(out: &mut String, flags: u8) {
+    for (flag, tag) in [
+        (FACE_UNDERLINE, "</u>"),
+        (FACE_ITALIC, "</i>"),
+        (FACE_BOLD, "</b>"),
+    ] {
+        if flags & flag != 0 {
+            out.push_str(tag);
+        }
+    }
+}
+
+// Strips tags from cue text, keeping `<b>`, `<i>` and `<u>` as styles. WebVTT text
+// also has its character references decoded.
+fn parse_markup(input: &str, unescape: bool) -> (String, Vec<TextStyle>) {
+    let mut text = String::with_capacity(input.len());
+    let mut flags = Vec::new();
+    let mut depth = [0u32; 3];
+    let mut rest = input;
+
+    let current = |depth: &[u32; 3]| {
+        [FACE_BOLD, FACE_ITALIC, FACE_UNDERLINE]
+            .into_iter()
+            .zip(*depth)
+            .filter(|(_, depth)| *depth > 0)
+            .fold(0, |flags, (flag, _)| flags | flag)
+    };
+
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                let tag = &rest[1..end];
+                let (closing, tag) = match tag.strip_prefix('/') {
+                    Some(tag) => (true, tag),
+                    None => (false, tag),
+                };
+                let name = tag
+                    .split(|c: char| c == '.' || c.is_whitespace())
+                    .next()
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+                let slot = match name.as_str() {
+                    "b" => Some(0),
+                    "i" => Some(1),
+                    "u" => Some(2),
+                    _ => None,
+                };
+                if let Some(slot) = slot {
+                    depth[slot] = if closing {
+                        depth[slot].saturating_sub(1)
+                    } else {
+                        depth[slot] + 1
+                    };
+                }
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+
+        // SubStation overrides like `{\an8}` that end up in SRT files
+        if c == '{' && rest[1..].starts_with('\\') {
+            if let Some(end) = rest.find('}') {
+                rest = &rest[end + 1..];
+                continue;
+            }
+        }
+
+        if c == '&' && unescape {
+            let entity = [
+                ("&amp;", '&'),
+                ("&lt;", '<'),
+                ("&gt;", '>'),
+                ("&quot;", '"'),
+                ("&nbsp;", '\u{a0}'),
+                ("&lrm;", '\u{200e}'),
+                ("&rlm;", '\u{200f}'),
+            ]
+            .into_iter()
+            .find(|(entity, _)| rest.starts_with(entity));
+            if let Some((entity, c)) = entity {
+                text.push(c);
+                flags.push(current(&depth));
+                rest = &rest[entity.len()..];
+                continue;
+            }
+        }
+
+        text.push(c);
+        flags.push(current(&depth));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    (text, styles_from_flags(flags))
+}
+
+// Merges the face style of every character into `styl` ranges
+fn styles_from_flags(flags: Vec<u8>) -> Vec<TextStyle> {
+    let mut styles: Vec<TextStyle> = Vec::new();
+    for (index, flag) in flags.into_iter().enumerate() {
+        let index = index.min(u16::MAX as usize - 1) as u16;
+        match styles.last_mut() {
+            Some(style) if style.end_char == index && style.face_style_flags == flag => {
+                style.end_char += 1;
+            }
+            _ if flag != 0 => styles.push(TextStyle::new(index..index + 1, flag)),
+            _ => {}
+        }
+    }
+
+    styles
+}
+
+fn format_timestamp(time: Duration, separator: char) -> String {
+    let millis = time.as_millis();
+    let hours = millis / 3_600_000;
+    let minutes = millis / 60_000 % 60;
+    let seconds = millis / 1000 % 60;
+    let millis = millis % 1000;
+    format!("{hours:02}:{minutes:02}:{seconds:02}{separator}{millis:03}")
+}
+
+// `HH:MM:SS.mmm`, `HH:MM:SS,mmm` or WebVTT's `MM:SS.mmm`
+fn parse_timestamp(value: &str) -> Option<Duration> {
+    let (clock, fraction) = value.split_once(['.', ','])?;
+
+    let mut seconds = 0u64;
+    let parts: Vec<&str> = clock.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    for part in parts {
+        seconds = seconds * 60 + part.trim().parse::<u64>().ok()?;
+    }
+
+    let digits = &fraction[..fraction.len().min(3)];
+    let mut millis = digits.parse::<u64>().ok()?;
+    for _ in digits.len()..3 {
+        millis *= 10;
+    }
+
+    Some(Duration::from_secs(seconds) + Duration::from_millis(millis))
+}
+
+// `start --> end`, followed by cue settings in WebVTT
+fn parse_timing(line: &str) -> Option<(Duration, Duration, &str)> {
+    let (start, rest) = line.split_once("-->")?;
+    let rest = rest.trim_start();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((
+        parse_timestamp(start.trim())?,
+        parse_timestamp(end)?,
+        settings.trim(),
+    ))
+}
+
+fn blocks(input: &str) -> impl Iterator<Item = Vec<&str>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+
+    for line in input.lines() {
+        if line.trim().is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+
+    blocks.into_iter()
+}
+
+fn parse_cues<'a>(blocks: impl Iterator<Item = Vec<&'a str>>, webvtt: bool) -> Result<Vec<Cue>> {
+    let mut cues = Vec::new();
+
+    for (index, block) in blocks.enumerate() {
+        let Some(timing) = block.iter().position(|line| line.contains("-->")) else {
+            continue;
+        };
+        let Some((start, end, settings)) = parse_timing(block[timing]) else {
+            return Err(Error::invalid_value(format!(
+                "invalid timing `{}` in cue {}",
+                block[timing],
+                index + 1
+            )));
+        };
+
+        let (text, styles) = parse_markup(&block[timing + 1..].join("\n"), webvtt);
+        // SRT numbers its cues, WebVTT identifiers are optional
+        let id = (webvtt && timing > 0).then(|| block[..timing].join("\n"));
+        let settings = (webvtt && !settings.is_empty()).then(|| settings.to_owned());
+        cues.push(Cue {
+            start,
+            end,
+            text,
+            styles,
+            id,
+            settings,
+            ..Default::default()
+        });
+    }
+
+    Ok(cues)
+}
+
+pub fn parse_srt(input: &str) -> Result<Vec<Cue>> {
+    parse_cues(blocks(input), false)
+}
+
+// `NOTE`, `STYLE` and `REGION` blocks are skipped along with cue settings
+pub fn parse_webvtt(input: &str) -> Result<Vec<Cue>> {
+    let mut blocks = blocks(input);
+    let header = blocks.next().unwrap_or_default();
+    if !header
+        .first()
+        .is_some_and(|line| line.starts_with("WEBVTT"))
+    {
+        return Err(Error::invalid_value("missing WEBVTT header"));
+    }
+
+    let cues = blocks.filter(|block| {
+        !block.first().is_some_and(|line| {
+            ["NOTE", "STYLE", "REGION"]
+                .iter()
+                .any(|k| line.starts_with(k))
+        })
+    });
+    parse_cues(cues, true)
+}
+
+// `ttp:frameRate` and `ttp:tickRate` of the `tt` element, for `f` and `t` times
+struct TtmlRates {
+    frame_rate: f64,
+    tick_rate: f64,
+}
+
+// Clock times (`01:02:03.5`, or `01:02:03:12` with frames) and offset times
+// (`1.5s`, `1500ms`, `90f`, `100t`)
+fn parse_ttml_time(value: &str, rates: &TtmlRates) -> Option<Duration> {
+    let value = value.trim();
+    let number = |value: &str| value.parse::<f64>().ok();
+
+    let seconds = match value.split(':').collect::<Vec<_>>()[..] {
+        [hours, minutes, seconds] => {
+            number(hours)? * 3600.0 + number(minutes)? * 60.0 + number(seconds)?
+        }
+        [hours, minutes, seconds, frames] => {
+            number(hours)? * 3600.0
+                + number(minutes)? * 60.0
+                + number(seconds)?
+                + number(frames)? / rates.frame_rate
+        }
+        [offset] => {
+            let split = offset.find(|c: char| c.is_ascii_alphabetic())?;
+            let (count, metric) = offset.split_at(split);
+            let count = number(count)?;
+            match metric {
+                "h" => count * 3600.0,
+                "m" => count * 60.0,
+                "s" => count,
+                "ms" => count / 1000.0,
+                "f" => count / rates.frame_rate,
+                "t" => count / rates.tick_rate,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    (seconds.is_finite() && seconds >= 0.0)
+        .then(|| Duration::from_nanos((seconds * 1e9).round() as u64))
+}
+
+// The attributes of a tag by local name, namespace prefixes are dropped
+fn xml_attributes(tag: &str) -> Vec<(&str, &str)> {
+    let mut attributes = Vec::new();
+    let mut rest = tag
+        .trim_end_matches('/')
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, rest)| rest);
+
+    while let Some((name, value)) = rest.split_once('=') {
+        let value = value.trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(end) = value[1..].find(quote) else {
+            break;
+        };
+        let name = name.trim();
+        let name = name.rsplit(':').next().unwrap_or(name);
+        attributes.push((name, &value[1..end + 1]));
+        rest = &value[end + 2..];
+    }
+
+    attributes
+}
+
+// `&name;`, `&#N;` or `&#xN;` at the start of `input`, with its length
+fn xml_entity(input: &str) -> Option<(char, usize)> {
+    let end = input.find(';').filter(|end| *end < 12)?;
+    let c = match &input[1..end] {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        reference => {
+            let code = match reference
+                .strip_prefix("#x")
+                .or(reference.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => reference.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    };
+    Some((c, end + 1))
+}
+
+// Timing of a `body` or `div`, children are timed relative to its start
+struct TtmlContainer {
+    begin: Duration,
+    end: Option<Duration>,
+}
+
+// An open `<p>` with the face style of every character and its open spans
+struct TtmlParagraph {
+    start: Duration,
+    end: Duration,
+    text: String,
+    flags: Vec<u8>,
+    spans: Vec<u8>,
+}
+
+// Cues for the `<p>` elements of a TTML document, as carried in `stpp` samples.
+// `<span>` bold, italic and underline styling is kept, `<br/>` becomes a line break
+// and whitespace is collapsed as with `xml:space="default"`. Style and region
+// references aren't resolved.
+pub fn parse_ttml(document: &str) -> Result<Vec<Cue>> {
+    let mut cues = Vec::new();
+    let mut rates = None;
+    let mut containers: Vec<(String, TtmlContainer)> = Vec::new();
+    let mut paragraph: Option<TtmlParagraph> = None;
+    let mut rest = document;
+
+    let time = |value: &str, rates: &TtmlRates| {
+        parse_ttml_time(value, rates)
+            .ok_or_else(|| Error::invalid_value(format!("invalid TTML time `{value}`")))
+    };
+
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            break;
+        };
+
+        if let Some(TtmlParagraph {
+            text, flags, spans, ..
+        }) = &mut paragraph
+        {
+            let flag = spans.last().copied().unwrap_or_default();
+            let mut chars = &rest[..open];
+            while let Some(c) = chars.chars().next() {
+                let (c, len) = match c {
+                    '&' => xml_entity(chars).unwrap_or(('&', 1)),
+                    c if c.is_whitespace() => {
+                        chars = &chars[c.len_utf8()..];
+                        if !text.is_empty() && !text.ends_with([' ', '\n']) {
+                            text.push(' ');
+                            flags.push(flag);
+                        }
+                        continue;
+                    }
+                    c => (c, c.len_utf8()),
+                };
+                text.push(c);
+                flags.push(flag);
+                chars = &chars[len..];
+            }
+        }
+        rest = &rest[open..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(close) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+        if tag.starts_with(['?', '!']) {
+            continue;
+        }
+
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let self_closing = tag.ends_with('/');
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        let name = name.rsplit(':').next().unwrap_or(name);
+        let attributes = xml_attributes(tag);
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| *value)
+        };
+
+        if name == "tt" && !closing {
+            let frame_rate = attribute("frameRate").and_then(|rate| rate.parse::<f64>().ok());
+            let tick_rate = attribute("tickRate").and_then(|rate| rate.parse::<f64>().ok());
+            rates = Some(TtmlRates {
+                frame_rate: frame_rate.unwrap_or(30.0),
+                tick_rate: tick_rate.or(frame_rate).unwrap_or(1.0),
+            });
+            continue;
+        }
+        let Some(rates) = &rates else {
+            continue;
+        };
+
+        let parent = containers.last().map(|(_, container)| container);
+        let offset = parent.map(|container| container.begin).unwrap_or_default();
+        let parent_end = parent.and_then(|container| container.end);
+
+        match (name, closing) {
+            ("body" | "div", false) if !self_closing => {
+                let begin = match attribute("begin") {
+                    Some(begin) => offset + time(begin, rates)?,
+                    None => offset,
+                };
+                let end = match (attribute("end"), attribute("dur")) {
+                    (Some(end), _) => Some(offset + time(end, rates)?),
+                    (None, Some(dur)) => Some(begin + time(dur, rates)?),
+                    (None, None) => parent_end,
+                };
+                containers.push((name.to_owned(), TtmlContainer { begin, end }));
+            }
+            ("body" | "div", true) => {
+                if let Some(index) = containers.iter().rposition(|(open, _)| open == name) {
+                    containers.truncate(index);
+                }
+            }
+            ("p", false) if !self_closing => {
+                let begin = match attribute("begin") {
+                    Some(begin) => offset + time(begin, rates)?,
+                    None => offset,
+                };
+                let end = match (attribute("end"), attribute("dur")) {
+                    (Some(end), _) => Some(offset + time(end, rates)?),
+                    (None, Some(dur)) => Some(begin + time(dur, rates)?),
+                    (None, None) => parent_end,
+                };
+                // untimed paragraphs have no place on the timeline and are dropped
+                if let Some(end) = end {
+                    let flags = ttml_face_style(&attribute, 0);
+                    paragraph = Some(TtmlParagraph {
+                        start: begin,
+                        end,
+                        text: String::new(),
+                        flags: Vec::new(),
+                        spans: vec![flags],
+                    });
+                }
+            }
+            ("p", true) => {
+                if let Some(TtmlParagraph {
+                    start,
+                    end,
+                    mut text,
+                    mut flags,
+                    ..
+                }) = paragraph.take()
+                {
+                    while text.ends_with([' ', '\n']) {
+                        text.pop();
+                        flags.pop();
+                    }
+                    if !text.is_empty() && end > start {
+                        cues.push(Cue {
+                            start,
+                            end,
+                            text,
+                            styles: styles_from_flags(flags),
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+            ("span", false) if !self_closing => {
+                if let Some(TtmlParagraph { spans, .. }) = &mut paragraph {
+                    let inherited = spans.last().copied().unwrap_or_default();
+                    spans.push(ttml_face_style(&attribute, inherited));
+                }
+            }
+            ("span", true) => {
+                if let Some(TtmlParagraph { spans, .. }) = &mut paragraph {
+                    if spans.len() > 1 {
+                        spans.pop();
+                    }
+                }
+            }
+            ("br", false) => {
+                if let Some(TtmlParagraph {
+                    text, flags, spans, ..
+                }) = &mut paragraph
+                {
+                    if text.ends_with(' ') {
+                        text.pop();
+                        flags.pop();
+                    }
+                    text.push('\n');
+                    flags.push(spans.last().copied().unwrap_or_default());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if rates.is_none() {
+        return Err(Error::invalid_value("missing TTML tt element"));
+    }
+
+    Ok(cues)
+}
+
+// Bold, italic and underline from `tts:fontWeight`, `tts:fontStyle` and
+// `tts:textDecoration`, on top of the inherited style
+fn ttml_face_style<'a>(attribute: &impl Fn(&str) -> Option<&'a str>, inherited: u8) -> u8 {
+    let mut flags = inherited;
+    match attribute("fontWeight") {
+        Some("bold") => flags |= FACE_BOLD,
+        Some("normal") => flags &= !FACE_BOLD,
+        _ => {}
+    }
+    match attribute("fontStyle") {
+        Some("italic" | "oblique") => flags |= FACE_ITALIC,
+        Some("normal") => flags &= !FACE_ITALIC,
+        _ => {}
+    }
+    match attribute("textDecoration") {
+        Some(decoration) if decoration.contains("noUnderline") => flags &= !FACE_UNDERLINE,
+        Some(decoration) if decoration.contains("underline") => flags |= FACE_UNDERLINE,
+        _ => {}
+    }
+    flags
+}
+
+pub fn to_srt(cues: &[Cue]) -> String {
+    let mut out = String::new();
+    for (index, cue) in cues.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index + 1,
+            format_timestamp(cue.start, ','),
+            format_timestamp(cue.end, ','),
+            cue.markup(false)
+        ));
+    }
+    out
+}
+
+pub fn to_webvtt(cues: &[Cue]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues {
+        if let Some(id) = &cue.id {
+            out.push_str(id);
+            out.push('\n');
+        }
+        out.push_str(&format!(
+            "{} --> {}",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+        ));
+        if let Some(settings) = &cue.settings {
+            out.push(' ');
+            out.push_str(settings);
+        }
+        out.push('\n');
+        out.push_str(&cue.markup(true));
+        out.push_str("\n\n");
+    }
+    out
+}
+
+// Samples for a tx3g track with the given timescale. Timed text samples can't
+// overlap, so a cue starting before the previous one ended is pushed back, and
+// gaps are filled with empty samples.
+pub fn cue_samples(cues: &[Cue], timescale: u32) -> Vec<(Sample, Vec<u8>)> {
+    let to_ticks = |time: Duration| (time.as_nanos() * timescale as u128 / 1_000_000_000) as u64;
+    let mut samples = Vec::with_capacity(cues.len() * 2);
+    let mut position = 0u64;
+
+    let push = |samples: &mut Vec<(Sample, Vec<u8>)>, start: u64, end: u64, data| {
+        let sample = Sample {
+            is_sync: true,
+            timescale: timescale as u64,
+            decode_timestamp: start as i64,
+            composition_timestamp: start as i64,
+            duration: end - start,
+            ..Default::default()
+        };
+        samples.push((sample, data));
+    };
+
+    for cue in cues {
+        let start = to_ticks(cue.start).max(position);
+        let end = to_ticks(cue.end);
+        if end <= start {
+            continue;
+        }
+
+        if start > position {
+            push(&mut samples, position, start, Tx3gBox::encode_sample(""));
+        }
+        push(&mut samples, start, end, cue.to_sample());
+        position = end;
+    }
+
+    samples
+}
+
+impl Track {
+    // Decodes `tx3g`, `wvtt` and `stpp` samples. Empty samples only mark the gaps
+    // between cues and are left out, and a cue carried by several consecutive
+    // samples is merged back into one.
+    pub fn read_cues<B: AsRef<[u8]>, R: Read + Seek>(
+        &self,
+        mp4: &Mp4<B>,
+        reader: R,
+    ) -> Result<Vec<Cue>> {
+        let mut cues: Vec<Cue> = Vec::new();
+        // indices of the cues in the previous sample, the only ones that can continue
+        let mut active: Vec<usize> = Vec::new();
+
+        for sample in self.read_samples(mp4, reader) {
+            let (sample, data) = sample?;
+            let start = self.to_duration(sample.composition_timestamp);
+            let end = self.to_duration(sample.composition_timestamp + sample.duration as i64);
+
+            let decoded = match self.sample_description(mp4, &sample) {
+                Some(StsdBoxContent::Wvtt(_)) => Cue::from_wvtt_sample(start, end, &data),
+                Some(StsdBoxContent::Stpp(_)) => parse_ttml(&String::from_utf8_lossy(&data))?,
+                _ => vec![Cue::from_sample(start, end, &data)],
+            };
+
+            let mut current = Vec::with_capacity(decoded.len());
+            for cue in decoded.into_iter().filter(|cue| !cue.text.is_empty()) {
+                let continued = active.iter().copied().find(|&index| {
+                    let earlier = &cues[index];
+                    earlier.start <= cue.start
+                        && earlier.end >= cue.start
+                        && earlier.text == cue.text
+                        && earlier.styles == cue.styles
+                        && earlier.id == cue.id
+                        && earlier.settings == cue.settings
+                });
+                match continued {
+                    Some(index) => {
+                        cues[index].end = cues[index].end.max(cue.end);
+                        current.push(index);
+                    }
+                    None => {
+                        current.push(cues.len());
+                        cues.push(cue);
+                    }
+                }
+            }
+            active = current;
+        }
+
+        Ok(cues)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn srt() {
+        let input = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\nHello <b>world</b>\r\n\r\n\
+                     2\r\n00:01:00,25 --> 01:00:00,000\r\nTwo\r\nlines & more\r\n";
+        let cues = parse_srt(input).unwrap();
+        assert_eq!(cues.len(), 2);
+
+        assert_eq!((cues[0].start, cues[0].end), (millis(1000), millis(2500)));
+        assert_eq!(cues[0].text, "Hello world");
+        assert_eq!(cues[0].styles, vec![TextStyle::new(6..11, FACE_BOLD)]);
+        assert_eq!(cues[0].id, None);
+
+        assert_eq!(
+            (cues[1].start, cues[1].end),
+            (millis(60_250), millis(3_600_000))
+        );
+        assert_eq!(cues[1].text, "Two\nlines & more");
+
+        assert_eq!(parse_srt(&to_srt(&cues)).unwrap(), cues);
+        assert!(parse_srt("1\n00:00:01 --> 00:00:02\nNo millis\n").is_err());
+    }
+
+    #[test]
+    fn webvtt() {
+        let input = "WEBVTT - title\n\n\
+                     NOTE a comment\n--> with an arrow\n\n\
+                     STYLE\n::cue { color: red }\n\n\
+                     intro\n00:01.000 --> 00:02.000 line:0 align:start\n<i>Fish</i> &amp; chips\n\n\
+                     00:00:03.000 --> 00:00:04.000\n<c.loud>Plain</c>\n";
+        let cues = parse_webvtt(input).unwrap();
+        assert_eq!(cues.len(), 2);
+
+        assert_eq!((cues[0].start, cues[0].end), (millis(1000), millis(2000)));
+        assert_eq!(cues[0].id.as_deref(), Some("intro"));
+        assert_eq!(cues[0].settings.as_deref(), Some("line:0 align:start"));
+        assert_eq!(cues[0].text, "Fish & chips");
+        assert_eq!(cues[0].styles, vec![TextStyle::new(0..4, FACE_ITALIC)]);
+
+        assert_eq!(cues[1].id, None);
+        assert_eq!(cues[1].settings, None);
+        assert_eq!(cues[1].text, "Plain");
+
+        assert_eq!(parse_webvtt(&to_webvtt(&cues)).unwrap(), cues);
+        assert!(parse_webvtt("00:01.000 --> 00:02.000\nNo header\n").is_err());
+    }
+
+    #[test]
+    fn ttml() {
+        let document = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling"
+                xmlns:ttp="http://www.w3.org/ns/ttml#parameter" ttp:frameRate="25" ttp:tickRate="10">
+              <body>
+                <div begin="10s">
+                  <!-- <p begin="0s" end="1s">commented out</p> -->
+                  <p begin="1s" end="2.5s">Hello
+                    <span tts:fontWeight="bold">big</span><br/>world &amp; more</p>
+                  <p begin="00:00:03:05" dur="10t">Frames and ticks</p>
+                  <p>Untimed</p>
+                </div>
+                <p begin="500ms" end="1s" tts:fontStyle="italic">Slanted
+                  <span tts:fontStyle="normal">upright</span></p>
+              </body>
+            </tt>"#;
+        let cues = parse_ttml(document).unwrap();
+        assert_eq!(cues.len(), 3);
+
+        assert_eq!(
+            (cues[0].start, cues[0].end),
+            (millis(11_000), millis(12_500))
+        );
+        assert_eq!(cues[0].text, "Hello big\nworld & more");
+        assert_eq!(cues[0].styles, vec![TextStyle::new(6..9, FACE_BOLD)]);
+
+        assert_eq!(
+            (cues[1].start, cues[1].end),
+            (millis(13_200), millis(14_200))
+        );
+        assert_eq!(cues[1].text, "Frames and ticks");
+
+        assert_eq!((cues[2].start, cues[2].end), (millis(500), millis(1000)));
+        assert_eq!(cues[2].text, "Slanted upright");
+        assert_eq!(cues[2].styles, vec![TextStyle::new(0..8, FACE_ITALIC)]);
+
+        assert!(parse_ttml("<p begin=\"1s\" end=\"2s\">No tt</p>").is_err());
+        assert!(parse_ttml("<tt><body><p begin=\"soon\" end=\"2s\">x</p></body></tt>").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{boxes::tref::ReferenceType, Mp4, TrackId};
+
+// The `tref` boxes of all tracks as a directed graph, an edge points from the
+// track holding the reference to the track it names
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrackReferences {
+    edges: BTreeMap<TrackId, Vec<(ReferenceType, TrackId)>>,
+}
+
+impl TrackReferences {
+    pub fn edges(&self) -> impl Iterator<Item = (TrackId, ReferenceType, TrackId)> + '_ {
+        self.edges.iter().flat_map(|(from, references)| {
+            references
+                .iter()
+                .map(|(reference_type, to)| (*from, *reference_type, *to))
+        })
+    }
+
+    pub fn references(&self, track_id: TrackId) -> &[(ReferenceType, TrackId)] {
+        self.edges.get(&track_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn references_of_type(
+        &self,
+        track_id: TrackId,
+        reference_type: ReferenceType,
+    ) -> impl Iterator<Item = TrackId> + '_ {
+        self.references(track_id)
+            .iter()
+            .filter(move |(ty, _)| *ty == reference_type)
+            .map(|(_, to)| *to)
+    }
+
+    // `(reference_type, from)` for every track that references `track_id`
+    pub fn referenced_by(&self, track_id: TrackId) -> Vec<(ReferenceType, TrackId)> {
+        self.edges()
+            .filter(|(_, _, to)| *to == track_id)
+            .map(|(from, reference_type, _)| (reference_type, from))
+            .collect()
+    }
+
+    // All tracks connected to `track_id` in either direction, including itself,
+    // e.g. a video track with its chapter track and the chapter track's font track
+    pub fn group(&self, track_id: TrackId) -> BTreeSet<TrackId> {
+        let mut group = BTreeSet::from([track_id]);
+        let mut pending = vec![track_id];
+
+        while let Some(current) = pending.pop() {
+            for (from, _, to) in self.edges() {
+                let next = if from == current {
+                    to
+                } else if to == current {
+                    from
+                } else {
+                    continue;
+                };
+
+                if group.insert(next) {
+                    pending.push(next);
+                }
+            }
+        }
+
+        group
+    }
+}
+
+impl<B> Mp4<B> {
+    pub fn track_references(&self) -> TrackReferences {
+        let mut edges = BTreeMap::new();
+
+        for trak in &self.moov.traks {
+            let Some(tref) = &trak.tref else {
+                continue;
+            };
+
+            let references: Vec<_> = tref
+                .references
+                .iter()
+                .flat_map(|reference| {
+                    reference
+                        .track_ids
+                        .iter()
+                        // 0 marks an unused slot, the index of a reference can be meaningful
+                        .filter(|track_id| **track_id != 0)
+                        .map(|track_id| (reference.reference_type, *track_id))
+                })
+                .collect();
+
+            if !references.is_empty() {
+                edges
+                    .entry(trak.tkhd.track_id)
+                    .or_insert_with(Vec::new)
+                    .extend(references);
+            }
+        }
+
+        TrackReferences { edges }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        boxes::{avc1::Avc1Box, mp4a::Mp4aBox},
+        Mp4Writer, StsdBoxContent, TrackConfig,
+    };
+
+    #[test]
+    fn written_references() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let video = TrackConfig::new(90000, StsdBoxContent::Avc1(Avc1Box::default()));
+        let audio = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let video = writer.add_track(video).unwrap();
+        let stereo = writer.add_track(audio.clone()).unwrap();
+        let mono = writer.add_track(audio.clone()).unwrap();
+        let other = writer.add_track(audio).unwrap();
+        writer
+            .add_track_reference(mono, ReferenceType::Fallback, stereo)
+            .unwrap();
+        writer
+            .add_track_reference(stereo, ReferenceType::Sync, video)
+            .unwrap();
+        writer
+            .add_track_reference(mono, ReferenceType::Sync, video)
+            .unwrap();
+        assert!(writer
+            .add_track_reference(mono, ReferenceType::Sync, 9)
+            .is_err());
+        let file = writer.finish().unwrap().into_inner();
+
+        let mut mp4 = Mp4::read(Cursor::new(&file[..]), file.len() as u64).unwrap();
+        // 0 marks an unused slot
+        let trak = &mut mp4.moov.traks[mono as usize - 1];
+        trak.tref.as_mut().unwrap().add(ReferenceType::Hint, 0);
+
+        let references = mp4.track_references();
+        assert_eq!(
+            references.references(mono),
+            [
+                (ReferenceType::Fallback, stereo),
+                (ReferenceType::Sync, video)
+            ]
+        );
+        assert!(references.references(video).is_empty());
+        assert_eq!(
+            references
+                .references_of_type(mono, ReferenceType::Sync)
+                .collect::<Vec<_>>(),
+            [video]
+        );
+        assert_eq!(
+            references.referenced_by(video),
+            [(ReferenceType::Sync, stereo), (ReferenceType::Sync, mono)]
+        );
+        assert_eq!(references.edges().count(), 3);
+        assert_eq!(
+            references.group(video),
+            BTreeSet::from([video, stereo, mono])
+        );
+        assert_eq!(references.group(other), BTreeSet::from([other]));
+    }
+}
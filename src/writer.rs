@@ -0,0 +1,1291 @@
+use std::{
+    io::{self, Seek, Write},
+    time::Duration,
+};
+
+use crate::{
+    boxes::{
+        chpl::{ChplBox, ChplEntry},
+        co64::Co64Box,
+        ctts::{CttsBox, CttsEntry},
+        dinf::DinfBox,
+        edts::EdtsBox,
+        elst::{ElstBox, ElstEntry},
+        hdlr::HdlrBox,
+        mdhd::MdhdBox,
+        mehd::MehdBox,
+        meta::MetaBox,
+        mfhd::MfhdBox,
+        minf::MinfBox,
+        mvex::MvexBox,
+        mvhd::MvhdBox,
+        smhd::SmhdBox,
+        stco::StcoBox,
+        stsc::{StscBox, StscEntry},
+        stss::StssBox,
+        stsz::StszBox,
+        stts::{SttsBox, SttsEntry},
+        tfdt::TfdtBox,
+        tkhd::{TkhdBox, TrackFlag},
+        tref::{ReferenceType, TrefBox},
+        trex::TrexBox,
+        udta::UdtaBox,
+        vmhd::VmhdBox,
+    },
+    BigEndian, BoxHeader, BoxType, Chapter, Edit, Error, FixedPointU8, FourCC, FtypBox, MdiaBox,
+    MoofBox, MoovBox, Mp4Box, Result, Sample, StblBox, StsdBox, StsdBoxContent, TfhdBox, TrackId,
+    TrackKind, TrafBox, TrakBox, TrunBox, Tx3gBox, WriteBox, HANDLER_TYPE_CLCP_FOURCC,
+    HANDLER_TYPE_SUBT_FOURCC, HEADER_SIZE,
+};
+
+const MOVIE_TIMESCALE: u32 = 1000;
+const CHAPTER_TIMESCALE: u32 = 1000;
+const MDAT_HEADER_SIZE: u64 = HEADER_SIZE + 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackConfig {
+    pub timescale: u32,
+    pub language: String,
+    pub contents: StsdBoxContent,
+}
+
+impl TrackConfig {
+    pub fn new(timescale: u32, contents: StsdBoxContent) -> Self {
+        Self {
+            timescale,
+            language: String::from("und"),
+            contents,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SampleTables {
+    pub(crate) stts: Vec<SttsEntry>,
+    pub(crate) ctts: Vec<CttsEntry>,
+    pub(crate) stss: Vec<u32>,
+    pub(crate) sample_sizes: Vec<u32>,
+    /// Offset, sample count and `sample_description_index` of every chunk
+    pub(crate) chunks: Vec<(u64, u32, u32)>,
+    pub(crate) has_non_sync: bool,
+    pub(crate) duration: u64,
+}
+
+impl SampleTables {
+    pub(crate) fn push(&mut self, sample: &Sample, new_chunk_offset: Option<u64>) {
+        let sample_number = self.sample_sizes.len() as u32 + 1;
+
+        match self.stts.last_mut() {
+            Some(entry) if entry.sample_delta as u64 == sample.duration => entry.sample_count += 1,
+            _ => self.stts.push(SttsEntry {
+                sample_count: 1,
+                sample_delta: sample.duration as u32,
+            }),
+        }
+
+        let sample_offset = (sample.composition_timestamp - sample.decode_timestamp) as i32;
+        match self.ctts.last_mut() {
+            Some(entry) if entry.sample_offset == sample_offset => entry.sample_count += 1,
+            _ => self.ctts.push(CttsEntry {
+                sample_count: 1,
+                sample_offset,
+            }),
+        }
+
+        if sample.is_sync {
+            self.stss.push(sample_number);
+        } else {
+            self.has_non_sync = true;
+        }
+
+        match new_chunk_offset {
+            Some(offset) => {
+                let sample_description_index = sample.sample_description_index.max(1);
+                self.chunks.push((offset, 1, sample_description_index));
+            }
+            None => {
+                if let Some(chunk) = self.chunks.last_mut() {
+                    chunk.1 += 1;
+                }
+            }
+        }
+
+        self.sample_sizes.push(sample.size as u32);
+        self.duration += sample.duration;
+    }
+
+    pub(crate) fn build_stbl(&self, stsd: StsdBox) -> StblBox {
+        let ctts = if self.ctts.iter().any(|entry| entry.sample_offset != 0) {
+            Some(CttsBox {
+                version: self.ctts.iter().any(|entry| entry.sample_offset < 0) as u8,
+                flags: 0,
+                entries: self.ctts.clone(),
+            })
+        } else {
+            None
+        };
+
+        let stss = if self.has_non_sync {
+            Some(StssBox {
+                version: 0,
+                flags: 0,
+                entries: self.stss.clone(),
+            })
+        } else {
+            None
+        };
+
+        let mut stsc = StscBox::default();
+        let mut first_sample = 1;
+        for (i, &(_, samples_per_chunk, sample_description_index)) in self.chunks.iter().enumerate()
+        {
+            let last = stsc
+                .entries
+                .last()
+                .map(|e| (e.samples_per_chunk, e.sample_description_index));
+            if last != Some((samples_per_chunk, sample_description_index)) {
+                stsc.entries.push(StscEntry {
+                    first_chunk: i as u32 + 1,
+                    samples_per_chunk,
+                    sample_description_index,
+                    first_sample,
+                });
+            }
+            first_sample += samples_per_chunk;
+        }
+
+        let sample_count = self.sample_sizes.len() as u32;
+        let stsz = match self.sample_sizes.first() {
+            Some(size) if self.sample_sizes.iter().all(|s| s == size) => StszBox {
+                sample_size: *size,
+                sample_count,
+                ..Default::default()
+            },
+            _ => StszBox {
+                sample_count,
+                sample_sizes: self.sample_sizes.clone(),
+                ..Default::default()
+            },
+        };
+
+        let (stco, co64) = if self
+            .chunks
+            .iter()
+            .any(|(offset, ..)| *offset > u32::MAX as u64)
+        {
+            let co64 = Co64Box {
+                version: 0,
+                flags: 0,
+                entries: self.chunks.iter().map(|(offset, ..)| *offset).collect(),
+            };
+            (None, Some(co64))
+        } else {
+            let stco = StcoBox {
+                version: 0,
+                flags: 0,
+                entries: self
+                    .chunks
+                    .iter()
+                    .map(|(offset, ..)| *offset as u32)
+                    .collect(),
+            };
+            (Some(stco), None)
+        };
+
+        StblBox {
+            stsd,
+            stts: SttsBox {
+                version: 0,
+                flags: 0,
+                entries: self.stts.clone(),
+            },
+            ctts,
+            stss,
+            stsc,
+            stsz,
+            stco,
+            co64,
+            saiz: None,
+            saio: None,
+            unknown: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TrackWriter {
+    track_id: TrackId,
+    config: TrackConfig,
+    /// `config.contents` followed by the ones of `Mp4Writer::add_sample_description`
+    descriptions: Vec<StsdBoxContent>,
+    tables: SampleTables,
+    edits: Vec<Edit>,
+    tkhd: Option<TkhdBox>,
+    handler_name: Option<String>,
+    tref: Option<TrefBox>,
+}
+
+impl TrackWriter {
+    fn build_trak(&self, movie_timescale: u32) -> TrakBox {
+        let stsd = StsdBox {
+            version: 0,
+            flags: 0,
+            entries: self.descriptions.clone(),
+        };
+        let kind = stsd.kind();
+
+        let mut trak = build_trak(
+            self.track_id,
+            kind,
+            &self.config,
+            self.tables.duration,
+            movie_timescale,
+            self.tables.build_stbl(stsd),
+        );
+        if let Some(tkhd) = &self.tkhd {
+            trak.tkhd = TkhdBox {
+                version: trak.tkhd.version,
+                creation_time: trak.tkhd.creation_time,
+                modification_time: trak.tkhd.modification_time,
+                track_id: trak.tkhd.track_id,
+                duration: trak.tkhd.duration,
+                ..tkhd.clone()
+            };
+        }
+        if let Some(name) = &self.handler_name {
+            trak.mdia.hdlr.name = name.clone();
+        }
+        trak.tref = self.tref.clone();
+
+        if !self.edits.is_empty() {
+            let timescale = self.config.timescale as u64;
+            let entries: Vec<_> = self
+                .edits
+                .iter()
+                .map(|edit| {
+                    // movie ticks are coarse, round media up rather than cut the last
+                    // sample short, but don't let gaps delay what follows
+                    let rescale = if edit.is_empty() { rescale } else { rescale_up };
+                    ElstEntry {
+                        segment_duration: rescale(
+                            self.edit_duration(edit),
+                            timescale,
+                            movie_timescale as u64,
+                        ),
+                        media_time: edit.media_time.unwrap_or(-1),
+                        media_rate: edit.media_rate,
+                        media_rate_fraction: 0,
+                    }
+                })
+                .collect();
+
+            trak.tkhd.duration = entries.iter().map(|entry| entry.segment_duration).sum();
+            let version = entries.iter().any(|entry| {
+                entry.segment_duration > u32::MAX as u64 || i32::try_from(entry.media_time).is_err()
+            }) as u8;
+            trak.edts = Some(EdtsBox {
+                elst: Some(ElstBox {
+                    version,
+                    flags: 0,
+                    entries,
+                }),
+            });
+        }
+
+        trak
+    }
+
+    fn edit_duration(&self, edit: &Edit) -> u64 {
+        match (edit.duration, edit.media_time) {
+            // runs to the end of the media
+            (0, Some(media_time)) => self
+                .tables
+                .duration
+                .saturating_sub(media_time.max(0) as u64),
+            (duration, _) => duration,
+        }
+    }
+
+    // Length of the track once its edits are applied, in the track timescale
+    fn presented_duration(&self) -> u64 {
+        if self.edits.is_empty() {
+            self.tables.duration
+        } else {
+            self.edits.iter().map(|edit| self.edit_duration(edit)).sum()
+        }
+    }
+}
+
+pub(crate) fn build_trak(
+    track_id: TrackId,
+    kind: Option<TrackKind>,
+    config: &TrackConfig,
+    duration: u64,
+    movie_timescale: u32,
+    stbl: StblBox,
+) -> TrakBox {
+    let (width, height) = entry_dimensions(&config.contents);
+
+    let mut tkhd = TkhdBox {
+        flags: TrackFlag::TrackEnabled as u32
+            | TrackFlag::TrackInMovie as u32
+            | TrackFlag::TrackInPreview as u32,
+        track_id,
+        duration: rescale(duration, config.timescale as u64, movie_timescale as u64),
+        ..Default::default()
+    };
+    tkhd.set_width(width);
+    tkhd.set_height(height);
+    if kind != Some(TrackKind::Audio) {
+        tkhd.volume = FixedPointU8::new(0);
+    }
+
+    let mdhd = MdhdBox {
+        timescale: config.timescale,
+        duration,
+        language: config.language.clone(),
+        ..Default::default()
+    };
+
+    let (handler_type, name) = match kind {
+        // MPEG-4 part 30 text tracks use `subt` rather than the QuickTime `sbtl`
+        Some(TrackKind::Subtitle)
+            if matches!(
+                config.contents,
+                StsdBoxContent::Wvtt(_) | StsdBoxContent::Stpp(_)
+            ) =>
+        {
+            (
+                HANDLER_TYPE_SUBT_FOURCC.into(),
+                format!("{}Handler", TrackKind::Subtitle),
+            )
+        }
+        Some(TrackKind::Subtitle) if matches!(config.contents, StsdBoxContent::C608(_)) => (
+            HANDLER_TYPE_CLCP_FOURCC.into(),
+            String::from("ClosedCaptionHandler"),
+        ),
+        Some(kind) => (FourCC::from(kind), format!("{kind}Handler")),
+        None => (FourCC::default(), String::new()),
+    };
+    let hdlr = HdlrBox {
+        handler_type,
+        name,
+        ..Default::default()
+    };
+
+    let minf = MinfBox {
+        vmhd: (kind == Some(TrackKind::Video)).then(|| VmhdBox {
+            flags: 1,
+            ..Default::default()
+        }),
+        smhd: (kind == Some(TrackKind::Audio)).then(SmhdBox::default),
+        dinf: DinfBox::default(),
+        stbl,
+        unknown: Vec::new(),
+    };
+
+    TrakBox {
+        tkhd,
+        tref: None,
+        edts: None,
+        meta: None,
+        mdia: MdiaBox {
+            mdhd,
+            hdlr,
+            minf,
+            unknown: Vec::new(),
+        },
+        unknown: Vec::new(),
+    }
+}
+
+pub(crate) fn default_ftyp() -> FtypBox {
+    FtypBox {
+        major_brand: FourCC::from(*b"isom"),
+        minor_version: 0x200,
+        compatible_brands: vec![
+            FourCC::from(*b"isom"),
+            FourCC::from(*b"iso2"),
+            FourCC::from(*b"mp41"),
+        ],
+    }
+}
+
+fn entry_dimensions(contents: &StsdBoxContent) -> (u16, u16) {
+    match contents {
+        StsdBoxContent::Av01(bx) => (bx.width, bx.height),
+        StsdBoxContent::Avc1(bx) => (bx.width, bx.height),
+        StsdBoxContent::Hvc1(bx) | StsdBoxContent::Hev1(bx) => (bx.width, bx.height),
+        StsdBoxContent::Vp08(bx) => (bx.width, bx.height),
+        StsdBoxContent::Vp09(bx) => (bx.width, bx.height),
+        StsdBoxContent::Mp4a(_)
+        | StsdBoxContent::Tx3g(_)
+        | StsdBoxContent::Wvtt(_)
+        | StsdBoxContent::Stpp(_)
+        | StsdBoxContent::C608(_)
+        | StsdBoxContent::Unknown(_) => (0, 0),
+        StsdBoxContent::Encrypted { entry, .. } => entry_dimensions(entry),
+    }
+}
+
+fn rescale(value: u64, from: u64, to: u64) -> u64 {
+    if from == 0 {
+        return 0;
+    }
+    (value as u128 * to as u128 / from as u128) as u64
+}
+
+fn rescale_up(value: u64, from: u64, to: u64) -> u64 {
+    if from == 0 {
+        return 0;
+    }
+    (value as u128 * to as u128).div_ceil(from as u128) as u64
+}
+
+pub struct Mp4Writer<W> {
+    writer: W,
+    mdat_start: u64,
+    position: u64,
+    last_track: Option<TrackId>,
+    tracks: Vec<TrackWriter>,
+    chapters: Vec<Chapter>,
+    meta: Option<MetaBox>,
+    udta: Option<UdtaBox>,
+}
+
+impl<W: Write + Seek> Mp4Writer<W> {
+    pub fn new(mut writer: W) -> Result<Self> {
+        default_ftyp().write_box(&mut writer)?;
+
+        // the mdat size is patched in `finish`, always use the 64-bit form
+        // so the payload can grow past 4GiB
+        let mdat_start = writer.stream_position()?;
+        BigEndian::write_u32(&mut writer, 1)?;
+        BigEndian::write_u32(&mut writer, BoxType::MdatBox.into())?;
+        BigEndian::write_u64(&mut writer, MDAT_HEADER_SIZE)?;
+        let position = writer.stream_position()?;
+
+        Ok(Self {
+            writer,
+            mdat_start,
+            position,
+            last_track: None,
+            tracks: Vec::new(),
+            chapters: Vec::new(),
+            meta: None,
+            udta: None,
+        })
+    }
+
+    // Written on `finish` both as a QuickTime chapter track and as a Nero `chpl` list,
+    // chapters are sorted by their start
+    pub fn set_chapters(&mut self, mut chapters: Vec<Chapter>) {
+        chapters.sort_by_key(|chapter| chapter.start);
+        self.chapters = chapters;
+    }
+
+    // Copied to `moov/meta` and `moov/udta` on `finish`. Chapters set with
+    // `set_chapters` replace the `chpl` of `udta`.
+    pub fn set_metadata(&mut self, meta: Option<MetaBox>, udta: Option<UdtaBox>) {
+        self.meta = meta;
+        self.udta = udta;
+    }
+
+    pub fn add_track(&mut self, config: TrackConfig) -> Result<TrackId> {
+        if matches!(config.contents, StsdBoxContent::Unknown(_)) {
+            return Err(Error::invalid_input("track config has no sample entry"));
+        }
+        if config.timescale == 0 {
+            return Err(Error::invalid_input("track timescale must not be zero"));
+        }
+
+        let track_id = self.tracks.len() as TrackId + 1;
+        self.tracks.push(TrackWriter {
+            track_id,
+            descriptions: vec![config.contents.clone()],
+            config,
+            tables: SampleTables::default(),
+            edits: Vec::new(),
+            tkhd: None,
+            handler_name: None,
+            tref: None,
+        });
+
+        Ok(track_id)
+    }
+
+    // Adds a sample description to a track, samples pick it with the returned
+    // `sample_description_index`. The one of the track config is 1.
+    pub fn add_sample_description(
+        &mut self,
+        track_id: TrackId,
+        contents: StsdBoxContent,
+    ) -> Result<u32> {
+        let track = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id == track_id)
+            .ok_or(Error::TrackNotFound(track_id))?;
+        track.descriptions.push(contents);
+
+        Ok(track.descriptions.len() as u32)
+    }
+
+    // Written as the track's `elst`, edits are in the track timescale like the ones of
+    // `Track::edits`. Their presentation times follow from the durations.
+    pub fn set_edits(&mut self, track_id: TrackId, edits: Vec<Edit>) -> Result<()> {
+        let track = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id == track_id)
+            .ok_or(Error::TrackNotFound(track_id))?;
+        track.edits = edits;
+
+        Ok(())
+    }
+
+    // The flags, layer, alternate group, volume, matrix and size of `tkhd` are written
+    // instead of the defaults, the track id, times and duration are still filled in
+    pub fn set_track_header(&mut self, track_id: TrackId, tkhd: TkhdBox) -> Result<()> {
+        let track = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id == track_id)
+            .ok_or(Error::TrackNotFound(track_id))?;
+        track.tkhd = Some(tkhd);
+
+        Ok(())
+    }
+
+    // Written as the `hdlr` name instead of the default one, e.g. "SoundHandler"
+    pub fn set_handler_name(&mut self, track_id: TrackId, name: String) -> Result<()> {
+        let track = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id == track_id)
+            .ok_or(Error::TrackNotFound(track_id))?;
+        track.handler_name = Some(name);
+
+        Ok(())
+    }
+
+    // Written in the `tref` of `track_id`, the chapter track adds its own on `finish`
+    pub fn add_track_reference(
+        &mut self,
+        track_id: TrackId,
+        reference_type: ReferenceType,
+        reference: TrackId,
+    ) -> Result<()> {
+        if !self.tracks.iter().any(|track| track.track_id == reference) {
+            return Err(Error::TrackNotFound(reference));
+        }
+        let track = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id == track_id)
+            .ok_or(Error::TrackNotFound(track_id))?;
+        track
+            .tref
+            .get_or_insert_with(Default::default)
+            .add(reference_type, reference);
+
+        Ok(())
+    }
+
+    pub fn write_sample(&mut self, track_id: TrackId, sample: &Sample, data: &[u8]) -> Result<()> {
+        let offset = self.position;
+
+        let track = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id == track_id)
+            .ok_or(Error::TrackNotFound(track_id))?;
+
+        // 0, as in `Sample::default()`, stands for the first description
+        let sample_description_index = sample.sample_description_index.max(1);
+        if sample_description_index as usize > track.descriptions.len() {
+            return Err(Error::invalid_input(format!(
+                "track {track_id} has no sample description {sample_description_index}"
+            )));
+        }
+        // a chunk has a single sample description
+        let last_chunk = track.tables.chunks.last();
+        let new_chunk = self.last_track != Some(track_id)
+            || last_chunk.map(|chunk| chunk.2) != Some(sample_description_index);
+
+        self.writer.write_all(data)?;
+        self.position += data.len() as u64;
+        self.last_track = Some(track_id);
+
+        let sample = Sample {
+            size: data.len() as u64,
+            ..sample.clone()
+        };
+        track.tables.push(&sample, new_chunk.then_some(offset));
+
+        Ok(())
+    }
+
+    // The chapter titles are samples, so this has to run before the mdat is closed
+    fn write_chapter_track(&mut self) -> Result<TrackId> {
+        let end = self
+            .tracks
+            .iter()
+            .map(|track| {
+                rescale(
+                    track.presented_duration(),
+                    track.config.timescale as u64,
+                    CHAPTER_TIMESCALE as u64,
+                )
+            })
+            .max()
+            .unwrap_or_default();
+
+        let track_id = self.add_track(TrackConfig::new(
+            CHAPTER_TIMESCALE,
+            StsdBoxContent::Tx3g(Tx3gBox {
+                data_reference_index: 1,
+                ..Default::default()
+            }),
+        ))?;
+
+        let chapters = std::mem::take(&mut self.chapters);
+        for (index, chapter) in chapters.iter().enumerate() {
+            // sample times follow from the durations, so the first chapter covers any
+            // gap before it on the track (`chpl` keeps the exact start)
+            let start = if index == 0 {
+                0
+            } else {
+                chapter.start.as_millis() as u64
+            };
+            let next = chapters
+                .get(index + 1)
+                .map_or(end, |next| next.start.as_millis() as u64);
+
+            let sample = Sample {
+                is_sync: true,
+                timescale: CHAPTER_TIMESCALE as u64,
+                decode_timestamp: start as i64,
+                composition_timestamp: start as i64,
+                duration: next.saturating_sub(start).max(1),
+                ..Default::default()
+            };
+            self.write_sample(track_id, &sample, &Tx3gBox::encode_sample(&chapter.title))?;
+        }
+        self.chapters = chapters;
+
+        Ok(track_id)
+    }
+
+    pub fn finish(mut self) -> Result<W> {
+        let chapter_track = if self.chapters.is_empty() {
+            None
+        } else {
+            Some(self.write_chapter_track()?)
+        };
+
+        let mdat_end = self.position;
+
+        self.writer
+            .seek(io::SeekFrom::Start(self.mdat_start + HEADER_SIZE))?;
+        BigEndian::write_u64(&mut self.writer, mdat_end - self.mdat_start)?;
+        self.writer.seek(io::SeekFrom::Start(mdat_end))?;
+
+        let mut moov = MoovBox {
+            mvhd: MvhdBox {
+                timescale: MOVIE_TIMESCALE,
+                next_track_id: self.tracks.len() as u32 + 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        for track in &self.tracks {
+            let mut trak = track.build_trak(MOVIE_TIMESCALE);
+            if let Some(chapter_track) = chapter_track {
+                if track.track_id == chapter_track {
+                    // players show chapter tracks through the reference, not as a track
+                    trak.tkhd.flags = TrackFlag::TrackInMovie as u32;
+                } else {
+                    trak.tref
+                        .get_or_insert_with(Default::default)
+                        .add(ReferenceType::Chapter, chapter_track);
+                }
+            }
+            moov.mvhd.duration = moov.mvhd.duration.max(trak.tkhd.duration);
+            moov.traks.push(trak);
+        }
+
+        moov.meta = self.meta.take();
+        moov.udta = self.udta.take();
+        if !self.chapters.is_empty() {
+            let entries = self
+                .chapters
+                .iter()
+                .map(|chapter| ChplEntry {
+                    start: (chapter.start.as_nanos() / 100) as u64,
+                    title: chapter.title.clone(),
+                })
+                .collect();
+            moov.udta.get_or_insert_with(Default::default).chpl = Some(ChplBox {
+                entries,
+                ..Default::default()
+            });
+        }
+
+        moov.write_box(&mut self.writer)?;
+
+        Ok(self.writer)
+    }
+}
+
+impl<W> std::fmt::Debug for Mp4Writer<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mp4Writer")
+            .field("mdat_start", &self.mdat_start)
+            .field("position", &self.position)
+            .field("tracks", &self.tracks)
+            .finish()
+    }
+}
+
+const SAMPLE_FLAGS_SYNC: u32 = 0x02000000;
+const SAMPLE_FLAGS_NON_SYNC: u32 = 0x01010000;
+
+#[derive(Debug)]
+struct FragmentTrack {
+    track_id: TrackId,
+    config: TrackConfig,
+    samples: Vec<Sample>,
+    data: Vec<u8>,
+}
+
+impl FragmentTrack {
+    // Video mostly has samples depending on others, every sample of the other kinds is
+    // a sync sample
+    fn default_sample_flags(&self) -> u32 {
+        match self.config.contents.kind() {
+            Some(TrackKind::Video) => SAMPLE_FLAGS_NON_SYNC,
+            _ => SAMPLE_FLAGS_SYNC,
+        }
+    }
+
+    fn build_traf(&self) -> TrafBox {
+        let sample_count = self.samples.len() as u32;
+        let sample_cts = self
+            .samples
+            .iter()
+            .map(|sample| (sample.composition_timestamp - sample.decode_timestamp) as i32)
+            .collect::<Vec<_>>();
+
+        let mut trun = TrunBox {
+            version: sample_cts.iter().any(|cts| *cts < 0) as u8,
+            flags: TrunBox::FLAG_DATA_OFFSET
+                | TrunBox::FLAG_SAMPLE_DURATION
+                | TrunBox::FLAG_SAMPLE_SIZE,
+            sample_count,
+            data_offset: Some(0),
+            sample_duration: self.samples.iter().map(|s| s.duration as u32).collect(),
+            sample_sizes: self.samples.iter().map(|s| s.size as u32).collect(),
+            ..Default::default()
+        };
+
+        // flags matching the `trex` default are left out, a segment starting with a
+        // sync sample of an otherwise non-sync run only needs `first_sample_flags`
+        let sample_flags: Vec<_> = self
+            .samples
+            .iter()
+            .map(|s| match s.is_sync {
+                true => SAMPLE_FLAGS_SYNC,
+                false => SAMPLE_FLAGS_NON_SYNC,
+            })
+            .collect();
+        let default_flags = self.default_sample_flags();
+        if sample_flags[1..]
+            .iter()
+            .all(|flags| *flags == default_flags)
+        {
+            if sample_flags[0] != default_flags {
+                trun.flags |= TrunBox::FLAG_FIRST_SAMPLE_FLAGS;
+                trun.first_sample_flags = Some(sample_flags[0]);
+            }
+        } else {
+            trun.flags |= TrunBox::FLAG_SAMPLE_FLAGS;
+            trun.sample_flags = sample_flags;
+        }
+        if sample_cts.iter().any(|cts| *cts != 0) {
+            trun.flags |= TrunBox::FLAG_SAMPLE_CTS;
+            trun.sample_cts = sample_cts.into_iter().map(|cts| cts as u32).collect();
+        }
+
+        let base_media_decode_time = self.samples[0].decode_timestamp.max(0) as u64;
+
+        TrafBox {
+            tfhd: TfhdBox {
+                flags: TfhdBox::FLAG_DEFAULT_BASE_IS_MOOF,
+                track_id: self.track_id,
+                ..Default::default()
+            },
+            tfdt: Some(TfdtBox {
+                version: (base_media_decode_time > u32::MAX as u64) as u8,
+                flags: 0,
+                base_media_decode_time,
+            }),
+            truns: vec![trun],
+            ..Default::default()
+        }
+    }
+}
+
+pub struct FragmentedMp4Writer<W> {
+    writer: W,
+    sequence_number: u32,
+    init_written: bool,
+    duration: Option<Duration>,
+    tracks: Vec<FragmentTrack>,
+}
+
+impl<W: Write> FragmentedMp4Writer<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            sequence_number: 0,
+            init_written: false,
+            duration: None,
+            tracks: Vec::new(),
+        }
+    }
+
+    pub fn add_track(&mut self, config: TrackConfig) -> Result<TrackId> {
+        if self.init_written {
+            return Err(Error::invalid_input(
+                "tracks must be added before the init segment is written",
+            ));
+        }
+        if matches!(config.contents, StsdBoxContent::Unknown(_)) {
+            return Err(Error::invalid_input("track config has no sample entry"));
+        }
+        if config.timescale == 0 {
+            return Err(Error::invalid_input("track timescale must not be zero"));
+        }
+
+        let track_id = self.tracks.len() as TrackId + 1;
+        self.tracks.push(FragmentTrack {
+            track_id,
+            config,
+            samples: Vec::new(),
+            data: Vec::new(),
+        });
+
+        Ok(track_id)
+    }
+
+    // Known ahead for on-demand content, written as the `mvhd` duration and the `mehd`
+    // of the init segment. Live streams leave it unset, their duration stays 0.
+    pub fn set_duration(&mut self, duration: Duration) -> Result<()> {
+        if self.init_written {
+            return Err(Error::invalid_input(
+                "the duration must be set before the init segment is written",
+            ));
+        }
+        self.duration = Some(duration);
+
+        Ok(())
+    }
+
+    pub fn write_init_segment(&mut self) -> Result<()> {
+        if self.init_written {
+            return Err(Error::invalid_input("init segment was already written"));
+        }
+
+        let ftyp = FtypBox {
+            major_brand: FourCC::from(*b"iso6"),
+            minor_version: 0,
+            compatible_brands: vec![
+                FourCC::from(*b"iso6"),
+                FourCC::from(*b"cmfc"),
+                FourCC::from(*b"mp41"),
+            ],
+        };
+        ftyp.write_box(&mut self.writer)?;
+
+        let duration = self
+            .duration
+            .map(|duration| (duration.as_nanos() * MOVIE_TIMESCALE as u128 / 1_000_000_000) as u64);
+        let mut moov = MoovBox {
+            mvhd: MvhdBox {
+                timescale: MOVIE_TIMESCALE,
+                duration: duration.unwrap_or_default(),
+                next_track_id: self.tracks.len() as u32 + 1,
+                ..Default::default()
+            },
+            mvex: Some(MvexBox {
+                mehd: duration.map(|fragment_duration| MehdBox {
+                    version: (fragment_duration > u32::MAX as u64) as u8,
+                    flags: 0,
+                    fragment_duration,
+                }),
+                trexs: Vec::new(),
+            }),
+            ..Default::default()
+        };
+
+        for track in &self.tracks {
+            let stsd = StsdBox {
+                version: 0,
+                flags: 0,
+                entries: vec![track.config.contents.clone()],
+            };
+            let kind = stsd.kind();
+            let stbl = SampleTables::default().build_stbl(stsd);
+
+            moov.traks.push(build_trak(
+                track.track_id,
+                kind,
+                &track.config,
+                0,
+                MOVIE_TIMESCALE,
+                stbl,
+            ));
+            if let Some(mvex) = &mut moov.mvex {
+                mvex.trexs.push(TrexBox {
+                    track_id: track.track_id,
+                    default_sample_description_index: 1,
+                    default_sample_flags: track.default_sample_flags(),
+                    ..Default::default()
+                });
+            }
+        }
+
+        moov.write_box(&mut self.writer)?;
+        self.init_written = true;
+
+        Ok(())
+    }
+
+    pub fn write_sample(&mut self, track_id: TrackId, sample: &Sample, data: &[u8]) -> Result<()> {
+        if !self.init_written {
+            return Err(Error::invalid_input("init segment has not been written"));
+        }
+
+        let track = self
+            .tracks
+            .iter_mut()
+            .find(|track| track.track_id == track_id)
+            .ok_or(Error::TrackNotFound(track_id))?;
+
+        track.samples.push(Sample {
+            size: data.len() as u64,
+            ..sample.clone()
+        });
+        track.data.extend_from_slice(data);
+
+        Ok(())
+    }
+
+    // CMAF only allows a single track per fragment, so every track with
+    // pending samples gets its own moof+mdat pair
+    pub fn flush_segment(&mut self) -> Result<()> {
+        for track in &mut self.tracks {
+            if track.samples.is_empty() {
+                continue;
+            }
+
+            self.sequence_number += 1;
+            let mut moof = MoofBox {
+                start: 0,
+                mfhd: MfhdBox {
+                    version: 0,
+                    flags: 0,
+                    sequence_number: self.sequence_number,
+                },
+                trafs: vec![track.build_traf()],
+                psshs: Vec::new(),
+            };
+
+            let mdat_size = HEADER_SIZE + track.data.len() as u64;
+            let mdat_header_size = match mdat_size > u32::MAX as u64 {
+                true => MDAT_HEADER_SIZE,
+                false => HEADER_SIZE,
+            };
+            let data_offset = moof.box_size() + mdat_header_size;
+            moof.trafs[0].truns[0].data_offset = Some(data_offset as i32);
+
+            moof.write_box(&mut self.writer)?;
+            BoxHeader::new(BoxType::MdatBox, mdat_size + mdat_header_size - HEADER_SIZE)
+                .write(&mut self.writer)?;
+            self.writer.write_all(&track.data)?;
+
+            track.samples.clear();
+            track.data.clear();
+        }
+
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W> {
+        if !self.init_written {
+            self.write_init_segment()?;
+        }
+        self.flush_segment()?;
+
+        Ok(self.writer)
+    }
+}
+
+impl<W> std::fmt::Debug for FragmentedMp4Writer<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FragmentedMp4Writer")
+            .field("sequence_number", &self.sequence_number)
+            .field("init_written", &self.init_written)
+            .field("tracks", &self.tracks)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, SeekFrom};
+
+    use super::*;
+    use crate::{
+        boxes::{avc1::Avc1Box, mp4a::Mp4aBox},
+        Mp4,
+    };
+
+    // Keeps what's written except the large sample payloads, which read back as zeros,
+    // so files past 4GiB can be written and read without holding them in memory
+    #[derive(Default)]
+    struct SparseFile {
+        chunks: Vec<(u64, Vec<u8>)>,
+        position: u64,
+        len: u64,
+    }
+
+    impl Write for SparseFile {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if buf.len() < 1 << 20 {
+                self.chunks.retain(|(start, chunk)| {
+                    *start + chunk.len() as u64 <= self.position
+                        || *start >= self.position + buf.len() as u64
+                });
+                self.chunks.push((self.position, buf.to_vec()));
+            }
+            self.position += buf.len() as u64;
+            self.len = self.len.max(self.position);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for SparseFile {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let end = (self.position + buf.len() as u64).min(self.len);
+            let Some(len) = end.checked_sub(self.position).filter(|&len| len > 0) else {
+                return Ok(0);
+            };
+            let buf = &mut buf[..len as usize];
+            buf.fill(0);
+            for (start, chunk) in &self.chunks {
+                let chunk_end = start + chunk.len() as u64;
+                if chunk_end <= self.position || *start >= end {
+                    continue;
+                }
+                let from = self.position.max(*start);
+                let to = end.min(chunk_end);
+                buf[(from - self.position) as usize..(to - self.position) as usize]
+                    .copy_from_slice(&chunk[(from - start) as usize..(to - start) as usize]);
+            }
+            self.position = end;
+            Ok(len as usize)
+        }
+    }
+
+    impl Seek for SparseFile {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.position = match pos {
+                SeekFrom::Start(position) => position,
+                SeekFrom::End(delta) => self.len.saturating_add_signed(delta),
+                SeekFrom::Current(delta) => self.position.saturating_add_signed(delta),
+            };
+            Ok(self.position)
+        }
+    }
+
+    fn audio_sample(id: u32, is_sync: bool) -> Sample {
+        Sample {
+            id,
+            is_sync,
+            size: 0,
+            offset: 0,
+            timescale: 48000,
+            decode_timestamp: id as i64 * 1024,
+            composition_timestamp: id as i64 * 1024,
+            duration: 1024,
+            sample_description_index: 1,
+            encryption: None,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut writer = Mp4Writer::new(Cursor::new(Vec::new())).unwrap();
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config.clone()).unwrap();
+        let data: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; 100 + i as usize]).collect();
+        for (id, data) in data.iter().enumerate() {
+            let sample = audio_sample(id as u32, id % 4 == 0);
+            writer.write_sample(track_id, &sample, data).unwrap();
+        }
+        let file = writer.finish().unwrap().into_inner();
+
+        let mut reader = Cursor::new(&file[..]);
+        let mp4 = Mp4::read(&mut reader, file.len() as u64).unwrap();
+        let track = &mp4.tracks()[&track_id];
+        assert_eq!(track.time_scale, 48000);
+        assert_eq!(
+            track.trak(&mp4).mdia.minf.stbl.stsd.entries,
+            [config.contents]
+        );
+        assert_eq!(track.samples.len(), data.len());
+        for (id, data) in data.iter().enumerate() {
+            let sample = &track.samples[id];
+            assert_eq!(sample.decode_timestamp, id as i64 * 1024);
+            assert_eq!(sample.duration, 1024);
+            assert_eq!(sample.is_sync, id % 4 == 0);
+            assert_eq!(
+                &mp4.read_sample(&mut reader, track_id, id as u32).unwrap()[..],
+                &data[..]
+            );
+        }
+    }
+
+    #[test]
+    fn fragmented_round_trip() {
+        let mut writer = FragmentedMp4Writer::new(Cursor::new(Vec::new()));
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        writer.write_init_segment().unwrap();
+        // the composition offsets go negative, so the fragments use a version 1 trun
+        let offsets = [0, -1024, 2048, -512, 0, 1024];
+        for (id, offset) in offsets.into_iter().enumerate() {
+            let mut sample = audio_sample(id as u32, id % 3 == 0);
+            sample.composition_timestamp += offset;
+            writer
+                .write_sample(track_id, &sample, &[id as u8; 8])
+                .unwrap();
+            if id == 2 {
+                writer.flush_segment().unwrap();
+            }
+        }
+        let file = writer.finish().unwrap().into_inner();
+
+        let mut reader = Cursor::new(&file[..]);
+        let mp4 = Mp4::read(&mut reader, file.len() as u64).unwrap();
+        let track = &mp4.tracks()[&track_id];
+        assert_eq!(track.samples.len(), offsets.len());
+        for (id, offset) in offsets.into_iter().enumerate() {
+            let sample = &track.samples[id];
+            assert_eq!(sample.is_sync, id % 3 == 0);
+            assert_eq!(
+                sample.composition_timestamp - sample.decode_timestamp,
+                offset
+            );
+            let data = mp4.read_sample(&mut reader, track_id, id as u32).unwrap();
+            assert_eq!(&data[..], &[id as u8; 8]);
+        }
+    }
+
+    #[test]
+    fn fragmented_defaults() {
+        let mut writer = FragmentedMp4Writer::new(Cursor::new(Vec::new()));
+        let config = TrackConfig::new(90000, StsdBoxContent::Avc1(Avc1Box::default()));
+        let track_id = writer.add_track(config).unwrap();
+        writer.set_duration(Duration::from_millis(400)).unwrap();
+        writer.write_init_segment().unwrap();
+        assert!(writer.set_duration(Duration::ZERO).is_err());
+        // a segment starting with a sync sample, then one with a sync sample inside
+        for id in 0..12u32 {
+            let sample = Sample {
+                id,
+                is_sync: id == 0 || id == 9,
+                timescale: 90000,
+                decode_timestamp: id as i64 * 3000,
+                composition_timestamp: id as i64 * 3000,
+                duration: 3000,
+                ..Default::default()
+            };
+            writer.write_sample(track_id, &sample, &[0; 4]).unwrap();
+            if id == 5 {
+                writer.flush_segment().unwrap();
+            }
+        }
+        let file = writer.finish().unwrap().into_inner();
+
+        let mp4 = Mp4::read(&mut Cursor::new(&file[..]), file.len() as u64).unwrap();
+        assert_eq!(mp4.moov.mvhd.duration, 400);
+        let mvex = mp4.moov.mvex.as_ref().unwrap();
+        assert_eq!(mvex.mehd.as_ref().unwrap().fragment_duration, 400);
+        assert_eq!(mvex.trexs[0].default_sample_description_index, 1);
+        assert_eq!(mvex.trexs[0].default_sample_flags, SAMPLE_FLAGS_NON_SYNC);
+
+        let first = &mp4.moofs[0].trafs[0].truns[0];
+        assert_eq!(first.first_sample_flags, Some(SAMPLE_FLAGS_SYNC));
+        assert!(first.sample_flags.is_empty());
+        let second = &mp4.moofs[1].trafs[0].truns[0];
+        assert_eq!(second.first_sample_flags, None);
+        assert_eq!(second.sample_flags.len(), 6);
+
+        let sync: Vec<_> = mp4.tracks()[&track_id]
+            .samples
+            .iter()
+            .map(|sample| sample.is_sync)
+            .collect();
+        assert_eq!(
+            sync,
+            (0..12).map(|id| id == 0 || id == 9).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn mdat_past_4gib() {
+        let mut writer = Mp4Writer::new(SparseFile::default()).unwrap();
+        let config = TrackConfig::new(48000, StsdBoxContent::Mp4a(Mp4aBox::default()));
+        let track_id = writer.add_track(config).unwrap();
+        let payload = vec![0; 64 << 20];
+        let count = (u32::MAX as u64 / payload.len() as u64 + 2) as u32;
+        for id in 0..count {
+            writer
+                .write_sample(track_id, &audio_sample(id, true), &payload)
+                .unwrap();
+        }
+        writer
+            .write_sample(track_id, &audio_sample(count, true), b"last")
+            .unwrap();
+        let mut file = writer.finish().unwrap();
+
+        let size = file.len;
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mp4 = Mp4::read(&mut file, size).unwrap();
+        let track = &mp4.tracks()[&track_id];
+        let last = track.samples.last().unwrap();
+        assert!(last.offset > u32::MAX as u64);
+        assert_eq!(
+            &mp4.read_sample(&mut file, track_id, count).unwrap()[..],
+            b"last"
+        );
+
+        // 64-bit header: size 1, `mdat`, then the size of the whole box
+        let mdat_start = default_ftyp().box_size();
+        let mut header = [0; 16];
+        file.seek(SeekFrom::Start(mdat_start)).unwrap();
+        file.read_exact(&mut header).unwrap();
+        assert_eq!(header[..8], [0, 0, 0, 1, b'm', b'd', b'a', b't']);
+        let mdat_size = u64::from_be_bytes(header[8..].try_into().unwrap());
+        assert_eq!(mdat_start + mdat_size, last.offset + last.size);
+    }
+}